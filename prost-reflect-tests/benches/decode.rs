@@ -2,7 +2,7 @@ use std::{collections::BTreeMap, iter::FromIterator};
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use prost::Message;
-use prost_reflect::{DynamicMessage, ReflectMessage};
+use prost_reflect::{DynamicMessage, FieldExtractor, MessageView, ReflectMessage};
 use prost_reflect_tests::proto::WellKnownTypes;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
@@ -76,6 +76,33 @@ fn encode_wkt(c: &mut Criterion) {
     });
 }
 
+fn view_wkt(c: &mut Criterion) {
+    let value = sample_wkt().encode_to_vec();
+    let desc = prost_reflect_tests::test_file_descriptor()
+        .get_message_by_name("test.WellKnownTypes")
+        .unwrap();
+
+    c.bench_function("view_wkt", |b| {
+        b.iter(|| {
+            MessageView::new(desc.clone(), value.as_slice())
+                .get_field_by_name("string")
+                .unwrap()
+        })
+    });
+}
+
+fn extract_wkt(c: &mut Criterion) {
+    let value = sample_wkt().encode_to_vec();
+    let desc = prost_reflect_tests::test_file_descriptor()
+        .get_message_by_name("test.WellKnownTypes")
+        .unwrap();
+    let extractor = FieldExtractor::new(desc, ["string", "timestamp.seconds"]).unwrap();
+
+    c.bench_function("extract_wkt", |b| {
+        b.iter(|| extractor.extract(value.as_slice()).unwrap())
+    });
+}
+
 fn decode_wkt_multithread(c: &mut Criterion) {
     let value = sample_wkt().transcode_to_dynamic();
 
@@ -109,6 +136,6 @@ fn encode_wkt_multithread(c: &mut Criterion) {
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(500);
-    targets = decode_wkt, encode_wkt, decode_wkt_multithread, encode_wkt_multithread
+    targets = decode_wkt, encode_wkt, view_wkt, extract_wkt, decode_wkt_multithread, encode_wkt_multithread
 }
 criterion_main!(benches);
@@ -1,4 +1,4 @@
-use std::io;
+use std::{env, io, path::PathBuf, process::Command};
 
 fn main() -> io::Result<()> {
     let mut config = prost_build::Config::new();
@@ -49,5 +49,61 @@ fn main() -> io::Result<()> {
             ],
             &["src/"],
         )?;
+
+    // prost-build does not support generating code for editions, so these files are only compiled to
+    // descriptors.
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    protoc(&[
+        "--include_imports".to_owned(),
+        format!(
+            "--descriptor_set_out={}",
+            out_dir.join("editions.bin").display()
+        ),
+        "src/editions2023.proto".to_owned(),
+        "src/editions2024.proto".to_owned(),
+    ])?;
+    for (file, maximum) in [
+        ("editions_defaults.bin", "2024"),
+        ("editions_defaults_2023.bin", "2023"),
+    ] {
+        protoc(&[
+            format!("--edition_defaults_out={}", out_dir.join(file).display()),
+            "--edition_defaults_minimum=2023".to_owned(),
+            format!("--edition_defaults_maximum={maximum}"),
+            "src/custom_features.proto".to_owned(),
+        ])?;
+    }
+
+    // Used to check the output of the native parser matches protoc.
+    protoc(&[
+        "--include_imports".to_owned(),
+        "--include_source_info".to_owned(),
+        format!(
+            "--descriptor_set_out={}",
+            out_dir.join("parse.bin").display()
+        ),
+        "src/test.proto".to_owned(),
+        "src/test2.proto".to_owned(),
+        "src/desc.proto".to_owned(),
+        "src/desc2.proto".to_owned(),
+        "src/desc_no_package.proto".to_owned(),
+        "src/imports.proto".to_owned(),
+        "src/ext.proto".to_owned(),
+        "src/options.proto".to_owned(),
+        "src/editions2023.proto".to_owned(),
+        "src/editions2024.proto".to_owned(),
+        "src/parse.proto".to_owned(),
+    ])?;
+    Ok(())
+}
+
+fn protoc(args: &[String]) -> io::Result<()> {
+    let status = Command::new(prost_build::protoc_from_env())
+        .arg("-Isrc/")
+        .args(args)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("protoc failed: {status}")));
+    }
     Ok(())
 }
@@ -11,8 +11,8 @@ use prost_types::FileDescriptorSet;
 
 use crate::{
     proto::{
-        contains_group, message_with_oneof, ComplexType, ContainsGroup, MessageWithOneof,
-        ScalarArrays, Scalars, WellKnownTypes,
+        contains_group, message_with_oneof, ClosedEnums, ComplexType, ContainsGroup,
+        MessageWithOneof, ScalarArrays, Scalars, WellKnownTypes,
     },
     test_file_descriptor,
 };
@@ -788,6 +788,133 @@ fn unknown_fields_are_roundtripped() {
     assert_eq!(message.unknown_fields().count(), 0);
 }
 
+#[test]
+fn closed_enum_unknown_values() {
+    let desc = ClosedEnums::default().descriptor();
+    assert!(desc
+        .get_field_by_name("singular_enum")
+        .unwrap()
+        .kind()
+        .as_enum()
+        .unwrap()
+        .is_closed());
+    assert!(!ComplexType::default()
+        .descriptor()
+        .get_field_by_name("optional_enum")
+        .unwrap()
+        .kind()
+        .as_enum()
+        .unwrap()
+        .is_closed());
+
+    let known = ClosedEnums {
+        singular_enum: Some(3),
+        unpacked_enums: vec![2, 3],
+        packed_enums: vec![3, 2],
+        enum_map: HashMap::from([(1, 3)]),
+    };
+    let message = DynamicMessage::decode(desc.clone(), known.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(message.unknown_fields().count(), 0);
+    assert_eq!(message.transcode_to::<ClosedEnums>().unwrap(), known);
+
+    let unknown = ClosedEnums {
+        singular_enum: Some(5),
+        unpacked_enums: vec![2, -1, 3],
+        packed_enums: vec![3, 7, 2],
+        enum_map: HashMap::from([(1, 5), (2, 3)]),
+    };
+    let bytes = unknown.encode_to_vec();
+    for message in [
+        DynamicMessage::decode(desc.clone(), bytes.as_slice()).unwrap(),
+        DynamicMessage::decode_with_options(desc.clone(), bytes.as_slice(), &Default::default())
+            .unwrap(),
+    ] {
+        // Only the undefined values are stored as unknown fields.
+        assert!(!message.has_field_by_name("singular_enum"));
+        assert_eq!(
+            message
+                .get_field_by_name("unpacked_enums")
+                .unwrap()
+                .as_ref(),
+            &Value::List(vec![Value::EnumNumber(2), Value::EnumNumber(3)])
+        );
+        assert_eq!(
+            message.get_field_by_name("packed_enums").unwrap().as_ref(),
+            &Value::List(vec![Value::EnumNumber(3), Value::EnumNumber(2)])
+        );
+        assert_eq!(
+            message.get_field_by_name("enum_map").unwrap().as_ref(),
+            &Value::Map(HashMap::from([(MapKey::I32(2), Value::EnumNumber(3))]))
+        );
+        assert_eq!(message.unknown_fields().count(), 4);
+
+        // Unknown values are encoded after the known elements of their field.
+        assert_eq!(
+            ClosedEnums::decode(message.encode_to_vec().as_slice()).unwrap(),
+            ClosedEnums {
+                unpacked_enums: vec![2, 3, -1],
+                packed_enums: vec![3, 2, 7],
+                ..unknown.clone()
+            }
+        );
+    }
+
+    // Taking the unknown values keeps the known elements.
+    let mut message = DynamicMessage::decode(desc.clone(), bytes.as_slice()).unwrap();
+    assert_eq!(message.take_unknown_fields().count(), 4);
+    assert_eq!(
+        message.transcode_to::<ClosedEnums>().unwrap(),
+        ClosedEnums {
+            singular_enum: None,
+            unpacked_enums: vec![2, 3],
+            packed_enums: vec![3, 2],
+            enum_map: HashMap::from([(2, 3)]),
+        }
+    );
+
+    // A later known value replaces an unknown value of a singular field.
+    let message = DynamicMessage::decode(desc, b"\x08\x05\x08\x02".as_ref()).unwrap();
+    assert_eq!(
+        message.get_field_by_name("singular_enum").unwrap().as_ref(),
+        &Value::EnumNumber(2)
+    );
+    assert_eq!(message.unknown_fields().count(), 0);
+}
+
+#[test]
+fn closed_enum_is_valid() {
+    let desc = ClosedEnums::default().descriptor();
+    let singular = desc.get_field_by_name("singular_enum").unwrap();
+    let unpacked = desc.get_field_by_name("unpacked_enums").unwrap();
+    let enum_map = desc.get_field_by_name("enum_map").unwrap();
+
+    assert!(Value::EnumNumber(2).is_valid_for_field(&singular));
+    assert!(!Value::EnumNumber(5).is_valid_for_field(&singular));
+    assert!(
+        !Value::List(vec![Value::EnumNumber(2), Value::EnumNumber(0)])
+            .is_valid_for_field(&unpacked)
+    );
+    assert!(
+        !Value::Map(HashMap::from([(MapKey::I32(1), Value::EnumNumber(5))]))
+            .is_valid_for_field(&enum_map)
+    );
+
+    let mut message = DynamicMessage::new(desc);
+    assert!(message
+        .try_set_field(&singular, Value::EnumNumber(5))
+        .is_err());
+    assert!(message
+        .try_set_field(&singular, Value::EnumNumber(3))
+        .is_ok());
+
+    // Open enums accept any value.
+    let open = ComplexType::default()
+        .descriptor()
+        .get_field_by_name("optional_enum")
+        .unwrap();
+    assert!(Value::EnumNumber(5).is_valid_for_field(&open));
+}
+
 #[test]
 fn proto3_default_fields_are_not_encoded() {
     let message = ComplexType {
@@ -0,0 +1,233 @@
+use prost::Message;
+use prost_reflect::{
+    DecodeOptions, DynamicMessage, MessageDecodeError, ReflectMessage, UnknownFieldPolicy,
+};
+use prost_types::{value::Kind, ListValue};
+
+use crate::proto::{contains_group, ComplexType, ContainsGroup, Scalars, Scalars2};
+
+fn nested_list_value(depth: usize) -> prost_types::Value {
+    let mut value = prost_types::Value {
+        kind: Some(Kind::NullValue(0)),
+    };
+    for _ in 0..depth {
+        value = prost_types::Value {
+            kind: Some(Kind::ListValue(ListValue {
+                values: vec![value],
+            })),
+        };
+    }
+    value
+}
+
+#[test]
+fn default_options_match_decode() {
+    let message = ContainsGroup {
+        requiredgroup: Some(contains_group::RequiredGroup {
+            a: "bar".to_owned(),
+            b: None,
+        }),
+        repeatedgroup: vec![
+            contains_group::RepeatedGroup::default(),
+            contains_group::RepeatedGroup {
+                e: "hello".to_owned(),
+                f: Some(10),
+            },
+        ],
+        ..Default::default()
+    };
+    let mut bytes = message.encode_to_vec();
+    bytes.extend_from_slice(b"\xa0\x06\x01");
+
+    let expected = DynamicMessage::decode(message.descriptor(), bytes.as_slice()).unwrap();
+    let actual = DynamicMessage::decode_with_options(
+        message.descriptor(),
+        bytes.as_slice(),
+        &DecodeOptions::new(),
+    )
+    .unwrap();
+
+    assert_eq!(actual, expected);
+    assert_eq!(actual.unknown_fields().count(), 1);
+    assert_eq!(actual.encode_to_vec(), bytes);
+}
+
+#[test]
+fn discard_unknown_fields() {
+    // Field 3 (nested) containing an unknown field 100, followed by an unknown field 16.
+    let bytes = b"\x1a\x03\xa0\x06\x01\x80\x01\x02";
+    let options = DecodeOptions::new().unknown_fields(UnknownFieldPolicy::Discard);
+
+    let message = DynamicMessage::decode_with_options(
+        ComplexType::default().descriptor(),
+        bytes.as_ref(),
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(message.unknown_fields().count(), 0);
+    let nested = message.get_field_by_name("nested").unwrap();
+    assert_eq!(nested.as_message().unwrap().unknown_fields().count(), 0);
+    assert_eq!(message.encode_to_vec(), b"\x1a\x00");
+}
+
+#[test]
+fn reject_unknown_fields() {
+    let options = DecodeOptions::new().unknown_fields(UnknownFieldPolicy::Error);
+
+    let err = DynamicMessage::decode_with_options(
+        ComplexType::default().descriptor(),
+        b"\x1a\x03\xa0\x06\x01".as_ref(),
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        MessageDecodeError::UnknownField {
+            message: "test.Scalars".to_owned(),
+            path: "nested".to_owned(),
+            number: 100,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "unknown field 100 in message 'test.Scalars' at 'nested'"
+    );
+
+    let err = DynamicMessage::decode_with_options(
+        Scalars::default().descriptor(),
+        b"\x80\x01\x02".as_ref(),
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown field 16 in message 'test.Scalars'"
+    );
+}
+
+#[test]
+fn reject_unknown_fields_in_map_value() {
+    let options = DecodeOptions::new().unknown_fields(UnknownFieldPolicy::Error);
+
+    // Field 2 (int_map) with key 5 and a value containing an unknown field 100.
+    let err = DynamicMessage::decode_with_options(
+        ComplexType::default().descriptor(),
+        b"\x12\x07\x08\x05\x12\x03\xa0\x06\x01".as_ref(),
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown field 100 in message 'test.Scalars' at 'int_map[5]'"
+    );
+}
+
+#[test]
+fn custom_recursion_limit() {
+    let value = nested_list_value(60);
+    let bytes = value.encode_to_vec();
+
+    // Each level of nesting is a ListValue and a Value message.
+    assert!(DynamicMessage::decode(value.descriptor(), bytes.as_slice()).is_err());
+
+    let message = DynamicMessage::decode_with_options(
+        value.descriptor(),
+        bytes.as_slice(),
+        &DecodeOptions::new().recursion_limit(120),
+    )
+    .unwrap();
+    // The message is too deeply nested to transcode with prost's default limit.
+    assert_eq!(message.encode_to_vec(), bytes);
+
+    let err = DynamicMessage::decode_with_options(
+        value.descriptor(),
+        bytes.as_slice(),
+        &DecodeOptions::new().recursion_limit(3),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        MessageDecodeError::RecursionLimitReached {
+            path: "list_value.values[0].list_value.values[0]".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn max_message_size() {
+    let bytes = Scalars {
+        string: "hello".to_owned(),
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let err = DynamicMessage::decode_with_options(
+        Scalars::default().descriptor(),
+        bytes.as_slice(),
+        &DecodeOptions::new().max_message_size(4),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        MessageDecodeError::MessageTooLarge { len: 7, limit: 4 }
+    );
+
+    assert!(DynamicMessage::decode_with_options(
+        Scalars::default().descriptor(),
+        bytes.as_slice(),
+        &DecodeOptions::new().max_message_size(7),
+    )
+    .is_ok());
+}
+
+#[test]
+fn invalid_utf8_error() {
+    let bytes = b"\x72\x02\xff\xfe";
+
+    let err = DynamicMessage::decode_with_options(
+        Scalars2::default().descriptor(),
+        bytes.as_ref(),
+        &DecodeOptions::new(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, MessageDecodeError::Decode { ref path, .. } if path == "string"));
+
+    // Proto3 strings are always validated.
+    let err = DynamicMessage::decode_with_options(
+        Scalars::default().descriptor(),
+        bytes.as_ref(),
+        &DecodeOptions::new().preserve_invalid_utf8(true),
+    )
+    .unwrap_err();
+    assert!(matches!(err, MessageDecodeError::Decode { ref path, .. } if path == "string"));
+}
+
+#[test]
+fn invalid_utf8_preserved() {
+    let bytes = b"\x72\x01a\x72\x02\xff\xfe";
+
+    let message = DynamicMessage::decode_with_options(
+        Scalars2::default().descriptor(),
+        bytes.as_ref(),
+        &DecodeOptions::new().preserve_invalid_utf8(true),
+    )
+    .unwrap();
+
+    assert!(!message.has_field_by_name("string"));
+    assert_eq!(message.unknown_fields().count(), 1);
+    assert_eq!(message.encode_to_vec(), b"\x72\x02\xff\xfe");
+
+    // A later valid value replaces the invalid one.
+    let message = DynamicMessage::decode_with_options(
+        Scalars2::default().descriptor(),
+        b"\x72\x02\xff\xfe\x72\x01a".as_ref(),
+        &DecodeOptions::new().preserve_invalid_utf8(true),
+    )
+    .unwrap();
+    assert_eq!(
+        message.get_field_by_name("string").unwrap().as_str(),
+        Some("a")
+    );
+    assert_eq!(message.unknown_fields().count(), 0);
+}
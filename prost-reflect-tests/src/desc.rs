@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
 use prost::{bytes::Bytes, Message};
-use prost_reflect::{DescriptorPool, DynamicMessage, MapKey, ReflectMessage, Syntax, Value};
+use prost_reflect::{
+    Descriptor, DescriptorPool, DynamicMessage, MapKey, ReflectMessage, Syntax, Value,
+};
 
 use crate::{
     proto::{self, ComplexType, Scalars},
@@ -100,6 +102,78 @@ fn test_descriptor_methods() {
     assert_eq!(method_desc.full_name(), "my.package.MyService.MyMethod");
 }
 
+#[test]
+fn test_descriptor_comments() {
+    let message_desc = test_file_descriptor()
+        .get_message_by_name("my.package.MyMessage")
+        .unwrap();
+    assert_eq!(
+        message_desc.leading_comments(),
+        Some(" Leading comment for MyMessage.\n")
+    );
+    assert_eq!(message_desc.trailing_comments(), None);
+    assert_eq!(
+        message_desc.leading_detached_comments(),
+        [" Detached comment for MyMessage.\n"]
+    );
+    let span = message_desc.span().unwrap();
+    assert_eq!(
+        (
+            span.start_line(),
+            span.start_column(),
+            span.end_line(),
+            span.end_column()
+        ),
+        (Some(7), Some(0), Some(20), Some(1))
+    );
+
+    let field_desc = message_desc.get_field_by_name("my_field").unwrap();
+    assert_eq!(
+        field_desc.leading_comments(),
+        Some(" Leading comment for my_field.\n")
+    );
+    assert_eq!(
+        field_desc.trailing_comments(),
+        Some(" Trailing comment for my_field.\n")
+    );
+    assert!(field_desc.leading_detached_comments().is_empty());
+    let span = field_desc.span().unwrap();
+    assert_eq!(
+        (
+            span.start_line(),
+            span.start_column(),
+            span.end_line(),
+            span.end_column()
+        ),
+        (Some(10), Some(4), Some(10), Some(23))
+    );
+
+    let oneof_desc = field_desc.containing_oneof().unwrap();
+    assert_eq!(oneof_desc.leading_comments(), None);
+    assert_eq!(oneof_desc.span().unwrap().start_line(), Some(8));
+
+    let enum_desc = test_file_descriptor()
+        .get_enum_by_name("my.package.MyEnum")
+        .unwrap();
+    assert_eq!(enum_desc.leading_comments(), None);
+    let enum_value_desc = enum_desc.get_value_by_name("MY_VALUE").unwrap();
+    assert_eq!(
+        enum_value_desc.trailing_comments(),
+        Some(" Trailing comment for MY_VALUE.\n")
+    );
+
+    let service_desc = test_file_descriptor()
+        .get_service_by_name("my.package.MyService")
+        .unwrap();
+    assert_eq!(
+        service_desc.leading_comments(),
+        Some(" Leading comment for MyService.\n")
+    );
+    let method_desc = service_desc.methods().next().unwrap();
+    assert_eq!(method_desc.leading_comments(), None);
+    assert_eq!(method_desc.span().unwrap().start_line(), Some(31));
+}
+
 #[test]
 fn test_descriptor_methods_proto2() {
     let file_desc = test_file_descriptor()
@@ -367,6 +441,117 @@ fn descriptor_pool_add_individual_files() {
     assert_eq!(message_desc.package_name(), "my.package");
 }
 
+#[test]
+fn test_get_by_name() {
+    let pool = test_file_descriptor();
+
+    let message = pool.get_message_by_name("my.package.MyMessage").unwrap();
+    assert_eq!(
+        pool.get_by_name("my.package.MyMessage"),
+        Some(Descriptor::Message(message.clone()))
+    );
+    assert_eq!(
+        pool.get_by_name(".my.package.MyMessage.my_field"),
+        Some(Descriptor::Field(
+            message.get_field_by_name("my_field").unwrap()
+        ))
+    );
+    assert_eq!(
+        pool.get_by_name("my.package.MyMessage.my_oneof"),
+        Some(Descriptor::Oneof(message.oneofs().next().unwrap()))
+    );
+
+    let enum_ = pool
+        .get_enum_by_name("my.package.MyMessage.MyNestedEnum")
+        .unwrap();
+    assert_eq!(
+        pool.get_by_name("my.package.MyMessage.MY_VALUE"),
+        Some(Descriptor::EnumValue(
+            enum_.get_value_by_name("MY_VALUE").unwrap()
+        ))
+    );
+    let enum_ = pool.get_enum_by_name("my.package.MyEnum").unwrap();
+    assert_eq!(
+        pool.get_by_name("my.package.MyEnum"),
+        Some(Descriptor::Enum(enum_.clone()))
+    );
+    assert_eq!(
+        pool.get_by_name("my.package.MY_VALUE"),
+        Some(Descriptor::EnumValue(
+            enum_.get_value_by_name("MY_VALUE").unwrap()
+        ))
+    );
+
+    let service = pool.get_service_by_name("my.package.MyService").unwrap();
+    assert_eq!(
+        pool.get_by_name("my.package.MyService"),
+        Some(Descriptor::Service(service.clone()))
+    );
+    let method = pool.get_by_name("my.package.MyService.MyMethod").unwrap();
+    assert_eq!(
+        method,
+        Descriptor::Method(service.methods().next().unwrap())
+    );
+    assert_eq!(method.name(), "MyMethod");
+    assert_eq!(method.full_name(), "my.package.MyService.MyMethod");
+    assert_eq!(method.parent_file().name(), "desc.proto");
+    assert_eq!(method.parent_pool(), &pool);
+
+    assert_eq!(
+        pool.get_by_name("my.package2.in_file"),
+        Some(Descriptor::Extension(
+            pool.get_extension_by_name("my.package2.in_file").unwrap()
+        ))
+    );
+
+    assert_eq!(pool.get_by_name("my.package"), None);
+    assert_eq!(pool.get_by_name("my.package.NotFound"), None);
+}
+
+#[test]
+fn test_resolve_relative_name() {
+    let pool = test_file_descriptor();
+    let resolve = |scope: &str, name: &str| {
+        pool.resolve_relative_name(scope, name)
+            .map(|def| def.full_name().to_owned())
+    };
+
+    assert_eq!(
+        resolve("my.package.MyMessage", "MY_VALUE").as_deref(),
+        Some("my.package.MyMessage.MY_VALUE")
+    );
+    assert_eq!(
+        resolve("my.package", "MY_VALUE").as_deref(),
+        Some("my.package.MY_VALUE")
+    );
+    assert_eq!(
+        resolve("my.package.MyMessage", ".my.package.MY_VALUE").as_deref(),
+        Some("my.package.MY_VALUE")
+    );
+    assert_eq!(
+        resolve("my.package2.OtherMessage", "MyMessage").as_deref(),
+        Some("my.package2.MyMessage")
+    );
+    assert_eq!(
+        resolve("my.package2.OtherMessage", "package.MyMessage").as_deref(),
+        Some("my.package.MyMessage")
+    );
+    assert_eq!(
+        resolve("my.package2.OtherMessage", "MyMessage.in_extendee").as_deref(),
+        Some("my.package2.MyMessage.in_extendee")
+    );
+    assert_eq!(
+        resolve("", "my.package.MyService.MyMethod").as_deref(),
+        Some("my.package.MyService.MyMethod")
+    );
+
+    // The first component of the name resolves to `my.package2.MyMessage`, which does not contain
+    // `my_field`, so the search stops without finding `my.package.MyMessage.my_field`.
+    assert_eq!(resolve("my.package2.MyMessage", "MyMessage.my_field"), None);
+    assert_eq!(resolve("my.package", "NotFound"), None);
+    assert_eq!(resolve("my.package2", "package"), None);
+}
+
 #[test]
 fn test_enum_alias() {
     let enum_desc = test_file_descriptor()
@@ -0,0 +1,149 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::Hasher,
+};
+
+use prost::Message;
+use prost_reflect::{DynamicMessage, ReflectMessage};
+
+use crate::proto::{ComplexType, Scalars};
+
+fn int_map_entry(key: i32, value: i32) -> Vec<u8> {
+    ComplexType {
+        int_map: HashMap::from([(
+            key,
+            Scalars {
+                int32: value,
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn deterministic_map_order() {
+    let message = ComplexType {
+        int_map: (1..=20)
+            .rev()
+            .map(|key| {
+                (
+                    key,
+                    Scalars {
+                        int32: key * 2,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect(),
+        ..Default::default()
+    }
+    .transcode_to_dynamic();
+
+    let expected: Vec<u8> = (1..=20)
+        .flat_map(|key| int_map_entry(key, key * 2))
+        .collect();
+
+    let mut actual = Vec::new();
+    message.encode_deterministic(&mut actual).unwrap();
+    assert_eq!(actual, expected);
+    assert_eq!(message.canonical_bytes(), expected);
+    assert_eq!(message.encoded_len(), expected.len());
+}
+
+#[test]
+fn deterministic_string_map_order() {
+    let message = ComplexType {
+        string_map: HashMap::from([
+            ("c".to_owned(), Scalars::default()),
+            ("a".to_owned(), Scalars::default()),
+            ("b".to_owned(), Scalars::default()),
+        ]),
+        ..Default::default()
+    }
+    .transcode_to_dynamic();
+
+    let bytes = message.canonical_bytes();
+    let positions: Vec<usize> = ["a", "b", "c"]
+        .iter()
+        .map(|key| {
+            let entry = [0x0a, 0x05, 0x0a, 0x01, key.as_bytes()[0], 0x12, 0x00];
+            bytes
+                .windows(entry.len())
+                .position(|window| window == entry)
+                .unwrap()
+        })
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    assert_eq!(
+        DynamicMessage::decode(message.descriptor(), bytes.as_slice()).unwrap(),
+        message
+    );
+}
+
+#[test]
+fn deterministic_unknown_fields_in_number_order() {
+    let desc = Scalars::default().descriptor();
+    // Unknown field 100, then known field 3 (int32), then unknown field 16.
+    let message =
+        DynamicMessage::decode(desc, b"\xa0\x06\x01\x18\x05\x80\x01\x02".as_ref()).unwrap();
+
+    assert_eq!(
+        message.canonical_bytes(),
+        b"\x18\x05\x80\x01\x02\xa0\x06\x01"
+    );
+}
+
+#[test]
+fn canonical_hash_equal_messages() {
+    let build = |keys: &[&str]| {
+        ComplexType {
+            string_map: keys
+                .iter()
+                .map(|&key| {
+                    (
+                        key.to_owned(),
+                        Scalars {
+                            string: key.to_owned(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+        .transcode_to_dynamic()
+    };
+
+    let hash = |message: &DynamicMessage| {
+        let mut hasher = DefaultHasher::new();
+        message.canonical_hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let first = build(&["foo", "bar", "baz", "qux"]);
+    let second = build(&["qux", "baz", "bar", "foo"]);
+    assert_eq!(first.canonical_bytes(), second.canonical_bytes());
+    assert_eq!(hash(&first), hash(&second));
+
+    let third = build(&["foo", "bar", "baz"]);
+    assert_ne!(hash(&first), hash(&third));
+}
+
+#[test]
+fn encode_deterministic_insufficient_capacity() {
+    let message = Scalars {
+        int32: 5,
+        ..Default::default()
+    }
+    .transcode_to_dynamic();
+
+    let mut buf = [0u8; 1];
+    let err = message
+        .encode_deterministic(&mut buf.as_mut_slice())
+        .unwrap_err();
+    assert_eq!(err.required_capacity(), 2);
+    assert_eq!(err.remaining(), 1);
+}
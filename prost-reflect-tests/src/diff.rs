@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_reflect::{
+    diff::{ChangeKind, DiffOptions},
+    DynamicMessage, MapKey, PathSegment, ReflectMessage, Value,
+};
+use prost_types::{value::Kind, ListValue};
+
+use crate::{
+    proto::{ComplexType, ScalarArrays, Scalars},
+    test_file_descriptor,
+};
+
+fn diff(old: &impl ReflectMessage, new: &impl ReflectMessage, options: &DiffOptions) -> String {
+    old.transcode_to_dynamic()
+        .diff(&new.transcode_to_dynamic(), options)
+        .to_string()
+}
+
+fn list(values: &[Kind]) -> ListValue {
+    ListValue {
+        values: values
+            .iter()
+            .map(|kind| prost_types::Value {
+                kind: Some(kind.clone()),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn diff_equal() {
+    let message = ComplexType {
+        string_map: HashMap::from([("foo".to_owned(), Scalars::default())]),
+        nested: Some(Scalars {
+            double: f64::NAN,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .transcode_to_dynamic();
+
+    assert!(message.diff(&message, &DiffOptions::new()).is_empty());
+}
+
+#[test]
+fn diff_scalars() {
+    let old = Scalars {
+        int32: 1,
+        string: "foo".to_owned(),
+        ..Default::default()
+    };
+    let new = Scalars {
+        int32: 2,
+        bool: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "~ int32: 1 -> 2\n~ bool: false -> true\n~ string: \"foo\" -> \"\"\n"
+    );
+
+    let changes = old
+        .transcode_to_dynamic()
+        .diff(&new.transcode_to_dynamic(), &DiffOptions::new())
+        .into_changes();
+    assert_eq!(
+        changes[0].path().segments(),
+        [PathSegment::Field(
+            old.descriptor().get_field_by_name("int32").unwrap()
+        )]
+    );
+    assert_eq!(
+        changes[0].kind(),
+        &ChangeKind::Modified {
+            old: Value::I32(1),
+            new: Value::I32(2),
+        }
+    );
+}
+
+#[test]
+fn diff_nested_message() {
+    let old = ComplexType {
+        nested: Some(Scalars {
+            int32: 1,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let new = ComplexType {
+        nested: Some(Scalars {
+            int32: 2,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "~ nested.int32: 1 -> 2\n"
+    );
+    assert_eq!(
+        diff(&ComplexType::default(), &new, &DiffOptions::new()),
+        "+ nested: {int32:2}\n"
+    );
+    assert_eq!(
+        diff(&old, &ComplexType::default(), &DiffOptions::new()),
+        "- nested: {int32:1}\n"
+    );
+}
+
+#[test]
+fn diff_list() {
+    let old = ScalarArrays {
+        int32: vec![1, 2, 3, 4, 5],
+        ..Default::default()
+    };
+    let new = ScalarArrays {
+        int32: vec![1, 3, 4, 6, 5, 7],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "- int32[1]: 2\n+ int32[3]: 6\n+ int32[5]: 7\n"
+    );
+
+    let old = ScalarArrays {
+        string: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+        ..Default::default()
+    };
+    let new = ScalarArrays {
+        string: vec!["a".to_owned(), "x".to_owned(), "c".to_owned()],
+        ..Default::default()
+    };
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "~ string[1]: \"b\" -> \"x\"\n"
+    );
+}
+
+#[test]
+fn diff_long_list() {
+    let old = ScalarArrays {
+        int32: (0..2000).collect(),
+        ..Default::default()
+    };
+    let mut new = old.clone();
+    new.int32[0] = -1;
+    new.int32[1000] = -2;
+    new.int32[1999] = -3;
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "~ int32[0]: 0 -> -1\n~ int32[1000]: 1000 -> -2\n~ int32[1999]: 1999 -> -3\n"
+    );
+}
+
+#[test]
+fn diff_list_of_messages() {
+    let old = list(&[
+        Kind::StringValue("a".to_owned()),
+        Kind::NumberValue(1.0),
+        Kind::BoolValue(true),
+    ]);
+    let new = list(&[
+        Kind::StringValue("a".to_owned()),
+        Kind::NumberValue(2.0),
+        Kind::BoolValue(true),
+        Kind::BoolValue(false),
+    ]);
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "~ values[1].number_value: 1.0 -> 2.0\n+ values[3]: {bool_value:false}\n"
+    );
+}
+
+#[test]
+fn diff_repeated_as_set() {
+    let old = ScalarArrays {
+        int32: vec![1, 2, 3, 2],
+        ..Default::default()
+    };
+    let new = ScalarArrays {
+        int32: vec![3, 2, 1, 4],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new().repeated_as_set(true)),
+        "- int32[3]: 2\n+ int32[3]: 4\n"
+    );
+}
+
+#[test]
+fn diff_map() {
+    let old = ComplexType {
+        string_map: HashMap::from([
+            ("a".to_owned(), Scalars::default()),
+            (
+                "b".to_owned(),
+                Scalars {
+                    int32: 1,
+                    ..Default::default()
+                },
+            ),
+        ]),
+        int_map: HashMap::from([(1, Scalars::default())]),
+        ..Default::default()
+    };
+    let new = ComplexType {
+        string_map: HashMap::from([
+            (
+                "b".to_owned(),
+                Scalars {
+                    int32: 2,
+                    ..Default::default()
+                },
+            ),
+            ("c".to_owned(), Scalars::default()),
+        ]),
+        int_map: HashMap::from([(1, Scalars::default()), (-2, Scalars::default())]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new()),
+        "- string_map[\"a\"]: {}\n~ string_map[\"b\"].int32: 1 -> 2\n+ string_map[\"c\"]: {}\n+ int_map[-2]: {}\n"
+    );
+
+    let changes = old
+        .transcode_to_dynamic()
+        .diff(&new.transcode_to_dynamic(), &DiffOptions::new())
+        .into_changes();
+    assert_eq!(
+        changes[3].path().segments()[1],
+        PathSegment::Key(MapKey::I32(-2))
+    );
+}
+
+#[test]
+fn diff_float_tolerance() {
+    let old = Scalars {
+        double: 1.0,
+        float: 2.0,
+        ..Default::default()
+    };
+    let new = Scalars {
+        double: 1.0005,
+        float: 2.01,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new().float_tolerance(0.001)),
+        "~ float: 2.0 -> 2.01\n"
+    );
+    assert_eq!(
+        diff(&old, &new, &DiffOptions::new().float_tolerance(0.1)),
+        ""
+    );
+}
+
+#[test]
+fn diff_ignore_field() {
+    let old = ComplexType {
+        nested: Some(Scalars {
+            int32: 1,
+            string: "foo".to_owned(),
+            ..Default::default()
+        }),
+        optional_enum: 1,
+        ..Default::default()
+    };
+    let new = ComplexType {
+        nested: Some(Scalars {
+            int32: 2,
+            string: "bar".to_owned(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let complex_type = old.descriptor();
+    let scalars = complex_type
+        .get_field_by_name("nested")
+        .unwrap()
+        .kind()
+        .as_message()
+        .unwrap()
+        .clone();
+    let options = DiffOptions::new()
+        .ignore_field(&complex_type.get_field_by_name("optional_enum").unwrap())
+        .ignore_field(&scalars.get_field_by_name("int32").unwrap());
+
+    assert_eq!(
+        diff(&old, &new, &options),
+        "~ nested.string: \"foo\" -> \"bar\"\n"
+    );
+}
+
+#[test]
+fn diff_extensions() {
+    let pool = test_file_descriptor();
+    let desc = pool.get_message_by_name("my.package2.MyMessage").unwrap();
+    let in_file = pool.get_extension_by_name("my.package2.in_file").unwrap();
+    let in_other = pool
+        .get_extension_by_name("my.package2.OtherMessage.in_other")
+        .unwrap();
+
+    let mut old = DynamicMessage::new(desc.clone());
+    old.set_extension(&in_file, Value::F32(1.0));
+    old.set_extension(&in_other, Value::F64(1.0));
+    let mut new = DynamicMessage::new(desc);
+    new.set_extension(&in_file, Value::F32(2.0));
+
+    assert_eq!(
+        old.diff(&new, &DiffOptions::new()).to_string(),
+        "~ [my.package2.in_file]: 1.0 -> 2.0\n- [my.package2.OtherMessage.in_other]: 1.0\n"
+    );
+    assert!(old
+        .diff(
+            &new,
+            &DiffOptions::new()
+                .ignore_extension(&in_file)
+                .ignore_extension(&in_other)
+        )
+        .is_empty());
+}
+
+#[test]
+fn diff_unknown_fields() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.ComplexType")
+        .unwrap();
+
+    let mut old = DynamicMessage::new(desc.clone());
+    old.merge(b"\x1a\x03\xa0\x06\x01\xa8\x06\x02".as_ref())
+        .unwrap();
+    let mut new = DynamicMessage::new(desc);
+    new.merge(b"\x1a\x03\xa0\x06\x01\xb0\x06\x03".as_ref())
+        .unwrap();
+
+    assert_eq!(
+        old.diff(&new, &DiffOptions::new()).to_string(),
+        "- 101:2\n+ 102:3\n"
+    );
+}
+
+#[test]
+fn diff_different_types() {
+    let old = Scalars::default().transcode_to_dynamic();
+    let new = ComplexType::default().transcode_to_dynamic();
+
+    let diff = old.diff(&new, &DiffOptions::new());
+    assert_eq!(diff.changes().len(), 1);
+    assert!(diff.changes()[0].path().is_empty());
+    assert_eq!(diff.to_string(), "~ {} -> {}\n");
+}
@@ -0,0 +1,277 @@
+use prost_reflect::{
+    feature_set::{
+        EnumType, FieldPresence, JsonFormat, MessageEncoding, RepeatedFieldEncoding, Utf8Validation,
+    },
+    DescriptorPool, DynamicMessage, Edition, Kind, Syntax, Value,
+};
+
+use crate::test_file_descriptor;
+
+const EDITIONS_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/editions.bin"));
+const EDITIONS_DEFAULTS_BYTES: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/editions_defaults.bin"));
+const EDITIONS_DEFAULTS_2023_BYTES: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/editions_defaults_2023.bin"));
+
+const LOW: i32 = 1;
+const HIGH: i32 = 2;
+
+fn editions_pool(defaults: Option<&[u8]>) -> DescriptorPool {
+    let mut pool = DescriptorPool::new();
+    if let Some(defaults) = defaults {
+        pool.set_feature_set_defaults(defaults).unwrap();
+    }
+    pool.decode_file_descriptor_set(EDITIONS_BYTES).unwrap();
+    pool
+}
+
+fn custom_level(pool: &DescriptorPool, features: &DynamicMessage) -> Option<i32> {
+    let extension = pool.get_extension_by_name("test.features.custom").unwrap();
+    if !features.has_extension(&extension) {
+        return None;
+    }
+
+    match features
+        .get_extension(&extension)
+        .as_message()
+        .unwrap()
+        .get_field_by_name("level")
+        .unwrap()
+        .as_ref()
+    {
+        Value::EnumNumber(level) => Some(*level),
+        value => panic!("unexpected value {value:?}"),
+    }
+}
+
+#[test]
+fn resolved_features() {
+    let pool = editions_pool(None);
+
+    let file = pool.get_file_by_name("editions2023.proto").unwrap();
+    assert_eq!(file.syntax(), Syntax::Editions(Edition::Edition2023));
+    assert_eq!(file.features().field_presence(), FieldPresence::Explicit);
+    assert_eq!(file.features().enum_type(), EnumType::Closed);
+    assert_eq!(file.features().json_format(), JsonFormat::Allow);
+
+    let message = pool.get_message_by_name("test.editions.Message").unwrap();
+    assert_eq!(message.features(), file.features());
+
+    let field = |name: &str| message.get_field_by_name(name).unwrap().features();
+    assert_eq!(field("explicit").field_presence(), FieldPresence::Explicit);
+    assert_eq!(field("implicit").field_presence(), FieldPresence::Implicit);
+    assert_eq!(
+        field("packed").repeated_field_encoding(),
+        RepeatedFieldEncoding::Packed
+    );
+    assert_eq!(
+        field("expanded").repeated_field_encoding(),
+        RepeatedFieldEncoding::Expanded
+    );
+    assert_eq!(
+        field("delimited").message_encoding(),
+        MessageEncoding::Delimited
+    );
+    assert_eq!(field("unverified").utf8_validation(), Utf8Validation::None);
+    assert_eq!(field("explicit").utf8_validation(), Utf8Validation::Verify);
+
+    let oneof = message.oneofs().next().unwrap();
+    assert_eq!(oneof.features(), message.features());
+    assert_eq!(field("oneof_field"), oneof.features());
+
+    let closed = pool.get_enum_by_name("test.editions.Closed").unwrap();
+    assert_eq!(closed.features().enum_type(), EnumType::Closed);
+    assert_eq!(
+        closed.values().next().unwrap().features().enum_type(),
+        EnumType::Closed
+    );
+    let open = pool.get_enum_by_name("test.editions.Open").unwrap();
+    assert_eq!(open.features().enum_type(), EnumType::Open);
+    assert_eq!(
+        open.values().next().unwrap().features().enum_type(),
+        EnumType::Open
+    );
+
+    let service = pool.get_service_by_name("test.editions.Service").unwrap();
+    assert_eq!(service.features(), file.features());
+    assert_eq!(
+        service.methods().next().unwrap().features(),
+        file.features()
+    );
+}
+
+#[test]
+fn resolved_features_message() {
+    let pool = editions_pool(None);
+
+    let message = pool.get_message_by_name("test.editions.Message").unwrap();
+    let features = message
+        .get_field_by_name("implicit")
+        .unwrap()
+        .features_message();
+    assert_eq!(
+        features
+            .get_field_by_name("field_presence")
+            .unwrap()
+            .as_ref(),
+        &Value::EnumNumber(2)
+    );
+    assert_eq!(
+        features.get_field_by_name("enum_type").unwrap().as_ref(),
+        &Value::EnumNumber(2)
+    );
+
+    // Custom features only take their defaults when the defaults are provided.
+    assert_eq!(custom_level(&pool, &features), None);
+
+    let nested = pool
+        .get_message_by_name("test.editions.Message.Nested")
+        .unwrap();
+    assert_eq!(custom_level(&pool, &nested.features_message()), Some(HIGH));
+}
+
+#[test]
+fn custom_feature_set_defaults() {
+    let pool = editions_pool(Some(EDITIONS_DEFAULTS_BYTES));
+
+    let message = pool.get_message_by_name("test.editions.Message").unwrap();
+    assert_eq!(custom_level(&pool, &message.features_message()), Some(LOW));
+    let field = message.get_field_by_name("explicit").unwrap();
+    assert_eq!(custom_level(&pool, &field.features_message()), Some(LOW));
+
+    let nested = pool
+        .get_message_by_name("test.editions.Message.Nested")
+        .unwrap();
+    let inherited = nested.get_field_by_name("inherited").unwrap();
+    assert_eq!(
+        custom_level(&pool, &inherited.features_message()),
+        Some(HIGH)
+    );
+    let overridden = nested.get_field_by_name("overridden").unwrap();
+    assert_eq!(
+        custom_level(&pool, &overridden.features_message()),
+        Some(LOW)
+    );
+
+    let message = pool
+        .get_message_by_name("test.editions.Message2024")
+        .unwrap();
+    assert_eq!(
+        message.parent_file().syntax(),
+        Syntax::Editions(Edition::Edition2024)
+    );
+    assert_eq!(custom_level(&pool, &message.features_message()), Some(HIGH));
+}
+
+#[test]
+fn custom_feature_set_defaults_unsupported_edition() {
+    let mut pool = DescriptorPool::new();
+    pool.set_feature_set_defaults(EDITIONS_DEFAULTS_2023_BYTES)
+        .unwrap();
+    let err = pool.decode_file_descriptor_set(EDITIONS_BYTES).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported edition '1001'");
+    assert_eq!(err.file(), Some("editions2024.proto"));
+}
+
+#[test]
+fn add_editions_file_descriptor_proto() {
+    let mut pool = editions_pool(None);
+    let file = pool.get_file_by_name("editions2023.proto").unwrap();
+
+    let mut new_pool = DescriptorPool::new();
+    let err = new_pool
+        .add_file_descriptor_proto(file.file_descriptor_proto().clone())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the edition of file 'editions2023.proto' is not available, since it is not supported by prost_types"
+    );
+    assert_eq!(new_pool.files().len(), 0);
+
+    // Files already in the pool are skipped.
+    pool.add_file_descriptor_proto(file.file_descriptor_proto().clone())
+        .unwrap();
+}
+
+#[test]
+fn invalid_feature_set_defaults() {
+    let mut pool = DescriptorPool::new();
+    assert!(pool.set_feature_set_defaults(&b"\x0a\x02\x12"[..]).is_err());
+}
+
+#[test]
+fn legacy_field_features() {
+    let pool = test_file_descriptor();
+    let field = |message: &str, field: &str| {
+        pool.get_message_by_name(message)
+            .unwrap()
+            .get_field_by_name(field)
+            .unwrap()
+    };
+
+    assert_eq!(
+        field("test2.RequiredFields", "id")
+            .features()
+            .field_presence(),
+        FieldPresence::LegacyRequired
+    );
+    assert_eq!(
+        field("test2.ClosedEnums", "packed_enums")
+            .features()
+            .repeated_field_encoding(),
+        RepeatedFieldEncoding::Packed
+    );
+    assert_eq!(
+        field("test2.ContainsGroup", "requiredgroup")
+            .features()
+            .message_encoding(),
+        MessageEncoding::Delimited
+    );
+    assert_eq!(
+        field("test.MessageWithOptionalEnum", "optional_enum")
+            .features()
+            .field_presence(),
+        FieldPresence::Explicit
+    );
+
+    for message in pool
+        .all_messages()
+        .filter(|message| matches!(message.parent_file().name(), "test.proto" | "test2.proto"))
+    {
+        for field in message.fields() {
+            let name = field.full_name();
+            let features = field.features();
+            assert_eq!(
+                field.is_required(),
+                features.field_presence() == FieldPresence::LegacyRequired,
+                "{name}"
+            );
+            if field.is_list()
+                && !matches!(field.kind(), Kind::String | Kind::Bytes | Kind::Message(_))
+            {
+                assert_eq!(
+                    field.is_packed(),
+                    features.repeated_field_encoding() == RepeatedFieldEncoding::Packed,
+                    "{name}"
+                );
+            }
+            if field.kind().as_message().is_some() {
+                assert_eq!(
+                    field.is_group(),
+                    features.message_encoding() == MessageEncoding::Delimited,
+                    "{name}"
+                );
+            } else if !field.is_list()
+                && field
+                    .containing_oneof()
+                    .is_none_or(|oneof| oneof.is_synthetic())
+            {
+                assert_eq!(
+                    field.supports_presence(),
+                    features.field_presence() != FieldPresence::Implicit,
+                    "{name}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_reflect::{DynamicMessage, FieldExtractor, ReflectMessage, Value};
+
+use crate::proto::{complex_type::MyEnum, contains_group, ComplexType, ContainsGroup, Scalars};
+
+#[test]
+fn extract_matches_decode() {
+    let message = ComplexType {
+        string_map: HashMap::from([(
+            "foo".to_owned(),
+            Scalars {
+                double: 1.5,
+                ..Default::default()
+            },
+        )]),
+        nested: Some(Scalars {
+            int32: 42,
+            string: "hello".to_owned(),
+            ..Default::default()
+        }),
+        my_enum: vec![MyEnum::Foo as i32, MyEnum::Neg as i32],
+        enum_map: HashMap::from([(1, MyEnum::Bar as i32)]),
+        ..Default::default()
+    };
+    let bytes = message.encode_to_vec();
+    let dynamic = DynamicMessage::decode(message.descriptor(), bytes.as_slice()).unwrap();
+
+    let paths = [
+        "nested.string",
+        "string_map",
+        "my_enum",
+        "nested",
+        "enum_map",
+        "nested.int32",
+    ];
+    let extractor = FieldExtractor::new(message.descriptor(), paths).unwrap();
+    let values = extractor.extract(bytes.as_slice()).unwrap();
+
+    assert_eq!(values.len(), paths.len());
+    for (path, value) in paths.iter().zip(values) {
+        assert_eq!(
+            value.as_ref(),
+            Some(dynamic.get_path(path).unwrap().as_ref()),
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn extract_missing_field() {
+    let bytes = ComplexType {
+        nested: Some(Scalars::default()),
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let extractor = FieldExtractor::new(
+        ComplexType::default().descriptor(),
+        ["nested", "nested.int32", "optional_enum", "int_map"],
+    )
+    .unwrap();
+    let values = extractor.extract(bytes.as_slice()).unwrap();
+    assert_eq!(
+        values,
+        [
+            Some(Value::Message(Scalars::default().transcode_to_dynamic())),
+            None,
+            None,
+            None,
+        ]
+    );
+}
+
+#[test]
+fn extract_merges_occurrences() {
+    let mut bytes = ComplexType {
+        nested: Some(Scalars {
+            int32: 1,
+            string: "first".to_owned(),
+            ..Default::default()
+        }),
+        my_enum: vec![MyEnum::Foo as i32],
+        ..Default::default()
+    }
+    .encode_to_vec();
+    ComplexType {
+        nested: Some(Scalars {
+            int32: 2,
+            ..Default::default()
+        }),
+        my_enum: vec![MyEnum::Bar as i32],
+        ..Default::default()
+    }
+    .encode(&mut bytes)
+    .unwrap();
+
+    let extractor = FieldExtractor::new(
+        ComplexType::default().descriptor(),
+        ["nested.int32", "nested.string", "my_enum"],
+    )
+    .unwrap();
+    let values = extractor.extract(bytes.as_slice()).unwrap();
+    assert_eq!(
+        values,
+        [
+            Some(Value::I32(2)),
+            Some(Value::String("first".to_owned())),
+            Some(Value::List(vec![
+                Value::EnumNumber(MyEnum::Foo as i32),
+                Value::EnumNumber(MyEnum::Bar as i32),
+            ])),
+        ]
+    );
+}
+
+#[test]
+fn extract_group() {
+    let message = ContainsGroup {
+        requiredgroup: Some(contains_group::RequiredGroup {
+            a: "bar".to_owned(),
+            b: Some(7),
+        }),
+        repeatedgroup: vec![contains_group::RepeatedGroup {
+            e: "hello".to_owned(),
+            f: Some(10),
+        }],
+        ..Default::default()
+    };
+    let bytes = message.encode_to_vec();
+
+    let extractor = FieldExtractor::new(
+        message.descriptor(),
+        ["requiredgroup.b", "requiredgroup.a", "optionalgroup.c"],
+    )
+    .unwrap();
+    let values = extractor.extract(bytes.as_slice()).unwrap();
+    assert_eq!(
+        values,
+        [
+            Some(Value::I32(7)),
+            Some(Value::String("bar".to_owned())),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn extract_invalid_paths() {
+    let desc = ComplexType::default().descriptor();
+
+    assert_eq!(
+        FieldExtractor::new(desc.clone(), ["int_map.string"])
+            .unwrap_err()
+            .to_string(),
+        "the value at 'int_map' is not a message",
+    );
+    assert_eq!(
+        FieldExtractor::new(desc.clone(), ["my_enum[0]"])
+            .unwrap_err()
+            .to_string(),
+        "the value at 'my_enum' is not a message",
+    );
+    assert_eq!(
+        FieldExtractor::new(desc, ["nested.foo"])
+            .unwrap_err()
+            .to_string(),
+        "field 'foo' not found in message 'test.Scalars'",
+    );
+}
+
+#[test]
+fn extract_errors() {
+    let extractor =
+        FieldExtractor::new(ComplexType::default().descriptor(), ["nested.int32"]).unwrap();
+
+    // Truncated nested message.
+    assert!(extractor.extract(b"\x1a\x05\x18\x01".as_ref()).is_err());
+    // Invalid wire type for a nested message.
+    assert!(extractor.extract(b"\x18\x01".as_ref()).is_err());
+    // Unexpected end group tag.
+    assert!(extractor.extract(b"\x0c".as_ref()).is_err());
+    // Other fields are skipped without being decoded.
+    assert_eq!(
+        extractor.extract(b"\x0a\x02\xff\xff".as_ref()).unwrap(),
+        [None]
+    );
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_reflect::{field_mask, field_mask::MergeOptions, DynamicMessage, ReflectMessage, Value};
+use prost_types::FieldMask;
+
+use crate::proto::{complex_type::MyEnum, ComplexType, MessageWithOneof, Scalars};
+
+fn mask(paths: &[&str]) -> FieldMask {
+    FieldMask {
+        paths: paths.iter().map(|&path| path.to_owned()).collect(),
+    }
+}
+
+fn complex(string: &str, int32: i32, map_key: &str, my_enum: &[MyEnum]) -> ComplexType {
+    ComplexType {
+        string_map: HashMap::from([(
+            map_key.to_owned(),
+            Scalars {
+                int32,
+                ..Default::default()
+            },
+        )]),
+        nested: Some(Scalars {
+            string: string.to_owned(),
+            int32,
+            ..Default::default()
+        }),
+        my_enum: my_enum.iter().map(|&value| value as i32).collect(),
+        optional_enum: MyEnum::Foo as i32,
+        ..Default::default()
+    }
+}
+
+fn merge(
+    dst: ComplexType,
+    src: ComplexType,
+    paths: &[&str],
+    options: &MergeOptions,
+) -> ComplexType {
+    let mut dst = dst.transcode_to_dynamic();
+    dst.merge_with_mask(&src.transcode_to_dynamic(), &mask(paths), options)
+        .unwrap();
+    dst.transcode_to().unwrap()
+}
+
+#[test]
+fn validate() {
+    let desc = ComplexType::default().descriptor();
+
+    assert_eq!(
+        field_mask::validate(&mask(&["nested.string", "string_map", "my_enum"]), &desc),
+        Ok(())
+    );
+
+    let err = field_mask::validate(&mask(&["nested.missing"]), &desc).unwrap_err();
+    assert_eq!(err.path(), Some("nested.missing"));
+    assert_eq!(
+        err.to_string(),
+        "invalid field mask path 'nested.missing': field not found in message 'test.Scalars'"
+    );
+
+    let err = field_mask::validate(&mask(&["optional_enum.foo"]), &desc).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid field mask path 'optional_enum.foo': field 'optional_enum' is not a message"
+    );
+
+    let err = field_mask::validate(&mask(&["string_map.key"]), &desc).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid field mask path 'string_map.key': repeated field 'string_map' cannot have subfields"
+    );
+
+    let err = field_mask::validate(&mask(&["nested", ""]), &desc).unwrap_err();
+    assert_eq!(err.path(), Some(""));
+}
+
+#[test]
+fn normalize_union_intersect() {
+    assert_eq!(
+        field_mask::normalize(&mask(&["b.c", "a", "", "b", "a.b", "b.d"])),
+        mask(&["a", "b"])
+    );
+    assert_eq!(
+        field_mask::normalize(&mask(&["b.d", "b.c.e", "b.c"])),
+        mask(&["b.c", "b.d"])
+    );
+
+    assert_eq!(
+        field_mask::union(&mask(&["a.b", "c.d"]), &mask(&["a.c", "c"])),
+        mask(&["a.b", "a.c", "c"])
+    );
+    assert_eq!(
+        field_mask::intersect(&mask(&["a.b", "c", "d"]), &mask(&["a", "c.d", "e"])),
+        mask(&["a.b", "c.d"])
+    );
+    assert_eq!(
+        field_mask::intersect(&mask(&["a.b"]), &mask(&["a.c"])),
+        mask(&[])
+    );
+}
+
+#[test]
+fn apply_mask() {
+    let mut message = complex("foo", 1, "key", &[MyEnum::Bar]).transcode_to_dynamic();
+    message
+        .apply_mask(&mask(&["nested.string", "my_enum"]))
+        .unwrap();
+
+    assert_eq!(
+        message.transcode_to::<ComplexType>().unwrap(),
+        ComplexType {
+            nested: Some(Scalars {
+                string: "foo".to_owned(),
+                ..Default::default()
+            }),
+            my_enum: vec![MyEnum::Bar as i32],
+            ..Default::default()
+        }
+    );
+
+    let original = message.clone();
+    assert!(message.apply_mask(&mask(&["my_enum.foo"])).is_err());
+    assert_eq!(message, original);
+
+    message.apply_mask(&mask(&[])).unwrap();
+    assert_eq!(
+        message,
+        DynamicMessage::new(ComplexType::default().descriptor())
+    );
+}
+
+#[test]
+fn merge_with_mask_default() {
+    let dst = complex("dst", 1, "dst", &[MyEnum::Foo]);
+    let src = complex("src", 0, "src", &[MyEnum::Bar]);
+
+    let actual = merge(
+        dst.clone(),
+        src.clone(),
+        &["nested.string", "nested.int32", "string_map", "my_enum"],
+        &MergeOptions::new(),
+    );
+    assert_eq!(actual.nested.as_ref().unwrap().string, "src");
+    // Unset primitive fields in the source are not copied.
+    assert_eq!(actual.nested.as_ref().unwrap().int32, 1);
+    assert_eq!(actual.string_map.len(), 2);
+    assert_eq!(actual.my_enum, [MyEnum::Foo as i32, MyEnum::Bar as i32]);
+
+    let actual = merge(dst.clone(), src.clone(), &["nested"], &MergeOptions::new());
+    assert_eq!(
+        actual.nested,
+        Some(Scalars {
+            string: "src".to_owned(),
+            int32: 1,
+            ..Default::default()
+        })
+    );
+    assert_eq!(actual.string_map, dst.string_map);
+
+    let actual = merge(
+        ComplexType::default(),
+        src.clone(),
+        &["nested.string"],
+        &MergeOptions::new(),
+    );
+    assert_eq!(
+        actual,
+        ComplexType {
+            nested: Some(Scalars {
+                string: "src".to_owned(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    );
+
+    let actual = merge(
+        ComplexType::default(),
+        ComplexType::default(),
+        &["nested.string"],
+        &MergeOptions::new(),
+    );
+    assert_eq!(actual, ComplexType::default());
+}
+
+#[test]
+fn merge_with_mask_replace() {
+    let dst = complex("dst", 1, "dst", &[MyEnum::Foo]);
+    let src = complex("src", 0, "src", &[MyEnum::Bar]);
+
+    let options = MergeOptions::new()
+        .replace_message_fields(true)
+        .replace_repeated_fields(true)
+        .replace_primitive_fields(true);
+
+    let actual = merge(
+        dst.clone(),
+        src.clone(),
+        &["nested.int32", "string_map", "my_enum"],
+        &options,
+    );
+    assert_eq!(actual.nested.as_ref().unwrap().int32, 0);
+    assert_eq!(actual.nested.as_ref().unwrap().string, "dst");
+    assert_eq!(actual.string_map, src.string_map);
+    assert_eq!(actual.my_enum, src.my_enum);
+
+    let actual = merge(dst.clone(), src.clone(), &["nested"], &options);
+    assert_eq!(actual.nested, src.nested);
+
+    let actual = merge(dst.clone(), ComplexType::default(), &["nested"], &options);
+    assert_eq!(actual.nested, None);
+}
+
+#[test]
+fn merge_with_mask_oneof() {
+    let mut dst = MessageWithOneof {
+        test_oneof: Some(crate::proto::message_with_oneof::TestOneof::OneofField1(
+            "foo".to_owned(),
+        )),
+    }
+    .transcode_to_dynamic();
+    let src = MessageWithOneof {
+        test_oneof: Some(crate::proto::message_with_oneof::TestOneof::OneofField2(5)),
+    }
+    .transcode_to_dynamic();
+
+    dst.merge_with_mask(&src, &mask(&["oneof_field_2"]), &MergeOptions::new())
+        .unwrap();
+    assert!(!dst.has_field_by_name("oneof_field_1"));
+    assert_eq!(
+        dst.get_field_by_name("oneof_field_2").unwrap().as_i32(),
+        Some(5)
+    );
+}
+
+#[test]
+fn merge_with_mask_type_mismatch() {
+    let mut dst = ComplexType::default().transcode_to_dynamic();
+    let src = Scalars::default().transcode_to_dynamic();
+
+    let err = dst
+        .merge_with_mask(&src, &mask(&["nested"]), &MergeOptions::new())
+        .unwrap_err();
+    assert_eq!(err.path(), None);
+    assert_eq!(
+        err.to_string(),
+        "expected a message of type 'test.ComplexType', but found 'test.Scalars'"
+    );
+}
+
+#[test]
+fn merge_with_mask_unknown_fields() {
+    let mut nested_bytes = Scalars {
+        string: "src".to_owned(),
+        ..Default::default()
+    }
+    .encode_to_vec();
+    // Field 1000 with varint value 1, which is not defined for Scalars.
+    nested_bytes.extend_from_slice(&[0xc0, 0x3e, 0x01]);
+    let nested =
+        DynamicMessage::decode(Scalars::default().descriptor(), nested_bytes.as_slice()).unwrap();
+
+    let mut src = ComplexType::default().transcode_to_dynamic();
+    src.set_field_by_name("nested", Value::Message(nested));
+
+    let mut dst = complex("dst", 1, "dst", &[]).transcode_to_dynamic();
+    dst.merge_with_mask(&src, &mask(&["nested"]), &MergeOptions::new())
+        .unwrap();
+
+    let nested = dst.get_field_by_name("nested").unwrap();
+    let nested = nested.as_message().unwrap();
+    assert_eq!(
+        nested.get_field_by_name("string").unwrap().as_str(),
+        Some("src")
+    );
+    assert_eq!(nested.get_field_by_name("int32").unwrap().as_i32(), Some(1));
+    assert_eq!(nested.unknown_fields().count(), 1);
+    assert_eq!(nested.unknown_fields().next().unwrap().number(), 1000);
+}
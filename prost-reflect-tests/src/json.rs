@@ -6,15 +6,18 @@ use std::{
 
 use proptest::{prelude::*, test_runner::TestCaseError};
 use prost::Message;
-use prost_reflect::{DeserializeOptions, DynamicMessage, ReflectMessage, SerializeOptions};
+use prost_reflect::{
+    DeserializeOptions, DynamicMessage, MessageDescriptor, ReflectMessage, SerializeOptions,
+    TypeResolver,
+};
 use prost_types::FileDescriptorSet;
 use serde_json::json;
 
 use crate::{
     arbitrary,
     proto::{
-        contains_group, message_with_oneof, ComplexType, ContainsGroup, MessageWithAliasedEnum,
-        MessageWithOneof, Point, ScalarArrays, Scalars, WellKnownTypes,
+        contains_group, message_with_oneof, ClosedEnums, ComplexType, ContainsGroup,
+        MessageWithAliasedEnum, MessageWithOneof, Point, ScalarArrays, Scalars, WellKnownTypes,
     },
     test_file_descriptor,
 };
@@ -440,6 +443,66 @@ fn serialize_string_skip_default_fields() {
     );
 }
 
+#[test]
+fn serialize_always_print_fields_with_no_presence() {
+    let value = to_json_with_options(
+        &ComplexType::default(),
+        &SerializeOptions::new().always_print_fields_with_no_presence(true),
+    );
+
+    assert_eq!(
+        value,
+        json!({
+            "stringMap": {},
+            "intMap": {},
+            "myEnum": [],
+            "optionalEnum": "DEFAULT",
+            "enumMap": {}
+        })
+    );
+}
+
+#[test]
+fn serialize_use_enum_numbers_for_field() {
+    let desc = ComplexType::default().descriptor();
+    let message = ComplexType {
+        my_enum: vec![1, 3],
+        optional_enum: 3,
+        enum_map: HashMap::from_iter([(1, 1)]),
+        ..Default::default()
+    };
+
+    let value = to_json_with_options(
+        &message,
+        &SerializeOptions::new()
+            .use_enum_numbers_for_field(&desc.get_field_by_name("my_enum").unwrap(), true)
+            .use_enum_numbers_for_field(&desc.get_field_by_name("enum_map").unwrap(), true),
+    );
+    assert_eq!(
+        value,
+        json!({
+            "myEnum": [1, 3],
+            "optionalEnum": "BAR",
+            "enumMap": { "1": 1 },
+        })
+    );
+
+    let value = to_json_with_options(
+        &message,
+        &SerializeOptions::new()
+            .use_enum_numbers(true)
+            .use_enum_numbers_for_field(&desc.get_field_by_name("optional_enum").unwrap(), false),
+    );
+    assert_eq!(
+        value,
+        json!({
+            "myEnum": [1, 3],
+            "optionalEnum": "BAR",
+            "enumMap": { "1": 1 },
+        })
+    );
+}
+
 #[test]
 fn deserialize_scalars() {
     let value: Scalars = from_json(
@@ -729,6 +792,116 @@ fn deserialize_enum_in_map_unknown_value_allow_unknown_fields() {
     assert_eq!(value.enum_map, HashMap::from_iter([(1, 1), (3, 3),]));
 }
 
+#[test]
+fn deserialize_ignore_unknown_enum_values() {
+    let options = DeserializeOptions::new().ignore_unknown_enum_values(true);
+    let value: ComplexType = from_json_with_options(
+        json!({
+            "optionalEnum": "UNKNOWN",
+            "myEnum": ["FOO", "UNKNOWN", 5],
+            "enumMap": {
+                "1": "UNKNOWN",
+                "2": "BAR",
+            },
+        }),
+        ".test.ComplexType",
+        &options,
+    );
+
+    assert_eq!(value.optional_enum, 0);
+    assert_eq!(value.my_enum.as_slice(), &[1, 5]);
+    assert_eq!(value.enum_map, HashMap::from_iter([(2, 3)]));
+
+    // Unknown fields are still rejected.
+    let err =
+        try_from_json_string_with_options(r#"{ "unknown": "FOO" }"#, ".test.ComplexType", &options)
+            .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("unrecognized field name 'unknown'"));
+}
+
+#[test]
+fn deserialize_closed_enum_unknown_number() {
+    let json = r#"{ "singularEnum": 5 }"#;
+
+    let err = try_from_json_string_with_options(json, "test2.ClosedEnums", &Default::default())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("unrecognized enum value 5"));
+
+    let value: ClosedEnums = from_json_with_options(
+        json!({
+            "singularEnum": 5,
+            "unpackedEnums": [2, 5, "OTHER"],
+            "enumMap": {
+                "1": 5,
+                "2": 3,
+            },
+        }),
+        "test2.ClosedEnums",
+        &DeserializeOptions::new().ignore_unknown_enum_values(true),
+    );
+    assert_eq!(
+        value,
+        ClosedEnums {
+            singular_enum: None,
+            unpacked_enums: vec![2, 3],
+            packed_enums: vec![],
+            enum_map: HashMap::from_iter([(2, 3)]),
+        }
+    );
+}
+
+#[test]
+fn deserialize_case_insensitive_enum_names() {
+    let json = r#"{ "optionalEnum": "bar", "myEnum": ["Foo", "NEG"] }"#;
+
+    let err = try_from_json_string_with_options(json, ".test.ComplexType", &Default::default())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("unrecognized enum value 'bar'"));
+
+    let value: ComplexType = from_json_string_with_options(
+        json,
+        ".test.ComplexType",
+        &DeserializeOptions::new().case_insensitive_enum_names(true),
+    );
+    assert_eq!(value.optional_enum, 3);
+    assert_eq!(value.my_enum.as_slice(), &[1, -4]);
+}
+
+#[test]
+fn deserialize_allow_proto_field_names_only() {
+    let options = DeserializeOptions::new().allow_proto_field_names_only(true);
+
+    let value: ComplexType = from_json_with_options(
+        json!({
+            "optional_enum": "FOO",
+            "nested": { "int32": 5 },
+        }),
+        ".test.ComplexType",
+        &options,
+    );
+    assert_eq!(value.optional_enum, 1);
+    assert_eq!(value.nested.unwrap().int32, 5);
+
+    let err = try_from_json_string_with_options(
+        r#"{ "optionalEnum": "FOO" }"#,
+        ".test.ComplexType",
+        &options,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("unrecognized field name 'optionalEnum'"));
+
+    let value: ComplexType = from_json_string_with_options(
+        r#"{ "optionalEnum": "FOO", "unknown": 1 }"#,
+        ".test.ComplexType",
+        &options.deny_unknown_fields(false),
+    );
+    assert_eq!(value.optional_enum, 0);
+}
+
 #[test]
 fn deserialize_array() {
     let value: ScalarArrays = from_json(
@@ -998,6 +1171,186 @@ fn deserialize_any() {
     );
 }
 
+struct PointResolver;
+
+impl TypeResolver for PointResolver {
+    fn resolve_message_type(&self, type_url: &str) -> Option<MessageDescriptor> {
+        (type_url == "example.com/custom.Point").then(|| Point::default().descriptor())
+    }
+}
+
+#[test]
+fn serialize_any_type_resolver() {
+    let mut message = DynamicMessage::new(
+        test_file_descriptor()
+            .get_message_by_name("google.protobuf.Any")
+            .unwrap(),
+    );
+    message
+        .transcode_from(&prost_types::Any {
+            type_url: "example.com/custom.Point".to_owned(),
+            value: Point {
+                longitude: 1,
+                latitude: 2,
+            }
+            .encode_to_vec(),
+        })
+        .unwrap();
+
+    let err = serde_json::to_value(&message).unwrap_err();
+    assert_eq!(err.to_string(), "message 'custom.Point' not found");
+
+    let value = message
+        .serialize_with_options(
+            serde_json::value::Serializer,
+            &SerializeOptions::new().type_resolver(PointResolver),
+        )
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "@type": "example.com/custom.Point",
+            "longitude": 1,
+            "latitude": 2,
+        })
+    );
+}
+
+#[test]
+fn deserialize_any_type_resolver() {
+    let json = r#"{ "@type": "example.com/custom.Point", "longitude": 1, "latitude": 2 }"#;
+
+    let err = try_from_json_string_with_options(json, "google.protobuf.Any", &Default::default())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("message 'custom.Point' not found"));
+
+    let value: prost_types::Any = from_json_string_with_options(
+        json,
+        "google.protobuf.Any",
+        &DeserializeOptions::new().type_resolver(PointResolver),
+    );
+    assert_eq!(
+        value,
+        prost_types::Any {
+            type_url: "example.com/custom.Point".to_owned(),
+            value: Point {
+                longitude: 1,
+                latitude: 2,
+            }
+            .encode_to_vec(),
+        }
+    );
+
+    // Types not known to the resolver are not found, even if they are in the message's pool.
+    let err = try_from_json_string_with_options(
+        r#"{ "@type": "type.googleapis.com/test.Point" }"#,
+        "google.protobuf.Any",
+        &DeserializeOptions::new().type_resolver(PointResolver),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("message 'test.Point' not found"));
+}
+
+#[test]
+fn serialize_any_unresolved_as_bytes() {
+    let mut message = DynamicMessage::new(
+        test_file_descriptor()
+            .get_message_by_name("google.protobuf.Any")
+            .unwrap(),
+    );
+    message
+        .transcode_from(&prost_types::Any {
+            type_url: "example.com/custom.Unknown".to_owned(),
+            value: b"\x08\x01".to_vec(),
+        })
+        .unwrap();
+
+    let value = message
+        .serialize_with_options(
+            serde_json::value::Serializer,
+            &SerializeOptions::new().unresolved_any_as_bytes(true),
+        )
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "@type": "example.com/custom.Unknown",
+            "value": "CAE=",
+        })
+    );
+
+    // Types which can be resolved are still expanded.
+    message
+        .transcode_from(&prost_types::Any {
+            type_url: "type.googleapis.com/test.Point".to_owned(),
+            value: Point {
+                longitude: 1,
+                latitude: 2,
+            }
+            .encode_to_vec(),
+        })
+        .unwrap();
+    let value = message
+        .serialize_with_options(
+            serde_json::value::Serializer,
+            &SerializeOptions::new().unresolved_any_as_bytes(true),
+        )
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "@type": "type.googleapis.com/test.Point",
+            "longitude": 1,
+            "latitude": 2,
+        })
+    );
+}
+
+#[test]
+fn deserialize_any_unresolved_as_bytes() {
+    let options = DeserializeOptions::new().unresolved_any_as_bytes(true);
+    let expected = prost_types::Any {
+        type_url: "example.com/custom.Unknown".to_owned(),
+        value: b"\x08\x01".to_vec(),
+    };
+
+    let value: prost_types::Any = from_json_string_with_options(
+        r#"{ "@type": "example.com/custom.Unknown", "value": "CAE=" }"#,
+        "google.protobuf.Any",
+        &options,
+    );
+    assert_eq!(value, expected);
+
+    let value: prost_types::Any = from_json_string_with_options(
+        r#"{ "value": "CAE=", "@type": "example.com/custom.Unknown" }"#,
+        "google.protobuf.Any",
+        &options,
+    );
+    assert_eq!(value, expected);
+
+    let err = try_from_json_string_with_options(
+        r#"{ "@type": "example.com/custom.Unknown", "value": "CAE=", "foo": 1 }"#,
+        "google.protobuf.Any",
+        &options,
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("unrecognized field name 'foo'"));
+
+    let err = try_from_json_string_with_options(
+        r#"{ "@type": "example.com/custom.Unknown", "value": "CAE=" }"#,
+        "google.protobuf.Any",
+        &Default::default(),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("message 'custom.Unknown' not found"));
+}
+
 #[test]
 fn deserialize_any_buffer_fields() {
     let value: prost_types::Any = from_json(
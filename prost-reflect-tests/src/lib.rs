@@ -6,11 +6,33 @@ mod arbitrary;
 #[cfg(test)]
 mod decode;
 #[cfg(test)]
+mod decode_options;
+#[cfg(test)]
 mod desc;
 #[cfg(test)]
+mod deterministic;
+#[cfg(test)]
+mod diff;
+#[cfg(test)]
+mod editions;
+#[cfg(test)]
+mod extract;
+#[cfg(test)]
+mod field_mask;
+#[cfg(test)]
 mod json;
 #[cfg(test)]
+mod parse;
+#[cfg(test)]
+mod path;
+#[cfg(test)]
+mod required;
+#[cfg(test)]
+mod stream;
+#[cfg(test)]
 mod text_format;
+#[cfg(test)]
+mod view;
 
 pub mod proto {
     #![allow(clippy::all)]
@@ -0,0 +1,257 @@
+use std::{collections::HashMap, io};
+
+use prost::Message;
+use prost_reflect::{DescriptorPool, FileResolver, IncludePaths};
+use prost_types::{DescriptorProto, FileDescriptorProto};
+
+const PARSE_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/parse.bin"));
+
+const FILES: &[&str] = &[
+    "test.proto",
+    "test2.proto",
+    "desc.proto",
+    "desc2.proto",
+    "desc_no_package.proto",
+    "imports.proto",
+    "ext.proto",
+    "options.proto",
+    "editions2023.proto",
+    "editions2024.proto",
+    "parse.proto",
+];
+
+/// Compiles the test files, using the well-known types bundled with protoc, which may be newer than
+/// the built-in definitions.
+fn compile_pool() -> DescriptorPool {
+    let mut pool = well_known_types_pool();
+    pool.compile(FILES, &IncludePaths::new(["src"])).unwrap();
+    pool
+}
+
+fn well_known_types_pool() -> DescriptorPool {
+    let protoc_pool = DescriptorPool::decode(PARSE_BYTES).unwrap();
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(
+        protoc_pool
+            .files()
+            .filter(|file| file.name().starts_with("google/protobuf/"))
+            .map(|file| file.file_descriptor_proto().clone()),
+    )
+    .unwrap();
+    pool
+}
+
+/// protoc always populates the JSON name of fields, whereas the parser only sets it when given
+/// explicitly.
+fn clear_json_names(file: &mut FileDescriptorProto) {
+    fn clear_message(message: &mut DescriptorProto) {
+        for field in message.field.iter_mut().chain(&mut message.extension) {
+            field.json_name = None;
+        }
+        message.nested_type.iter_mut().for_each(clear_message);
+    }
+
+    for field in &mut file.extension {
+        field.json_name = None;
+    }
+    file.message_type.iter_mut().for_each(clear_message);
+}
+
+/// Removes differences expected after printing a file: source info is lost, and group messages
+/// are declared alongside their field rather than in their original position.
+fn normalize_printed(file: &mut FileDescriptorProto) {
+    fn sort_messages(messages: &mut [DescriptorProto]) {
+        messages.sort_by(|l, r| l.name.cmp(&r.name));
+        for message in messages {
+            sort_messages(&mut message.nested_type);
+        }
+    }
+
+    file.source_code_info = None;
+    clear_json_names(file);
+    sort_messages(&mut file.message_type);
+}
+
+#[test]
+fn compile_matches_protoc() {
+    let expected_pool = DescriptorPool::decode(PARSE_BYTES).unwrap();
+    let actual_pool = compile_pool();
+
+    for &name in FILES {
+        let expected = expected_pool.get_file_by_name(name).unwrap();
+        let actual = actual_pool.get_file_by_name(name).unwrap();
+        assert_eq!(actual.syntax(), expected.syntax(), "{name}");
+
+        let mut expected = expected.file_descriptor_proto().clone();
+        let mut actual = actual.file_descriptor_proto().clone();
+        clear_json_names(&mut expected);
+        clear_json_names(&mut actual);
+        assert_eq!(actual, expected, "{name}");
+    }
+}
+
+#[test]
+fn compile_resolved_features() {
+    let pool = compile_pool();
+
+    let message = pool.get_message_by_name("test.editions.Message").unwrap();
+    let field = |name: &str| message.get_field_by_name(name).unwrap();
+    assert!(!field("implicit").supports_presence());
+    assert!(!field("expanded").is_packed());
+    assert!(field("packed").is_packed());
+    assert!(field("delimited").is_group());
+
+    let message = pool.get_message_by_name("test.parse.Message").unwrap();
+    assert!(message
+        .get_field_by_name("packed_field")
+        .unwrap()
+        .is_packed());
+}
+
+#[test]
+fn compile_json_names() {
+    let pool = compile_pool();
+
+    let message = pool.get_message_by_name("test.parse.Message").unwrap();
+    assert_eq!(
+        message.get_field_by_name("int_field").unwrap().json_name(),
+        "intField"
+    );
+    assert_eq!(
+        message
+            .get_field_by_name("string_field")
+            .unwrap()
+            .json_name(),
+        "str"
+    );
+}
+
+#[test]
+fn include_paths_reject_names_outside_include_dirs() {
+    let resolver = IncludePaths::new(["src"]);
+    assert!(resolver.read_file("test.proto").is_ok());
+    for name in ["../build.rs", "google/../../build.rs", "/etc/hosts"] {
+        let err = resolver.read_file(name).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{name}");
+    }
+}
+
+#[test]
+fn print_round_trip() {
+    let expected_pool = DescriptorPool::decode(PARSE_BYTES).unwrap();
+    let sources: HashMap<String, String> = expected_pool
+        .files()
+        .filter(|file| !file.name().starts_with("google/protobuf/"))
+        .map(|file| (file.name().to_owned(), file.to_proto_source()))
+        .collect();
+
+    let mut actual_pool = well_known_types_pool();
+    actual_pool
+        .compile(FILES, &|name: &str| {
+            sources
+                .get(name)
+                .cloned()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        })
+        .unwrap_or_else(|err| panic!("{err:?}"));
+
+    for (name, source) in &sources {
+        let expected = expected_pool.get_file_by_name(name).unwrap();
+        let actual = actual_pool.get_file_by_name(name).unwrap();
+        assert_eq!(&actual.to_proto_source(), source, "{name}");
+
+        let mut expected = expected.file_descriptor_proto().clone();
+        let mut actual = actual.file_descriptor_proto().clone();
+        normalize_printed(&mut expected);
+        normalize_printed(&mut actual);
+        assert_eq!(actual, expected, "{name}");
+    }
+}
+
+#[test]
+fn pruned_file_descriptor_set_source_info() {
+    macro_rules! assert_source_eq {
+        ($actual:expr, $expected:expr) => {{
+            let (actual, expected) = (&$actual, &$expected);
+            assert_eq!(
+                (
+                    actual.span(),
+                    actual.leading_comments(),
+                    actual.trailing_comments()
+                ),
+                (
+                    expected.span(),
+                    expected.leading_comments(),
+                    expected.trailing_comments()
+                ),
+                "{}",
+                actual.full_name(),
+            );
+        }};
+    }
+
+    let pool = DescriptorPool::decode(PARSE_BYTES).unwrap();
+    let roots = pool
+        .all_messages()
+        .map(|message| message.full_name().to_owned())
+        .chain(
+            pool.services()
+                .map(|service| service.full_name().to_owned()),
+        )
+        .filter(|name| !name.starts_with("google.protobuf."));
+
+    for root in roots {
+        let bytes = pool.encode_pruned_file_descriptor_set([&root]).unwrap();
+        let pruned = DescriptorPool::decode(bytes.as_slice())
+            .unwrap_or_else(|err| panic!("{root}: {err:?}"));
+        assert!(pruned.files().len() <= pool.files().len());
+
+        for file in pruned.files() {
+            let expected = pool.get_file_by_name(file.name()).unwrap();
+            assert_eq!(file.syntax(), expected.syntax(), "{}", file.name());
+            assert_eq!(
+                file.options().encode_to_vec(),
+                expected.options().encode_to_vec(),
+                "{}",
+                file.name()
+            );
+        }
+
+        for message in pruned.all_messages() {
+            let expected = pool.get_message_by_name(message.full_name()).unwrap();
+            assert_source_eq!(message, expected);
+            assert_eq!(
+                message.options().encode_to_vec(),
+                expected.options().encode_to_vec()
+            );
+            for field in message.fields() {
+                let expected = expected.get_field(field.number()).unwrap();
+                assert_source_eq!(field, expected);
+                assert_eq!(
+                    field.options().encode_to_vec(),
+                    expected.options().encode_to_vec(),
+                    "{}",
+                    field.full_name()
+                );
+            }
+        }
+        for enum_ in pruned.all_enums() {
+            let expected = pool.get_enum_by_name(enum_.full_name()).unwrap();
+            assert_source_eq!(enum_, expected);
+            for value in enum_.values() {
+                assert_source_eq!(value, expected.get_value_by_name(value.name()).unwrap());
+            }
+        }
+        for extension in pruned.all_extensions() {
+            let expected = pool.get_extension_by_name(extension.full_name()).unwrap();
+            assert_source_eq!(extension, expected);
+        }
+        for service in pruned.services() {
+            let expected = pool.get_service_by_name(service.full_name()).unwrap();
+            assert_source_eq!(service, expected);
+            for (method, expected) in service.methods().zip(expected.methods()) {
+                assert_source_eq!(method, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldPath, MapKey, PathError, PathSegment, ReflectMessage,
+    Value,
+};
+
+use crate::proto::{ComplexType, Scalars};
+
+const SOURCE: &str = r#"
+    syntax = "proto2";
+
+    package paths;
+
+    message Maps {
+        map<bool, int32> bool_map = 1;
+        map<int32, int32> int32_map = 2;
+        map<sint64, int32> int64_map = 3;
+        map<fixed32, int32> uint32_map = 4;
+        map<uint64, int32> uint64_map = 5;
+        map<string, Maps> string_map = 6;
+        repeated Maps list = 7;
+        optional int32 value = 8;
+
+        extensions 100 to 200;
+    }
+
+    extend Maps {
+        repeated Maps ext_list = 100;
+        optional int32 ext_value = 101;
+    }
+"#;
+
+fn maps() -> DynamicMessage {
+    static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+    let pool = POOL.get_or_init(|| {
+        let mut pool = DescriptorPool::new();
+        pool.add_proto_source("paths.proto", SOURCE).unwrap();
+        pool
+    });
+    DynamicMessage::new(pool.get_message_by_name("paths.Maps").unwrap())
+}
+
+fn complex() -> DynamicMessage {
+    ComplexType {
+        string_map: HashMap::from([(
+            "foo".to_owned(),
+            Scalars {
+                int32: 1,
+                ..Default::default()
+            },
+        )]),
+        int_map: HashMap::from([(-3, Scalars::default())]),
+        nested: Some(Scalars {
+            string: "hello".to_owned(),
+            ..Default::default()
+        }),
+        my_enum: vec![1, 3],
+        ..Default::default()
+    }
+    .transcode_to_dynamic()
+}
+
+#[test]
+fn parse_round_trip() {
+    let desc = maps().descriptor();
+    for path in [
+        "value",
+        "list[0].list[12].value",
+        "bool_map[true]",
+        "int32_map[-5]",
+        "int64_map[-9223372036854775808]",
+        "uint32_map[4294967295]",
+        "uint64_map[18446744073709551615]",
+        "string_map[\"a.b[c]\"].string_map[\"\\n\\\"\\303\\251\"]",
+        "[paths.ext_value]",
+        "list[1].[paths.ext_list][0].value",
+    ] {
+        assert_eq!(FieldPath::parse(&desc, path).unwrap().to_string(), path);
+    }
+
+    let path = FieldPath::parse(&desc, "string_map['\\x41é'].[.paths.ext_value]").unwrap();
+    assert_eq!(
+        path.to_string(),
+        "string_map[\"A\\303\\251\"].[paths.ext_value]"
+    );
+    assert_eq!(
+        path.segments()[1],
+        PathSegment::Key(MapKey::String("Aé".to_owned()))
+    );
+    assert_eq!(
+        path.segments()[2],
+        PathSegment::Extension(desc.get_extension_by_full_name("paths.ext_value").unwrap())
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let desc = maps().descriptor();
+    let parse = |path: &str| FieldPath::parse(&desc, path).unwrap_err();
+
+    assert_eq!(
+        parse(""),
+        PathError::InvalidSyntax {
+            path: "".to_owned(),
+            position: 0
+        }
+    );
+    assert_eq!(
+        parse("list[0]value"),
+        PathError::InvalidSyntax {
+            path: "list[0]value".to_owned(),
+            position: 7
+        }
+    );
+    assert_eq!(
+        parse("list[-1]"),
+        PathError::InvalidSyntax {
+            path: "list[-1]".to_owned(),
+            position: 5
+        }
+    );
+    assert_eq!(
+        parse("string_map[\"foo"),
+        PathError::InvalidSyntax {
+            path: "string_map[\"foo".to_owned(),
+            position: 15
+        }
+    );
+    assert_eq!(
+        parse("list.value").to_string(),
+        "the value at 'list' is not a message"
+    );
+    assert_eq!(
+        parse("value.value").to_string(),
+        "the value at 'value' is not a message"
+    );
+    assert_eq!(
+        parse("value[0]").to_string(),
+        "the value at 'value' is not a list or map"
+    );
+    assert_eq!(
+        parse("list[0].missing").to_string(),
+        "field 'missing' not found in message 'paths.Maps'"
+    );
+    assert_eq!(
+        parse("[paths.missing]").to_string(),
+        "extension 'paths.missing' not found for message 'paths.Maps'"
+    );
+    assert_eq!(
+        parse("bool_map[1]").to_string(),
+        "invalid key '1' for the map at 'bool_map'"
+    );
+    assert_eq!(
+        parse("uint32_map[-1]").to_string(),
+        "invalid key '-1' for the map at 'uint32_map'"
+    );
+    assert_eq!(
+        parse("string_map[foo]").to_string(),
+        "invalid key 'foo' for the map at 'string_map'"
+    );
+}
+
+#[test]
+fn get_path() {
+    let message = complex();
+
+    assert_eq!(
+        message.get_path("nested.string").unwrap().as_str(),
+        Some("hello")
+    );
+    assert_eq!(message.get_path("nested.int32").unwrap().as_i32(), Some(0));
+    assert_eq!(
+        message
+            .get_path("string_map[\"foo\"].int32")
+            .unwrap()
+            .as_i32(),
+        Some(1)
+    );
+    assert_eq!(
+        message.get_path("int_map[-3]").unwrap().as_message(),
+        Some(&Scalars::default().transcode_to_dynamic())
+    );
+    assert_eq!(
+        message.get_path("my_enum[1]").unwrap().as_enum_number(),
+        Some(3)
+    );
+    assert_eq!(
+        message.get_path("my_enum").unwrap().as_list(),
+        Some([Value::EnumNumber(1), Value::EnumNumber(3)].as_ref())
+    );
+
+    assert_eq!(
+        message.get_path("my_enum[2]"),
+        Err(PathError::IndexOutOfBounds {
+            path: "my_enum[2]".to_owned(),
+            len: 2,
+        })
+    );
+    assert_eq!(
+        message.get_path("string_map[\"bar\"].int32"),
+        Err(PathError::KeyNotFound {
+            path: "string_map[\"bar\"]".to_owned(),
+        })
+    );
+    assert_eq!(
+        message
+            .get_path("string_map[\"bar\"].int32")
+            .unwrap_err()
+            .to_string(),
+        "map key not found at 'string_map[\"bar\"]'"
+    );
+}
+
+#[test]
+fn get_path_unset() {
+    let message = maps();
+
+    assert_eq!(message.get_path("value").unwrap().as_i32(), Some(0));
+    assert_eq!(
+        message.get_path("string_map[\"a\"].value"),
+        Err(PathError::KeyNotFound {
+            path: "string_map[\"a\"]".to_owned(),
+        })
+    );
+    assert_eq!(
+        message.get_path("list[0]"),
+        Err(PathError::IndexOutOfBounds {
+            path: "list[0]".to_owned(),
+            len: 0,
+        })
+    );
+    assert_eq!(
+        message.get_path("[paths.ext_value]").unwrap().as_i32(),
+        Some(0)
+    );
+    assert_eq!(
+        message.get_path("[paths.ext_list]").unwrap().as_list(),
+        Some([].as_ref())
+    );
+}
+
+#[test]
+fn set_path() {
+    let mut message = maps();
+
+    message.set_path("value", Value::I32(1)).unwrap();
+    message.set_path("bool_map[true]", Value::I32(2)).unwrap();
+    message.set_path("int32_map[-1]", Value::I32(3)).unwrap();
+    message.set_path("int64_map[-2]", Value::I32(4)).unwrap();
+    message.set_path("uint32_map[3]", Value::I32(5)).unwrap();
+    message.set_path("uint64_map[4]", Value::I32(6)).unwrap();
+    message
+        .set_path("string_map[\"a\"].string_map[\"b\"].value", Value::I32(7))
+        .unwrap();
+    message
+        .set_path("[paths.ext_value]", Value::I32(8))
+        .unwrap();
+    message
+        .set_path(
+            "list",
+            Value::List(vec![Value::Message(maps()), Value::Message(maps())]),
+        )
+        .unwrap();
+    message.set_path("list[1].value", Value::I32(9)).unwrap();
+
+    assert_eq!(
+        message.to_string(),
+        "bool_map:[{key:true,value:2}],int32_map:[{key:-1,value:3}],int64_map:[{key:-2,value:4}],\
+         uint32_map:[{key:3,value:5}],uint64_map:[{key:4,value:6}],\
+         string_map:[{key:\"a\",value{string_map:[{key:\"b\",value{value:7}}]}}],list:[{},{value:9}],\
+         value:1,[paths.ext_value]:8"
+    );
+
+    message
+        .set_path(
+            "list[0]",
+            Value::Message({
+                let mut element = maps();
+                element.set_field_by_name("value", Value::I32(10));
+                element
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        message.get_path("list[0].value").unwrap().as_i32(),
+        Some(10)
+    );
+}
+
+#[test]
+fn set_path_errors() {
+    let mut message = maps();
+    let original = message.clone();
+
+    assert_eq!(
+        message.set_path("list[0].value", Value::I32(1)),
+        Err(PathError::IndexOutOfBounds {
+            path: "list[0]".to_owned(),
+            len: 0,
+        })
+    );
+    assert_eq!(
+        message.set_path("string_map[\"a\"].list[0]", Value::Message(maps())),
+        Err(PathError::IndexOutOfBounds {
+            path: "string_map[\"a\"].list[0]".to_owned(),
+            len: 0,
+        })
+    );
+    assert_eq!(
+        message.set_path("value", Value::U32(1)),
+        Err(PathError::InvalidType {
+            path: "value".to_owned(),
+            value: Value::U32(1),
+        })
+    );
+    assert_eq!(
+        message
+            .set_path("int32_map[1]", Value::String("foo".to_owned()))
+            .unwrap_err()
+            .to_string(),
+        "the value '\"foo\"' is not valid for 'int32_map[1]'"
+    );
+    assert_eq!(
+        message.set_path("list", Value::List(vec![Value::I32(1)])),
+        Err(PathError::InvalidType {
+            path: "list".to_owned(),
+            value: Value::List(vec![Value::I32(1)]),
+        })
+    );
+    assert!(matches!(
+        message.set_path(
+            "list[0]",
+            Value::Message(ComplexType::default().transcode_to_dynamic())
+        ),
+        Err(PathError::InvalidType { .. })
+    ));
+    assert_eq!(message, original);
+}
+
+#[test]
+fn clear_path() {
+    let mut message = complex();
+
+    message.clear_path("nested.string").unwrap();
+    assert_eq!(
+        message.get_path("nested").unwrap().as_message(),
+        Some(&Scalars::default().transcode_to_dynamic())
+    );
+    assert!(message.has_field_by_name("nested"));
+
+    message.clear_path("my_enum[0]").unwrap();
+    assert_eq!(
+        message.get_path("my_enum").unwrap().as_list(),
+        Some([Value::EnumNumber(3)].as_ref())
+    );
+
+    message.clear_path("string_map[\"foo\"]").unwrap();
+    message.clear_path("string_map[\"bar\"]").unwrap();
+    message.clear_path("int_map[-3].int32").unwrap();
+    message.clear_path("int_map[4].int32").unwrap();
+    assert_eq!(
+        message.get_path("string_map").unwrap().as_map(),
+        Some(&HashMap::new())
+    );
+    assert_eq!(
+        message.get_path("int_map").unwrap().as_map().unwrap().len(),
+        1
+    );
+
+    message.clear_path("nested").unwrap();
+    message.clear_path("nested.string").unwrap();
+    assert!(!message.has_field_by_name("nested"));
+
+    let original = message.clone();
+    assert_eq!(
+        message.clear_path("my_enum[1]"),
+        Err(PathError::IndexOutOfBounds {
+            path: "my_enum[1]".to_owned(),
+            len: 1,
+        })
+    );
+    assert_eq!(message, original);
+}
+
+#[test]
+fn clear_path_extension() {
+    let mut message = maps();
+    message
+        .set_path("[paths.ext_value]", Value::I32(1))
+        .unwrap();
+    message
+        .set_path(
+            "[paths.ext_list]",
+            Value::List(vec![Value::Message(maps())]),
+        )
+        .unwrap();
+    message
+        .set_path("[paths.ext_list][0].value", Value::I32(2))
+        .unwrap();
+    assert_eq!(
+        message.to_string(),
+        "[paths.ext_list]:[{value:2}],[paths.ext_value]:1"
+    );
+
+    message.clear_path("[paths.ext_list][0].value").unwrap();
+    message.clear_path("[paths.ext_value]").unwrap();
+    assert_eq!(message.to_string(), "[paths.ext_list]:[{}]");
+}
@@ -0,0 +1,135 @@
+use prost::Message;
+use prost_reflect::{
+    text_format::ParseOptions, DecodeOptions, DeserializeOptions, DynamicMessage,
+    MessageDecodeError, MessageDescriptor,
+};
+
+use crate::test_file_descriptor;
+
+fn required_fields() -> MessageDescriptor {
+    test_file_descriptor()
+        .get_message_by_name("test2.RequiredFields")
+        .unwrap()
+}
+
+fn missing_required_fields(message: &DynamicMessage) -> Vec<String> {
+    message
+        .missing_required_fields()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn initialized() {
+    let message =
+        DynamicMessage::parse_text_format(required_fields(), "id: 1\nchildren { id: 2 }").unwrap();
+    assert!(message.is_initialized());
+    assert!(message.missing_required_fields().is_empty());
+
+    let message = DynamicMessage::new(required_fields());
+    assert!(!message.is_initialized());
+    assert_eq!(missing_required_fields(&message), ["id"]);
+}
+
+#[test]
+fn missing_nested_required_fields() {
+    let message = DynamicMessage::parse_text_format(
+        required_fields(),
+        r#"
+            id: 1
+            child {}
+            children { id: 2 }
+            children {}
+            child_map { key: "b" value {} }
+            child_map { key: "a" value { id: 3 child {} } }
+            [test2.child_ext] {}
+        "#,
+    )
+    .unwrap();
+
+    assert!(!message.is_initialized());
+    assert_eq!(
+        missing_required_fields(&message),
+        [
+            "child.id",
+            "children[1].id",
+            "child_map[\"a\"].child.id",
+            "child_map[\"b\"].id",
+            "[test2.child_ext].id",
+        ]
+    );
+}
+
+#[test]
+fn decode_allow_partial() {
+    let message =
+        DynamicMessage::parse_text_format(required_fields(), "id: 1\nchildren {}").unwrap();
+    let bytes = message.encode_to_vec();
+
+    let decoded = DynamicMessage::decode_with_options(
+        required_fields(),
+        bytes.as_slice(),
+        &DecodeOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(decoded, message);
+
+    let options = DecodeOptions::new().allow_partial(false);
+    let err = DynamicMessage::decode_with_options(required_fields(), bytes.as_slice(), &options)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        MessageDecodeError::MissingRequiredFields {
+            paths: vec!["children[0].id".to_owned()],
+        }
+    );
+    assert_eq!(err.to_string(), "missing required fields: 'children[0].id'");
+
+    assert!(
+        DynamicMessage::decode_with_options(required_fields(), b"\x08\x01".as_ref(), &options)
+            .is_ok()
+    );
+}
+
+#[test]
+fn deserialize_allow_partial() {
+    let json = r#"{ "id": 1, "child": {} }"#;
+
+    let mut de = serde_json::Deserializer::from_str(json);
+    assert!(DynamicMessage::deserialize_with_options(
+        required_fields(),
+        &mut de,
+        &DeserializeOptions::new()
+    )
+    .is_ok());
+
+    let mut de = serde_json::Deserializer::from_str(json);
+    let err = DynamicMessage::deserialize_with_options(
+        required_fields(),
+        &mut de,
+        &DeserializeOptions::new().allow_partial(false),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "missing required fields: 'child.id'");
+}
+
+#[test]
+fn parse_text_format_allow_partial() {
+    let options = ParseOptions::new().allow_partial(false);
+
+    assert!(
+        DynamicMessage::parse_text_format_with_options(required_fields(), "id: 1", &options)
+            .is_ok()
+    );
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            required_fields(),
+            "id: 1\nchild {}",
+            &options
+        )
+        .unwrap_err()
+        .to_string(),
+        "required field 'id' is not set for message 'test2.RequiredFields'"
+    );
+}
@@ -0,0 +1,168 @@
+use std::io;
+
+use prost::{bytes::Bytes, Message};
+use prost_reflect::{
+    stream::{LengthDelimitedIter, LengthDelimitedReader, LengthDelimitedWriter, StreamError},
+    DynamicMessage, ReflectMessage,
+};
+
+use crate::proto::{ComplexType, Scalars};
+
+fn sample_messages() -> Vec<DynamicMessage> {
+    vec![
+        Scalars {
+            int32: 5,
+            string: "hello".to_owned(),
+            ..Default::default()
+        }
+        .transcode_to_dynamic(),
+        Scalars::default().transcode_to_dynamic(),
+        Scalars {
+            bytes: vec![0; 300],
+            ..Default::default()
+        }
+        .transcode_to_dynamic(),
+    ]
+}
+
+#[test]
+fn decode_length_delimited() {
+    let message = ComplexType {
+        nested: Some(Scalars {
+            int32: 42,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mut bytes = message.encode_length_delimited_to_vec();
+    bytes.extend_from_slice(b"\x01\x02");
+
+    let mut buf = bytes.as_slice();
+    let dynamic = DynamicMessage::decode_length_delimited(message.descriptor(), &mut buf).unwrap();
+    assert_eq!(dynamic, message.transcode_to_dynamic());
+    assert_eq!(buf, b"\x01\x02");
+}
+
+#[test]
+fn write_and_read_stream() {
+    let messages = sample_messages();
+
+    let mut writer = LengthDelimitedWriter::new(Vec::new());
+    let offsets: Vec<u64> = messages
+        .iter()
+        .map(|message| writer.write(message).unwrap())
+        .collect();
+    assert_eq!(offsets, [0, 10, 11]);
+    assert_eq!(writer.offset(), 316);
+
+    let bytes = writer.into_inner();
+    let mut expected = Vec::new();
+    for message in &messages {
+        message.encode_length_delimited(&mut expected).unwrap();
+    }
+    assert_eq!(bytes, expected);
+
+    let mut reader = LengthDelimitedReader::new(Scalars::default().descriptor(), bytes.as_slice());
+    for message in &messages {
+        assert_eq!(&reader.next().unwrap().unwrap(), message);
+    }
+    assert!(reader.next().is_none());
+    assert_eq!(reader.offset(), 316);
+
+    let mut iter =
+        LengthDelimitedIter::new(Scalars::default().descriptor(), Bytes::from(bytes.clone()));
+    for message in &messages {
+        assert_eq!(&iter.next().unwrap().unwrap(), message);
+    }
+    assert!(iter.next().is_none());
+    assert_eq!(iter.offset(), 316);
+}
+
+#[test]
+fn read_empty_stream() {
+    let desc = Scalars::default().descriptor();
+
+    assert!(LengthDelimitedReader::new(desc.clone(), io::empty())
+        .next()
+        .is_none());
+    assert!(LengthDelimitedIter::new(desc, Bytes::new())
+        .next()
+        .is_none());
+}
+
+#[test]
+fn read_truncated_stream() {
+    let desc = Scalars::default().descriptor();
+    let mut bytes = Vec::new();
+    LengthDelimitedWriter::new(&mut bytes)
+        .write(&sample_messages()[0])
+        .unwrap();
+
+    // Truncated length prefix.
+    let mut input = bytes.clone();
+    input.push(0x80);
+    let results: Vec<_> = LengthDelimitedReader::new(desc.clone(), input.as_slice()).collect();
+    assert_eq!(results.len(), 2);
+    let err = results[1].as_ref().unwrap_err();
+    assert!(matches!(err, StreamError::UnexpectedEof { offset: 10 }));
+
+    // Truncated message.
+    let mut input = bytes.clone();
+    input.extend_from_slice(b"\x05\x18\x01");
+    let err = LengthDelimitedIter::new(desc, input.as_slice())
+        .nth(1)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.offset(), 10);
+    assert_eq!(
+        err.to_string(),
+        "unexpected end of stream in record at offset 10"
+    );
+}
+
+#[test]
+fn read_invalid_record() {
+    let desc = Scalars::default().descriptor();
+
+    // An empty record followed by a record with an invalid wire type.
+    let input = b"\x00\x02\x1f\x00\x00";
+    let mut reader = LengthDelimitedReader::new(desc.clone(), input.as_ref());
+    assert_eq!(
+        reader.next().unwrap().unwrap(),
+        DynamicMessage::new(desc.clone())
+    );
+    let err = reader.next().unwrap().unwrap_err();
+    assert!(matches!(err, StreamError::Decode { offset: 1, .. }));
+    assert!(err
+        .to_string()
+        .starts_with("failed to decode record at offset 1: "));
+    // Iteration stops after an error.
+    assert!(reader.next().is_none());
+
+    // A length prefix longer than ten bytes.
+    let input = b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01";
+    let err = LengthDelimitedIter::new(desc, input.as_ref())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(err, StreamError::Decode { offset: 0, .. }));
+}
+
+#[test]
+fn read_io_error() {
+    struct FailingReader;
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("oh no"))
+        }
+    }
+
+    let bytes = io::Read::chain(&b"\x00"[..], FailingReader);
+    let mut reader = LengthDelimitedReader::new(Scalars::default().descriptor(), bytes);
+    assert!(reader.next().unwrap().is_ok());
+
+    let err = reader.next().unwrap().unwrap_err();
+    assert!(matches!(err, StreamError::Io { offset: 1, .. }));
+    assert_eq!(err.to_string(), "failed to read record at offset 1: oh no");
+}
@@ -6,12 +6,15 @@ use std::{
 
 use proptest::prelude::*;
 use prost::Message;
-use prost_reflect::{text_format::FormatOptions, DynamicMessage, ReflectMessage, Value};
+use prost_reflect::{
+    text_format::{self, Document, FormatOptions, ParseOptions},
+    DynamicMessage, MessageDescriptor, ReflectMessage, TypeResolver, Value,
+};
 
 use crate::{
     proto::{
-        contains_group, ComplexType, ContainsGroup, IndexOrder, MessageWithAliasedEnum, Point,
-        ScalarArrays, Scalars, WellKnownTypes,
+        contains_group, ClosedEnums, ComplexType, ContainsGroup, IndexOrder,
+        MessageWithAliasedEnum, Point, ScalarArrays, Scalars, WellKnownTypes,
     },
     test_file_descriptor,
 };
@@ -321,6 +324,27 @@ fn fmt_any_invalid_bytes() {
     );
 }
 
+#[test]
+fn fmt_any_type_resolver() {
+    let value = transcode_any(&prost_types::Any {
+        type_url: "example.com/custom.Point".to_owned(),
+        value: Point {
+            longitude: 1,
+            latitude: 2,
+        }
+        .encode_to_vec(),
+    });
+
+    assert_eq!(
+        value.to_text_format(),
+        "type_url:\"example.com/custom.Point\",value:\"\\010\\002\\020\\001\"",
+    );
+    assert_eq!(
+        value.to_text_format_with_options(&FormatOptions::new().type_resolver(PointResolver)),
+        "[example.com/custom.Point]{latitude:2,longitude:1}"
+    );
+}
+
 #[test]
 fn fmt_group() {
     let value = ContainsGroup {
@@ -657,6 +681,61 @@ fn deserialize_any() {
     );
 }
 
+#[test]
+fn deserialize_any_type_resolver() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("google.protobuf.Any")
+        .unwrap();
+    let input = "[example.com/custom.Point] { longitude: 1, latitude: 2 }";
+
+    let err = DynamicMessage::parse_text_format(desc.clone(), input).unwrap_err();
+    assert_eq!(err.to_string(), "message type 'custom.Point' not found");
+
+    let value: prost_types::Any = DynamicMessage::parse_text_format_with_options(
+        desc,
+        input,
+        &ParseOptions::new().type_resolver(PointResolver),
+    )
+    .unwrap()
+    .transcode_to()
+    .unwrap();
+    assert_eq!(
+        value,
+        prost_types::Any {
+            type_url: "example.com/custom.Point".to_owned(),
+            value: Point {
+                longitude: 1,
+                latitude: 2,
+            }
+            .encode_to_vec(),
+        }
+    );
+}
+
+#[test]
+fn deserialize_closed_enum() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test2.ClosedEnums")
+        .unwrap();
+
+    let value: ClosedEnums = DynamicMessage::parse_text_format(
+        desc.clone(),
+        "singular_enum: 3, unpacked_enums: [2, OTHER]",
+    )
+    .unwrap()
+    .transcode_to()
+    .unwrap();
+    assert_eq!(value.singular_enum, Some(3));
+    assert_eq!(value.unpacked_enums, [2, 3]);
+
+    assert_eq!(
+        DynamicMessage::parse_text_format(desc, "singular_enum: 5")
+            .unwrap_err()
+            .to_string(),
+        "value '5' was not found for enum 'test2.Proto2Enum'"
+    );
+}
+
 #[test]
 fn parse_error() {
     fn error(s: &str) -> String {
@@ -806,6 +885,310 @@ fn duplicate_oneof_field() {
     );
 }
 
+#[test]
+fn parse_unknown_fields() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.Scalars")
+        .unwrap();
+    let input = "int32: 1, unknown: [1, -2.5, 'a' 'b', { x: inf }] other < [a.b] { y: FOO } > [ext.name]: 3 bool: true";
+
+    assert_eq!(
+        DynamicMessage::parse_text_format(desc.clone(), input)
+            .unwrap_err()
+            .to_string(),
+        "field 'unknown' not found for message 'test.Scalars'"
+    );
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            desc.clone(),
+            input,
+            &ParseOptions::new().allow_unknown_fields(true)
+        )
+        .unwrap_err()
+        .to_string(),
+        "extension 'ext.name' not found for message 'test.Scalars'"
+    );
+
+    let options = ParseOptions::new()
+        .allow_unknown_fields(true)
+        .allow_unknown_extensions(true);
+    let value: Scalars = DynamicMessage::parse_text_format_with_options(desc, input, &options)
+        .unwrap()
+        .transcode_to()
+        .unwrap();
+    assert_eq!(
+        value,
+        Scalars {
+            int32: 1,
+            bool: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn parse_field_numbers() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.ComplexType")
+        .unwrap();
+    let options = ParseOptions::new().allow_field_numbers(true);
+
+    assert_eq!(
+        DynamicMessage::parse_text_format(desc.clone(), "3 { 3: 5 }")
+            .unwrap_err()
+            .to_string(),
+        "expected a field name, but found '3'"
+    );
+
+    let value: ComplexType = DynamicMessage::parse_text_format_with_options(
+        desc.clone(),
+        "3 { 3: 5 } 4: [1, BAR]",
+        &options,
+    )
+    .unwrap()
+    .transcode_to()
+    .unwrap();
+    assert_eq!(
+        value,
+        ComplexType {
+            nested: Some(Scalars {
+                int32: 5,
+                ..Default::default()
+            }),
+            my_enum: vec![1, 3],
+            ..Default::default()
+        }
+    );
+
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(desc.clone(), "99: 1", &options)
+            .unwrap_err()
+            .to_string(),
+        "field '99' not found for message 'test.ComplexType'"
+    );
+    assert!(DynamicMessage::parse_text_format_with_options(
+        desc,
+        "99 { 1: 2 }",
+        &options.allow_unknown_fields(true)
+    )
+    .is_ok());
+}
+
+#[test]
+fn parse_partial() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test2.ContainsGroup")
+        .unwrap();
+    let options = ParseOptions::new().allow_partial(false);
+
+    assert!(DynamicMessage::parse_text_format(desc.clone(), "RequiredGroup { b: 1 }").is_ok());
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            desc.clone(),
+            "RequiredGroup { b: 1 }",
+            &options
+        )
+        .unwrap_err()
+        .to_string(),
+        "required field 'a' is not set for message 'test2.ContainsGroup.RequiredGroup'"
+    );
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            desc.clone(),
+            "RepeatedGroup { e: 'x' } RepeatedGroup { f: 1 }",
+            &options
+        )
+        .unwrap_err()
+        .to_string(),
+        "required field 'e' is not set for message 'test2.ContainsGroup.RepeatedGroup'"
+    );
+    assert!(DynamicMessage::parse_text_format_with_options(
+        desc,
+        "RequiredGroup { a: 'x' } RepeatedGroup { e: 'y' }",
+        &options
+    )
+    .is_ok());
+}
+
+#[test]
+fn parse_recursion_limit() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.ComplexType")
+        .unwrap();
+
+    assert!(DynamicMessage::parse_text_format_with_options(
+        desc.clone(),
+        "nested { }",
+        &ParseOptions::new().recursion_limit(1)
+    )
+    .is_ok());
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            desc.clone(),
+            "nested { }",
+            &ParseOptions::new().recursion_limit(0)
+        )
+        .unwrap_err()
+        .to_string(),
+        "recursion limit exceeded"
+    );
+    assert_eq!(
+        DynamicMessage::parse_text_format_with_options(
+            desc,
+            "unknown { a { } }",
+            &ParseOptions::new()
+                .recursion_limit(1)
+                .allow_unknown_fields(true)
+        )
+        .unwrap_err()
+        .to_string(),
+        "recursion limit exceeded"
+    );
+}
+
+#[test]
+fn parse_duplicate_fields() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.ComplexType")
+        .unwrap();
+    let input = "optional_enum: FOO nested { int32: 1 } optional_enum: BAR nested { bool: true }";
+
+    assert_eq!(
+        DynamicMessage::parse_text_format(desc.clone(), input)
+            .unwrap_err()
+            .to_string(),
+        "'optional_enum' is already set"
+    );
+
+    let options = ParseOptions::new().reject_duplicate_fields(false);
+    let value: ComplexType = DynamicMessage::parse_text_format_with_options(desc, input, &options)
+        .unwrap()
+        .transcode_to()
+        .unwrap();
+    assert_eq!(
+        value,
+        ComplexType {
+            optional_enum: 3,
+            nested: Some(Scalars {
+                int32: 1,
+                bool: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    );
+
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.MessageWithOneof")
+        .unwrap();
+    let d = DynamicMessage::parse_text_format_with_options(
+        desc,
+        "oneof_field_1: 'hello', oneof_field_2: 5",
+        &options,
+    )
+    .unwrap();
+    assert!(!d.has_field_by_name("oneof_field_1"));
+    assert_eq!(
+        d.get_field_by_name("oneof_field_2").unwrap().as_ref(),
+        &Value::I32(5)
+    );
+}
+
+#[test]
+fn document_to_message() {
+    let desc = test_file_descriptor()
+        .get_message_by_name("test.ComplexType")
+        .unwrap();
+
+    let mut document =
+        Document::parse("# comment\nnested {\n  int32: 1 # one\n}\nmy_enum: [FOO]\n").unwrap();
+    document.set("nested.int32", "2").unwrap();
+    document.insert("my_enum", "BAR").unwrap();
+    assert_eq!(
+        document.to_string(),
+        "# comment\nnested {\n  int32: 2 # one\n}\nmy_enum: [FOO, BAR]\n"
+    );
+
+    let value: ComplexType = document
+        .to_message(desc.clone())
+        .unwrap()
+        .transcode_to()
+        .unwrap();
+    assert_eq!(
+        value,
+        ComplexType {
+            nested: Some(Scalars {
+                int32: 2,
+                ..Default::default()
+            }),
+            my_enum: vec![1, 3],
+            ..Default::default()
+        }
+    );
+
+    document.set("nested.unknown", "1").unwrap();
+    assert_eq!(
+        document.validate(desc.clone()).unwrap_err().to_string(),
+        "field 'unknown' not found for message 'test.Scalars'"
+    );
+    assert!(document
+        .to_message_with_options(desc, &ParseOptions::new().allow_unknown_fields(true))
+        .is_ok());
+}
+
+#[test]
+fn parse_with_header() {
+    let pool = test_file_descriptor();
+
+    let value: Scalars = text_format::parse_with_header(
+        &pool,
+        "# proto-file: test.proto\n# proto-message: Scalars\n\nint32: 5\n",
+    )
+    .unwrap()
+    .transcode_to()
+    .unwrap();
+    assert_eq!(
+        value,
+        Scalars {
+            int32: 5,
+            ..Default::default()
+        }
+    );
+
+    let message =
+        text_format::parse_with_header(&pool, "# comment\n#proto-message: .test.Scalars\n")
+            .unwrap();
+    assert_eq!(message.descriptor().full_name(), "test.Scalars");
+
+    assert_eq!(
+        text_format::parse_with_header(&pool, "int32: 5\n# proto-message: test.Scalars\n")
+            .unwrap_err()
+            .to_string(),
+        "the input does not have a 'proto-message' header"
+    );
+    assert_eq!(
+        text_format::parse_with_header(
+            &pool,
+            "# proto-file: nope.proto\n# proto-message: Scalars\n"
+        )
+        .unwrap_err()
+        .to_string(),
+        "file 'nope.proto' not found"
+    );
+    assert_eq!(
+        text_format::parse_with_header(&pool, "# proto-message: test.Nope\n")
+            .unwrap_err()
+            .to_string(),
+        "message type 'test.Nope' not found"
+    );
+    assert_eq!(
+        text_format::parse_with_header(&pool, "# proto-message: test.Scalars\nunknown: 1\n")
+            .unwrap_err()
+            .to_string(),
+        "field 'unknown' not found for message 'test.Scalars'"
+    );
+}
+
 proptest! {
     #![proptest_config(ProptestConfig {
         cases: 32,
@@ -880,6 +1263,14 @@ where
     assert_eq!(value, &parsed_pretty);
 }
 
+struct PointResolver;
+
+impl TypeResolver for PointResolver {
+    fn resolve_message_type(&self, type_url: &str) -> Option<MessageDescriptor> {
+        (type_url == "example.com/custom.Point").then(|| Point::default().descriptor())
+    }
+}
+
 fn transcode_any(t: &prost_types::Any) -> DynamicMessage {
     // Look up the type in the test pool instead of the global pool used for google types,
     // so we can find the payload.
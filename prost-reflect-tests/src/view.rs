@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_reflect::{MessageView, ReflectMessage, ValueView};
+
+use crate::proto::{
+    complex_type::MyEnum, contains_group, ComplexType, ContainsGroup, ScalarArrays, Scalars,
+};
+
+#[test]
+fn view_scalars() {
+    let message = Scalars {
+        double: 1.1,
+        float: 2.2,
+        int32: 3,
+        int64: 4,
+        uint32: 5,
+        uint64: 6,
+        sint32: 7,
+        sint64: 8,
+        fixed32: 9,
+        fixed64: 10,
+        sfixed32: 11,
+        sfixed64: 12,
+        r#bool: true,
+        string: "5".to_owned(),
+        bytes: b"6".to_vec(),
+    };
+    let bytes = message.encode_to_vec();
+    let view = MessageView::new(message.descriptor(), &bytes);
+
+    let get = |name: &str| view.get_field_by_name(name).unwrap().unwrap();
+    assert_eq!(get("double"), ValueView::F64(1.1));
+    assert_eq!(get("float"), ValueView::F32(2.2));
+    assert_eq!(get("int32"), ValueView::I32(3));
+    assert_eq!(get("int64"), ValueView::I64(4));
+    assert_eq!(get("uint32"), ValueView::U32(5));
+    assert_eq!(get("uint64"), ValueView::U64(6));
+    assert_eq!(get("sint32"), ValueView::I32(7));
+    assert_eq!(get("sint64"), ValueView::I64(8));
+    assert_eq!(get("fixed32"), ValueView::U32(9));
+    assert_eq!(get("fixed64"), ValueView::U64(10));
+    assert_eq!(get("sfixed32"), ValueView::I32(11));
+    assert_eq!(get("sfixed64"), ValueView::I64(12));
+    assert_eq!(get("bool"), ValueView::Bool(true));
+    assert_eq!(get("string"), ValueView::String("5"));
+    assert_eq!(get("bytes"), ValueView::Bytes(b"6"));
+
+    // Strings and bytes borrow from the buffer.
+    assert!(bytes
+        .as_ptr_range()
+        .contains(&get("string").as_str().unwrap().as_ptr()));
+}
+
+#[test]
+fn view_missing_field() {
+    let bytes = Scalars {
+        int32: 3,
+        ..Default::default()
+    }
+    .encode_to_vec();
+    let view = MessageView::new(Scalars::default().descriptor(), &bytes);
+
+    assert_eq!(view.get_field_by_name("int64").unwrap(), None);
+    assert_eq!(view.get_field_by_name("not_a_field").unwrap(), None);
+    assert_eq!(
+        view.get_field_by_number(3).unwrap(),
+        Some(ValueView::I32(3))
+    );
+}
+
+#[test]
+fn view_scalar_arrays() {
+    let message = ScalarArrays {
+        int32: vec![1, -2, 3],
+        sint64: vec![-4, 5],
+        string: vec!["foo".to_owned(), "bar".to_owned()],
+        ..Default::default()
+    };
+    let bytes = message.encode_to_vec();
+    let view = MessageView::new(message.descriptor(), &bytes);
+
+    assert_eq!(
+        view.get_field_by_name("int32").unwrap().unwrap(),
+        ValueView::List(vec![
+            ValueView::I32(1),
+            ValueView::I32(-2),
+            ValueView::I32(3)
+        ])
+    );
+    assert_eq!(
+        view.get_field_by_name("sint64").unwrap().unwrap(),
+        ValueView::List(vec![ValueView::I64(-4), ValueView::I64(5)])
+    );
+    assert_eq!(
+        view.get_field_by_name("string").unwrap().unwrap(),
+        ValueView::List(vec![ValueView::String("foo"), ValueView::String("bar")])
+    );
+}
+
+#[test]
+fn view_packed_and_unpacked() {
+    // Field 3 (int32) packed with [1, 2], then unpacked with 3.
+    let bytes = b"\x1a\x02\x01\x02\x18\x03";
+    let view = MessageView::new(ScalarArrays::default().descriptor(), bytes);
+
+    assert_eq!(
+        view.get_field_by_name("int32").unwrap().unwrap(),
+        ValueView::List(vec![
+            ValueView::I32(1),
+            ValueView::I32(2),
+            ValueView::I32(3)
+        ])
+    );
+
+    let occurrences: Vec<_> = view.fields().map(|field| field.unwrap().1).collect();
+    assert_eq!(
+        occurrences,
+        vec![
+            ValueView::List(vec![ValueView::I32(1), ValueView::I32(2)]),
+            ValueView::List(vec![ValueView::I32(3)]),
+        ]
+    );
+}
+
+#[test]
+fn view_complex_type() {
+    let message = ComplexType {
+        int_map: HashMap::from([(
+            5,
+            Scalars {
+                string: "five".to_owned(),
+                ..Default::default()
+            },
+        )]),
+        nested: Some(Scalars {
+            int32: 42,
+            ..Default::default()
+        }),
+        my_enum: vec![MyEnum::Foo as i32, MyEnum::Neg as i32],
+        enum_map: HashMap::from([(1, MyEnum::Bar as i32)]),
+        ..Default::default()
+    };
+    let bytes = message.encode_to_vec();
+    let view = MessageView::new(message.descriptor(), &bytes);
+
+    let nested = view.get_field_by_name("nested").unwrap().unwrap();
+    let nested = nested.as_message().unwrap();
+    assert_eq!(nested.descriptor().full_name(), "test.Scalars");
+    assert_eq!(
+        nested.get_field_by_name("int32").unwrap(),
+        Some(ValueView::I32(42))
+    );
+
+    let int_map = view.get_field_by_name("int_map").unwrap().unwrap();
+    let int_map = int_map.as_map().unwrap();
+    assert_eq!(int_map.len(), 1);
+    assert_eq!(int_map[0].0, ValueView::I32(5));
+    assert_eq!(
+        int_map[0]
+            .1
+            .as_message()
+            .unwrap()
+            .get_field_by_name("string")
+            .unwrap(),
+        Some(ValueView::String("five"))
+    );
+
+    assert_eq!(
+        view.get_field_by_name("enum_map").unwrap().unwrap(),
+        ValueView::Map(vec![(ValueView::I32(1), ValueView::EnumNumber(3))])
+    );
+    assert_eq!(
+        view.get_field_by_name("my_enum").unwrap().unwrap(),
+        ValueView::List(vec![ValueView::EnumNumber(1), ValueView::EnumNumber(-4)])
+    );
+
+    assert_eq!(view.to_dynamic().unwrap(), message.transcode_to_dynamic());
+}
+
+#[test]
+fn view_map_entry_defaults() {
+    // Field 2 (int_map) with an empty entry.
+    let bytes = b"\x12\x00";
+    let view = MessageView::new(ComplexType::default().descriptor(), bytes);
+
+    let int_map = view.get_field_by_name("int_map").unwrap().unwrap();
+    let int_map = int_map.as_map().unwrap();
+    assert_eq!(int_map[0].0, ValueView::I32(0));
+    assert_eq!(int_map[0].1.as_message().unwrap().as_bytes(), b"");
+}
+
+#[test]
+fn view_group() {
+    let message = ContainsGroup {
+        requiredgroup: Some(contains_group::RequiredGroup {
+            a: "bar".to_owned(),
+            b: Some(7),
+        }),
+        repeatedgroup: vec![
+            contains_group::RepeatedGroup::default(),
+            contains_group::RepeatedGroup {
+                e: "hello".to_owned(),
+                f: Some(10),
+            },
+        ],
+        ..Default::default()
+    };
+    let bytes = message.encode_to_vec();
+    let view = MessageView::new(message.descriptor(), &bytes);
+
+    let group = view.get_field_by_name("requiredgroup").unwrap().unwrap();
+    let group = group.as_message().unwrap();
+    assert_eq!(
+        group.get_field_by_name("a").unwrap(),
+        Some(ValueView::String("bar"))
+    );
+    assert_eq!(
+        group.get_field_by_name("b").unwrap(),
+        Some(ValueView::I32(7))
+    );
+
+    let groups = view.get_field_by_name("repeatedgroup").unwrap().unwrap();
+    let groups = groups.as_list().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(
+        groups[1]
+            .as_message()
+            .unwrap()
+            .get_field_by_name("f")
+            .unwrap(),
+        Some(ValueView::I32(10))
+    );
+}
+
+#[test]
+fn view_fields_skips_unknown() {
+    // Field 3 (int32), unknown field 100, field 14 (string).
+    let bytes = b"\x18\x05\xa0\x06\x01\x72\x02hi";
+    let view = MessageView::new(Scalars::default().descriptor(), bytes);
+
+    let fields: Vec<_> = view
+        .fields()
+        .map(|field| {
+            let (field, value) = field.unwrap();
+            (field.name().to_owned(), value)
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("int32".to_owned(), ValueView::I32(5)),
+            ("string".to_owned(), ValueView::String("hi")),
+        ]
+    );
+}
+
+#[test]
+fn view_errors() {
+    let desc = Scalars::default().descriptor();
+
+    let view = MessageView::new(desc.clone(), b"\x72\x02\xff\xfe");
+    assert!(view.get_field_by_name("string").is_err());
+    assert!(view.get_field_by_name("int32").is_ok());
+
+    let view = MessageView::new(desc.clone(), b"\x72\x05ab");
+    assert!(view.get_field_by_name("int32").is_err());
+
+    let mut fields = view.fields();
+    assert!(fields.next().unwrap().is_err());
+    assert!(fields.next().is_none());
+
+    // Field 3 (int32) with the length-delimited wire type.
+    let view = MessageView::new(desc, b"\x1a\x00");
+    assert!(view.get_field_by_name("int32").is_err());
+}
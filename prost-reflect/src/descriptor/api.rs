@@ -12,24 +12,27 @@ use prost::{
     DecodeError, EncodeError, Message,
 };
 use prost_types::{
-    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
-    FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto, OneofDescriptorProto,
-    ServiceDescriptorProto,
+    source_code_info::Location, DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto,
+    FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+    OneofDescriptorProto, ServiceDescriptorProto,
 };
 
 use crate::{
     descriptor::{
         error::DescriptorErrorKind,
+        features::merge_features_message,
         find_enum_proto, find_message_proto, tag, to_index,
         types::{self, Options},
-        Definition, DefinitionKind, DescriptorIndex, EnumDescriptorInner, EnumValueDescriptorInner,
-        ExtensionDescriptorInner, FieldDescriptorInner, FileDescriptorInner, KindIndex,
-        MessageDescriptorInner, MethodDescriptorInner, OneofDescriptorInner,
-        ServiceDescriptorInner, MAP_ENTRY_KEY_NUMBER, MAP_ENTRY_VALUE_NUMBER,
+        Definition, DefinitionKind, Descriptor, DescriptorIndex, EnumDescriptorInner,
+        EnumValueDescriptorInner, ExtensionDescriptorInner, FieldDescriptorInner,
+        FileDescriptorInner, FileIndex, Identity, KindIndex, MessageDescriptorInner,
+        MethodDescriptorInner, OneofDescriptorInner, ServiceDescriptorInner, MAP_ENTRY_KEY_NUMBER,
+        MAP_ENTRY_VALUE_NUMBER,
     },
-    Cardinality, DescriptorError, DescriptorPool, DynamicMessage, EnumDescriptor,
-    EnumValueDescriptor, ExtensionDescriptor, FieldDescriptor, FileDescriptor, Kind,
-    MessageDescriptor, MethodDescriptor, OneofDescriptor, ServiceDescriptor, Syntax, Value,
+    feature_set, Cardinality, DescriptorError, DescriptorPool, DynamicMessage, Edition,
+    EnumDescriptor, EnumValueDescriptor, ExtensionDescriptor, FeatureSet, FieldDescriptor,
+    FileDescriptor, Kind, MessageDescriptor, MethodDescriptor, OneofDescriptor, ReflectMessage,
+    ServiceDescriptor, Span, Syntax, Value,
 };
 
 impl fmt::Debug for Syntax {
@@ -37,6 +40,8 @@ impl fmt::Debug for Syntax {
         match *self {
             Syntax::Proto2 => write!(f, "proto2"),
             Syntax::Proto3 => write!(f, "proto3"),
+            Syntax::Editions(Edition::Edition2023) => write!(f, "edition 2023"),
+            Syntax::Editions(Edition::Edition2024) => write!(f, "edition 2024"),
         }
     }
 }
@@ -218,7 +223,8 @@ impl DescriptorPool {
     ///
     /// Returns an error if the descriptor set is invalid, for example if it references types not yet added
     /// to the pool. When using a file descriptor set generated by the protobuf compiler, this method will
-    /// always succeed.
+    /// always succeed, unless it contains files using the `editions` syntax (see
+    /// [`add_file_descriptor_protos`](DescriptorPool::add_file_descriptor_protos)).
     pub fn add_file_descriptor_set(
         &mut self,
         file_descriptor_set: FileDescriptorSet,
@@ -237,15 +243,32 @@ impl DescriptorPool {
     ///
     /// Returns an error if any of the given file descriptor is invalid, for example if they reference
     /// types not yet added to the pool.
+    ///
+    /// An error is also returned for files using the `editions` syntax, since their edition is not
+    /// supported by [`prost_types`]. These files must be added from their encoded bytes, for example
+    /// using [`decode_file_descriptor_proto`](DescriptorPool::decode_file_descriptor_proto).
     pub fn add_file_descriptor_protos<I>(&mut self, files: I) -> Result<(), DescriptorError>
     where
         I: IntoIterator<Item = FileDescriptorProto>,
     {
-        self.build_files(
-            files
-                .into_iter()
-                .map(types::FileDescriptorProto::from_prost),
-        )
+        let mut errors = Vec::new();
+        let files: Vec<_> = files
+            .into_iter()
+            .inspect(|file| {
+                // Files already in the pool are skipped, so their edition is not needed.
+                if file.syntax() == "editions" && !self.inner.file_names.contains_key(file.name()) {
+                    errors.push(DescriptorErrorKind::EditionNotSupportedByProstTypes {
+                        name: file.name().to_owned(),
+                    });
+                }
+            })
+            .map(types::FileDescriptorProto::from_prost)
+            .collect();
+        if !errors.is_empty() {
+            return Err(DescriptorError::new(errors));
+        }
+
+        self.build_files(files)
     }
 
     /// Add a single file descriptor to the pool.
@@ -318,6 +341,27 @@ impl DescriptorPool {
         self.build_files(file.file)
     }
 
+    /// Decodes and sets the default features for each edition, used when resolving the features of files added to
+    /// this pool.
+    ///
+    /// The bytes should be a protobuf-encoded `google.protobuf.FeatureSetDefaults` message, which may be generated
+    /// by running the protobuf compiler with the `--edition_defaults_out` flag. This allows the defaults for custom
+    /// features to be included in [`features_message()`][FieldDescriptor::features_message], and restricts the
+    /// editions supported by the pool to those covered by the defaults.
+    ///
+    /// This method should be called before adding any files which use editions, since the features of files already
+    /// in the pool are not recomputed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given bytes are not a valid protobuf-encoded feature set defaults message.
+    pub fn set_feature_set_defaults<B>(&mut self, bytes: B) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        Arc::make_mut(&mut self.inner).set_feature_set_defaults(bytes)
+    }
+
     /// Gets an iterator over the file descriptors added to this pool.
     pub fn files(&self) -> impl ExactSizeIterator<Item = FileDescriptor> + '_ {
         indices(&self.inner.files).map(|index| FileDescriptor {
@@ -339,6 +383,10 @@ impl DescriptorPool {
     }
 
     /// Gets a iterator over the raw [`FileDescriptorProto`] instances wrapped by this [`DescriptorPool`].
+    ///
+    /// The edition of files using the `editions` syntax is not included, since it is not supported
+    /// by [`prost_types`], so these files cannot be added to another pool. To preserve it, use
+    /// [`encode_to_vec`](DescriptorPool::encode_to_vec) instead.
     pub fn file_descriptor_protos(
         &self,
     ) -> impl ExactSizeIterator<Item = &FileDescriptorProto> + '_ {
@@ -518,6 +566,111 @@ impl DescriptorPool {
             _ => None,
         }
     }
+
+    /// Gets the definition with the given fully qualified name, for example `my.package.MessageName`
+    /// or `my.package.MessageName.field_name`.
+    ///
+    /// This may return a descriptor of any kind. Returns `None` if no definition has the given name,
+    /// or if the name refers to a package.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{Descriptor, DescriptorPool};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let field = pool.get_by_name("package.MyMessage.foo").unwrap();
+    /// assert!(matches!(field, Descriptor::Field(_)));
+    /// assert_eq!(field.full_name(), "package.MyMessage.foo");
+    /// assert!(pool.get_by_name("package").is_none());
+    /// ```
+    pub fn get_by_name(&self, name: &str) -> Option<Descriptor> {
+        self.inner
+            .get_by_name(name)
+            .and_then(|def| self.to_descriptor(def))
+    }
+
+    /// Resolves a name relative to the given scope, using the same rules the protobuf compiler uses
+    /// for type names in `.proto` files.
+    ///
+    /// The scope is the fully qualified name of the definition the name appears in, for example the
+    /// message containing a field or the package of a file. A name starting with `.` is treated as
+    /// fully qualified. Otherwise, the innermost enclosing scope is searched first, followed by each
+    /// parent scope in turn. If the name contains multiple components, only its first component is
+    /// looked up this way, and the rest of the name must then be found within the resulting scope.
+    ///
+    /// Returns `None` if the name cannot be resolved, or if it refers to a package.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{Descriptor, DescriptorPool};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let message = pool.resolve_relative_name("package.MyMessage", "MyMessage").unwrap();
+    /// assert_eq!(message.full_name(), "package.MyMessage");
+    /// let field = pool.resolve_relative_name("package.MyMessage", "foo").unwrap();
+    /// assert_eq!(field.full_name(), "package.MyMessage.foo");
+    /// let empty = pool.resolve_relative_name("package", "google.protobuf.Empty").unwrap();
+    /// assert!(matches!(empty, Descriptor::Message(_)));
+    /// ```
+    pub fn resolve_relative_name(&self, scope: &str, name: &str) -> Option<Descriptor> {
+        let scope = scope.strip_prefix('.').unwrap_or(scope);
+        self.inner
+            .resolve_relative_name(scope, name)
+            .and_then(|def| self.to_descriptor(def))
+    }
+
+    fn to_descriptor(&self, def: &Definition) -> Option<Descriptor> {
+        // The paths of fields, oneofs, methods and enum values end in the index of the
+        // definition within its parent, followed by the tag of the name field.
+        let index = || def.path[def.path.len() - 2] as DescriptorIndex;
+        match def.kind {
+            DefinitionKind::Package => None,
+            DefinitionKind::Message(index) => Some(Descriptor::Message(MessageDescriptor {
+                pool: self.clone(),
+                index,
+            })),
+            DefinitionKind::Field(message) => Some(Descriptor::Field(FieldDescriptor {
+                message: MessageDescriptor {
+                    pool: self.clone(),
+                    index: message,
+                },
+                index: index(),
+            })),
+            DefinitionKind::Oneof(message) => Some(Descriptor::Oneof(OneofDescriptor {
+                message: MessageDescriptor {
+                    pool: self.clone(),
+                    index: message,
+                },
+                index: index(),
+            })),
+            DefinitionKind::Service(index) => Some(Descriptor::Service(ServiceDescriptor {
+                pool: self.clone(),
+                index,
+            })),
+            DefinitionKind::Method(service) => Some(Descriptor::Method(MethodDescriptor {
+                service: ServiceDescriptor {
+                    pool: self.clone(),
+                    index: service,
+                },
+                index: index(),
+            })),
+            DefinitionKind::Enum(index) => Some(Descriptor::Enum(EnumDescriptor {
+                pool: self.clone(),
+                index,
+            })),
+            DefinitionKind::EnumValue(enum_) => Some(Descriptor::EnumValue(EnumValueDescriptor {
+                parent: EnumDescriptor {
+                    pool: self.clone(),
+                    index: enum_,
+                },
+                index: index(),
+            })),
+            DefinitionKind::Extension(index) => Some(Descriptor::Extension(ExtensionDescriptor {
+                pool: self.clone(),
+                index,
+            })),
+        }
+    }
 }
 
 impl fmt::Debug for DescriptorPool {
@@ -652,6 +805,10 @@ impl FileDescriptor {
     }
 
     /// Gets a reference to the raw [`FileDescriptorProto`] wrapped by this [`FileDescriptor`].
+    ///
+    /// If this file uses the `editions` syntax, its edition is not included, since it is not
+    /// supported by [`prost_types`], so the file cannot be added to another pool. To preserve it,
+    /// use [`encode_to_vec`](FileDescriptor::encode_to_vec) instead.
     pub fn file_descriptor_proto(&self) -> &FileDescriptorProto {
         &self.inner().prost
     }
@@ -687,6 +844,26 @@ impl FileDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`FileDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`FileDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = self.parent_pool().edition_defaults_message(self.syntax());
+        merge_features_message(
+            &mut features,
+            &self.inner().raw.options,
+            tag::file::options::FEATURES,
+        );
+        features
+    }
+
     fn inner(&self) -> &FileDescriptorInner {
         &self.pool.inner.files[self.index as usize]
     }
@@ -747,6 +924,30 @@ impl MessageDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this message, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this message, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this message which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this message in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the [`FileDescriptorProto`] in which this message is defined.
     pub fn parent_file_descriptor_proto(&self) -> &FileDescriptorProto {
         &self.pool.inner.files[self.inner().id.file as usize].prost
@@ -766,6 +967,29 @@ impl MessageDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`MessageDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`MessageDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = match self.parent_message() {
+            Some(parent) => parent.features_message(),
+            None => self.parent_file().features_message(),
+        };
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::message::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets an iterator yielding a [`FieldDescriptor`] for each field defined in this message.
     pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
         self.inner()
@@ -963,7 +1187,11 @@ impl MessageDescriptor {
         &self.pool.inner.messages[self.index as usize]
     }
 
-    fn raw(&self) -> &types::DescriptorProto {
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
+    pub(super) fn raw(&self) -> &types::DescriptorProto {
         find_message_proto(self.raw_file(), self.path())
     }
 
@@ -1017,6 +1245,30 @@ impl FieldDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this field, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this field, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this field which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this field in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the raw [`FieldDescriptorProto`] wrapped by this [`FieldDescriptor`].
     pub fn field_descriptor_proto(&self) -> &FieldDescriptorProto {
         &self.parent_message().descriptor_proto().field[*self.path().last().unwrap() as usize]
@@ -1031,6 +1283,29 @@ impl FieldDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`FieldDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`FieldDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = match self.containing_oneof() {
+            Some(oneof) => oneof.features_message(),
+            None => self.parent_message().features_message(),
+        };
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::field::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets the unique number for this message field.
     pub fn number(&self) -> u32 {
         self.inner().number
@@ -1045,6 +1320,9 @@ impl FieldDescriptor {
     }
 
     /// Whether this field is encoded using the proto2 group encoding.
+    ///
+    /// For files using editions, this returns `true` for message fields with the `DELIMITED`
+    /// message encoding feature.
     pub fn is_group(&self) -> bool {
         matches!(self.inner().kind, KindIndex::Group(_))
     }
@@ -1077,7 +1355,8 @@ impl FieldDescriptor {
 
     /// Whether this field is required.
     ///
-    /// For proto3 this always returns `false`.
+    /// For proto3 this always returns `false`. For files using editions, this returns `true` for fields
+    /// with the `LEGACY_REQUIRED` field presence feature.
     pub fn is_required(&self) -> bool {
         self.cardinality() == Cardinality::Required
     }
@@ -1092,7 +1371,7 @@ impl FieldDescriptor {
     ///
     /// For proto2 messages this returns `true` for all non-repeated fields.
     /// For proto3 this returns `true` for message fields, and fields contained
-    /// in a `oneof`.
+    /// in a `oneof`. For files using editions, this is determined by the field presence feature.
     pub fn supports_presence(&self) -> bool {
         self.inner().supports_presence
     }
@@ -1138,6 +1417,10 @@ impl FieldDescriptor {
         &self.message.inner().fields[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::FieldDescriptorProto {
         &self.message.raw().field[self.index as usize]
     }
@@ -1156,7 +1439,7 @@ impl fmt::Debug for FieldDescriptor {
                 "containing_oneof",
                 &self.containing_oneof().map(|o| o.name().to_owned()),
             )
-            .field("default_value", &self.default_value())
+            .field("default_value", &debug_default_value(self.default_value()))
             .field("is_group", &self.is_group())
             .field("is_list", &self.is_list())
             .field("is_map", &self.is_map())
@@ -1217,6 +1500,30 @@ impl ExtensionDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this extension, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this extension, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this extension which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this extension in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the [`FileDescriptorProto`] in which this extension is defined.
     pub fn parent_file_descriptor_proto(&self) -> &FileDescriptorProto {
         &self.pool.inner.files[self.inner().id.file as usize].prost
@@ -1247,6 +1554,29 @@ impl ExtensionDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`ExtensionDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`ExtensionDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = match self.parent_message() {
+            Some(parent) => parent.features_message(),
+            None => self.parent_file().features_message(),
+        };
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::field::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets the number for this extension field.
     pub fn number(&self) -> u32 {
         self.inner().number
@@ -1258,6 +1588,9 @@ impl ExtensionDescriptor {
     }
 
     /// Whether this field is encoded using the proto2 group encoding.
+    ///
+    /// For files using editions, this returns `true` for message fields with the `DELIMITED`
+    /// message encoding feature.
     pub fn is_group(&self) -> bool {
         matches!(self.inner().kind, KindIndex::Group(_))
     }
@@ -1336,6 +1669,10 @@ impl ExtensionDescriptor {
         &self.pool.inner.extensions[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::FieldDescriptorProto {
         let file = self.raw_file();
         let path = self.path();
@@ -1369,7 +1706,7 @@ impl fmt::Debug for ExtensionDescriptor {
                 "containing_message",
                 &self.containing_message().name().to_owned(),
             )
-            .field("default_value", &self.default_value())
+            .field("default_value", &debug_default_value(self.default_value()))
             .field("is_group", &self.is_group())
             .field("is_list", &self.is_list())
             .field("is_map", &self.is_map())
@@ -1425,6 +1762,30 @@ impl EnumDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this enum, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this enum, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this enum which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this enum in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the [`FileDescriptorProto`] in which this enum is defined.
     pub fn parent_file_descriptor_proto(&self) -> &FileDescriptorProto {
         &self.pool.inner.files[self.inner().id.file as usize].prost
@@ -1455,6 +1816,38 @@ impl EnumDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`EnumDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`EnumDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = match self.parent_message() {
+            Some(parent) => parent.features_message(),
+            None => self.parent_file().features_message(),
+        };
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::enum_::options::FEATURES,
+        );
+        features
+    }
+
+    /// Returns `true` if this is a closed enum, such as an enum defined in a proto2 file.
+    ///
+    /// Fields with a closed enum type can only hold values defined in the enum. When decoding, values
+    /// not defined in the enum are stored as unknown fields. Enums in proto3 files are open, and fields
+    /// with an open enum type can hold any `i32` value.
+    pub fn is_closed(&self) -> bool {
+        self.features().enum_type() == feature_set::EnumType::Closed
+    }
+
     /// Gets the default value for the enum type.
     pub fn default_value(&self) -> EnumValueDescriptor {
         EnumValueDescriptor {
@@ -1517,6 +1910,10 @@ impl EnumDescriptor {
         &self.pool.inner.enums[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::EnumDescriptorProto {
         find_enum_proto(self.raw_file(), self.path())
     }
@@ -1570,6 +1967,30 @@ impl EnumValueDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this enum value, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this enum value, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this enum value which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this enum value in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the raw [`EnumValueDescriptorProto`] wrapped by this [`EnumValueDescriptor`].
     pub fn enum_value_descriptor_proto(&self) -> &EnumValueDescriptorProto {
         &self.parent.enum_descriptor_proto().value[self.index as usize]
@@ -1584,6 +2005,26 @@ impl EnumValueDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`EnumValueDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`EnumValueDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = self.parent_enum().features_message();
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::enum_value::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets the number representing this enum value.
     pub fn number(&self) -> i32 {
         self.inner().number
@@ -1593,6 +2034,10 @@ impl EnumValueDescriptor {
         &self.parent.inner().values[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::EnumValueDescriptorProto {
         &self.parent.raw().value[self.index as usize]
     }
@@ -1641,6 +2086,30 @@ impl OneofDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this oneof, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this oneof, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this oneof which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this oneof in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the raw [`OneofDescriptorProto`] wrapped by this [`OneofDescriptor`].
     pub fn oneof_descriptor_proto(&self) -> &OneofDescriptorProto {
         &self.message.descriptor_proto().oneof_decl[self.index as usize]
@@ -1655,6 +2124,26 @@ impl OneofDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`OneofDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`OneofDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = self.parent_message().features_message();
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::oneof::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets an iterator yielding a [`FieldDescriptor`] for each field of the parent message this oneof contains.
     pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
         self.inner().fields.iter().map(|&index| FieldDescriptor {
@@ -1677,6 +2166,10 @@ impl OneofDescriptor {
         &self.message.inner().oneofs[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::OneofDescriptorProto {
         &self.message.raw().oneof_decl[self.index as usize]
     }
@@ -1748,6 +2241,30 @@ impl ServiceDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this service, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this service, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this service which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this service in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the [`FileDescriptorProto`] in which this service is defined.
     pub fn parent_file_descriptor_proto(&self) -> &FileDescriptorProto {
         &self.pool.inner.files[self.inner().id.file as usize].prost
@@ -1769,6 +2286,26 @@ impl ServiceDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`ServiceDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`ServiceDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = self.parent_file().features_message();
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::service::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets an iterator yielding a [`MethodDescriptor`] for each method defined in this service.
     pub fn methods(&self) -> impl ExactSizeIterator<Item = MethodDescriptor> + '_ {
         indices(&self.inner().methods).map(|index| MethodDescriptor {
@@ -1781,6 +2318,10 @@ impl ServiceDescriptor {
         &self.pool.inner.services[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::ServiceDescriptorProto {
         let path = self.path();
         debug_assert!(!path.is_empty());
@@ -1854,6 +2395,30 @@ impl MethodDescriptor {
         &self.inner().id.path
     }
 
+    /// Gets the comments appearing directly before the definition of this method, if any.
+    ///
+    /// Like the [`span`](Self::span), comments require source code info; see [`Span`] for details.
+    pub fn leading_comments(&self) -> Option<&str> {
+        self.location()?.leading_comments.as_deref()
+    }
+
+    /// Gets the comments appearing directly after the definition of this method, if any.
+    pub fn trailing_comments(&self) -> Option<&str> {
+        self.location()?.trailing_comments.as_deref()
+    }
+
+    /// Gets any comments appearing before the definition of this method which are separated from it by a blank line.
+    pub fn leading_detached_comments(&self) -> &[String] {
+        self.location()
+            .map(|location| location.leading_detached_comments.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Gets the location of the definition of this method in its source file, if available.
+    pub fn span(&self) -> Option<Span> {
+        Span::new(&self.location()?.span)
+    }
+
     /// Gets a reference to the raw [`MethodDescriptorProto`] wrapped by this [`MethodDescriptor`].
     pub fn method_descriptor_proto(&self) -> &MethodDescriptorProto {
         &self.service.service_descriptor_proto().method[self.index as usize]
@@ -1868,6 +2433,26 @@ impl MethodDescriptor {
         )
     }
 
+    /// Gets the resolved values of the standard features for this [`MethodDescriptor`].
+    ///
+    /// Features not set explicitly are inherited from the enclosing element, or take their default value for the
+    /// edition of the file.
+    pub fn features(&self) -> FeatureSet {
+        FeatureSet::new(&self.inner().features)
+    }
+
+    /// Gets the resolved features for this [`MethodDescriptor`], including any custom features defined as extensions of
+    /// `google.protobuf.FeatureSet`.
+    pub fn features_message(&self) -> DynamicMessage {
+        let mut features = self.parent_service().features_message();
+        merge_features_message(
+            &mut features,
+            &self.raw().options,
+            tag::method::options::FEATURES,
+        );
+        features
+    }
+
     /// Gets the [`MessageDescriptor`] for the input type of this method.
     pub fn input(&self) -> MessageDescriptor {
         MessageDescriptor {
@@ -1898,6 +2483,10 @@ impl MethodDescriptor {
         &self.service.inner().methods[self.index as usize]
     }
 
+    fn location(&self) -> Option<&Location> {
+        find_location(self.parent_pool(), &self.inner().id)
+    }
+
     fn raw(&self) -> &types::MethodDescriptorProto {
         &self.service.raw().method[self.index as usize]
     }
@@ -1917,6 +2506,64 @@ impl fmt::Debug for MethodDescriptor {
     }
 }
 
+impl Descriptor {
+    /// Gets a reference to the [`DescriptorPool`] this definition was loaded from.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        match self {
+            Descriptor::Message(message) => message.parent_pool(),
+            Descriptor::Field(field) => field.parent_pool(),
+            Descriptor::Oneof(oneof) => oneof.parent_pool(),
+            Descriptor::Service(service) => service.parent_pool(),
+            Descriptor::Method(method) => method.parent_pool(),
+            Descriptor::Enum(enum_) => enum_.parent_pool(),
+            Descriptor::EnumValue(value) => value.parent_pool(),
+            Descriptor::Extension(extension) => extension.parent_pool(),
+        }
+    }
+
+    /// Gets the [`FileDescriptor`] this definition is defined in.
+    pub fn parent_file(&self) -> FileDescriptor {
+        match self {
+            Descriptor::Message(message) => message.parent_file(),
+            Descriptor::Field(field) => field.parent_file(),
+            Descriptor::Oneof(oneof) => oneof.parent_file(),
+            Descriptor::Service(service) => service.parent_file(),
+            Descriptor::Method(method) => method.parent_file(),
+            Descriptor::Enum(enum_) => enum_.parent_file(),
+            Descriptor::EnumValue(value) => value.parent_file(),
+            Descriptor::Extension(extension) => extension.parent_file(),
+        }
+    }
+
+    /// Gets the short name of this definition, e.g. `MyMessage`.
+    pub fn name(&self) -> &str {
+        match self {
+            Descriptor::Message(message) => message.name(),
+            Descriptor::Field(field) => field.name(),
+            Descriptor::Oneof(oneof) => oneof.name(),
+            Descriptor::Service(service) => service.name(),
+            Descriptor::Method(method) => method.name(),
+            Descriptor::Enum(enum_) => enum_.name(),
+            Descriptor::EnumValue(value) => value.name(),
+            Descriptor::Extension(extension) => extension.name(),
+        }
+    }
+
+    /// Gets the full name of this definition, e.g. `my.package.MyMessage`.
+    pub fn full_name(&self) -> &str {
+        match self {
+            Descriptor::Message(message) => message.full_name(),
+            Descriptor::Field(field) => field.full_name(),
+            Descriptor::Oneof(oneof) => oneof.full_name(),
+            Descriptor::Service(service) => service.full_name(),
+            Descriptor::Method(method) => method.full_name(),
+            Descriptor::Enum(enum_) => enum_.full_name(),
+            Descriptor::EnumValue(value) => value.full_name(),
+            Descriptor::Extension(extension) => extension.full_name(),
+        }
+    }
+}
+
 fn debug_fmt_iter<I>(i: I) -> impl fmt::Debug
 where
     I: Iterator,
@@ -1936,6 +2583,26 @@ where
     Wrapper(i.collect())
 }
 
+/// Formats the default value of a field, printing only the type name of message values so that
+/// recursive message types do not recurse infinitely.
+fn debug_default_value(value: Value) -> impl fmt::Debug {
+    struct Wrapper(Value);
+
+    impl fmt::Debug for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.0 {
+                Value::Message(message) => f
+                    .debug_tuple("Message")
+                    .field(&message.descriptor().full_name())
+                    .finish(),
+                value => value.fmt(f),
+            }
+        }
+    }
+
+    Wrapper(value)
+}
+
 #[allow(clippy::ptr_arg)]
 fn indices<T>(f: &Vec<T>) -> Range<DescriptorIndex> {
     0..to_index(f.len())
@@ -1949,7 +2616,62 @@ fn join_name<'a>(namespace: &str, name: &'a str) -> Cow<'a, str> {
     }
 }
 
-fn decode_options<T>(
+impl Span {
+    pub(crate) fn new(span: &[i32]) -> Option<Self> {
+        match *span {
+            [start_line, start_column, end_column] => Some(Span {
+                start_line,
+                start_column,
+                end_line: start_line,
+                end_column,
+            }),
+            [start_line, start_column, end_line, end_column] => Some(Span {
+                start_line,
+                start_column,
+                end_line,
+                end_column,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Gets the 0-based line number at which this span starts, or `None` if it is negative.
+    pub fn start_line(&self) -> Option<usize> {
+        usize::try_from(self.start_line).ok()
+    }
+
+    /// Gets the 0-based column number at which this span starts, or `None` if it is negative.
+    pub fn start_column(&self) -> Option<usize> {
+        usize::try_from(self.start_column).ok()
+    }
+
+    /// Gets the 0-based line number at which this span ends, or `None` if it is negative.
+    pub fn end_line(&self) -> Option<usize> {
+        usize::try_from(self.end_line).ok()
+    }
+
+    /// Gets the 0-based column number at which this span ends, exclusive, or `None` if it is
+    /// negative.
+    pub fn end_column(&self) -> Option<usize> {
+        usize::try_from(self.end_column).ok()
+    }
+}
+
+fn find_location<'a>(pool: &'a DescriptorPool, id: &Identity) -> Option<&'a Location> {
+    find_location_by_path(pool, id.file, &id.path)
+}
+
+pub(super) fn find_location_by_path<'a>(
+    pool: &'a DescriptorPool,
+    file: FileIndex,
+    path: &[i32],
+) -> Option<&'a Location> {
+    let file = &pool.inner.files[file as usize];
+    let &index = file.locations.get(path)?;
+    Some(&file.raw.source_code_info.as_ref()?.location[index])
+}
+
+pub(super) fn decode_options<T>(
     pool: &DescriptorPool,
     name: &str,
     option: &Option<Options<T>>,
@@ -3,6 +3,8 @@ mod options;
 mod resolve;
 mod visit;
 
+pub(in crate::descriptor) use self::visit::Visitor;
+
 use core::fmt;
 use std::{
     borrow::Cow,
@@ -36,6 +38,7 @@ enum ResolveNameFilter {
     Message,
     Extension,
     FieldType,
+    Any,
 }
 
 enum ResolveNameResult<'a, 'b> {
@@ -59,6 +62,34 @@ enum ResolveNameResult<'a, 'b> {
     NotFound,
 }
 
+impl DescriptorPoolInner {
+    /// Visits every definition in the pool.
+    pub(in crate::descriptor) fn visit(&self, visitor: &mut dyn Visitor) {
+        let offsets = DescriptorPoolOffsets {
+            file: 0,
+            message: 0,
+            enum_: 0,
+            service: 0,
+            extension: 0,
+        };
+        visit::visit(offsets, self.files.iter().map(|file| &file.raw), visitor);
+    }
+
+    /// Resolves a possibly-relative name from within the given scope, using the same rules as
+    /// type names in `.proto` files.
+    pub(in crate::descriptor) fn resolve_relative_name(
+        &self,
+        scope: &str,
+        name: &str,
+    ) -> Option<&Definition> {
+        let files = (0..to_index(self.files.len())).collect();
+        match resolve_name(&files, &self.names, scope, name, ResolveNameFilter::Any) {
+            ResolveNameResult::Found { def, .. } => Some(def),
+            _ => None,
+        }
+    }
+}
+
 impl DescriptorPoolOffsets {
     fn new(pool: &DescriptorPoolInner) -> Self {
         DescriptorPoolOffsets {
@@ -142,6 +173,7 @@ impl DescriptorPool {
         let inner = Arc::get_mut(&mut self.inner).unwrap();
         for file in &mut inner.files[offsets.file as usize..] {
             file.prost = file.raw.to_prost();
+            file.locations = index_locations(&file.raw);
         }
 
         Ok(())
@@ -152,7 +184,8 @@ impl ResolveNameFilter {
     fn is_match(&self, def: &DefinitionKind) -> bool {
         matches!(
             (self, def),
-            (ResolveNameFilter::Message, DefinitionKind::Message(_))
+            (ResolveNameFilter::Any, _)
+                | (ResolveNameFilter::Message, DefinitionKind::Message(_))
                 | (ResolveNameFilter::Extension, DefinitionKind::Extension(_))
                 | (
                     ResolveNameFilter::FieldType,
@@ -168,6 +201,7 @@ impl fmt::Display for ResolveNameFilter {
             ResolveNameFilter::Message => f.write_str("a message type"),
             ResolveNameFilter::Extension => f.write_str("an extension"),
             ResolveNameFilter::FieldType => f.write_str("a message or enum type"),
+            ResolveNameFilter::Any => f.write_str("a definition"),
         }
     }
 }
@@ -295,7 +329,7 @@ impl<'a, 'b> ResolveNameResult<'a, 'b> {
     }
 }
 
-fn to_json_name(name: &str) -> String {
+pub(super) fn to_json_name(name: &str) -> String {
     let mut result = String::with_capacity(name.len());
     let mut uppercase_next = false;
 
@@ -391,3 +425,16 @@ fn join_path(path1: &[i32], path2: &[i32]) -> Box<[i32]> {
     path.extend_from_slice(path2);
     path.into_boxed_slice()
 }
+
+/// Indexes the locations of a file by path. If several locations share a path, the first is used.
+fn index_locations(file: &FileDescriptorProto) -> HashMap<Box<[i32]>, usize> {
+    let mut locations = HashMap::new();
+    if let Some(source_code_info) = &file.source_code_info {
+        for (index, location) in source_code_info.location.iter().enumerate() {
+            locations
+                .entry(location.path.as_slice().into())
+                .or_insert(index);
+        }
+    }
+    locations
+}
@@ -9,10 +9,12 @@ use crate::{
             DescriptorPoolOffsets,
         },
         error::{DescriptorError, DescriptorErrorKind, Label},
+        features::{EDITION_2023, EDITION_2024},
         tag, to_index,
         types::{
-            DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
-            FileDescriptorProto, MethodDescriptorProto, OneofDescriptorProto,
+            feature_set::{EnumType, JsonFormat},
+            DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FeatureSet,
+            FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto, OneofDescriptorProto,
             ServiceDescriptorProto,
         },
         Definition, DefinitionKind, DescriptorPoolInner, EnumDescriptorInner, EnumIndex,
@@ -20,7 +22,7 @@ use crate::{
         FileIndex, Identity, MessageDescriptorInner, MessageIndex, MethodIndex,
         OneofDescriptorInner, OneofIndex, ServiceIndex,
     },
-    Syntax,
+    Edition, Syntax,
 };
 
 impl DescriptorPoolInner {
@@ -51,23 +53,6 @@ impl Visitor for NameVisitor<'_> {
     fn visit_file(&mut self, path: &[i32], index: FileIndex, file: &FileDescriptorProto) {
         debug_assert_eq!(to_index(self.pool.files.len()), index);
 
-        let syntax = match file.syntax.as_deref() {
-            None | Some("proto2") => Syntax::Proto2,
-            Some("proto3") => Syntax::Proto3,
-            Some(syntax) => {
-                self.errors.push(DescriptorErrorKind::UnknownSyntax {
-                    syntax: syntax.to_owned(),
-                    found: Label::new(
-                        &self.pool.files,
-                        "found here",
-                        index,
-                        join_path(path, &[tag::file::SYNTAX]),
-                    ),
-                });
-                return;
-            }
-        };
-
         if self
             .pool
             .file_names
@@ -79,13 +64,25 @@ impl Visitor for NameVisitor<'_> {
             });
         }
         self.pool.files.push(FileDescriptorInner {
-            syntax,
+            syntax: Syntax::Proto2,
+            features: FeatureSet::default(),
             raw: file.clone(),
             prost: Default::default(), // the prost descriptor is initialized from the internal descriptor once resolution is complete, to avoid needing to duplicate all modifications
+            locations: HashMap::default(),
             dependencies: Vec::with_capacity(file.dependency.len()),
             transitive_dependencies: HashSet::default(),
         });
 
+        let syntax = self.resolve_syntax(path, index, file);
+        let features = self
+            .pool
+            .edition_defaults(syntax)
+            .unwrap_or_else(|| FeatureSet::builtin_defaults(syntax))
+            .resolve(file.options.as_ref(), tag::file::options::FEATURES);
+        let inner = &mut self.pool.files[index as usize];
+        inner.syntax = syntax;
+        inner.features = features;
+
         if !file.package().is_empty() {
             for (i, _) in file.package().match_indices('.') {
                 self.add_name(
@@ -123,6 +120,10 @@ impl Visitor for NameVisitor<'_> {
             DefinitionKind::Message(index),
         );
 
+        let features = self
+            .parent_features(file, parent)
+            .resolve(message.options.as_ref(), tag::message::options::FEATURES);
+
         debug_assert_eq!(to_index(self.pool.messages.len()), index);
         self.pool.messages.push(MessageDescriptorInner {
             id: Identity::new(file, path, full_name, message.name()),
@@ -133,9 +134,10 @@ impl Visitor for NameVisitor<'_> {
             oneofs: Vec::with_capacity(message.oneof_decl.len()),
             extensions: Vec::new(),
             parent,
+            features,
         });
 
-        if self.pool.files[file as usize].syntax != Syntax::Proto2 {
+        if features.json_format() == JsonFormat::Allow {
             self.check_message_field_camel_case_names(file, path, message);
         }
     }
@@ -179,11 +181,15 @@ impl Visitor for NameVisitor<'_> {
             to_index(self.pool.messages[message as usize].oneofs.len()),
             index
         );
+        let features = self.pool.messages[message as usize]
+            .features
+            .resolve(oneof.options.as_ref(), tag::oneof::options::FEATURES);
         self.pool.messages[message as usize]
             .oneofs
             .push(OneofDescriptorInner {
                 id: Identity::new(file, path, full_name, oneof.name()),
                 fields: Vec::new(),
+                features,
             });
     }
 
@@ -239,13 +245,15 @@ impl Visitor for NameVisitor<'_> {
             DefinitionKind::Enum(index),
         );
 
+        let features = self
+            .parent_features(file, parent)
+            .resolve(enum_.options.as_ref(), tag::enum_::options::FEATURES);
+
         if enum_.value.is_empty() {
             self.errors.push(DescriptorErrorKind::EmptyEnum {
                 found: Label::new(&self.pool.files, "enum defined here", file, path.into()),
             });
-        } else if self.pool.files[file as usize].syntax != Syntax::Proto2
-            && enum_.value[0].number() != 0
-        {
+        } else if features.enum_type() == EnumType::Open && enum_.value[0].number() != 0 {
             self.errors
                 .push(DescriptorErrorKind::InvalidProto3EnumDefault {
                     found: Label::new(
@@ -275,6 +283,7 @@ impl Visitor for NameVisitor<'_> {
             value_numbers: Vec::with_capacity(enum_.value.len()),
             value_names: HashMap::with_capacity(enum_.value.len()),
             allow_alias,
+            features,
         });
     }
 
@@ -299,11 +308,15 @@ impl Visitor for NameVisitor<'_> {
             to_index(self.pool.enums[enum_ as usize].values.len()),
             index
         );
+        let features = self.pool.enums[enum_ as usize]
+            .features
+            .resolve(value.options.as_ref(), tag::enum_value::options::FEATURES);
         self.pool.enums[enum_ as usize]
             .values
             .push(EnumValueDescriptorInner {
                 id: Identity::new(file, path, full_name, value.name()),
                 number: value.number(),
+                features,
             });
     }
 
@@ -327,6 +340,74 @@ impl Visitor for NameVisitor<'_> {
 }
 
 impl NameVisitor<'_> {
+    fn resolve_syntax(
+        &mut self,
+        path: &[i32],
+        index: FileIndex,
+        file: &FileDescriptorProto,
+    ) -> Syntax {
+        match file.syntax.as_deref() {
+            None | Some("proto2") => Syntax::Proto2,
+            Some("proto3") => Syntax::Proto3,
+            Some("editions") => match file.edition {
+                Some(edition) => {
+                    let syntax = match edition {
+                        EDITION_2023 => Some(Syntax::Editions(Edition::Edition2023)),
+                        EDITION_2024 => Some(Syntax::Editions(Edition::Edition2024)),
+                        _ => None,
+                    };
+                    // The edition must also be supported by the feature set defaults of the pool.
+                    if let Some(syntax) =
+                        syntax.filter(|&syntax| self.pool.edition_defaults(syntax).is_some())
+                    {
+                        return syntax;
+                    }
+
+                    self.errors.push(DescriptorErrorKind::UnknownEdition {
+                        edition,
+                        found: Label::new(
+                            &self.pool.files,
+                            "found here",
+                            index,
+                            join_path(path, &[tag::file::EDITION]),
+                        ),
+                    });
+                    Syntax::Proto2
+                }
+                None => {
+                    self.errors.push(DescriptorErrorKind::MissingRequiredField {
+                        label: Label::new(
+                            &self.pool.files,
+                            "edition not set",
+                            index,
+                            join_path(path, &[tag::file::EDITION]),
+                        ),
+                    });
+                    Syntax::Proto2
+                }
+            },
+            Some(syntax) => {
+                self.errors.push(DescriptorErrorKind::UnknownSyntax {
+                    syntax: syntax.to_owned(),
+                    found: Label::new(
+                        &self.pool.files,
+                        "found here",
+                        index,
+                        join_path(path, &[tag::file::SYNTAX]),
+                    ),
+                });
+                Syntax::Proto2
+            }
+        }
+    }
+
+    fn parent_features(&self, file: FileIndex, parent: Option<MessageIndex>) -> FeatureSet {
+        match parent {
+            Some(parent) => self.pool.messages[parent as usize].features,
+            None => self.pool.files[file as usize].features,
+        }
+    }
+
     fn add_name(
         &mut self,
         file: FileIndex,
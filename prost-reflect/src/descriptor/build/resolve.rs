@@ -5,14 +5,17 @@ use prost::bytes::Bytes;
 use crate::{
     descriptor::{
         build::{
-            join_path, resolve_name, to_json_name,
+            join_path,
+            options::option_to_bool,
+            resolve_name, to_json_name,
             visit::{visit, Visitor},
             DescriptorPoolOffsets, ResolveNameFilter,
         },
         error::{DescriptorError, DescriptorErrorKind, Label},
         find_enum_proto, find_message_proto, tag, to_index,
         types::{
-            field_descriptor_proto, DescriptorProto, EnumValueDescriptorProto,
+            feature_set::{FieldPresence, MessageEncoding, RepeatedFieldEncoding},
+            field_descriptor_proto, DescriptorProto, EnumValueDescriptorProto, FeatureSet,
             FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
             ServiceDescriptorProto,
         },
@@ -103,32 +106,35 @@ impl Visitor for ResolveVisitor<'_> {
             index
         );
 
-        let syntax = self.pool.files[file as usize].syntax;
-
         self.check_field_number(message, field, file, path);
 
-        let cardinality = match field.label() {
-            field_descriptor_proto::Label::Optional => Cardinality::Optional,
-            field_descriptor_proto::Label::Required => Cardinality::Required,
-            field_descriptor_proto::Label::Repeated => Cardinality::Repeated,
-        };
+        let parent_features = match field.oneof_index {
+            Some(oneof_index) => self.pool.messages[message as usize]
+                .oneofs
+                .get(oneof_index as usize)
+                .map(|oneof| oneof.features),
+            None => None,
+        }
+        .unwrap_or(self.pool.messages[message as usize].features);
+        let features = infer_legacy_features(
+            field,
+            self.pool.files[file as usize].syntax,
+            parent_features.resolve(field.options.as_ref(), tag::field::options::FEATURES),
+        );
+
+        let cardinality = field_cardinality(field, &features);
 
-        let kind =
-            self.resolve_field_type(field.r#type(), field.type_name(), full_name, file, path);
+        let kind = self.resolve_field_type(field, &features, Some(message), full_name, file, path);
 
         let json_name: Box<str> = self.resolve_field_json_name(field, file, path).into();
 
-        let is_packed = cardinality == Cardinality::Repeated
-            && kind.is_some_and(|k| k.is_packable())
-            && (field
-                .options
-                .as_ref()
-                .map_or(syntax == Syntax::Proto3, |o| o.value.packed()));
+        let is_packed = field_is_packed(field, &features, cardinality, kind);
 
         let supports_presence = field.proto3_optional()
             || field.oneof_index.is_some()
             || (cardinality != Cardinality::Repeated
-                && (kind.is_some_and(|k| k.is_message()) || syntax == Syntax::Proto2));
+                && (kind.is_some_and(|k| k.is_message())
+                    || features.field_presence() != FieldPresence::Implicit));
 
         let default = kind.and_then(|kind| {
             self.parse_field_default_value(kind, field.default_value.as_deref(), file, path)
@@ -156,6 +162,7 @@ impl Visitor for ResolveVisitor<'_> {
             json_name: json_name.clone(),
             cardinality,
             default,
+            features,
         });
         if let Some(existing) = message.field_numbers.insert(field.number() as u32, index) {
             self.errors.push(DescriptorErrorKind::DuplicateFieldNumber {
@@ -230,9 +237,14 @@ impl Visitor for ResolveVisitor<'_> {
     ) {
         debug_assert_eq!(to_index(self.pool.services.len()), index);
 
+        let features = self.pool.files[file as usize]
+            .features
+            .resolve(service.options.as_ref(), tag::service::options::FEATURES);
+
         self.pool.services.push(ServiceDescriptorInner {
             id: Identity::new(file, path, full_name, service.name()),
             methods: Vec::with_capacity(service.method.len()),
+            features,
         });
     }
 
@@ -269,12 +281,17 @@ impl Visitor for ResolveVisitor<'_> {
             )
             .unwrap_or(MessageIndex::MAX);
 
+        let features = self.pool.services[service as usize]
+            .features
+            .resolve(method.options.as_ref(), tag::method::options::FEATURES);
+
         self.pool.services[service as usize]
             .methods
             .push(MethodDescriptorInner {
                 id: Identity::new(file, path, full_name, method.name()),
                 input,
                 output,
+                features,
             });
     }
 
@@ -371,30 +388,23 @@ impl Visitor for ResolveVisitor<'_> {
             self.check_field_number(extendee, extension, file, path);
         }
 
-        let syntax = self.pool.files[file as usize].syntax;
-
-        let cardinality = match extension.label() {
-            field_descriptor_proto::Label::Optional => Cardinality::Optional,
-            field_descriptor_proto::Label::Required => Cardinality::Required,
-            field_descriptor_proto::Label::Repeated => Cardinality::Repeated,
+        let parent_features = match parent_message {
+            Some(parent_message) => self.pool.messages[parent_message as usize].features,
+            None => self.pool.files[file as usize].features,
         };
-
-        let kind = self.resolve_field_type(
-            extension.r#type(),
-            extension.type_name(),
-            full_name,
-            file,
-            path,
+        let features = infer_legacy_features(
+            extension,
+            self.pool.files[file as usize].syntax,
+            parent_features.resolve(extension.options.as_ref(), tag::field::options::FEATURES),
         );
 
+        let cardinality = field_cardinality(extension, &features);
+
+        let kind = self.resolve_field_type(extension, &features, None, full_name, file, path);
+
         self.resolve_field_json_name(extension, file, path);
 
-        let is_packed = cardinality == Cardinality::Repeated
-            && kind.is_some_and(|k| k.is_packable())
-            && (extension
-                .options
-                .as_ref()
-                .map_or(syntax == Syntax::Proto3, |o| o.value.packed()));
+        let is_packed = field_is_packed(extension, &features, cardinality, kind);
 
         let default = kind.and_then(|kind| {
             self.parse_field_default_value(kind, extension.default_value.as_deref(), file, path)
@@ -410,6 +420,7 @@ impl Visitor for ResolveVisitor<'_> {
             is_packed,
             cardinality,
             default,
+            features,
         });
     }
 }
@@ -568,12 +579,15 @@ impl ResolveVisitor<'_> {
 
     fn resolve_field_type(
         &mut self,
-        ty: field_descriptor_proto::Type,
-        ty_name: &str,
+        field: &FieldDescriptorProto,
+        features: &FeatureSet,
+        message: Option<MessageIndex>,
         scope: &str,
         file: FileIndex,
         path: &[i32],
     ) -> Option<KindIndex> {
+        let ty = field.r#type();
+        let ty_name = field.type_name();
         if ty_name.is_empty() {
             match ty {
                 field_descriptor_proto::Type::Double => Some(KindIndex::Double),
@@ -611,11 +625,15 @@ impl ResolveVisitor<'_> {
                 ResolveNameFilter::FieldType,
             )?;
             match def.kind {
-                DefinitionKind::Message(message) => {
-                    if ty == field_descriptor_proto::Type::Group {
-                        Some(KindIndex::Group(message))
+                DefinitionKind::Message(ty_message) => {
+                    if ty == field_descriptor_proto::Type::Group
+                        || (features.message_encoding() == MessageEncoding::Delimited
+                            && !self.is_map_entry(ty_message)
+                            && !message.is_some_and(|m| self.is_map_entry(m)))
+                    {
+                        Some(KindIndex::Group(ty_message))
                     } else {
-                        Some(KindIndex::Message(message))
+                        Some(KindIndex::Message(ty_message))
                     }
                 }
                 DefinitionKind::Enum(enum_) => Some(KindIndex::Enum(enum_)),
@@ -624,6 +642,17 @@ impl ResolveVisitor<'_> {
         }
     }
 
+    fn is_map_entry(&self, message: MessageIndex) -> bool {
+        let message = &self.pool.messages[message as usize];
+        find_message_proto(
+            &self.pool.files[message.id.file as usize].raw,
+            &message.id.path,
+        )
+        .options
+        .as_ref()
+        .is_some_and(|o| o.value.map_entry())
+    }
+
     fn parse_field_default_value(
         &mut self,
         kind: KindIndex,
@@ -766,6 +795,72 @@ impl ResolveVisitor<'_> {
     }
 }
 
+fn field_cardinality(field: &FieldDescriptorProto, features: &FeatureSet) -> Cardinality {
+    match field.label() {
+        field_descriptor_proto::Label::Optional
+            if features.field_presence() == FieldPresence::LegacyRequired =>
+        {
+            Cardinality::Required
+        }
+        field_descriptor_proto::Label::Optional => Cardinality::Optional,
+        field_descriptor_proto::Label::Required => Cardinality::Required,
+        field_descriptor_proto::Label::Repeated => Cardinality::Repeated,
+    }
+}
+
+/// Infers the features of a field in a `proto2` or `proto3` file from its label, type and
+/// `packed` option, in the same way as `protoc`.
+fn infer_legacy_features(
+    field: &FieldDescriptorProto,
+    syntax: Syntax,
+    mut features: FeatureSet,
+) -> FeatureSet {
+    if let Syntax::Editions(_) = syntax {
+        return features;
+    }
+
+    if field.label() == field_descriptor_proto::Label::Required {
+        features.set_field_presence(FieldPresence::LegacyRequired);
+    }
+    if field.proto3_optional() {
+        features.set_field_presence(FieldPresence::Explicit);
+    }
+    if field.r#type() == field_descriptor_proto::Type::Group {
+        features.set_message_encoding(MessageEncoding::Delimited);
+    }
+    match packed_option(field) {
+        Some(true) => features.set_repeated_field_encoding(RepeatedFieldEncoding::Packed),
+        Some(false) => features.set_repeated_field_encoding(RepeatedFieldEncoding::Expanded),
+        None => (),
+    }
+    features
+}
+
+fn field_is_packed(
+    field: &FieldDescriptorProto,
+    features: &FeatureSet,
+    cardinality: Cardinality,
+    kind: Option<KindIndex>,
+) -> bool {
+    cardinality == Cardinality::Repeated
+        && kind.is_some_and(|k| k.is_packable())
+        && packed_option(field)
+            .unwrap_or(features.repeated_field_encoding() == RepeatedFieldEncoding::Packed)
+}
+
+/// Gets the value of the `packed` option of a field, which may not yet have been interpreted.
+fn packed_option(field: &FieldDescriptorProto) -> Option<bool> {
+    field.options.as_ref().and_then(|o| {
+        o.value.packed.or_else(|| {
+            o.value.uninterpreted_option.iter().find_map(|u| {
+                (u.name.len() == 1 && u.name[0].name_part == "packed" && !u.name[0].is_extension)
+                    .then(|| option_to_bool(u).ok())
+                    .flatten()
+            })
+        })
+    })
+}
+
 fn parse_simple_value(
     kind: KindIndex,
     value: &str,
@@ -9,7 +9,7 @@ use crate::descriptor::{
     OneofIndex, ServiceIndex,
 };
 
-pub(super) trait Visitor {
+pub(in crate::descriptor) trait Visitor {
     fn visit_file(&mut self, _path: &[i32], _index: FileIndex, _file: &FileDescriptorProto) {}
 
     fn visit_message(
@@ -100,9 +100,9 @@ pub(super) trait Visitor {
     }
 }
 
-pub(super) fn visit(
+pub(super) fn visit<'a>(
     offsets: DescriptorPoolOffsets,
-    files: &[FileDescriptorProto],
+    files: impl IntoIterator<Item = &'a FileDescriptorProto>,
     visitor: &mut dyn Visitor,
 ) {
     let mut context = Context {
@@ -0,0 +1,862 @@
+use std::fmt;
+
+use crate::{
+    descriptor::{error::Label, Span},
+    Cardinality, DescriptorPool, EnumDescriptor, ExtensionDescriptor, FieldDescriptor,
+    FileDescriptor, Kind, MessageDescriptor, ServiceDescriptor,
+};
+
+/// A change between two versions of a schema which may break existing clients or stored data.
+///
+/// Breaking changes are found by [`DescriptorPool::check_compatibility`] and
+/// [`FileDescriptor::check_compatibility`]. Each change is associated with the location of the
+/// affected definition in the new schema, or in the old schema if the definition was removed
+/// (see [`BreakingChangeKind::is_removal`]).
+pub struct BreakingChange {
+    kind: BreakingChangeKind,
+    message: String,
+    wire: bool,
+    json: bool,
+    label: Label,
+    #[cfg(feature = "miette")]
+    source: Option<miette::NamedSource<String>>,
+}
+
+/// The kind of a [`BreakingChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BreakingChangeKind {
+    /// A message was removed.
+    MessageRemoved,
+    /// An enum was removed.
+    EnumRemoved,
+    /// A service was removed.
+    ServiceRemoved,
+    /// An extension was removed.
+    ExtensionRemoved,
+    /// A field was removed, without reserving both its name and number.
+    FieldRemoved,
+    /// A field was given a different number.
+    FieldNumberChanged,
+    /// A field was renamed.
+    FieldNameChanged,
+    /// The JSON name of a field was changed.
+    FieldJsonNameChanged,
+    /// The type of a field was changed.
+    FieldTypeChanged,
+    /// A field was changed between singular, `required` and `repeated`.
+    FieldCardinalityChanged,
+    /// A field was moved into or out of a oneof.
+    FieldOneofChanged,
+    /// A field or enum value was added with a number reserved in the old schema.
+    ReservedNumberUsed,
+    /// A field or enum value was added with a name reserved in the old schema.
+    ReservedNameUsed,
+    /// An enum value was removed, without reserving both its name and number.
+    EnumValueRemoved,
+    /// An enum value was renamed.
+    EnumValueNameChanged,
+    /// A method was removed from a service.
+    MethodRemoved,
+    /// The input type of a method was changed.
+    MethodInputChanged,
+    /// The output type of a method was changed.
+    MethodOutputChanged,
+    /// A method was changed between streaming and non-streaming.
+    MethodStreamingChanged,
+}
+
+impl BreakingChangeKind {
+    /// Returns `true` if this change is the removal of a definition. The location of such
+    /// changes refers to the old schema.
+    pub fn is_removal(self) -> bool {
+        matches!(
+            self,
+            BreakingChangeKind::MessageRemoved
+                | BreakingChangeKind::EnumRemoved
+                | BreakingChangeKind::ServiceRemoved
+                | BreakingChangeKind::ExtensionRemoved
+                | BreakingChangeKind::FieldRemoved
+                | BreakingChangeKind::EnumValueRemoved
+                | BreakingChangeKind::MethodRemoved
+        )
+    }
+}
+
+impl DescriptorPool {
+    /// Checks whether `new` is a compatible revision of the schema in this pool.
+    ///
+    /// Definitions are matched by their fully-qualified name, so moving a definition between
+    /// files is not considered a breaking change. Fields and enum values are matched by number.
+    /// The returned changes are ordered by the definitions they affect, starting with messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, BreakingChangeKind};
+    /// # use prost_types::{field_descriptor_proto::{Label, Type}, DescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+    /// let file = |ty| FileDescriptorProto {
+    ///     name: Some("my.proto".to_owned()),
+    ///     syntax: Some("proto3".to_owned()),
+    ///     message_type: vec![DescriptorProto {
+    ///         name: Some("MyMessage".to_owned()),
+    ///         field: vec![FieldDescriptorProto {
+    ///             name: Some("foo".to_owned()),
+    ///             number: Some(1),
+    ///             label: Some(Label::Optional as i32),
+    ///             r#type: Some(ty as i32),
+    ///             ..Default::default()
+    ///         }],
+    ///         ..Default::default()
+    ///     }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut old = DescriptorPool::new();
+    /// old.add_file_descriptor_proto(file(Type::Int32)).unwrap();
+    /// let mut new = DescriptorPool::new();
+    /// new.add_file_descriptor_proto(file(Type::String)).unwrap();
+    ///
+    /// let changes = old.check_compatibility(&new);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].kind(), BreakingChangeKind::FieldTypeChanged);
+    /// assert!(changes[0].is_wire_breaking());
+    /// assert_eq!(changes[0].to_string(), "field 'MyMessage.foo' changed type from int32 to string");
+    /// ```
+    pub fn check_compatibility(&self, new: &DescriptorPool) -> Vec<BreakingChange> {
+        let mut checker = Checker {
+            new,
+            changes: Vec::new(),
+        };
+        checker.check_messages(self.all_messages());
+        checker.check_enums(self.all_enums());
+        checker.check_extensions(self.all_extensions());
+        checker.check_services(self.services());
+        checker.changes
+    }
+}
+
+impl FileDescriptor {
+    /// Checks whether `new` is a compatible revision of the definitions in this file.
+    ///
+    /// Only definitions in this file are checked. They are looked up by their fully-qualified
+    /// name in the pool containing `new`, so definitions moved to one of its dependencies are
+    /// still matched. See [`DescriptorPool::check_compatibility`] for more details.
+    pub fn check_compatibility(&self, new: &FileDescriptor) -> Vec<BreakingChange> {
+        let new_pool = new.parent_pool();
+        let mut checker = Checker {
+            new: new_pool,
+            changes: Vec::new(),
+        };
+        checker.check_messages(
+            self.parent_pool()
+                .all_messages()
+                .filter(|message| message.parent_file() == *self),
+        );
+        checker.check_enums(
+            self.parent_pool()
+                .all_enums()
+                .filter(|enum_| enum_.parent_file() == *self),
+        );
+        checker.check_extensions(
+            self.parent_pool()
+                .all_extensions()
+                .filter(|extension| extension.parent_file() == *self),
+        );
+        checker.check_services(self.services());
+        checker.changes
+    }
+}
+
+impl BreakingChange {
+    /// Gets the kind of this change.
+    pub fn kind(&self) -> BreakingChangeKind {
+        self.kind
+    }
+
+    /// Returns `true` if this change may prevent data encoded in the protobuf binary format with
+    /// one schema from being decoded correctly with the other.
+    pub fn is_wire_breaking(&self) -> bool {
+        self.wire
+    }
+
+    /// Returns `true` if this change may prevent data encoded in the JSON format with one schema
+    /// from being decoded correctly with the other.
+    pub fn is_json_breaking(&self) -> bool {
+        self.json
+    }
+
+    /// The file containing the affected definition.
+    pub fn file(&self) -> &str {
+        self.label.file()
+    }
+
+    /// The 0-based line number of the affected definition, if available.
+    ///
+    /// This field may be `None` if the [`source_code_info`](prost_types::FileDescriptorProto::source_code_info)
+    /// field was not populated for the file.
+    pub fn line(&self) -> Option<usize> {
+        self.label.line()
+    }
+
+    /// The 0-based column number of the affected definition, if available.
+    ///
+    /// This field may be `None` if the [`source_code_info`](prost_types::FileDescriptorProto::source_code_info)
+    /// field was not populated for the file.
+    pub fn column(&self) -> Option<usize> {
+        self.label.column()
+    }
+
+    /// Gets the path of the affected definition in its [`FileDescriptorProto`][prost_types::FileDescriptorProto].
+    ///
+    /// See [`path`][prost_types::source_code_info::Location::path] for more details on the structure of the path.
+    pub fn path(&self) -> &[i32] {
+        self.label.path()
+    }
+
+    #[cfg(feature = "miette")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
+    /// Provide source code information for this change.
+    ///
+    /// The source should correspond to the contents of [`file()`][BreakingChange::file].
+    pub fn with_source_code(mut self, source: &str) -> Self {
+        let file = self.file().to_owned();
+        self.label.resolve_span(&file, source);
+        self.source = Some(miette::NamedSource::new(&file, source.to_owned()));
+        self
+    }
+
+    fn new(
+        kind: BreakingChangeKind,
+        message: String,
+        (wire, json): (bool, bool),
+        label: Label,
+    ) -> Self {
+        BreakingChange {
+            kind,
+            message,
+            wire,
+            json,
+            label,
+            #[cfg(feature = "miette")]
+            source: None,
+        }
+    }
+}
+
+impl std::error::Error for BreakingChange {}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Debug for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.file())?;
+        if let (Some(line), Some(column)) = (self.line(), self.column()) {
+            write!(f, "{}:{}:", line + 1, column + 1)?;
+        }
+        write!(f, " {self}")
+    }
+}
+
+#[cfg(feature = "miette")]
+#[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
+impl miette::Diagnostic for BreakingChange {
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        match self.kind {
+            BreakingChangeKind::FieldRemoved | BreakingChangeKind::EnumValueRemoved => Some(
+                Box::new("reserve the name and number to prevent them from being reused"),
+            ),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        match &self.source {
+            Some(source) => Some(source),
+            None => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.label.to_span()?;
+        Some(Box::new(std::iter::once(span)))
+    }
+}
+
+const WIRE_AND_JSON: (bool, bool) = (true, true);
+const WIRE_ONLY: (bool, bool) = (true, false);
+const JSON_ONLY: (bool, bool) = (false, true);
+
+struct Checker<'a> {
+    new: &'a DescriptorPool,
+    changes: Vec<BreakingChange>,
+}
+
+/// The properties of a field or extension which affect compatibility.
+struct FieldInfo {
+    name: String,
+    full_name: String,
+    json_name: String,
+    kind: Kind,
+    cardinality: Cardinality,
+    is_map: bool,
+    is_group: bool,
+    oneof: Option<String>,
+    file: FileDescriptor,
+    path: Box<[i32]>,
+    span: Option<Span>,
+}
+
+impl Checker<'_> {
+    fn check_messages(&mut self, messages: impl Iterator<Item = MessageDescriptor>) {
+        for old in messages {
+            if old.is_map_entry() {
+                continue;
+            }
+
+            match self.new.get_message_by_name(old.full_name()) {
+                Some(new) => self.check_message(&old, &new),
+                None => self.push(
+                    BreakingChangeKind::MessageRemoved,
+                    format!("message '{}' was removed", old.full_name()),
+                    WIRE_AND_JSON,
+                    label(&old.parent_file(), "removed", old.path(), old.span()),
+                ),
+            }
+        }
+    }
+
+    fn check_message(&mut self, old: &MessageDescriptor, new: &MessageDescriptor) {
+        for old_field in old.fields() {
+            let old_info = FieldInfo::from_field(&old_field);
+            match new.get_field(old_field.number()) {
+                Some(new_field) => {
+                    self.check_field(&old_info, &FieldInfo::from_field(&new_field));
+                }
+                None => match new.get_field_by_name(old_field.name()) {
+                    Some(new_field) => self.push(
+                        BreakingChangeKind::FieldNumberChanged,
+                        format!(
+                            "field '{}' changed number from {} to {}",
+                            old_field.full_name(),
+                            old_field.number(),
+                            new_field.number()
+                        ),
+                        WIRE_ONLY,
+                        FieldInfo::from_field(&new_field).label("number changed"),
+                    ),
+                    None => {
+                        let number_reserved = new
+                            .reserved_ranges()
+                            .any(|range| range.contains(&old_field.number()));
+                        let name_reserved = new.reserved_names().any(|n| n == old_field.name());
+                        if !number_reserved || !name_reserved {
+                            self.push(
+                                BreakingChangeKind::FieldRemoved,
+                                format!("field '{}' was removed", old_field.full_name()),
+                                (!number_reserved, !name_reserved),
+                                old_info.label("removed"),
+                            );
+                        }
+                    }
+                },
+            }
+        }
+
+        for new_field in new.fields() {
+            if old.get_field(new_field.number()).is_some()
+                || old.get_field_by_name(new_field.name()).is_some()
+            {
+                continue;
+            }
+
+            if old
+                .reserved_ranges()
+                .any(|range| range.contains(&new_field.number()))
+            {
+                self.push(
+                    BreakingChangeKind::ReservedNumberUsed,
+                    format!(
+                        "field '{}' uses reserved number {}",
+                        new_field.full_name(),
+                        new_field.number()
+                    ),
+                    WIRE_ONLY,
+                    FieldInfo::from_field(&new_field).label("added here"),
+                );
+            }
+            if old.reserved_names().any(|name| name == new_field.name()) {
+                self.push(
+                    BreakingChangeKind::ReservedNameUsed,
+                    format!(
+                        "field '{}' uses reserved name '{}'",
+                        new_field.full_name(),
+                        new_field.name()
+                    ),
+                    JSON_ONLY,
+                    FieldInfo::from_field(&new_field).label("added here"),
+                );
+            }
+        }
+    }
+
+    fn check_field(&mut self, old: &FieldInfo, new: &FieldInfo) {
+        if old.name != new.name {
+            self.push(
+                BreakingChangeKind::FieldNameChanged,
+                format!("field '{}' was renamed to '{}'", old.full_name, new.name),
+                JSON_ONLY,
+                new.label("renamed"),
+            );
+        } else if old.json_name != new.json_name {
+            self.push(
+                BreakingChangeKind::FieldJsonNameChanged,
+                format!(
+                    "field '{}' changed JSON name from '{}' to '{}'",
+                    new.full_name, old.json_name, new.json_name
+                ),
+                JSON_ONLY,
+                new.label("JSON name changed"),
+            );
+        }
+
+        let (old_kinds, new_kinds) = (old.value_kinds(), new.value_kinds());
+        let kinds = || old_kinds.iter().zip(&new_kinds);
+        if old.is_map != new.is_map || !kinds().all(|(old, new)| same_type(old, new)) {
+            let wire = old.is_map != new.is_map
+                || old.is_group != new.is_group
+                || !kinds().all(|(old, new)| is_wire_compatible(old, new));
+            self.push(
+                BreakingChangeKind::FieldTypeChanged,
+                format!(
+                    "field '{}' changed type from {} to {}",
+                    new.full_name,
+                    old.type_name(),
+                    new.type_name()
+                ),
+                (wire, true),
+                new.label("type changed"),
+            );
+        } else if old.is_group != new.is_group {
+            self.push(
+                BreakingChangeKind::FieldTypeChanged,
+                format!(
+                    "field '{}' changed between group and message encoding",
+                    new.full_name,
+                ),
+                WIRE_ONLY,
+                new.label("encoding changed"),
+            );
+        }
+
+        if old.cardinality != new.cardinality && !old.is_map && !new.is_map {
+            self.push(
+                BreakingChangeKind::FieldCardinalityChanged,
+                format!(
+                    "field '{}' changed from {} to {}",
+                    new.full_name,
+                    cardinality_name(old.cardinality),
+                    cardinality_name(new.cardinality)
+                ),
+                WIRE_AND_JSON,
+                new.label("cardinality changed"),
+            );
+        }
+
+        if old.oneof != new.oneof {
+            let message = match (&old.oneof, &new.oneof) {
+                (None, Some(oneof)) => {
+                    format!("field '{}' was moved into oneof '{oneof}'", new.full_name)
+                }
+                (Some(oneof), None) => {
+                    format!("field '{}' was moved out of oneof '{oneof}'", new.full_name)
+                }
+                (Some(old_oneof), Some(new_oneof)) => format!(
+                    "field '{}' was moved from oneof '{old_oneof}' to '{new_oneof}'",
+                    new.full_name
+                ),
+                (None, None) => unreachable!(),
+            };
+            self.push(
+                BreakingChangeKind::FieldOneofChanged,
+                message,
+                WIRE_AND_JSON,
+                new.label("oneof changed"),
+            );
+        }
+    }
+
+    fn check_enums(&mut self, enums: impl Iterator<Item = EnumDescriptor>) {
+        for old in enums {
+            match self.new.get_enum_by_name(old.full_name()) {
+                Some(new) => self.check_enum(&old, &new),
+                None => self.push(
+                    BreakingChangeKind::EnumRemoved,
+                    format!("enum '{}' was removed", old.full_name()),
+                    WIRE_AND_JSON,
+                    label(&old.parent_file(), "removed", old.path(), old.span()),
+                ),
+            }
+        }
+    }
+
+    fn check_enum(&mut self, old: &EnumDescriptor, new: &EnumDescriptor) {
+        for old_value in old.values() {
+            match new.get_value(old_value.number()) {
+                Some(new_value) => {
+                    // With aliases, the value may still be present under another name.
+                    if new.get_value_by_name(old_value.name()).is_none() {
+                        self.push(
+                            BreakingChangeKind::EnumValueNameChanged,
+                            format!(
+                                "enum value '{}' was renamed to '{}'",
+                                old_value.full_name(),
+                                new_value.name()
+                            ),
+                            JSON_ONLY,
+                            label(
+                                &new_value.parent_file(),
+                                "renamed",
+                                new_value.path(),
+                                new_value.span(),
+                            ),
+                        );
+                    }
+                }
+                None => {
+                    let number_reserved = new
+                        .reserved_ranges()
+                        .any(|range| range.contains(&old_value.number()));
+                    let name_reserved = new.reserved_names().any(|n| n == old_value.name());
+                    if !number_reserved || !name_reserved {
+                        self.push(
+                            BreakingChangeKind::EnumValueRemoved,
+                            format!("enum value '{}' was removed", old_value.full_name()),
+                            (!number_reserved, !name_reserved),
+                            label(
+                                &old_value.parent_file(),
+                                "removed",
+                                old_value.path(),
+                                old_value.span(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        for new_value in new.values() {
+            if old.get_value(new_value.number()).is_some() {
+                continue;
+            }
+
+            let new_label = || {
+                label(
+                    &new_value.parent_file(),
+                    "added here",
+                    new_value.path(),
+                    new_value.span(),
+                )
+            };
+            if old
+                .reserved_ranges()
+                .any(|range| range.contains(&new_value.number()))
+            {
+                self.push(
+                    BreakingChangeKind::ReservedNumberUsed,
+                    format!(
+                        "enum value '{}' uses reserved number {}",
+                        new_value.full_name(),
+                        new_value.number()
+                    ),
+                    WIRE_ONLY,
+                    new_label(),
+                );
+            }
+            if old.reserved_names().any(|name| name == new_value.name()) {
+                self.push(
+                    BreakingChangeKind::ReservedNameUsed,
+                    format!(
+                        "enum value '{}' uses reserved name '{}'",
+                        new_value.full_name(),
+                        new_value.name()
+                    ),
+                    JSON_ONLY,
+                    new_label(),
+                );
+            }
+        }
+    }
+
+    fn check_extensions(&mut self, extensions: impl Iterator<Item = ExtensionDescriptor>) {
+        for old in extensions {
+            let old_info = FieldInfo::from_extension(&old);
+            match self.new.get_extension_by_name(old.full_name()) {
+                Some(new)
+                    if new.containing_message().full_name()
+                        == old.containing_message().full_name() =>
+                {
+                    let new_info = FieldInfo::from_extension(&new);
+                    if old.number() != new.number() {
+                        self.push(
+                            BreakingChangeKind::FieldNumberChanged,
+                            format!(
+                                "extension '{}' changed number from {} to {}",
+                                old.full_name(),
+                                old.number(),
+                                new.number()
+                            ),
+                            WIRE_ONLY,
+                            new_info.label("number changed"),
+                        );
+                    }
+                    self.check_field(&old_info, &new_info);
+                }
+                _ => self.push(
+                    BreakingChangeKind::ExtensionRemoved,
+                    format!(
+                        "extension '{}' of '{}' was removed",
+                        old.full_name(),
+                        old.containing_message().full_name()
+                    ),
+                    WIRE_AND_JSON,
+                    old_info.label("removed"),
+                ),
+            }
+        }
+    }
+
+    fn check_services(&mut self, services: impl Iterator<Item = ServiceDescriptor>) {
+        for old in services {
+            match self.new.get_service_by_name(old.full_name()) {
+                Some(new) => self.check_service(&old, &new),
+                None => self.push(
+                    BreakingChangeKind::ServiceRemoved,
+                    format!("service '{}' was removed", old.full_name()),
+                    WIRE_AND_JSON,
+                    label(&old.parent_file(), "removed", old.path(), old.span()),
+                ),
+            }
+        }
+    }
+
+    fn check_service(&mut self, old: &ServiceDescriptor, new: &ServiceDescriptor) {
+        for old_method in old.methods() {
+            let Some(new_method) = new.methods().find(|m| m.name() == old_method.name()) else {
+                self.push(
+                    BreakingChangeKind::MethodRemoved,
+                    format!("method '{}' was removed", old_method.full_name()),
+                    WIRE_AND_JSON,
+                    label(
+                        &old.parent_file(),
+                        "removed",
+                        old_method.path(),
+                        old_method.span(),
+                    ),
+                );
+                continue;
+            };
+            let new_label = |message| {
+                label(
+                    &new.parent_file(),
+                    message,
+                    new_method.path(),
+                    new_method.span(),
+                )
+            };
+
+            if old_method.input().full_name() != new_method.input().full_name() {
+                self.push(
+                    BreakingChangeKind::MethodInputChanged,
+                    format!(
+                        "method '{}' changed input type from '{}' to '{}'",
+                        new_method.full_name(),
+                        old_method.input().full_name(),
+                        new_method.input().full_name()
+                    ),
+                    WIRE_AND_JSON,
+                    new_label("input type changed"),
+                );
+            }
+            if old_method.output().full_name() != new_method.output().full_name() {
+                self.push(
+                    BreakingChangeKind::MethodOutputChanged,
+                    format!(
+                        "method '{}' changed output type from '{}' to '{}'",
+                        new_method.full_name(),
+                        old_method.output().full_name(),
+                        new_method.output().full_name()
+                    ),
+                    WIRE_AND_JSON,
+                    new_label("output type changed"),
+                );
+            }
+            if old_method.is_client_streaming() != new_method.is_client_streaming()
+                || old_method.is_server_streaming() != new_method.is_server_streaming()
+            {
+                self.push(
+                    BreakingChangeKind::MethodStreamingChanged,
+                    format!(
+                        "method '{}' changed from {} to {}",
+                        new_method.full_name(),
+                        streaming_name(
+                            old_method.is_client_streaming(),
+                            old_method.is_server_streaming()
+                        ),
+                        streaming_name(
+                            new_method.is_client_streaming(),
+                            new_method.is_server_streaming()
+                        ),
+                    ),
+                    WIRE_AND_JSON,
+                    new_label("streaming changed"),
+                );
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        kind: BreakingChangeKind,
+        message: String,
+        breaks: (bool, bool),
+        label: Label,
+    ) {
+        self.changes
+            .push(BreakingChange::new(kind, message, breaks, label));
+    }
+}
+
+impl FieldInfo {
+    fn from_field(field: &FieldDescriptor) -> Self {
+        FieldInfo {
+            name: field.name().to_owned(),
+            full_name: field.full_name().to_owned(),
+            json_name: field.json_name().to_owned(),
+            kind: field.kind(),
+            cardinality: field.cardinality(),
+            is_map: field.is_map(),
+            is_group: field.is_group(),
+            oneof: field
+                .containing_oneof()
+                .filter(|oneof| !oneof.is_synthetic())
+                .map(|oneof| oneof.name().to_owned()),
+            file: field.parent_file(),
+            path: field.path().into(),
+            span: field.span(),
+        }
+    }
+
+    fn from_extension(extension: &ExtensionDescriptor) -> Self {
+        FieldInfo {
+            name: extension.name().to_owned(),
+            full_name: extension.full_name().to_owned(),
+            json_name: extension.json_name().to_owned(),
+            kind: extension.kind(),
+            cardinality: extension.cardinality(),
+            is_map: extension.is_map(),
+            is_group: extension.is_group(),
+            oneof: None,
+            file: extension.parent_file(),
+            path: extension.path().into(),
+            span: extension.span(),
+        }
+    }
+
+    /// Gets the types of the values of this field: the key and value types for maps, and
+    /// otherwise just the field type.
+    fn value_kinds(&self) -> Vec<Kind> {
+        match self.kind.as_message() {
+            Some(entry) if self.is_map => vec![
+                entry.map_entry_key_field().kind(),
+                entry.map_entry_value_field().kind(),
+            ],
+            _ => vec![self.kind.clone()],
+        }
+    }
+
+    fn type_name(&self) -> String {
+        if self.is_map {
+            let entry = self
+                .kind
+                .as_message()
+                .expect("map entry should be a message");
+            format!(
+                "map<{:?}, {:?}>",
+                entry.map_entry_key_field().kind(),
+                entry.map_entry_value_field().kind()
+            )
+        } else {
+            format!("{:?}", self.kind)
+        }
+    }
+
+    fn label(&self, message: &str) -> Label {
+        label(&self.file, message, &self.path, self.span)
+    }
+}
+
+fn label(file: &FileDescriptor, message: &str, path: &[i32], span: Option<Span>) -> Label {
+    Label::from_location(file.name(), message, path, span)
+}
+
+/// Returns `true` if values of the two types are represented identically in both the binary and
+/// JSON formats.
+fn same_type(old: &Kind, new: &Kind) -> bool {
+    match (old, new) {
+        (Kind::Message(old), Kind::Message(new)) => old.full_name() == new.full_name(),
+        (Kind::Enum(old), Kind::Enum(new)) => old.full_name() == new.full_name(),
+        (old, new) => old == new,
+    }
+}
+
+/// Returns `true` if values encoded with one type can be decoded as the other, although they
+/// may be truncated or reinterpreted.
+fn is_wire_compatible(old: &Kind, new: &Kind) -> bool {
+    fn group(kind: &Kind) -> u8 {
+        match kind {
+            Kind::Int32
+            | Kind::Int64
+            | Kind::Uint32
+            | Kind::Uint64
+            | Kind::Bool
+            | Kind::Enum(_) => 0,
+            Kind::Sint32 | Kind::Sint64 => 1,
+            Kind::Fixed32 | Kind::Sfixed32 => 2,
+            Kind::Fixed64 | Kind::Sfixed64 => 3,
+            Kind::String | Kind::Bytes => 4,
+            Kind::Float => 5,
+            Kind::Double => 6,
+            Kind::Message(_) => 7,
+        }
+    }
+
+    // Messages are only compatible with each other if they are the same type, although they may
+    // be compatible with bytes fields.
+    match (old, new) {
+        (Kind::Message(_), Kind::Message(_)) => same_type(old, new),
+        (Kind::Message(_), Kind::Bytes) | (Kind::Bytes, Kind::Message(_)) => true,
+        (old, new) => group(old) == group(new),
+    }
+}
+
+fn cardinality_name(cardinality: Cardinality) -> &'static str {
+    match cardinality {
+        Cardinality::Optional => "optional",
+        Cardinality::Required => "required",
+        Cardinality::Repeated => "repeated",
+    }
+}
+
+fn streaming_name(client_streaming: bool, server_streaming: bool) -> &'static str {
+    match (client_streaming, server_streaming) {
+        (false, false) => "unary",
+        (true, false) => "client streaming",
+        (false, true) => "server streaming",
+        (true, true) => "bidirectional streaming",
+    }
+}
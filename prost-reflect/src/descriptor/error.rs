@@ -3,7 +3,7 @@ use std::{
     ops::{Range, RangeInclusive},
 };
 
-use crate::descriptor::{FileDescriptorInner, FileIndex};
+use crate::descriptor::{FileDescriptorInner, FileIndex, Span};
 
 /// An error that may occur while creating a [`DescriptorPool`][crate::DescriptorPool].
 pub struct DescriptorError {
@@ -21,9 +21,16 @@ pub(super) enum DescriptorErrorKind {
         syntax: String,
         found: Label,
     },
+    UnknownEdition {
+        edition: i32,
+        found: Label,
+    },
     DuplicateFileName {
         name: String,
     },
+    EditionNotSupportedByProstTypes {
+        name: String,
+    },
     FileNotFound {
         name: String,
         found: Label,
@@ -152,6 +159,95 @@ pub(super) enum DescriptorErrorKind {
     DecodeFileDescriptorSet {
         err: prost::DecodeError,
     },
+    InvalidRoot {
+        name: String,
+    },
+    FileNotInPool {
+        name: String,
+    },
+    FileInUse {
+        name: String,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidToken {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidStringCharacters {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidStringEscape {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    UnterminatedString {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    UnterminatedBlockComment {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    UnexpectedToken {
+        expected: String,
+        token: String,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    UnexpectedEof {
+        expected: String,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    IntegerOutOfRange {
+        value: String,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidGroupName {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    FieldLabelNotAllowed {
+        label: String,
+        context: &'static str,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    MapFieldNotAllowed {
+        context: &'static str,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidMapKeyType {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    GroupSyntaxNotSupported {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    InvalidReservedName {
+        editions: bool,
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    DuplicatePackage {
+        found: Label,
+    },
+    #[cfg(feature = "proto-parse")]
+    FileRead {
+        name: String,
+        err: std::io::Error,
+        found: Option<Label>,
+    },
+    #[cfg(feature = "proto-parse")]
+    ImportCycle {
+        cycle: String,
+        found: Label,
+    },
 }
 
 #[derive(Debug)]
@@ -310,7 +406,9 @@ impl DescriptorErrorKind {
         match self {
             DescriptorErrorKind::MissingRequiredField { label } => Some(label),
             DescriptorErrorKind::UnknownSyntax { found, .. } => Some(found),
+            DescriptorErrorKind::UnknownEdition { found, .. } => Some(found),
             DescriptorErrorKind::DuplicateFileName { .. } => None,
+            DescriptorErrorKind::EditionNotSupportedByProstTypes { .. } => None,
             DescriptorErrorKind::FileNotFound { found, .. } => Some(found),
             DescriptorErrorKind::InvalidImportIndex => None,
             DescriptorErrorKind::InvalidOneofIndex => None,
@@ -337,6 +435,28 @@ impl DescriptorErrorKind {
             DescriptorErrorKind::InvalidMessageOption { found, .. } => Some(found),
             DescriptorErrorKind::DuplicateOption { found, .. } => Some(found),
             DescriptorErrorKind::DecodeFileDescriptorSet { .. } => None,
+            DescriptorErrorKind::InvalidRoot { .. } => None,
+            DescriptorErrorKind::FileNotInPool { .. } => None,
+            DescriptorErrorKind::FileInUse { found, .. } => Some(found),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidToken { found }
+            | DescriptorErrorKind::InvalidStringCharacters { found }
+            | DescriptorErrorKind::InvalidStringEscape { found }
+            | DescriptorErrorKind::UnterminatedString { found }
+            | DescriptorErrorKind::UnterminatedBlockComment { found }
+            | DescriptorErrorKind::UnexpectedToken { found, .. }
+            | DescriptorErrorKind::UnexpectedEof { found, .. }
+            | DescriptorErrorKind::IntegerOutOfRange { found, .. }
+            | DescriptorErrorKind::InvalidGroupName { found }
+            | DescriptorErrorKind::FieldLabelNotAllowed { found, .. }
+            | DescriptorErrorKind::MapFieldNotAllowed { found, .. }
+            | DescriptorErrorKind::InvalidMapKeyType { found }
+            | DescriptorErrorKind::GroupSyntaxNotSupported { found }
+            | DescriptorErrorKind::InvalidReservedName { found, .. }
+            | DescriptorErrorKind::DuplicatePackage { found }
+            | DescriptorErrorKind::ImportCycle { found, .. } => Some(found),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FileRead { found, .. } => found.as_ref(),
         }
     }
 
@@ -349,7 +469,11 @@ impl DescriptorErrorKind {
             DescriptorErrorKind::UnknownSyntax { found, .. } => {
                 found.resolve_span(file, source);
             }
+            DescriptorErrorKind::UnknownEdition { found, .. } => {
+                found.resolve_span(file, source);
+            }
             DescriptorErrorKind::DuplicateFileName { .. } => {}
+            DescriptorErrorKind::EditionNotSupportedByProstTypes { .. } => {}
             DescriptorErrorKind::FileNotFound { found, .. } => {
                 found.resolve_span(file, source);
             }
@@ -429,6 +553,36 @@ impl DescriptorErrorKind {
                 found.resolve_span(file, source);
             }
             DescriptorErrorKind::DecodeFileDescriptorSet { .. } => {}
+            DescriptorErrorKind::InvalidRoot { .. } => {}
+            DescriptorErrorKind::FileNotInPool { .. } => {}
+            DescriptorErrorKind::FileInUse { found, .. } => {
+                found.resolve_span(file, source);
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidToken { found }
+            | DescriptorErrorKind::InvalidStringCharacters { found }
+            | DescriptorErrorKind::InvalidStringEscape { found }
+            | DescriptorErrorKind::UnterminatedString { found }
+            | DescriptorErrorKind::UnterminatedBlockComment { found }
+            | DescriptorErrorKind::UnexpectedToken { found, .. }
+            | DescriptorErrorKind::UnexpectedEof { found, .. }
+            | DescriptorErrorKind::IntegerOutOfRange { found, .. }
+            | DescriptorErrorKind::InvalidGroupName { found }
+            | DescriptorErrorKind::FieldLabelNotAllowed { found, .. }
+            | DescriptorErrorKind::MapFieldNotAllowed { found, .. }
+            | DescriptorErrorKind::InvalidMapKeyType { found }
+            | DescriptorErrorKind::GroupSyntaxNotSupported { found }
+            | DescriptorErrorKind::InvalidReservedName { found, .. }
+            | DescriptorErrorKind::DuplicatePackage { found }
+            | DescriptorErrorKind::ImportCycle { found, .. } => {
+                found.resolve_span(file, source);
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FileRead { found, .. } => {
+                if let Some(found) = found {
+                    found.resolve_span(file, source);
+                }
+            }
         }
     }
 }
@@ -439,6 +593,8 @@ impl std::error::Error for DescriptorErrorKind {
             DescriptorErrorKind::DecodeFileDescriptorSet { err } => Some(err),
             #[cfg(feature = "text-format")]
             DescriptorErrorKind::InvalidMessageOption { err, .. } => Some(err),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FileRead { err, .. } => Some(err),
             _ => None,
         }
     }
@@ -453,9 +609,18 @@ impl fmt::Display for DescriptorErrorKind {
             DescriptorErrorKind::UnknownSyntax { syntax, .. } => {
                 write!(f, "unknown syntax '{syntax}'")
             }
+            DescriptorErrorKind::UnknownEdition { edition, .. } => {
+                write!(f, "unsupported edition '{edition}'")
+            }
             DescriptorErrorKind::DuplicateFileName { name, .. } => {
                 write!(f, "a different file named '{name}' has already been added")
             }
+            DescriptorErrorKind::EditionNotSupportedByProstTypes { name } => {
+                write!(
+                    f,
+                    "the edition of file '{name}' is not available, since it is not supported by prost_types"
+                )
+            }
             DescriptorErrorKind::FileNotFound { name, .. } => {
                 write!(f, "imported file '{name}' has not been added")
             }
@@ -548,7 +713,7 @@ impl fmt::Display for DescriptorErrorKind {
                 write!(f, "enums must have at least one value")
             }
             DescriptorErrorKind::InvalidProto3EnumDefault { .. } => {
-                write!(f, "the first value for proto3 enums must be 0")
+                write!(f, "the first value for open enums must be 0")
             }
             DescriptorErrorKind::DuplicateEnumNumber { number, .. } => {
                 write!(f, "enum number '{number}' has already been used")
@@ -605,6 +770,88 @@ impl fmt::Display for DescriptorErrorKind {
             DescriptorErrorKind::DecodeFileDescriptorSet { .. } => {
                 write!(f, "failed to decode file descriptor set")
             }
+            DescriptorErrorKind::InvalidRoot { name } => {
+                write!(
+                    f,
+                    "'{name}' is not a message, enum, service or extension defined in the pool"
+                )
+            }
+            DescriptorErrorKind::FileNotInPool { name } => {
+                write!(f, "file '{name}' has not been added")
+            }
+            DescriptorErrorKind::FileInUse { name, found } => {
+                write!(
+                    f,
+                    "file '{name}' cannot be removed because it is imported by '{}'",
+                    found.file
+                )
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidToken { .. } => write!(f, "invalid token"),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidStringCharacters { .. } => {
+                write!(f, "invalid string character")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidStringEscape { .. } => write!(f, "invalid string escape"),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::UnterminatedString { .. } => write!(f, "unterminated string"),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::UnterminatedBlockComment { .. } => {
+                write!(f, "unterminated block comment")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::UnexpectedToken {
+                expected, token, ..
+            } => write!(f, "expected {expected}, but found '{token}'"),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::UnexpectedEof { expected, .. } => {
+                write!(f, "expected {expected}, but reached end of file")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::IntegerOutOfRange { value, .. } => {
+                write!(f, "integer '{value}' is out of range")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidGroupName { .. } => {
+                write!(f, "group names must start with a capital letter")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FieldLabelNotAllowed { label, context, .. } => {
+                write!(f, "the '{label}' label is not allowed {context}")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::MapFieldNotAllowed { context, .. } => {
+                write!(f, "map fields are not allowed {context}")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidMapKeyType { .. } => {
+                write!(f, "map keys must be an integral, bool or string type")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::GroupSyntaxNotSupported { .. } => {
+                write!(f, "group syntax is not supported in editions")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidReservedName { editions, .. } => {
+                if *editions {
+                    write!(f, "reserved names must be identifiers in editions")
+                } else {
+                    write!(f, "reserved names must be string literals")
+                }
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::DuplicatePackage { .. } => {
+                write!(f, "multiple package definitions")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FileRead { name, .. } => {
+                write!(f, "failed to read file '{name}'")
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::ImportCycle { cycle, .. } => {
+                write!(f, "import cycle detected: {cycle}")
+            }
         }
     }
 }
@@ -617,10 +864,16 @@ impl miette::Diagnostic for DescriptorErrorKind {
 
         match self {
             DescriptorErrorKind::MissingRequiredField { .. } => None,
-            DescriptorErrorKind::UnknownSyntax { .. } => {
-                Some(Box::new("valid values are 'proto2' and 'proto3'"))
+            DescriptorErrorKind::UnknownSyntax { .. } => Some(Box::new(
+                "valid values are 'proto2', 'proto3' and 'editions'",
+            )),
+            DescriptorErrorKind::UnknownEdition { .. } => {
+                Some(Box::new("supported editions are 2023 and 2024"))
             }
             DescriptorErrorKind::DuplicateFileName { .. } => None,
+            DescriptorErrorKind::EditionNotSupportedByProstTypes { .. } => Some(Box::new(
+                "files using the editions syntax must be added from their encoded bytes",
+            )),
             DescriptorErrorKind::FileNotFound { .. } => None,
             DescriptorErrorKind::InvalidImportIndex => None,
             DescriptorErrorKind::InvalidOneofIndex => None,
@@ -666,7 +919,40 @@ impl miette::Diagnostic for DescriptorErrorKind {
             DescriptorErrorKind::InvalidMessageOption { .. } => None,
             DescriptorErrorKind::DuplicateOption { .. } => None,
             DescriptorErrorKind::DecodeFileDescriptorSet { .. } => None,
+            DescriptorErrorKind::InvalidRoot { .. } => None,
+            DescriptorErrorKind::FileNotInPool { .. } => None,
+            DescriptorErrorKind::FileInUse { .. } => Some(Box::new(
+                "remove the files which import it at the same time",
+            )),
             DescriptorErrorKind::ExtensionNumberOutOfRange { .. } => None,
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FieldLabelNotAllowed {
+                context: "in editions",
+                ..
+            } => Some(Box::new(
+                "use the 'features.field_presence' option to control field presence",
+            )),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::GroupSyntaxNotSupported { .. } => Some(Box::new(
+                "use a message field with the 'features.message_encoding = DELIMITED' option",
+            )),
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidToken { .. }
+            | DescriptorErrorKind::InvalidStringCharacters { .. }
+            | DescriptorErrorKind::InvalidStringEscape { .. }
+            | DescriptorErrorKind::UnterminatedString { .. }
+            | DescriptorErrorKind::UnterminatedBlockComment { .. }
+            | DescriptorErrorKind::UnexpectedToken { .. }
+            | DescriptorErrorKind::UnexpectedEof { .. }
+            | DescriptorErrorKind::IntegerOutOfRange { .. }
+            | DescriptorErrorKind::InvalidGroupName { .. }
+            | DescriptorErrorKind::FieldLabelNotAllowed { .. }
+            | DescriptorErrorKind::MapFieldNotAllowed { .. }
+            | DescriptorErrorKind::InvalidMapKeyType { .. }
+            | DescriptorErrorKind::InvalidReservedName { .. }
+            | DescriptorErrorKind::DuplicatePackage { .. }
+            | DescriptorErrorKind::FileRead { .. }
+            | DescriptorErrorKind::ImportCycle { .. } => None,
         }
     }
 
@@ -677,7 +963,11 @@ impl miette::Diagnostic for DescriptorErrorKind {
             DescriptorErrorKind::UnknownSyntax { found: defined, .. } => {
                 spans.extend(defined.to_span());
             }
+            DescriptorErrorKind::UnknownEdition { found, .. } => {
+                spans.extend(found.to_span());
+            }
             DescriptorErrorKind::DuplicateFileName { .. } => {}
+            DescriptorErrorKind::EditionNotSupportedByProstTypes { .. } => {}
             DescriptorErrorKind::FileNotFound { found, .. } => {
                 spans.extend(found.to_span());
             }
@@ -755,6 +1045,34 @@ impl miette::Diagnostic for DescriptorErrorKind {
                 spans.extend(found.to_span());
             }
             DescriptorErrorKind::DecodeFileDescriptorSet { .. } => {}
+            DescriptorErrorKind::InvalidRoot { .. } => {}
+            DescriptorErrorKind::FileNotInPool { .. } => {}
+            DescriptorErrorKind::FileInUse { found, .. } => {
+                spans.extend(found.to_span());
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::InvalidToken { found }
+            | DescriptorErrorKind::InvalidStringCharacters { found }
+            | DescriptorErrorKind::InvalidStringEscape { found }
+            | DescriptorErrorKind::UnterminatedString { found }
+            | DescriptorErrorKind::UnterminatedBlockComment { found }
+            | DescriptorErrorKind::UnexpectedToken { found, .. }
+            | DescriptorErrorKind::UnexpectedEof { found, .. }
+            | DescriptorErrorKind::IntegerOutOfRange { found, .. }
+            | DescriptorErrorKind::InvalidGroupName { found }
+            | DescriptorErrorKind::FieldLabelNotAllowed { found, .. }
+            | DescriptorErrorKind::MapFieldNotAllowed { found, .. }
+            | DescriptorErrorKind::InvalidMapKeyType { found }
+            | DescriptorErrorKind::GroupSyntaxNotSupported { found }
+            | DescriptorErrorKind::InvalidReservedName { found, .. }
+            | DescriptorErrorKind::DuplicatePackage { found }
+            | DescriptorErrorKind::ImportCycle { found, .. } => {
+                spans.extend(found.to_span());
+            }
+            #[cfg(feature = "proto-parse")]
+            DescriptorErrorKind::FileRead { found, .. } => {
+                spans.extend(found.as_ref().and_then(|found| found.to_span()));
+            }
         }
         if spans.is_empty() {
             None
@@ -785,15 +1103,8 @@ impl Label {
             .source_code_info
             .as_ref()
             .and_then(|s| s.location.iter().find(|l| *l.path == *path))
-            .and_then(|l| match *l.span {
-                [start_line, start_col, end_col] => {
-                    Some([start_line, start_col, start_line, end_col])
-                }
-                [start_line, start_col, end_line, end_col] => {
-                    Some([start_line, start_col, end_line, end_col])
-                }
-                _ => None,
-            });
+            .and_then(|l| Span::new(&l.span))
+            .map(|s| [s.start_line, s.start_column, s.end_line, s.end_column]);
 
         Label {
             file: file.name().to_owned(),
@@ -806,29 +1117,70 @@ impl Label {
         }
     }
 
+    #[cfg(feature = "proto-parse")]
+    pub fn from_span(
+        file: &str,
+        #[cfg_attr(not(feature = "miette"), allow(unused_variables))] message: impl ToString,
+        path: Box<[i32]>,
+        span: [i32; 4],
+    ) -> Self {
+        Label {
+            file: file.to_owned(),
+            span: Some(span),
+            path,
+            #[cfg(feature = "miette")]
+            message: message.to_string(),
+            #[cfg(feature = "miette")]
+            resolved: None,
+        }
+    }
+
+    pub fn from_location(
+        file: &str,
+        #[cfg_attr(not(feature = "miette"), allow(unused_variables))] message: impl ToString,
+        path: &[i32],
+        span: Option<Span>,
+    ) -> Self {
+        Label {
+            file: file.to_owned(),
+            span: span.map(|s| [s.start_line, s.start_column, s.end_line, s.end_column]),
+            path: path.into(),
+            #[cfg(feature = "miette")]
+            message: message.to_string(),
+            #[cfg(feature = "miette")]
+            resolved: None,
+        }
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn path(&self) -> &[i32] {
+        &self.path
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.span.map(|s| s[0] as usize)
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.span.map(|s| s[1] as usize)
+    }
+
     #[cfg(feature = "miette")]
     pub fn resolve_span(&mut self, file: &str, source: &str) {
         if file == self.file {
             if let Some([start_line, start_col, end_line, end_col]) = self.span {
-                let start = miette::SourceOffset::from_location(
-                    source,
-                    start_line.saturating_add(1) as _,
-                    start_col.saturating_add(1) as _,
-                )
-                .offset();
-                let end = miette::SourceOffset::from_location(
-                    source,
-                    end_line.saturating_add(1) as _,
-                    end_col.saturating_add(1) as _,
-                )
-                .offset();
+                let start = resolve_offset(source, start_line, start_col);
+                let end = resolve_offset(source, end_line, end_col);
                 self.resolved = Some(miette::SourceSpan::from(start..end));
             }
         }
     }
 
     #[cfg(feature = "miette")]
-    fn to_span(&self) -> Option<miette::LabeledSpan> {
+    pub fn to_span(&self) -> Option<miette::LabeledSpan> {
         match self.resolved {
             Some(span) if !span.is_empty() => Some(miette::LabeledSpan::new_with_span(
                 Some(self.message.clone()),
@@ -838,3 +1190,30 @@ impl Label {
         }
     }
 }
+
+/// Converts a line and column, as used in source code info, to a byte offset. Columns are
+/// measured in bytes, with tabs advancing to the next multiple of 8.
+#[cfg(feature = "miette")]
+fn resolve_offset(source: &str, line: i32, column: i32) -> usize {
+    let mut offset = 0;
+    for _ in 0..line {
+        match source[offset..].find('\n') {
+            Some(end) => offset += end + 1,
+            None => return source.len(),
+        }
+    }
+
+    let mut current_column = 0;
+    for (i, byte) in source[offset..].bytes().enumerate() {
+        if current_column >= column || byte == b'\n' {
+            return offset + i;
+        }
+
+        if byte == b'\t' {
+            current_column += 8 - current_column % 8;
+        } else {
+            current_column += 1;
+        }
+    }
+    source.len()
+}
@@ -0,0 +1,462 @@
+use prost::{
+    bytes::Buf,
+    encoding::{self, decode_key, skip_field, DecodeContext, WireType},
+    DecodeError, Message,
+};
+
+use crate::{
+    descriptor::{
+        tag,
+        types::{
+            self, feature_set_defaults::FeatureSetEditionDefault, FeatureSetDefaults, Options,
+        },
+        DescriptorPoolInner,
+    },
+    DescriptorPool, DynamicMessage, Edition, Syntax,
+};
+
+pub(super) const EDITION_LEGACY: i32 = 900;
+pub(super) const EDITION_PROTO2: i32 = 998;
+pub(super) const EDITION_PROTO3: i32 = 999;
+pub(super) const EDITION_2023: i32 = 1000;
+pub(super) const EDITION_2024: i32 = 1001;
+
+const FEATURE_SET_NAME: &str = "google.protobuf.FeatureSet";
+
+/// The resolved values of the standard features defined in `google.protobuf.FeatureSet`.
+///
+/// Features are set in the options of a descriptor, and inherited by all descriptors nested within it. Any
+/// feature not set explicitly takes its default value for the edition of the file. Files using the `proto2` and
+/// `proto3` syntax have fixed features which match the behaviour of that syntax.
+///
+/// Custom features, defined as extensions of `google.protobuf.FeatureSet`, are not included here. They can be
+/// accessed through the `features_message()` method of each descriptor.
+///
+/// See the [protobuf documentation](https://protobuf.dev/editions/features/) for more details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FeatureSet {
+    field_presence: feature_set::FieldPresence,
+    enum_type: feature_set::EnumType,
+    repeated_field_encoding: feature_set::RepeatedFieldEncoding,
+    utf8_validation: feature_set::Utf8Validation,
+    message_encoding: feature_set::MessageEncoding,
+    json_format: feature_set::JsonFormat,
+}
+
+/// Enums used by the fields of [`FeatureSet`].
+pub mod feature_set {
+    /// Whether a field tracks presence.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum FieldPresence {
+        /// Presence is tracked for singular fields, equivalent to `optional` fields in proto2.
+        Explicit,
+        /// Presence is not tracked, equivalent to fields without a label in proto3.
+        Implicit,
+        /// The field is required, equivalent to `required` fields in proto2.
+        LegacyRequired,
+    }
+
+    /// Whether an enum is open or closed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum EnumType {
+        /// Unknown values are stored in the enum field, as for proto3 enums.
+        Open,
+        /// Unknown values are stored in the unknown field set, as for proto2 enums.
+        Closed,
+    }
+
+    /// How repeated fields of scalar types are encoded.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum RepeatedFieldEncoding {
+        /// Repeated fields are encoded using the packed encoding.
+        Packed,
+        /// Each element of a repeated field is encoded as a separate record.
+        Expanded,
+    }
+
+    /// Whether string fields are validated as UTF-8.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum Utf8Validation {
+        /// Invalid UTF-8 is rejected when parsing.
+        Verify,
+        /// String fields are not validated.
+        None,
+    }
+
+    /// How message fields are encoded.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum MessageEncoding {
+        /// Messages are encoded as length-prefixed records.
+        LengthPrefixed,
+        /// Messages are encoded using start and end group tags, as for proto2 groups.
+        Delimited,
+    }
+
+    /// Whether a message or enum must support the JSON format.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum JsonFormat {
+        /// The type must support the JSON format, so conflicting JSON field names are an error.
+        Allow,
+        /// The type is not required to support the JSON format.
+        LegacyBestEffort,
+    }
+}
+
+impl FeatureSet {
+    pub(super) fn new(features: &types::FeatureSet) -> Self {
+        use types::feature_set as raw;
+
+        FeatureSet {
+            field_presence: match features.field_presence() {
+                raw::FieldPresence::Explicit | raw::FieldPresence::Unknown => {
+                    feature_set::FieldPresence::Explicit
+                }
+                raw::FieldPresence::Implicit => feature_set::FieldPresence::Implicit,
+                raw::FieldPresence::LegacyRequired => feature_set::FieldPresence::LegacyRequired,
+            },
+            enum_type: match features.enum_type() {
+                raw::EnumType::Open | raw::EnumType::Unknown => feature_set::EnumType::Open,
+                raw::EnumType::Closed => feature_set::EnumType::Closed,
+            },
+            repeated_field_encoding: match features.repeated_field_encoding() {
+                raw::RepeatedFieldEncoding::Packed | raw::RepeatedFieldEncoding::Unknown => {
+                    feature_set::RepeatedFieldEncoding::Packed
+                }
+                raw::RepeatedFieldEncoding::Expanded => {
+                    feature_set::RepeatedFieldEncoding::Expanded
+                }
+            },
+            utf8_validation: match features.utf8_validation() {
+                raw::Utf8Validation::Verify | raw::Utf8Validation::Unknown => {
+                    feature_set::Utf8Validation::Verify
+                }
+                raw::Utf8Validation::None => feature_set::Utf8Validation::None,
+            },
+            message_encoding: match features.message_encoding() {
+                raw::MessageEncoding::LengthPrefixed | raw::MessageEncoding::Unknown => {
+                    feature_set::MessageEncoding::LengthPrefixed
+                }
+                raw::MessageEncoding::Delimited => feature_set::MessageEncoding::Delimited,
+            },
+            json_format: match features.json_format() {
+                raw::JsonFormat::Allow | raw::JsonFormat::Unknown => feature_set::JsonFormat::Allow,
+                raw::JsonFormat::LegacyBestEffort => feature_set::JsonFormat::LegacyBestEffort,
+            },
+        }
+    }
+
+    /// Gets the value of the `field_presence` feature.
+    pub fn field_presence(&self) -> feature_set::FieldPresence {
+        self.field_presence
+    }
+
+    /// Gets the value of the `enum_type` feature.
+    pub fn enum_type(&self) -> feature_set::EnumType {
+        self.enum_type
+    }
+
+    /// Gets the value of the `repeated_field_encoding` feature.
+    pub fn repeated_field_encoding(&self) -> feature_set::RepeatedFieldEncoding {
+        self.repeated_field_encoding
+    }
+
+    /// Gets the value of the `utf8_validation` feature.
+    pub fn utf8_validation(&self) -> feature_set::Utf8Validation {
+        self.utf8_validation
+    }
+
+    /// Gets the value of the `message_encoding` feature.
+    pub fn message_encoding(&self) -> feature_set::MessageEncoding {
+        self.message_encoding
+    }
+
+    /// Gets the value of the `json_format` feature.
+    pub fn json_format(&self) -> feature_set::JsonFormat {
+        self.json_format
+    }
+}
+
+/// The default feature values for each edition, as defined in `descriptor.proto`. Each entry
+/// applies to all editions up to the next entry.
+const EDITION_DEFAULTS: [(i32, types::FeatureSet); 3] = [
+    (
+        EDITION_LEGACY,
+        types::FeatureSet {
+            field_presence: Some(types::feature_set::FieldPresence::Explicit as i32),
+            enum_type: Some(types::feature_set::EnumType::Closed as i32),
+            repeated_field_encoding: Some(
+                types::feature_set::RepeatedFieldEncoding::Expanded as i32,
+            ),
+            utf8_validation: Some(types::feature_set::Utf8Validation::None as i32),
+            message_encoding: Some(types::feature_set::MessageEncoding::LengthPrefixed as i32),
+            json_format: Some(types::feature_set::JsonFormat::LegacyBestEffort as i32),
+        },
+    ),
+    (
+        EDITION_PROTO3,
+        types::FeatureSet {
+            field_presence: Some(types::feature_set::FieldPresence::Implicit as i32),
+            enum_type: Some(types::feature_set::EnumType::Open as i32),
+            repeated_field_encoding: Some(types::feature_set::RepeatedFieldEncoding::Packed as i32),
+            utf8_validation: Some(types::feature_set::Utf8Validation::Verify as i32),
+            message_encoding: Some(types::feature_set::MessageEncoding::LengthPrefixed as i32),
+            json_format: Some(types::feature_set::JsonFormat::Allow as i32),
+        },
+    ),
+    (
+        EDITION_2023,
+        types::FeatureSet {
+            field_presence: Some(types::feature_set::FieldPresence::Explicit as i32),
+            enum_type: Some(types::feature_set::EnumType::Open as i32),
+            repeated_field_encoding: Some(types::feature_set::RepeatedFieldEncoding::Packed as i32),
+            utf8_validation: Some(types::feature_set::Utf8Validation::Verify as i32),
+            message_encoding: Some(types::feature_set::MessageEncoding::LengthPrefixed as i32),
+            json_format: Some(types::feature_set::JsonFormat::Allow as i32),
+        },
+    ),
+];
+
+impl Syntax {
+    fn edition(self) -> i32 {
+        match self {
+            Syntax::Proto2 => EDITION_PROTO2,
+            Syntax::Proto3 => EDITION_PROTO3,
+            Syntax::Editions(Edition::Edition2023) => EDITION_2023,
+            Syntax::Editions(Edition::Edition2024) => EDITION_2024,
+        }
+    }
+}
+
+impl types::FeatureSet {
+    pub(super) fn builtin_defaults(syntax: Syntax) -> Self {
+        let edition = syntax.edition();
+        EDITION_DEFAULTS
+            .iter()
+            .rev()
+            .find(|(default_edition, _)| *default_edition <= edition)
+            .map(|(_, features)| *features)
+            .unwrap_or(EDITION_DEFAULTS[0].1)
+    }
+
+    /// Resolves the features of a child element, by overriding these features with any features set
+    /// explicitly in its options.
+    pub(super) fn resolve<T>(&self, options: Option<&Options<T>>, tag: i32) -> Self {
+        let mut features = *self;
+        if let Some(options) = options {
+            // Malformed features are ignored, since they do not prevent the options being decoded.
+            let _ = for_each_option_features(&options.encoded, tag, |bytes| {
+                if let Ok(explicit) = types::FeatureSet::decode(bytes) {
+                    features.merge_explicit(&explicit);
+                }
+            });
+            // Options parsed from source are not interpreted until after features are resolved,
+            // so standard features are also read from uninterpreted options.
+            let _ =
+                for_each_option_features(&options.encoded, tag::UNINTERPRETED_OPTION, |bytes| {
+                    if let Ok(option) = prost_types::UninterpretedOption::decode(bytes) {
+                        if let Some(explicit) = Self::from_uninterpreted_option(&option) {
+                            features.merge_explicit(&explicit);
+                        }
+                    }
+                });
+        }
+        features
+    }
+
+    fn from_uninterpreted_option(option: &prost_types::UninterpretedOption) -> Option<Self> {
+        let [features, name] = option.name.as_slice() else {
+            return None;
+        };
+        if features.is_extension || features.name_part != "features" || name.is_extension {
+            return None;
+        }
+
+        let mut result = types::FeatureSet::default();
+        let value = option.identifier_value.as_deref()?;
+        let (field, value) = match (name.name_part.as_str(), value) {
+            ("field_presence", "EXPLICIT") => (&mut result.field_presence, 1),
+            ("field_presence", "IMPLICIT") => (&mut result.field_presence, 2),
+            ("field_presence", "LEGACY_REQUIRED") => (&mut result.field_presence, 3),
+            ("enum_type", "OPEN") => (&mut result.enum_type, 1),
+            ("enum_type", "CLOSED") => (&mut result.enum_type, 2),
+            ("repeated_field_encoding", "PACKED") => (&mut result.repeated_field_encoding, 1),
+            ("repeated_field_encoding", "EXPANDED") => (&mut result.repeated_field_encoding, 2),
+            ("utf8_validation", "VERIFY") => (&mut result.utf8_validation, 2),
+            ("utf8_validation", "NONE") => (&mut result.utf8_validation, 3),
+            ("message_encoding", "LENGTH_PREFIXED") => (&mut result.message_encoding, 1),
+            ("message_encoding", "DELIMITED") => (&mut result.message_encoding, 2),
+            ("json_format", "ALLOW") => (&mut result.json_format, 1),
+            ("json_format", "LEGACY_BEST_EFFORT") => (&mut result.json_format, 2),
+            _ => return None,
+        };
+        *field = Some(value);
+        Some(result)
+    }
+
+    fn merge_explicit(&mut self, explicit: &Self) {
+        use types::feature_set::{
+            EnumType, FieldPresence, JsonFormat, MessageEncoding, RepeatedFieldEncoding,
+            Utf8Validation,
+        };
+
+        fn merge<E: TryFrom<i32>>(dst: &mut Option<i32>, src: Option<i32>) {
+            // Unknown values are treated as unset.
+            if let Some(value) = src.filter(|&v| v != 0 && E::try_from(v).is_ok()) {
+                *dst = Some(value);
+            }
+        }
+
+        merge::<FieldPresence>(&mut self.field_presence, explicit.field_presence);
+        merge::<EnumType>(&mut self.enum_type, explicit.enum_type);
+        merge::<RepeatedFieldEncoding>(
+            &mut self.repeated_field_encoding,
+            explicit.repeated_field_encoding,
+        );
+        merge::<Utf8Validation>(&mut self.utf8_validation, explicit.utf8_validation);
+        merge::<MessageEncoding>(&mut self.message_encoding, explicit.message_encoding);
+        merge::<JsonFormat>(&mut self.json_format, explicit.json_format);
+    }
+}
+
+impl FeatureSetDefaults {
+    fn get(&self, syntax: Syntax) -> Option<&FeatureSetEditionDefault> {
+        let edition = syntax.edition();
+        if let Syntax::Editions(_) = syntax {
+            if self.minimum_edition.is_some_and(|min| edition < min)
+                || self.maximum_edition.is_some_and(|max| edition > max)
+            {
+                return None;
+            }
+        }
+
+        self.defaults
+            .iter()
+            .rev()
+            .find(|default| default.edition() <= edition)
+    }
+}
+
+impl FeatureSetEditionDefault {
+    fn encoded_features(&self) -> impl Iterator<Item = &[u8]> {
+        self.features
+            .iter()
+            .chain(&self.overridable_features)
+            .chain(&self.fixed_features)
+            .map(Vec::as_slice)
+    }
+}
+
+impl DescriptorPoolInner {
+    /// Gets the default features for files with the given syntax, or `None` if the edition is not
+    /// supported by the feature set defaults of this pool.
+    pub(super) fn edition_defaults(&self, syntax: Syntax) -> Option<types::FeatureSet> {
+        let mut features = types::FeatureSet::builtin_defaults(syntax);
+        match self
+            .feature_set_defaults
+            .as_ref()
+            .and_then(|defaults| defaults.get(syntax))
+        {
+            Some(default) => {
+                for bytes in default.encoded_features() {
+                    if let Ok(explicit) = types::FeatureSet::decode(bytes) {
+                        features.merge_explicit(&explicit);
+                    }
+                }
+            }
+            None if self.feature_set_defaults.is_some()
+                && matches!(syntax, Syntax::Editions(_)) =>
+            {
+                return None
+            }
+            None => (),
+        }
+        Some(features)
+    }
+
+    pub(super) fn set_feature_set_defaults<B>(&mut self, bytes: B) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        let mut defaults = FeatureSetDefaults::decode(bytes)?;
+        for default in &defaults.defaults {
+            for bytes in default.encoded_features() {
+                types::FeatureSet::decode(bytes)?;
+            }
+        }
+        defaults.defaults.sort_by_key(|default| default.edition());
+
+        self.feature_set_defaults = Some(defaults);
+        Ok(())
+    }
+}
+
+impl DescriptorPool {
+    /// Decodes the default features for files with the given syntax, including any custom features.
+    pub(super) fn edition_defaults_message(&self, syntax: Syntax) -> DynamicMessage {
+        let mut message = DynamicMessage::new(
+            self.get_message_by_name(FEATURE_SET_NAME)
+                .unwrap_or_else(|| {
+                    DescriptorPool::global()
+                        .get_message_by_name(FEATURE_SET_NAME)
+                        .unwrap()
+                }),
+        );
+
+        match self
+            .inner
+            .feature_set_defaults
+            .as_ref()
+            .and_then(|defaults| defaults.get(syntax))
+        {
+            Some(default) => {
+                for bytes in default.encoded_features() {
+                    let _ = message.merge(bytes);
+                }
+            }
+            None => {
+                let defaults = types::FeatureSet::builtin_defaults(syntax);
+                let _ = message.merge(defaults.encode_to_vec().as_slice());
+            }
+        }
+
+        message
+    }
+}
+
+/// Overrides the features in `features` with any features set explicitly in the given options.
+pub(super) fn merge_features_message<T>(
+    features: &mut DynamicMessage,
+    options: &Option<Options<T>>,
+    tag: i32,
+) {
+    if let Some(options) = options {
+        let _ = for_each_option_features(&options.encoded, tag, |bytes| {
+            let _ = features.merge(bytes);
+        });
+    }
+}
+
+fn for_each_option_features(
+    mut buf: &[u8],
+    tag: i32,
+    mut f: impl FnMut(&[u8]),
+) -> Result<(), DecodeError> {
+    while buf.has_remaining() {
+        let (number, wire_type) = decode_key(&mut buf)?;
+        if number as i32 == tag && wire_type == WireType::LengthDelimited {
+            let mut bytes = Vec::new();
+            encoding::bytes::merge(wire_type, &mut bytes, &mut buf, DecodeContext::default())?;
+            f(&bytes);
+        } else {
+            skip_field(wire_type, number, &mut buf, DecodeContext::default())?;
+        }
+    }
+
+    Ok(())
+}
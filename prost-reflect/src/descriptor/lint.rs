@@ -0,0 +1,838 @@
+//! Checks for common style and design issues in protobuf definitions.
+//!
+//! A [`Linter`] runs a set of [`LintRule`]s over each definition in a [`DescriptorPool`] or
+//! [`FileDescriptor`]. A number of built-in rules are provided in this module, and custom rules
+//! can be added by implementing [`LintRule`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use prost_reflect::{DescriptorPool, lint::{EnumZeroValueUnspecified, Linter}};
+//! # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+//! let linter = Linter::new().with_rule(EnumZeroValueUnspecified);
+//! for diagnostic in linter.lint(&pool) {
+//!     println!("{:?}", diagnostic);
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::{
+    descriptor::{
+        api::find_location_by_path,
+        build::Visitor,
+        error::Label,
+        types::{
+            DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+            FileDescriptorProto, MethodDescriptorProto, OneofDescriptorProto,
+            ServiceDescriptorProto,
+        },
+        EnumIndex, EnumValueIndex, ExtensionIndex, FieldIndex, FileIndex, MessageIndex,
+        MethodIndex, OneofIndex, ServiceIndex, Span,
+    },
+    DescriptorPool, EnumDescriptor, EnumValueDescriptor, ExtensionDescriptor, FieldDescriptor,
+    FileDescriptor, MessageDescriptor, MethodDescriptor, OneofDescriptor, ServiceDescriptor,
+};
+
+/// A check run by a [`Linter`] against each definition in a schema.
+///
+/// Each method is called once for every definition of the corresponding kind, and the default
+/// implementations do nothing. Violations are reported using [`LintContext::report`].
+pub trait LintRule: Send + Sync {
+    /// The name of this rule, for example `enum-zero-value-unspecified`.
+    ///
+    /// This is used as the code of any diagnostics reported by the rule.
+    fn name(&self) -> &str;
+
+    /// Checks a file.
+    fn check_file(&self, _file: &FileDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a message. This is not called for the messages generated for map fields.
+    fn check_message(&self, _message: &MessageDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a field of a message.
+    fn check_field(&self, _field: &FieldDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a oneof. This is not called for synthetic oneofs.
+    fn check_oneof(&self, _oneof: &OneofDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks an extension field.
+    fn check_extension(&self, _extension: &ExtensionDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks an enum.
+    fn check_enum(&self, _enum: &EnumDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a value of an enum.
+    fn check_enum_value(&self, _value: &EnumValueDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a service.
+    fn check_service(&self, _service: &ServiceDescriptor, _cx: &mut LintContext<'_>) {}
+
+    /// Checks a method of a service.
+    fn check_method(&self, _method: &MethodDescriptor, _cx: &mut LintContext<'_>) {}
+}
+
+/// Runs a set of [`LintRule`]s over protobuf definitions.
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+/// Collects the diagnostics reported by a [`LintRule`].
+#[derive(Debug)]
+pub struct LintContext<'a> {
+    rule: &'a str,
+    diagnostics: &'a mut Vec<LintDiagnostic>,
+}
+
+/// A violation of a [`LintRule`].
+pub struct LintDiagnostic {
+    rule: String,
+    message: String,
+    label: Label,
+    #[cfg(feature = "miette")]
+    source: Option<miette::NamedSource<String>>,
+}
+
+/// Requires message, enum, service and method names to be `PascalCase`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PascalCaseNames;
+
+/// Requires field, oneof and extension names to be `lower_snake_case`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SnakeCaseFieldNames;
+
+/// Requires enum value names to be `UPPER_SNAKE_CASE`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UpperSnakeCaseEnumValues;
+
+/// Requires messages, fields, enums, enum values, services and methods to have a leading
+/// comment.
+///
+/// Comments are only available if the file descriptor includes
+/// [`source_code_info`](prost_types::FileDescriptorProto::source_code_info). Files without source
+/// code info are not checked.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MissingComments;
+
+/// Requires the zero value of each enum to be named after the enum, with the suffix
+/// `_UNSPECIFIED`, for example `MY_ENUM_UNSPECIFIED` for an enum named `MyEnum`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EnumZeroValueUnspecified;
+
+/// Requires files to be in a directory matching their package, for example `foo/bar/baz.proto`
+/// for a file in the package `foo.bar`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PackageDirectoryMatch;
+
+/// Requires the field numbers of each message to be contiguous, starting from 1. Numbers used by
+/// reserved ranges and extension ranges are not considered gaps.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FieldNumberGaps;
+
+impl Linter {
+    /// Creates a new linter with no rules.
+    pub fn new() -> Self {
+        Linter::default()
+    }
+
+    /// Creates a new linter with all the built-in rules in this module.
+    pub fn with_default_rules() -> Self {
+        Linter::new()
+            .with_rule(PascalCaseNames)
+            .with_rule(SnakeCaseFieldNames)
+            .with_rule(UpperSnakeCaseEnumValues)
+            .with_rule(MissingComments)
+            .with_rule(EnumZeroValueUnspecified)
+            .with_rule(PackageDirectoryMatch)
+            .with_rule(FieldNumberGaps)
+    }
+
+    /// Adds a rule to this linter.
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Removes the rule with the given name from this linter, if present.
+    pub fn without_rule(mut self, name: &str) -> Self {
+        self.rules.retain(|rule| rule.name() != name);
+        self
+    }
+
+    /// Gets an iterator over the rules in this linter.
+    pub fn rules(&self) -> impl ExactSizeIterator<Item = &dyn LintRule> + '_ {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Checks every definition in the pool.
+    ///
+    /// Diagnostics are returned in the order definitions appear in the pool.
+    pub fn lint(&self, pool: &DescriptorPool) -> Vec<LintDiagnostic> {
+        let mut visitor = LintVisitor {
+            linter: self,
+            pool,
+            file: None,
+            diagnostics: Vec::new(),
+        };
+        pool.inner.visit(&mut visitor);
+        visitor.diagnostics
+    }
+
+    /// Checks every definition in a single file.
+    pub fn lint_file(&self, file: &FileDescriptor) -> Vec<LintDiagnostic> {
+        let mut visitor = LintVisitor {
+            linter: self,
+            pool: &file.pool,
+            file: Some(file.index),
+            diagnostics: Vec::new(),
+        };
+        file.pool.inner.visit(&mut visitor);
+        visitor.diagnostics
+    }
+}
+
+impl fmt::Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|rule| rule.name()))
+            .finish()
+    }
+}
+
+impl LintContext<'_> {
+    /// Reports a violation of the current rule, for the definition at the given path in `file`.
+    ///
+    /// See [`path`][prost_types::source_code_info::Location::path] for more details on the
+    /// structure of the path. The path of a definition can be obtained from its `path()`
+    /// method, for example [`MessageDescriptor::path`].
+    pub fn report(&mut self, file: &FileDescriptor, path: &[i32], message: impl ToString) {
+        let span = find_location_by_path(&file.pool, file.index, path)
+            .and_then(|location| Span::new(&location.span));
+
+        self.diagnostics.push(LintDiagnostic {
+            rule: self.rule.to_owned(),
+            message: message.to_string(),
+            label: Label::from_location(file.name(), "defined here", path, span),
+            #[cfg(feature = "miette")]
+            source: None,
+        });
+    }
+}
+
+impl LintDiagnostic {
+    /// The name of the rule which reported this diagnostic.
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// The file containing the definition which violates the rule.
+    pub fn file(&self) -> &str {
+        self.label.file()
+    }
+
+    /// The 0-based line number of the definition, if available.
+    ///
+    /// This field may be `None` if the [`source_code_info`](prost_types::FileDescriptorProto::source_code_info)
+    /// field was not populated for the file.
+    pub fn line(&self) -> Option<usize> {
+        self.label.line()
+    }
+
+    /// The 0-based column number of the definition, if available.
+    ///
+    /// This field may be `None` if the [`source_code_info`](prost_types::FileDescriptorProto::source_code_info)
+    /// field was not populated for the file.
+    pub fn column(&self) -> Option<usize> {
+        self.label.column()
+    }
+
+    /// Gets the path of the definition in its [`FileDescriptorProto`][prost_types::FileDescriptorProto].
+    ///
+    /// See [`path`][prost_types::source_code_info::Location::path] for more details on the structure of the path.
+    pub fn path(&self) -> &[i32] {
+        self.label.path()
+    }
+
+    #[cfg(feature = "miette")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
+    /// Provide source code information for this diagnostic.
+    ///
+    /// The source should correspond to the contents of [`file()`][LintDiagnostic::file].
+    pub fn with_source_code(mut self, source: &str) -> Self {
+        let file = self.file().to_owned();
+        self.label.resolve_span(&file, source);
+        self.source = Some(miette::NamedSource::new(&file, source.to_owned()));
+        self
+    }
+}
+
+impl std::error::Error for LintDiagnostic {}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Debug for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.file())?;
+        if let (Some(line), Some(column)) = (self.line(), self.column()) {
+            write!(f, "{}:{}:", line + 1, column + 1)?;
+        }
+        write!(f, " {self} [{}]", self.rule)
+    }
+}
+
+#[cfg(feature = "miette")]
+#[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
+impl miette::Diagnostic for LintDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(&self.rule))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Warning)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        match &self.source {
+            Some(source) => Some(source),
+            None => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.label.to_span()?;
+        Some(Box::new(std::iter::once(span)))
+    }
+}
+
+struct LintVisitor<'a> {
+    linter: &'a Linter,
+    pool: &'a DescriptorPool,
+    file: Option<FileIndex>,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl LintVisitor<'_> {
+    fn run(&mut self, file: FileIndex, mut check: impl FnMut(&dyn LintRule, &mut LintContext<'_>)) {
+        if self.file.is_some_and(|index| index != file) {
+            return;
+        }
+
+        let linter = self.linter;
+        for rule in &linter.rules {
+            let mut cx = LintContext {
+                rule: rule.name(),
+                diagnostics: &mut self.diagnostics,
+            };
+            check(rule.as_ref(), &mut cx);
+        }
+    }
+
+    fn message(&self, index: MessageIndex) -> MessageDescriptor {
+        MessageDescriptor {
+            pool: self.pool.clone(),
+            index,
+        }
+    }
+
+    fn enum_(&self, index: EnumIndex) -> EnumDescriptor {
+        EnumDescriptor {
+            pool: self.pool.clone(),
+            index,
+        }
+    }
+
+    fn service(&self, index: ServiceIndex) -> ServiceDescriptor {
+        ServiceDescriptor {
+            pool: self.pool.clone(),
+            index,
+        }
+    }
+}
+
+impl Visitor for LintVisitor<'_> {
+    fn visit_file(&mut self, _: &[i32], index: FileIndex, _: &FileDescriptorProto) {
+        let file = FileDescriptor {
+            pool: self.pool.clone(),
+            index,
+        };
+        self.run(index, |rule, cx| rule.check_file(&file, cx));
+    }
+
+    fn visit_message(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        _: Option<MessageIndex>,
+        index: MessageIndex,
+        _: &DescriptorProto,
+    ) {
+        let message = self.message(index);
+        if !message.is_map_entry() {
+            self.run(file, |rule, cx| rule.check_message(&message, cx));
+        }
+    }
+
+    fn visit_field(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        message: MessageIndex,
+        index: FieldIndex,
+        _: &FieldDescriptorProto,
+    ) {
+        let field = FieldDescriptor {
+            message: self.message(message),
+            index,
+        };
+        if !field.parent_message().is_map_entry() {
+            self.run(file, |rule, cx| rule.check_field(&field, cx));
+        }
+    }
+
+    fn visit_oneof(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        message: MessageIndex,
+        index: OneofIndex,
+        _: &OneofDescriptorProto,
+    ) {
+        let oneof = OneofDescriptor {
+            message: self.message(message),
+            index,
+        };
+        if !oneof.is_synthetic() {
+            self.run(file, |rule, cx| rule.check_oneof(&oneof, cx));
+        }
+    }
+
+    fn visit_service(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        index: ServiceIndex,
+        _: &ServiceDescriptorProto,
+    ) {
+        let service = self.service(index);
+        self.run(file, |rule, cx| rule.check_service(&service, cx));
+    }
+
+    fn visit_method(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        service: ServiceIndex,
+        index: MethodIndex,
+        _: &MethodDescriptorProto,
+    ) {
+        let method = MethodDescriptor {
+            service: self.service(service),
+            index,
+        };
+        self.run(file, |rule, cx| rule.check_method(&method, cx));
+    }
+
+    fn visit_enum(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        _: Option<MessageIndex>,
+        index: EnumIndex,
+        _: &EnumDescriptorProto,
+    ) {
+        let enum_ = self.enum_(index);
+        self.run(file, |rule, cx| rule.check_enum(&enum_, cx));
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        enum_: EnumIndex,
+        index: EnumValueIndex,
+        _: &EnumValueDescriptorProto,
+    ) {
+        let value = EnumValueDescriptor {
+            parent: self.enum_(enum_),
+            index,
+        };
+        self.run(file, |rule, cx| rule.check_enum_value(&value, cx));
+    }
+
+    fn visit_extension(
+        &mut self,
+        _: &[i32],
+        _: &str,
+        file: FileIndex,
+        _: Option<MessageIndex>,
+        index: ExtensionIndex,
+        _: &FieldDescriptorProto,
+    ) {
+        let extension = ExtensionDescriptor {
+            pool: self.pool.clone(),
+            index,
+        };
+        self.run(file, |rule, cx| rule.check_extension(&extension, cx));
+    }
+}
+
+impl LintRule for PascalCaseNames {
+    fn name(&self) -> &str {
+        "pascal-case-names"
+    }
+
+    fn check_message(&self, message: &MessageDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "message",
+            message.name(),
+            is_pascal_case,
+            "PascalCase",
+            &message.parent_file(),
+            message.path(),
+        );
+    }
+
+    fn check_enum(&self, enum_: &EnumDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "enum",
+            enum_.name(),
+            is_pascal_case,
+            "PascalCase",
+            &enum_.parent_file(),
+            enum_.path(),
+        );
+    }
+
+    fn check_service(&self, service: &ServiceDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "service",
+            service.name(),
+            is_pascal_case,
+            "PascalCase",
+            &service.parent_file(),
+            service.path(),
+        );
+    }
+
+    fn check_method(&self, method: &MethodDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "method",
+            method.name(),
+            is_pascal_case,
+            "PascalCase",
+            &method.parent_file(),
+            method.path(),
+        );
+    }
+}
+
+impl LintRule for SnakeCaseFieldNames {
+    fn name(&self) -> &str {
+        "snake-case-field-names"
+    }
+
+    fn check_field(&self, field: &FieldDescriptor, cx: &mut LintContext<'_>) {
+        // The name of a group field is derived from the name of its message.
+        if !field.is_group() || field.parent_file().syntax() != crate::Syntax::Proto2 {
+            check_case(
+                cx,
+                "field",
+                field.name(),
+                is_lower_snake_case,
+                "lower_snake_case",
+                &field.parent_file(),
+                field.path(),
+            );
+        }
+    }
+
+    fn check_oneof(&self, oneof: &OneofDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "oneof",
+            oneof.name(),
+            is_lower_snake_case,
+            "lower_snake_case",
+            &oneof.parent_file(),
+            oneof.path(),
+        );
+    }
+
+    fn check_extension(&self, extension: &ExtensionDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "extension",
+            extension.name(),
+            is_lower_snake_case,
+            "lower_snake_case",
+            &extension.parent_file(),
+            extension.path(),
+        );
+    }
+}
+
+impl LintRule for UpperSnakeCaseEnumValues {
+    fn name(&self) -> &str {
+        "upper-snake-case-enum-values"
+    }
+
+    fn check_enum_value(&self, value: &EnumValueDescriptor, cx: &mut LintContext<'_>) {
+        check_case(
+            cx,
+            "enum value",
+            value.name(),
+            is_upper_snake_case,
+            "UPPER_SNAKE_CASE",
+            &value.parent_file(),
+            value.path(),
+        );
+    }
+}
+
+impl LintRule for MissingComments {
+    fn name(&self) -> &str {
+        "missing-comments"
+    }
+
+    fn check_message(&self, message: &MessageDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "message",
+            message.full_name(),
+            &message.parent_file(),
+            message.path(),
+            message.leading_comments(),
+        );
+    }
+
+    fn check_field(&self, field: &FieldDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "field",
+            field.full_name(),
+            &field.parent_file(),
+            field.path(),
+            field.leading_comments(),
+        );
+    }
+
+    fn check_enum(&self, enum_: &EnumDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "enum",
+            enum_.full_name(),
+            &enum_.parent_file(),
+            enum_.path(),
+            enum_.leading_comments(),
+        );
+    }
+
+    fn check_enum_value(&self, value: &EnumValueDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "enum value",
+            value.full_name(),
+            &value.parent_file(),
+            value.path(),
+            value.leading_comments(),
+        );
+    }
+
+    fn check_service(&self, service: &ServiceDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "service",
+            service.full_name(),
+            &service.parent_file(),
+            service.path(),
+            service.leading_comments(),
+        );
+    }
+
+    fn check_method(&self, method: &MethodDescriptor, cx: &mut LintContext<'_>) {
+        check_comment(
+            cx,
+            "method",
+            method.full_name(),
+            &method.parent_file(),
+            method.path(),
+            method.leading_comments(),
+        );
+    }
+}
+
+impl LintRule for EnumZeroValueUnspecified {
+    fn name(&self) -> &str {
+        "enum-zero-value-unspecified"
+    }
+
+    fn check_enum(&self, enum_: &EnumDescriptor, cx: &mut LintContext<'_>) {
+        let Some(value) = enum_.get_value(0) else {
+            return;
+        };
+
+        let expected = format!("{}_UNSPECIFIED", to_upper_snake_case(enum_.name()));
+        if enum_
+            .values()
+            .all(|value| value.number() != 0 || value.name() != expected)
+        {
+            cx.report(
+                &value.parent_file(),
+                value.path(),
+                format!(
+                    "zero value of enum '{}' should be named '{}'",
+                    enum_.full_name(),
+                    expected
+                ),
+            );
+        }
+    }
+}
+
+impl LintRule for PackageDirectoryMatch {
+    fn name(&self) -> &str {
+        "package-directory-match"
+    }
+
+    fn check_file(&self, file: &FileDescriptor, cx: &mut LintContext<'_>) {
+        let directory = match file.name().rfind('/') {
+            Some(index) => &file.name()[..index],
+            None => "",
+        };
+        let expected = file.package_name().replace('.', "/");
+        if directory != expected {
+            cx.report(
+                file,
+                &[crate::descriptor::tag::file::PACKAGE],
+                format!(
+                    "file '{}' with package '{}' should be in directory '{}'",
+                    file.name(),
+                    file.package_name(),
+                    expected
+                ),
+            );
+        }
+    }
+}
+
+impl LintRule for FieldNumberGaps {
+    fn name(&self) -> &str {
+        "field-number-gaps"
+    }
+
+    fn check_message(&self, message: &MessageDescriptor, cx: &mut LintContext<'_>) {
+        let mut used: Vec<_> = message
+            .fields()
+            .map(|field| field.number()..field.number() + 1)
+            .chain(message.reserved_ranges())
+            .chain(message.extension_ranges())
+            .collect();
+        used.sort_by_key(|range| range.start);
+
+        let mut next = 1;
+        for range in used {
+            if range.start > next {
+                let gap = if range.start - next == 1 {
+                    format!("field number {next} is unused")
+                } else {
+                    format!("field numbers {} to {} are unused", next, range.start - 1)
+                };
+                cx.report(
+                    &message.parent_file(),
+                    message.path(),
+                    format!("{} in message '{}'", gap, message.full_name()),
+                );
+            }
+            next = next.max(range.end);
+        }
+    }
+}
+
+fn check_case(
+    cx: &mut LintContext<'_>,
+    kind: &str,
+    name: &str,
+    is_valid: fn(&str) -> bool,
+    case: &str,
+    file: &FileDescriptor,
+    path: &[i32],
+) {
+    if !is_valid(name) {
+        cx.report(file, path, format!("{kind} name '{name}' should be {case}"));
+    }
+}
+
+fn check_comment(
+    cx: &mut LintContext<'_>,
+    kind: &str,
+    name: &str,
+    file: &FileDescriptor,
+    path: &[i32],
+    comment: Option<&str>,
+) {
+    if file.file_descriptor_proto().source_code_info.is_none() {
+        return;
+    }
+
+    if comment.is_none_or(|comment| comment.trim().is_empty()) {
+        cx.report(file, path, format!("{kind} '{name}' should have a comment"));
+    }
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_uppercase())
+        && name.chars().all(|ch| ch.is_ascii_alphanumeric())
+}
+
+fn is_lower_snake_case(name: &str) -> bool {
+    is_snake_case(name, |ch| ch.is_ascii_lowercase())
+}
+
+fn is_upper_snake_case(name: &str) -> bool {
+    is_snake_case(name, |ch| ch.is_ascii_uppercase())
+}
+
+fn is_snake_case(name: &str, is_letter: fn(char) -> bool) -> bool {
+    name.starts_with(is_letter)
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && name
+            .chars()
+            .all(|ch| is_letter(ch) || ch.is_ascii_digit() || ch == '_')
+}
+
+/// Converts a `PascalCase` name to `UPPER_SNAKE_CASE`, treating runs of capitals as one word.
+fn to_upper_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, &ch) in chars.iter().enumerate() {
+        if i != 0 && ch.is_ascii_uppercase() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|ch| ch.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.push(ch.to_ascii_uppercase());
+    }
+    result
+}
@@ -1,13 +1,24 @@
 mod api;
 mod build;
+mod compat;
 mod error;
+mod features;
 mod global;
+pub mod lint;
+#[cfg(feature = "proto-parse")]
+mod parse;
+mod print;
+mod subset;
 mod tag;
 #[cfg(test)]
 mod tests;
 pub(crate) mod types;
 
+pub use self::compat::{BreakingChange, BreakingChangeKind};
 pub use self::error::DescriptorError;
+pub use self::features::{feature_set, FeatureSet};
+#[cfg(feature = "proto-parse")]
+pub use self::parse::{FileResolver, IncludePaths};
 use self::types::{DescriptorProto, EnumDescriptorProto};
 
 use std::{
@@ -44,6 +55,35 @@ pub enum Syntax {
     Proto2,
     /// The `proto3` syntax.
     Proto3,
+    /// The `editions` syntax, with the given edition.
+    Editions(Edition),
+}
+
+/// A protobuf edition, used by files with the `editions` syntax.
+///
+/// See the [protobuf documentation](https://protobuf.dev/editions/overview/) for more details.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Edition {
+    /// Edition 2023.
+    Edition2023,
+    /// Edition 2024.
+    Edition2024,
+}
+
+/// The location of a definition within its source file.
+///
+/// Line and column numbers are 0-based, and columns are measured in bytes. The end column is exclusive.
+///
+/// Spans and comments are only available if the file descriptor includes source code info, for example when
+/// compiled with the `--include_source_info` flag of `protoc`, or parsed from source by this crate. See
+/// [`Location`][prost_types::source_code_info::Location] for more details.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    start_line: i32,
+    start_column: i32,
+    end_line: i32,
+    end_column: i32,
 }
 
 /// The type of a protobuf message field.
@@ -107,6 +147,30 @@ enum KindIndex {
     Group(MessageIndex),
 }
 
+/// Any named definition in a [`DescriptorPool`].
+///
+/// This is returned by [`DescriptorPool::get_by_name`] and
+/// [`DescriptorPool::resolve_relative_name`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    /// A message type.
+    Message(MessageDescriptor),
+    /// A field of a message type.
+    Field(FieldDescriptor),
+    /// A oneof of a message type.
+    Oneof(OneofDescriptor),
+    /// A service.
+    Service(ServiceDescriptor),
+    /// A method of a service.
+    Method(MethodDescriptor),
+    /// An enum type.
+    Enum(EnumDescriptor),
+    /// A value of an enum type.
+    EnumValue(EnumValueDescriptor),
+    /// An extension field.
+    Extension(ExtensionDescriptor),
+}
+
 type DescriptorIndex = u32;
 type FileIndex = DescriptorIndex;
 type ServiceIndex = DescriptorIndex;
@@ -141,6 +205,7 @@ struct DescriptorPoolInner {
     enums: Vec<EnumDescriptorInner>,
     extensions: Vec<ExtensionDescriptorInner>,
     services: Vec<ServiceDescriptorInner>,
+    feature_set_defaults: Option<types::FeatureSetDefaults>,
 }
 
 #[derive(Clone)]
@@ -181,8 +246,11 @@ pub struct FileDescriptor {
 #[derive(Clone)]
 struct FileDescriptorInner {
     syntax: Syntax,
+    features: types::FeatureSet,
     raw: FileDescriptorProto,
     prost: prost_types::FileDescriptorProto,
+    /// Maps paths to the index of their location in the `source_code_info` of `raw`.
+    locations: HashMap<Box<[i32]>, usize>,
     dependencies: Vec<FileIndex>,
     transitive_dependencies: HashSet<FileIndex>,
 }
@@ -204,6 +272,7 @@ struct MessageDescriptorInner {
     field_names: HashMap<Box<str>, FieldIndex>,
     field_json_names: HashMap<Box<str>, FieldIndex>,
     oneofs: Vec<OneofDescriptorInner>,
+    features: types::FeatureSet,
 }
 
 /// A oneof field in a protobuf message.
@@ -217,6 +286,7 @@ pub struct OneofDescriptor {
 struct OneofDescriptorInner {
     id: Identity,
     fields: Vec<FieldIndex>,
+    features: types::FeatureSet,
 }
 
 /// A protobuf message definition.
@@ -237,6 +307,7 @@ struct FieldDescriptorInner {
     supports_presence: bool,
     cardinality: Cardinality,
     default: Option<Value>,
+    features: types::FeatureSet,
 }
 
 /// A protobuf extension field definition.
@@ -257,6 +328,7 @@ pub struct ExtensionDescriptorInner {
     is_packed: bool,
     cardinality: Cardinality,
     default: Option<Value>,
+    features: types::FeatureSet,
 }
 
 /// A protobuf enum type.
@@ -274,6 +346,7 @@ struct EnumDescriptorInner {
     value_numbers: Vec<(i32, EnumValueIndex)>,
     value_names: HashMap<Box<str>, EnumValueIndex>,
     allow_alias: bool,
+    features: types::FeatureSet,
 }
 
 /// A value in a protobuf enum type.
@@ -287,6 +360,7 @@ pub struct EnumValueDescriptor {
 struct EnumValueDescriptorInner {
     id: Identity,
     number: i32,
+    features: types::FeatureSet,
 }
 
 /// A protobuf service definition.
@@ -300,6 +374,7 @@ pub struct ServiceDescriptor {
 struct ServiceDescriptorInner {
     id: Identity,
     methods: Vec<MethodDescriptorInner>,
+    features: types::FeatureSet,
 }
 
 /// A method definition for a [`ServiceDescriptor`].
@@ -314,6 +389,7 @@ struct MethodDescriptorInner {
     id: Identity,
     input: MessageIndex,
     output: MessageIndex,
+    features: types::FeatureSet,
 }
 
 impl Identity {
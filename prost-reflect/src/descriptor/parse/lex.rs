@@ -0,0 +1,378 @@
+use std::{convert::TryInto, ops::Range};
+
+use logos::{Lexer, Logos};
+
+#[derive(Debug, Clone, Logos, PartialEq)]
+#[logos(extras = TokenExtras)]
+#[logos(skip r"[\t\v\f\r ]+")]
+#[logos(subpattern exponent = r"[eE][+\-]?[0-9]+")]
+pub(super) enum Token<'a> {
+    #[regex("[A-Za-z_][A-Za-z0-9_]*")]
+    Ident(&'a str),
+    #[regex("0", |lex| int(lex, 10, 0))]
+    #[regex("[1-9][0-9]*", |lex| int(lex, 10, 0))]
+    #[regex("0[0-9]+", |lex| int(lex, 8, 1))]
+    #[regex("0[xX][0-9A-Fa-f]+", |lex| int(lex, 16, 2))]
+    IntLiteral(Int<'a>),
+    #[regex(r#"[0-9]+\.[0-9]*(?&exponent)?"#, float)]
+    #[regex(r#"[0-9]+(?&exponent)"#, float)]
+    #[regex(r#"\.[0-9]+(?&exponent)?"#, float)]
+    FloatLiteral(f64),
+    #[regex(r#"'|""#, string)]
+    StringLiteral(Vec<u8>),
+    #[regex(r"//[^\n]*\n?", |lex| &lex.slice()[2..])]
+    LineComment(&'a str),
+    #[token("/*", block_comment)]
+    BlockComment(&'a str),
+    #[token("\n")]
+    Newline,
+    #[token(".")]
+    Dot,
+    #[token("-")]
+    Minus,
+    #[token("+")]
+    Plus,
+    #[token("=")]
+    Equals,
+    #[token("{")]
+    LeftBrace,
+    #[token("}")]
+    RightBrace,
+    #[token("[")]
+    LeftBracket,
+    #[token("]")]
+    RightBracket,
+    #[token("(")]
+    LeftParen,
+    #[token(")")]
+    RightParen,
+    #[token("<")]
+    LeftAngleBracket,
+    #[token(">")]
+    RightAngleBracket,
+    #[token(",")]
+    Comma,
+    #[token(":")]
+    Colon,
+    #[token(";")]
+    Semicolon,
+    #[token("/")]
+    ForwardSlash,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Int<'a> {
+    pub value: &'a str,
+    pub radix: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum TokenError {
+    InvalidToken,
+    InvalidStringCharacters,
+    InvalidStringEscape,
+    UnterminatedString,
+    UnterminatedBlockComment,
+}
+
+#[derive(Default)]
+pub(super) struct TokenExtras {
+    pub error: Option<(TokenError, Range<usize>)>,
+}
+
+impl Token<'_> {
+    pub fn is_comment(&self) -> bool {
+        matches!(self, Token::LineComment(_) | Token::BlockComment(_))
+    }
+}
+
+impl Int<'_> {
+    pub fn to_u64(&self) -> Option<u64> {
+        u64::from_str_radix(self.value, self.radix).ok()
+    }
+}
+
+/// Converts the contents of a block comment to the form stored in source code info, stripping
+/// leading whitespace and asterisks from each line after the first.
+pub(super) fn block_comment_text(comment: &str) -> String {
+    let mut result = String::with_capacity(comment.len());
+    for (i, line) in comment.split('\n').enumerate() {
+        if i == 0 {
+            result.push_str(line);
+        } else {
+            let line = line.trim_start_matches([' ', '\t', '\x0b', '\x0c', '\r']);
+            result.push('\n');
+            result.push_str(line.strip_prefix('*').unwrap_or(line));
+        }
+    }
+    result
+}
+
+fn int<'a>(lex: &mut Lexer<'a, Token<'a>>, radix: u32, prefix_len: usize) -> Result<Int<'a>, ()> {
+    debug_assert!(lex.slice().len() > prefix_len);
+    let span = lex.span().start + prefix_len..lex.span().end;
+
+    if matches!(lex.remainder().chars().next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_') {
+        let mut end = span.end + 1;
+        while end < lex.source().len() && lex.source().as_bytes()[end].is_ascii_alphanumeric() {
+            end += 1;
+        }
+        lex.extras.error = Some((TokenError::InvalidToken, lex.span().start..end));
+        return Err(());
+    }
+
+    let value = &lex.source()[span];
+    if !value.chars().all(|ch| ch.is_digit(radix)) {
+        lex.extras.error = Some((TokenError::InvalidToken, lex.span()));
+        return Err(());
+    }
+
+    Ok(Int { value, radix })
+}
+
+fn float<'a>(lex: &mut Lexer<'a, Token<'a>>) -> f64 {
+    lex.slice().parse().expect("failed to parse float")
+}
+
+fn block_comment<'a>(lex: &mut Lexer<'a, Token<'a>>) -> Result<&'a str, ()> {
+    match lex.remainder().find("*/") {
+        Some(end) => {
+            let comment = &lex.remainder()[..end];
+            lex.bump(end + 2);
+            Ok(comment)
+        }
+        None => {
+            lex.extras.error = Some((
+                TokenError::UnterminatedBlockComment,
+                lex.span().start..lex.source().len(),
+            ));
+            Err(())
+        }
+    }
+}
+
+fn string<'a>(lex: &mut Lexer<'a, Token<'a>>) -> Result<Vec<u8>, ()> {
+    #[derive(Logos)]
+    #[logos(subpattern hex = r"[0-9A-Fa-f]")]
+    enum Component<'a> {
+        #[regex(r#"[^\x00\n\\'"]+"#)]
+        Unescaped(&'a str),
+        #[regex(r#"['"]"#, terminator)]
+        Terminator(u8),
+        #[regex(r#"\\[xX](?&hex)(?&hex)?"#, hex_escape)]
+        #[regex(r#"\\[0-7][0-7]?[0-7]?"#, oct_escape)]
+        #[regex(r#"\\[abfnrtv?\\'"]"#, char_escape)]
+        Byte(u8),
+        #[regex(r#"\\u(?&hex)(?&hex)(?&hex)(?&hex)"#, unicode_escape)]
+        #[regex(
+            r#"\\U(?&hex)(?&hex)(?&hex)(?&hex)(?&hex)(?&hex)(?&hex)(?&hex)"#,
+            unicode_escape
+        )]
+        Char(char),
+    }
+
+    fn terminator<'a>(lex: &mut Lexer<'a, Component<'a>>) -> u8 {
+        debug_assert_eq!(lex.slice().len(), 1);
+        lex.slice().bytes().next().unwrap()
+    }
+
+    fn hex_escape<'a>(lex: &mut Lexer<'a, Component<'a>>) -> u8 {
+        u32::from_str_radix(&lex.slice()[2..], 16)
+            .expect("expected valid hex escape")
+            .try_into()
+            .expect("two-digit hex escape should be valid byte")
+    }
+
+    fn oct_escape<'a>(lex: &mut Lexer<'a, Component<'a>>) -> Result<u8, ()> {
+        u32::from_str_radix(&lex.slice()[1..], 8)
+            .expect("expected valid oct escape")
+            .try_into()
+            .map_err(drop)
+    }
+
+    fn char_escape<'a>(lex: &mut Lexer<'a, Component<'a>>) -> u8 {
+        match lex.slice().as_bytes()[1] {
+            b'a' => b'\x07',
+            b'b' => b'\x08',
+            b'f' => b'\x0c',
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => b'\x0b',
+            b'?' => b'?',
+            b'\\' => b'\\',
+            b'\'' => b'\'',
+            b'"' => b'"',
+            _ => panic!("failed to parse char escape"),
+        }
+    }
+
+    fn unicode_escape<'a>(lex: &mut Lexer<'a, Component<'a>>) -> Option<char> {
+        let value = u32::from_str_radix(&lex.slice()[2..], 16).expect("expected valid hex escape");
+        char::from_u32(value)
+    }
+
+    let mut result = Vec::new();
+
+    let mut char_lexer = Component::lexer(lex.remainder());
+    let terminator = lex.slice().as_bytes()[0];
+
+    loop {
+        match char_lexer.next() {
+            Some(Ok(Component::Unescaped(s))) => result.extend_from_slice(s.as_bytes()),
+            Some(Ok(Component::Terminator(t))) if t == terminator => {
+                break;
+            }
+            Some(Ok(Component::Terminator(ch) | Component::Byte(ch))) => result.push(ch),
+            Some(Ok(Component::Char(ch))) => {
+                let mut buf = [0; 4];
+                result.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+            Some(Err(())) => {
+                let start = lex.span().end + char_lexer.span().start;
+                let end = lex.span().end + char_lexer.span().end;
+
+                if char_lexer.slice().starts_with('\\') {
+                    lex.extras.error = Some((TokenError::InvalidStringEscape, start..end));
+                } else {
+                    lex.extras.error = Some((TokenError::InvalidStringCharacters, start..end));
+                }
+                return Err(());
+            }
+            None => {
+                lex.extras.error = Some((
+                    TokenError::UnterminatedString,
+                    lex.span().start..lex.source().len(),
+                ));
+                return Err(());
+            }
+        }
+    }
+
+    lex.bump(char_lexer.span().end);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_tokens() {
+        let source = r#"message Foo { int32 x = 0x2A [default = -1.5e3]; } // comment
+            'hello 😀' "\x2a\052" .5 08"#;
+        let mut lexer = Token::lexer(source);
+
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("message")));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("Foo")));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::LeftBrace));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("int32")));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("x")));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Equals));
+        assert_eq!(
+            lexer.next().unwrap(),
+            Ok(Token::IntLiteral(Int {
+                value: "2A",
+                radix: 16,
+            }))
+        );
+        assert_eq!(lexer.next().unwrap(), Ok(Token::LeftBracket));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("default")));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Equals));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Minus));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::FloatLiteral(1.5e3)));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::RightBracket));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Semicolon));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::RightBrace));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::LineComment(" comment\n")));
+        assert_eq!(
+            lexer.next().unwrap(),
+            Ok(Token::StringLiteral(
+                b"hello \xF0\x9F\x98\x80".as_ref().into()
+            ))
+        );
+        assert_eq!(
+            lexer.next().unwrap(),
+            Ok(Token::StringLiteral(b"**".as_ref().into()))
+        );
+        assert_eq!(lexer.next().unwrap(), Ok(Token::FloatLiteral(0.5)));
+        assert_eq!(lexer.next().unwrap(), Err(()));
+        assert_eq!(
+            lexer.extras.error,
+            Some((TokenError::InvalidToken, 101..103))
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = "/* block\n * comment */\n// line\n";
+        let mut lexer = Token::lexer(source);
+
+        assert_eq!(
+            lexer.next().unwrap(),
+            Ok(Token::BlockComment(" block\n * comment "))
+        );
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Newline));
+        assert_eq!(lexer.next().unwrap(), Ok(Token::LineComment(" line\n")));
+        assert_eq!(lexer.next(), None);
+
+        assert_eq!(
+            block_comment_text(" block\n * comment "),
+            " block\n comment "
+        );
+        assert_eq!(block_comment_text(" block\n "), " block\n");
+        assert_eq!(block_comment_text("*\n **"), "*\n*");
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let mut lexer = Token::lexer("foo /* bar");
+
+        assert_eq!(lexer.next().unwrap(), Ok(Token::Ident("foo")));
+        assert_eq!(lexer.next().unwrap(), Err(()));
+        assert_eq!(
+            lexer.extras.error,
+            Some((TokenError::UnterminatedBlockComment, 4..10))
+        );
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let mut lexer = Token::lexer("\"hello \n foo");
+
+        assert_eq!(lexer.next(), Some(Err(())));
+        assert_eq!(
+            lexer.extras.error,
+            Some((TokenError::InvalidStringCharacters, 7..8))
+        );
+
+        let mut lexer = Token::lexer("'hello");
+
+        assert_eq!(lexer.next(), Some(Err(())));
+        assert_eq!(
+            lexer.extras.error,
+            Some((TokenError::UnterminatedString, 0..6))
+        );
+    }
+
+    #[test]
+    fn invalid_string_escape() {
+        let mut lexer = Token::lexer(r#""\m""#);
+
+        assert_eq!(lexer.next(), Some(Err(())));
+        assert_eq!(
+            lexer.extras.error,
+            Some((TokenError::InvalidStringEscape, 1..2))
+        );
+    }
+
+    #[test]
+    fn no_space_between_int_and_ident() {
+        let mut lexer = Token::lexer("foo = 10bar;");
+
+        assert_eq!(lexer.next(), Some(Ok(Token::Ident("foo"))));
+        assert_eq!(lexer.next(), Some(Ok(Token::Equals)));
+        assert_eq!(lexer.next(), Some(Err(())));
+        assert_eq!(lexer.extras.error, Some((TokenError::InvalidToken, 6..11)));
+    }
+}
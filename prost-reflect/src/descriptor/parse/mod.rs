@@ -0,0 +1,325 @@
+mod lex;
+mod parser;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    descriptor::{
+        error::{DescriptorErrorKind, Label},
+        tag, types,
+    },
+    DescriptorError, DescriptorPool, Span,
+};
+
+/// A source of `.proto` files, used by [`DescriptorPool::compile`] to load files and their
+/// imports.
+///
+/// This is implemented for closures of the form `Fn(&str) -> io::Result<String>`, and by
+/// [`IncludePaths`], which reads files from the filesystem.
+#[cfg_attr(docsrs, doc(cfg(feature = "proto-parse")))]
+pub trait FileResolver {
+    /// Reads the contents of the file with the given name, as it appears in an `import`
+    /// statement.
+    ///
+    /// If the file does not exist, an error with kind [`io::ErrorKind::NotFound`] should be
+    /// returned.
+    fn read_file(&self, name: &str) -> io::Result<String>;
+}
+
+impl<F> FileResolver for F
+where
+    F: Fn(&str) -> io::Result<String>,
+{
+    fn read_file(&self, name: &str) -> io::Result<String> {
+        self(name)
+    }
+}
+
+/// A [`FileResolver`] which searches for files in a list of directories, similarly to the `-I`
+/// flag of `protoc`.
+///
+/// Directories are searched in the order they were added, and the first match is used. As with
+/// `protoc`, names which are absolute or contain `..` segments are rejected, so files outside the
+/// include directories cannot be read.
+#[cfg_attr(docsrs, doc(cfg(feature = "proto-parse")))]
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IncludePaths {
+    paths: Vec<PathBuf>,
+}
+
+impl IncludePaths {
+    /// Creates a new resolver which searches the given directories.
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        IncludePaths {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Adds a directory to search for files, after any already added.
+    pub fn add_path(&mut self, path: impl Into<PathBuf>) {
+        self.paths.push(path.into());
+    }
+
+    /// Gets the directories searched for files.
+    pub fn paths(&self) -> impl ExactSizeIterator<Item = &Path> + '_ {
+        self.paths.iter().map(PathBuf::as_path)
+    }
+}
+
+impl FileResolver for IncludePaths {
+    fn read_file(&self, name: &str) -> io::Result<String> {
+        if !Path::new(name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "file names must be relative and must not contain '..' segments",
+            ));
+        }
+
+        for path in &self.paths {
+            match fs::read_to_string(path.join(name)) {
+                Ok(source) => return Ok(source),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "file not found in any include path",
+        ))
+    }
+}
+
+impl fmt::Debug for IncludePaths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.paths).finish()
+    }
+}
+
+impl DescriptorPool {
+    /// Parses a `.proto` source file, and adds it to this pool.
+    ///
+    /// The file may use the `proto2` or `proto3` syntax, or any supported edition. All files
+    /// it imports must already have been added to the pool. The resulting descriptor includes
+    /// [`source_code_info`](prost_types::FileDescriptorProto::source_code_info), so errors
+    /// reference locations within the source.
+    ///
+    /// If a file with the same name is already in the pool, it will be skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source contains a syntax error, or if the resulting file
+    /// descriptor is invalid, for example if it references types not yet added to the pool.
+    /// If the `miette` feature is enabled, the error will include the source code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// let mut pool = DescriptorPool::new();
+    /// pool.add_proto_source("my.proto", r#"
+    ///     syntax = "proto3";
+    ///     package my.package;
+    ///
+    ///     message MyMessage {
+    ///         int32 foo = 1;
+    ///     }
+    /// "#).unwrap();
+    ///
+    /// let message = pool.get_message_by_name("my.package.MyMessage").unwrap();
+    /// assert_eq!(message.fields().len(), 1);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "proto-parse")))]
+    pub fn add_proto_source(&mut self, name: &str, source: &str) -> Result<(), DescriptorError> {
+        let file = parse_file(name, source)?;
+        self.build_files([file])
+            .map_err(|err| with_source_code(err, name, source))
+    }
+
+    /// Parses a set of `.proto` files along with all files they import, and adds them to this
+    /// pool.
+    ///
+    /// Files are loaded using the given [`FileResolver`]. Any imported files already in the
+    /// pool are not loaded again. If the resolver cannot find one of the well-known types
+    /// included with `protoc` (for example `google/protobuf/timestamp.proto`), the built-in
+    /// definition is used instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read, contains a syntax error, or the resulting file
+    /// descriptors are invalid. Imports which form a cycle are also an error. If the `miette`
+    /// feature is enabled, the error will include the source code of the file it occurred in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use prost_reflect::DescriptorPool;
+    /// let resolver = |name: &str| match name {
+    ///     "a.proto" => Ok("syntax = 'proto3'; import 'b.proto'; message A { B b = 1; }".to_owned()),
+    ///     "b.proto" => Ok("syntax = 'proto3'; message B {}".to_owned()),
+    ///     _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+    /// };
+    ///
+    /// let mut pool = DescriptorPool::new();
+    /// pool.compile(["a.proto"], &resolver).unwrap();
+    /// assert!(pool.get_message_by_name("B").is_some());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "proto-parse")))]
+    pub fn compile<I, S>(
+        &mut self,
+        files: I,
+        resolver: &impl FileResolver,
+    ) -> Result<(), DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut compiler = Compiler {
+            pool: self,
+            resolver,
+            files: Vec::new(),
+            sources: HashMap::new(),
+            visited: HashSet::new(),
+            stack: Vec::new(),
+            builtin: None,
+        };
+        for file in files {
+            compiler.visit(file.as_ref(), None)?;
+        }
+
+        let Compiler { files, sources, .. } = compiler;
+        self.build_files(files)
+            .map_err(|err| match err.file().and_then(|file| sources.get(file)) {
+                Some(source) => {
+                    let file = err.file().unwrap_or_default().to_owned();
+                    with_source_code(err, &file, source)
+                }
+                None => err,
+            })
+    }
+}
+
+/// Loads files and their imports in dependency order.
+struct Compiler<'a, R> {
+    pool: &'a DescriptorPool,
+    resolver: &'a R,
+    files: Vec<types::FileDescriptorProto>,
+    sources: HashMap<String, String>,
+    visited: HashSet<String>,
+    stack: Vec<String>,
+    builtin: Option<DescriptorPool>,
+}
+
+impl<R> Compiler<'_, R>
+where
+    R: FileResolver,
+{
+    fn visit(&mut self, name: &str, import: Option<Label>) -> Result<(), DescriptorError> {
+        if self.visited.contains(name) || self.pool.get_file_by_name(name).is_some() {
+            return Ok(());
+        }
+
+        if let Some(index) = self.stack.iter().position(|file| file == name) {
+            let mut cycle = self.stack[index..].join(" -> ");
+            cycle.push_str(" -> ");
+            cycle.push_str(name);
+            return Err(DescriptorError::new(vec![
+                DescriptorErrorKind::ImportCycle {
+                    cycle,
+                    found: import.expect("cycle must contain an import"),
+                },
+            ]));
+        }
+
+        let source = match self.resolver.read_file(name) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if let Some(file) = self.builtin_file(name) {
+                    self.visited.insert(name.to_owned());
+                    self.files.push(file);
+                    return Ok(());
+                }
+                return Err(file_read_error(name, err, import));
+            }
+            Err(err) => return Err(file_read_error(name, err, import)),
+        };
+
+        let file = parse_file(name, &source)?;
+        self.stack.push(name.to_owned());
+        for (index, dependency) in file.dependency.iter().enumerate() {
+            let label = import_label(&file, index);
+            self.visit(dependency, Some(label)).map_err(|err| {
+                if err.file() == Some(name) {
+                    with_source_code(err, name, &source)
+                } else {
+                    err
+                }
+            })?;
+        }
+        self.stack.pop();
+
+        self.visited.insert(name.to_owned());
+        self.sources.insert(name.to_owned(), source);
+        self.files.push(file);
+        Ok(())
+    }
+
+    fn builtin_file(&mut self, name: &str) -> Option<types::FileDescriptorProto> {
+        if !name.starts_with("google/protobuf/") {
+            return None;
+        }
+
+        let builtin = self.builtin.get_or_insert_with(|| {
+            crate::reflect::make_wkt_descriptor_pool().expect("failed to build well-known types")
+        });
+        builtin.get_file_by_name(name).map(|file| {
+            types::FileDescriptorProto::from_prost(file.file_descriptor_proto().clone())
+        })
+    }
+}
+
+fn parse_file(name: &str, source: &str) -> Result<types::FileDescriptorProto, DescriptorError> {
+    parser::Parser::new(name, source)
+        .parse_file()
+        .map_err(|kind| with_source_code(DescriptorError::new(vec![kind]), name, source))
+}
+
+fn import_label(file: &types::FileDescriptorProto, index: usize) -> Label {
+    let path = [tag::file::DEPENDENCY, index as i32];
+    let span = file
+        .source_code_info
+        .iter()
+        .flat_map(|info| &info.location)
+        .find(|location| location.path == path)
+        .and_then(|location| Span::new(&location.span));
+    Label::from_location(file.name(), "imported here", &path, span)
+}
+
+fn file_read_error(name: &str, err: io::Error, import: Option<Label>) -> DescriptorError {
+    DescriptorError::new(vec![DescriptorErrorKind::FileRead {
+        name: name.to_owned(),
+        err,
+        found: import,
+    }])
+}
+
+#[cfg_attr(not(feature = "miette"), allow(unused_variables))]
+fn with_source_code(err: DescriptorError, name: &str, source: &str) -> DescriptorError {
+    #[cfg(feature = "miette")]
+    if err.file() == Some(name) {
+        return err.with_source_code(source);
+    }
+    err
+}
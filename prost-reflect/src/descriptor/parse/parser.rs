@@ -0,0 +1,2281 @@
+use std::{collections::HashSet, mem, ops::Range};
+
+use logos::{Lexer, Logos};
+use prost_types::{
+    descriptor_proto::{ExtensionRange, ReservedRange},
+    enum_descriptor_proto::EnumReservedRange,
+    field_descriptor_proto::{Label as FieldLabel, Type},
+    source_code_info::Location,
+    uninterpreted_option::NamePart,
+    DescriptorProto, EnumDescriptorProto, EnumOptions, EnumValueDescriptorProto, EnumValueOptions,
+    ExtensionRangeOptions, FieldDescriptorProto, FieldOptions, FileDescriptorProto, FileOptions,
+    MessageOptions, MethodDescriptorProto, MethodOptions, OneofDescriptorProto, OneofOptions,
+    ServiceDescriptorProto, ServiceOptions, SourceCodeInfo, UninterpretedOption,
+};
+
+use super::lex::{block_comment_text, Token, TokenError};
+use crate::descriptor::{
+    error::{DescriptorErrorKind, Label},
+    features::{EDITION_2023, EDITION_2024},
+    tag, types, VALID_MESSAGE_FIELD_NUMBERS,
+};
+
+/// The end of an extension or reserved range declared with `max`, before it is adjusted for the
+/// wire format of the message.
+const MAX_RANGE_SENTINEL: i32 = -1;
+
+/// A parser for the `.proto` file format, producing a [`FileDescriptorProto`] with source code
+/// info.
+///
+/// This is modelled on the parser in `protoc`, and produces identical descriptors, including
+/// the source locations and attached comments.
+pub(super) struct Parser<'a> {
+    name: &'a str,
+    source: &'a str,
+    lexer: Lexer<'a, Token<'a>>,
+    offset: usize,
+    line: i32,
+    column: i32,
+    current: Option<SpannedToken<'a>>,
+    previous_end: (i32, i32),
+    syntax: Syntax,
+    locations: Vec<Location>,
+    upcoming_doc_comments: String,
+    upcoming_detached_comments: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct SpannedToken<'a> {
+    token: Token<'a>,
+    span: Range<usize>,
+    start: (i32, i32),
+    end: (i32, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Proto2,
+    Proto3,
+    Editions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionStyle {
+    Statement,
+    Assignment,
+}
+
+#[derive(Debug, Default)]
+struct Comments {
+    leading: String,
+    trailing: String,
+    detached: Vec<String>,
+}
+
+/// Groups comments into leading, trailing and detached comments, following the rules used by
+/// `protoc`.
+struct CommentCollector {
+    comments: Comments,
+    buffer: Option<String>,
+    is_line_comment: bool,
+    can_attach_to_previous: bool,
+    has_trailing_comment: bool,
+    num_comments: usize,
+}
+
+struct MapType {
+    key: (Option<Type>, String),
+    value: (Option<Type>, String),
+}
+
+#[allow(clippy::result_large_err)]
+impl<'a> Parser<'a> {
+    pub fn new(name: &'a str, source: &'a str) -> Self {
+        let mut lexer = Token::lexer(source);
+        let mut offset = 0;
+        if source.starts_with('\u{feff}') {
+            offset = '\u{feff}'.len_utf8();
+            lexer.bump(offset);
+        }
+
+        Parser {
+            name,
+            source,
+            lexer,
+            offset,
+            line: 0,
+            column: 0,
+            current: None,
+            previous_end: (0, 0),
+            syntax: Syntax::Proto2,
+            locations: Vec::new(),
+            upcoming_doc_comments: String::new(),
+            upcoming_detached_comments: Vec::new(),
+        }
+    }
+
+    pub fn parse_file(mut self) -> Result<types::FileDescriptorProto, DescriptorErrorKind> {
+        let mut file = FileDescriptorProto {
+            name: Some(self.name.to_owned()),
+            ..Default::default()
+        };
+
+        let comments = self.next_with_comments(None)?;
+        self.upcoming_doc_comments = comments.leading;
+        self.upcoming_detached_comments = comments.detached;
+
+        let root = self.add_location(vec![]);
+        let mut edition = None;
+        if self.at_ident("syntax") || self.at_ident("edition") {
+            edition = self.parse_syntax(&mut file)?;
+        }
+
+        let mut options = Vec::new();
+        while self.current.is_some() {
+            self.parse_top_level_statement(&mut file, &mut options)?;
+        }
+        self.end_location(root);
+
+        if !options.is_empty() {
+            file.options = Some(FileOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        }
+        file.source_code_info = Some(SourceCodeInfo {
+            location: self.locations,
+        });
+
+        let mut file = types::FileDescriptorProto::from_prost(file);
+        file.edition = edition;
+        Ok(file)
+    }
+
+    /// Parses the `syntax` or `edition` statement, returning the edition if present.
+    fn parse_syntax(
+        &mut self,
+        file: &mut FileDescriptorProto,
+    ) -> Result<Option<i32>, DescriptorErrorKind> {
+        let is_edition = self.at_ident("edition");
+        // protoc records the location of both statements using the path of the syntax field.
+        let location = self.add_location(vec![tag::file::SYNTAX]);
+        self.bump()?;
+        self.expect(Token::Equals)?;
+        let (value, value_token) = self.parse_string("a string")?;
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        self.end_location(location);
+
+        let value = String::from_utf8_lossy(&value);
+        if is_edition {
+            let edition = match value.as_ref() {
+                "2023" => EDITION_2023,
+                "2024" => EDITION_2024,
+                _ => {
+                    return Err(DescriptorErrorKind::UnexpectedToken {
+                        expected: "a supported edition ('2023' or '2024')".to_owned(),
+                        token: self.source[value_token.span].to_owned(),
+                        found: self.label(value_token.start, value_token.end),
+                    })
+                }
+            };
+            self.syntax = Syntax::Editions;
+            file.syntax = Some("editions".to_owned());
+            return Ok(Some(edition));
+        } else {
+            match value.as_ref() {
+                "proto2" => self.syntax = Syntax::Proto2,
+                "proto3" => {
+                    self.syntax = Syntax::Proto3;
+                    file.syntax = Some("proto3".to_owned());
+                }
+                _ => {
+                    return Err(DescriptorErrorKind::UnknownSyntax {
+                        syntax: value.into_owned(),
+                        found: self.label(value_token.start, value_token.end),
+                    })
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn parse_top_level_statement(
+        &mut self,
+        file: &mut FileDescriptorProto,
+        options: &mut Vec<UninterpretedOption>,
+    ) -> Result<(), DescriptorErrorKind> {
+        match self.peek() {
+            Some(Token::Semicolon) => {
+                self.try_consume_end_of_declaration(Token::Semicolon, None)?;
+            }
+            Some(Token::Ident("message")) => {
+                let path = vec![tag::file::MESSAGE_TYPE, file.message_type.len() as i32];
+                let location = self.add_location(path.clone());
+                let message = self.parse_message(&path, location)?;
+                self.end_location(location);
+                file.message_type.push(message);
+            }
+            Some(Token::Ident("enum")) => {
+                let path = vec![tag::file::ENUM_TYPE, file.enum_type.len() as i32];
+                let location = self.add_location(path.clone());
+                let enum_ = self.parse_enum(&path, location)?;
+                self.end_location(location);
+                file.enum_type.push(enum_);
+            }
+            Some(Token::Ident("service")) => {
+                let path = vec![tag::file::SERVICE, file.service.len() as i32];
+                let location = self.add_location(path.clone());
+                let service = self.parse_service(&path, location)?;
+                self.end_location(location);
+                file.service.push(service);
+            }
+            Some(Token::Ident("extend")) => {
+                let location = self.add_location(vec![tag::file::EXTENSION]);
+                self.parse_extend(
+                    &mut file.extension,
+                    &mut file.message_type,
+                    &[],
+                    tag::file::MESSAGE_TYPE,
+                    &[tag::file::EXTENSION],
+                    location,
+                )?;
+                self.end_location(location);
+            }
+            Some(Token::Ident("import")) => self.parse_import(file)?,
+            Some(Token::Ident("package")) => self.parse_package(file)?,
+            Some(Token::Ident("option")) => {
+                let location = self.add_location(vec![tag::file::OPTIONS]);
+                self.parse_option(&[tag::file::OPTIONS], options, OptionStyle::Statement)?;
+                self.end_location(location);
+            }
+            _ => return self.unexpected_token("a top-level statement (e.g. 'message')"),
+        }
+
+        Ok(())
+    }
+
+    fn parse_import(&mut self, file: &mut FileDescriptorProto) -> Result<(), DescriptorErrorKind> {
+        let index = file.dependency.len() as i32;
+        let location = self.add_location(vec![tag::file::DEPENDENCY, index]);
+        self.expect_ident("import")?;
+
+        if self.at_ident("public") {
+            let path = vec![
+                tag::file::PUBLIC_DEPENDENCY,
+                file.public_dependency.len() as i32,
+            ];
+            let public_location = self.add_location(path);
+            self.bump()?;
+            self.end_location(public_location);
+            file.public_dependency.push(index);
+        } else if self.at_ident("weak") {
+            let path = vec![
+                tag::file::WEAK_DEPENDENCY,
+                file.weak_dependency.len() as i32,
+            ];
+            let weak_location = self.add_location(path);
+            self.bump()?;
+            self.end_location(weak_location);
+            file.weak_dependency.push(index);
+        }
+
+        let (name, name_token) = self.parse_string("a string naming the file to import")?;
+        file.dependency.push(self.utf8_string(name, &name_token)?);
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        self.end_location(location);
+        Ok(())
+    }
+
+    fn parse_package(&mut self, file: &mut FileDescriptorProto) -> Result<(), DescriptorErrorKind> {
+        if file.package.is_some() {
+            return Err(DescriptorErrorKind::DuplicatePackage {
+                found: self.current_label(),
+            });
+        }
+
+        let location = self.add_location(vec![tag::file::PACKAGE]);
+        self.expect_ident("package")?;
+        let mut package = self.parse_ident()?;
+        while self.try_consume(Token::Dot)? {
+            package.push('.');
+            package.push_str(&self.parse_ident()?);
+        }
+        file.package = Some(package);
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        self.end_location(location);
+        Ok(())
+    }
+
+    fn parse_message(
+        &mut self,
+        path: &[i32],
+        location: usize,
+    ) -> Result<DescriptorProto, DescriptorErrorKind> {
+        self.expect_ident("message")?;
+        let name = self.parse_name(join(path, &[tag::message::NAME]))?;
+        let mut message = DescriptorProto {
+            name: Some(name),
+            ..Default::default()
+        };
+        self.parse_message_block(&mut message, path, location)?;
+
+        if self.syntax == Syntax::Proto3 {
+            generate_synthetic_oneofs(&mut message);
+        }
+        Ok(message)
+    }
+
+    fn parse_message_block(
+        &mut self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+        location: usize,
+    ) -> Result<(), DescriptorErrorKind> {
+        let mut options = Vec::new();
+        self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+        while !self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+            if self.current.is_none() {
+                return self.unexpected_token("'}'");
+            }
+            self.parse_message_statement(message, path, &mut options)?;
+        }
+
+        if !options.is_empty() {
+            message.options = Some(MessageOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        }
+
+        // Ranges ending at `max` extend to the largest valid field number, which is larger for
+        // messages using the message set wire format.
+        let max = if message.options.as_ref().is_some_and(|options| {
+            options.uninterpreted_option.iter().any(|option| {
+                option.name.len() == 1
+                    && option.name[0].name_part == "message_set_wire_format"
+                    && option.identifier_value.as_deref() == Some("true")
+            })
+        }) {
+            i32::MAX
+        } else {
+            VALID_MESSAGE_FIELD_NUMBERS.end
+        };
+        for range in &mut message.extension_range {
+            if range.end == Some(MAX_RANGE_SENTINEL) {
+                range.end = Some(max);
+            }
+        }
+        for range in &mut message.reserved_range {
+            if range.end == Some(MAX_RANGE_SENTINEL) {
+                range.end = Some(max);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_message_statement(
+        &mut self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+        options: &mut Vec<UninterpretedOption>,
+    ) -> Result<(), DescriptorErrorKind> {
+        match self.peek() {
+            Some(Token::Semicolon) => {
+                self.try_consume_end_of_declaration(Token::Semicolon, None)?;
+            }
+            Some(Token::Ident("message")) => {
+                let path = join(
+                    path,
+                    &[tag::message::NESTED_TYPE, message.nested_type.len() as i32],
+                );
+                let location = self.add_location(path.clone());
+                let nested = self.parse_message(&path, location)?;
+                self.end_location(location);
+                message.nested_type.push(nested);
+            }
+            Some(Token::Ident("enum")) => {
+                let path = join(
+                    path,
+                    &[tag::message::ENUM_TYPE, message.enum_type.len() as i32],
+                );
+                let location = self.add_location(path.clone());
+                let enum_ = self.parse_enum(&path, location)?;
+                self.end_location(location);
+                message.enum_type.push(enum_);
+            }
+            Some(Token::Ident("extensions")) => {
+                let path = join(path, &[tag::message::EXTENSION_RANGE]);
+                let location = self.add_location(path.clone());
+                self.parse_extensions(message, &path, location)?;
+                self.end_location(location);
+            }
+            Some(Token::Ident("reserved")) => self.parse_message_reserved(message, path)?,
+            Some(Token::Ident("extend")) => {
+                let extend_path = join(path, &[tag::message::EXTENSION]);
+                let location = self.add_location(extend_path.clone());
+                self.parse_extend(
+                    &mut message.extension,
+                    &mut message.nested_type,
+                    path,
+                    tag::message::NESTED_TYPE,
+                    &extend_path,
+                    location,
+                )?;
+                self.end_location(location);
+            }
+            Some(Token::Ident("option")) => {
+                let path = join(path, &[tag::message::OPTIONS]);
+                let location = self.add_location(path.clone());
+                self.parse_option(&path, options, OptionStyle::Statement)?;
+                self.end_location(location);
+            }
+            Some(Token::Ident("oneof")) => {
+                let index = message.oneof_decl.len() as i32;
+                let oneof_path = join(path, &[tag::message::ONEOF_DECL, index]);
+                let location = self.add_location(oneof_path.clone());
+                self.parse_oneof(message, index, &oneof_path, location, path)?;
+                self.end_location(location);
+            }
+            _ => {
+                let field_path = join(path, &[tag::message::FIELD, message.field.len() as i32]);
+                let location = self.add_location(field_path.clone());
+                let field = self.parse_field(
+                    FieldDescriptorProto::default(),
+                    &mut message.nested_type,
+                    path,
+                    tag::message::NESTED_TYPE,
+                    &field_path,
+                    location,
+                )?;
+                self.end_location(location);
+                message.field.push(field);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_field(
+        &mut self,
+        mut field: FieldDescriptorProto,
+        messages: &mut Vec<DescriptorProto>,
+        parent_path: &[i32],
+        nested_type_tag: i32,
+        path: &[i32],
+        location: usize,
+    ) -> Result<FieldDescriptorProto, DescriptorErrorKind> {
+        if let Some(label) = self.parse_label(path)? {
+            field.set_label(label);
+            if label == FieldLabel::Optional && self.syntax == Syntax::Proto3 {
+                field.proto3_optional = Some(true);
+            }
+        }
+
+        self.parse_field_no_label(
+            field,
+            messages,
+            parent_path,
+            nested_type_tag,
+            path,
+            location,
+        )
+    }
+
+    fn parse_label(&mut self, path: &[i32]) -> Result<Option<FieldLabel>, DescriptorErrorKind> {
+        let label = match self.peek() {
+            Some(Token::Ident("optional")) => FieldLabel::Optional,
+            Some(Token::Ident("repeated")) => FieldLabel::Repeated,
+            Some(Token::Ident("required")) => FieldLabel::Required,
+            _ => return Ok(None),
+        };
+
+        if self.syntax == Syntax::Editions && label != FieldLabel::Repeated {
+            return Err(DescriptorErrorKind::FieldLabelNotAllowed {
+                label: self.current_text().to_owned(),
+                context: "in editions",
+                found: self.current_label(),
+            });
+        }
+
+        let location = self.add_location(join(path, &[tag::field::LABEL]));
+        self.bump()?;
+        self.end_location(location);
+        Ok(Some(label))
+    }
+
+    fn parse_field_no_label(
+        &mut self,
+        mut field: FieldDescriptorProto,
+        messages: &mut Vec<DescriptorProto>,
+        parent_path: &[i32],
+        nested_type_tag: i32,
+        path: &[i32],
+        location: usize,
+    ) -> Result<FieldDescriptorProto, DescriptorErrorKind> {
+        let type_location = self.add_location(path.to_vec());
+        let mut map_type = None;
+        let mut ty = None;
+        if self.at_ident("map") {
+            let map_token = self.bump()?;
+            if self.at(&Token::LeftAngleBracket) {
+                let label = |field: &FieldDescriptorProto| field.label().as_str_name();
+                if field.oneof_index.is_some() {
+                    return Err(DescriptorErrorKind::MapFieldNotAllowed {
+                        context: "in oneofs",
+                        found: self.label(map_token.start, map_token.end),
+                    });
+                } else if field.label.is_some() {
+                    return Err(DescriptorErrorKind::FieldLabelNotAllowed {
+                        label: label(&field)
+                            .trim_start_matches("LABEL_")
+                            .to_ascii_lowercase(),
+                        context: "on map fields",
+                        found: self.label(map_token.start, map_token.end),
+                    });
+                } else if field.extendee.is_some() {
+                    return Err(DescriptorErrorKind::MapFieldNotAllowed {
+                        context: "as extensions",
+                        found: self.label(map_token.start, map_token.end),
+                    });
+                }
+
+                field.set_label(FieldLabel::Repeated);
+                self.bump()?;
+                let key_start = self.current_start();
+                let key = self.parse_type()?;
+                if !is_valid_map_key_type(key.0) {
+                    return Err(DescriptorErrorKind::InvalidMapKeyType {
+                        found: self.label(key_start, self.previous_end),
+                    });
+                }
+                self.expect(Token::Comma)?;
+                let value = self.parse_type()?;
+                self.expect(Token::RightAngleBracket)?;
+                self.locations[type_location]
+                    .path
+                    .push(tag::field::TYPE_NAME);
+                map_type = Some(MapType { key, value });
+            } else {
+                // The type of the field is a message or enum named 'map'.
+                ty = Some((None, "map".to_owned()));
+            }
+        }
+
+        if map_type.is_none() {
+            if field.label.is_none() {
+                if self.syntax == Syntax::Proto2 {
+                    return self.unexpected_token("'required', 'optional' or 'repeated'");
+                }
+                field.set_label(FieldLabel::Optional);
+            }
+
+            let ty = match ty {
+                Some(ty) => ty,
+                None => self.parse_type()?,
+            };
+            match ty {
+                (Some(ty), _) => {
+                    self.locations[type_location].path.push(tag::field::TYPE);
+                    field.set_type(ty);
+                }
+                (None, type_name) => {
+                    self.locations[type_location]
+                        .path
+                        .push(tag::field::TYPE_NAME);
+                    field.type_name = Some(type_name);
+                }
+            }
+        }
+        self.end_location(type_location);
+
+        let name_token = self.current.clone();
+        field.name = Some(self.parse_name(join(path, &[tag::field::NAME]))?);
+        self.expect(Token::Equals)?;
+        let number_location = self.add_location(join(path, &[tag::field::NUMBER]));
+        field.number = Some(self.parse_int(i32::MAX as u64)? as i32);
+        self.end_location(number_location);
+
+        let mut options = Vec::new();
+        self.parse_field_options(&mut field, path, &mut options)?;
+        if !options.is_empty() {
+            field.options = Some(FieldOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        }
+
+        if field.r#type == Some(Type::Group as i32) {
+            let name_token = name_token.expect("field name should have been parsed");
+            let group_path = join(parent_path, &[nested_type_tag, messages.len() as i32]);
+            let group_location = self.add_location(group_path.clone());
+            let (start_line, start_column) = (
+                self.locations[location].span[0],
+                self.locations[location].span[1],
+            );
+            self.locations[group_location].span = vec![start_line, start_column];
+            self.add_location_span(
+                join(&group_path, &[tag::message::NAME]),
+                name_token.start,
+                name_token.end,
+            );
+            self.add_location_span(
+                join(path, &[tag::field::TYPE_NAME]),
+                name_token.start,
+                name_token.end,
+            );
+
+            let name = field.name().to_owned();
+            if !name.starts_with(|ch: char| ch.is_ascii_uppercase()) {
+                return Err(DescriptorErrorKind::InvalidGroupName {
+                    found: self.label(name_token.start, name_token.end),
+                });
+            }
+            field.name = Some(name.to_ascii_lowercase());
+            field.type_name = Some(name.clone());
+
+            let mut group = DescriptorProto {
+                name: Some(name),
+                ..Default::default()
+            };
+            if !self.at(&Token::LeftBrace) {
+                return self.unexpected_token("'{'");
+            }
+            self.parse_message_block(&mut group, &group_path, group_location)?;
+            self.end_location(group_location);
+            messages.push(group);
+        } else {
+            self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        }
+
+        if let Some(map_type) = map_type {
+            let entry = generate_map_entry(&mut field, map_type);
+            messages.push(entry);
+        }
+
+        Ok(field)
+    }
+
+    fn parse_field_options(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        path: &[i32],
+        options: &mut Vec<UninterpretedOption>,
+    ) -> Result<(), DescriptorErrorKind> {
+        if !self.at(&Token::LeftBracket) {
+            return Ok(());
+        }
+
+        let options_path = join(path, &[tag::field::OPTIONS]);
+        let location = self.add_location(options_path.clone());
+        self.bump()?;
+        loop {
+            if self.at_ident("default") {
+                self.parse_default(field, path)?;
+            } else if self.at_ident("json_name") {
+                self.parse_json_name(field, path)?;
+            } else {
+                self.parse_option(&options_path, options, OptionStyle::Assignment)?;
+            }
+
+            if !self.try_consume(Token::Comma)? {
+                break;
+            }
+        }
+        self.expect(Token::RightBracket)?;
+        self.end_location(location);
+        Ok(())
+    }
+
+    fn parse_default(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        path: &[i32],
+    ) -> Result<(), DescriptorErrorKind> {
+        if field.default_value.is_some() {
+            return Err(DescriptorErrorKind::DuplicateOption {
+                name: "default".to_owned(),
+                found: self.current_label(),
+            });
+        }
+        self.bump()?;
+        self.expect(Token::Equals)?;
+
+        let location = self.add_location(join(path, &[tag::field::DEFAULT_VALUE]));
+        let ty = match field.r#type.and_then(|ty| Type::try_from(ty).ok()) {
+            Some(ty) => ty,
+            None => {
+                // The type is a message or enum, which is not known until names are resolved, so
+                // the value is validated later.
+                if self.current.is_none() {
+                    return self.unexpected_token("a default value");
+                }
+                let token = self.bump()?;
+                field.default_value = Some(self.source[token.span].to_owned());
+                self.end_location(location);
+                return Ok(());
+            }
+        };
+
+        let value = match ty {
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => {
+                self.parse_signed_int(i32::MAX as u64)?.to_string()
+            }
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+                self.parse_signed_int(i64::MAX as u64)?.to_string()
+            }
+            Type::Uint32 | Type::Fixed32 => self.parse_int(u32::MAX as u64)?.to_string(),
+            Type::Uint64 | Type::Fixed64 => self.parse_int(u64::MAX)?.to_string(),
+            Type::Float | Type::Double => {
+                let mut value = String::new();
+                if self.try_consume(Token::Minus)? {
+                    value.push('-');
+                }
+                value.push_str(&simple_dtoa(self.parse_number()?));
+                value
+            }
+            Type::Bool => {
+                if self.try_consume_ident("true")? {
+                    "true".to_owned()
+                } else if self.try_consume_ident("false")? {
+                    "false".to_owned()
+                } else {
+                    return self.unexpected_token("'true' or 'false'");
+                }
+            }
+            Type::String => {
+                let (value, token) = self.parse_string("a string")?;
+                self.utf8_string(value, &token)?
+            }
+            Type::Bytes => c_escape(&self.parse_string("a string")?.0),
+            Type::Enum => self.parse_ident()?,
+            Type::Message | Type::Group => {
+                return Err(DescriptorErrorKind::InvalidFieldDefault {
+                    value: self.current_text().to_owned(),
+                    kind: "group".to_owned(),
+                    found: self.current_label(),
+                })
+            }
+        };
+
+        field.default_value = Some(value);
+        self.end_location(location);
+        Ok(())
+    }
+
+    fn parse_json_name(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        path: &[i32],
+    ) -> Result<(), DescriptorErrorKind> {
+        if field.json_name.is_some() {
+            return Err(DescriptorErrorKind::DuplicateOption {
+                name: "json_name".to_owned(),
+                found: self.current_label(),
+            });
+        }
+
+        let path = join(path, &[tag::field::JSON_NAME]);
+        let location = self.add_location(path.clone());
+        self.bump()?;
+        self.expect(Token::Equals)?;
+        let value_location = self.add_location(path);
+        let (value, token) = self.parse_string("a string")?;
+        field.json_name = Some(self.utf8_string(value, &token)?);
+        self.end_location(value_location);
+        self.end_location(location);
+        Ok(())
+    }
+
+    fn parse_extensions(
+        &mut self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+        location: usize,
+    ) -> Result<(), DescriptorErrorKind> {
+        self.expect_ident("extensions")?;
+        let first_range = message.extension_range.len();
+        loop {
+            let range_path = join(path, &[message.extension_range.len() as i32]);
+            let (start, end) = self.parse_range(&range_path, i32::MAX as u64, false)?;
+            message.extension_range.push(ExtensionRange {
+                start: Some(start),
+                end: Some(end),
+                options: None,
+            });
+
+            if !self.try_consume(Token::Comma)? {
+                break;
+            }
+        }
+
+        if self.at(&Token::LeftBracket) {
+            // The options are parsed once, and then copied to each range in this statement,
+            // along with their source locations.
+            let options_path = join(
+                path,
+                &[first_range as i32, tag::message::extension_range::OPTIONS],
+            );
+            let first_location = self.locations.len();
+            let options_location = self.add_location(options_path.clone());
+            let mut options = Vec::new();
+            self.bump()?;
+            loop {
+                self.parse_option(&options_path, &mut options, OptionStyle::Assignment)?;
+                if !self.try_consume(Token::Comma)? {
+                    break;
+                }
+            }
+            self.expect(Token::RightBracket)?;
+            self.end_location(options_location);
+
+            let option_locations = self.locations.split_off(first_location);
+            for (index, range) in message.extension_range[first_range..]
+                .iter_mut()
+                .enumerate()
+            {
+                range.options = Some(ExtensionRangeOptions {
+                    uninterpreted_option: options.clone(),
+                });
+
+                for location in &option_locations {
+                    let mut location = location.clone();
+                    location.path[path.len()] = (first_range + index) as i32;
+                    self.locations.push(location);
+                }
+            }
+        }
+
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))
+    }
+
+    fn parse_message_reserved(
+        &mut self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+    ) -> Result<(), DescriptorErrorKind> {
+        let start = self.current_start();
+        self.expect_ident("reserved")?;
+
+        if matches!(self.peek(), Some(Token::StringLiteral(_) | Token::Ident(_))) {
+            let path = join(path, &[tag::message::RESERVED_NAME]);
+            let location = self.add_location_start(path.clone(), start);
+            self.parse_reserved_names(&mut message.reserved_name, &path, location)?;
+            self.end_location(location);
+        } else {
+            let path = join(path, &[tag::message::RESERVED_RANGE]);
+            let location = self.add_location_start(path.clone(), start);
+            loop {
+                let range_path = join(&path, &[message.reserved_range.len() as i32]);
+                let (start, end) = self.parse_range(&range_path, i32::MAX as u64, false)?;
+                message.reserved_range.push(ReservedRange {
+                    start: Some(start),
+                    end: Some(end),
+                });
+
+                if !self.try_consume(Token::Comma)? {
+                    break;
+                }
+            }
+            self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+            self.end_location(location);
+        }
+
+        Ok(())
+    }
+
+    fn parse_enum_reserved(
+        &mut self,
+        enum_: &mut EnumDescriptorProto,
+        path: &[i32],
+    ) -> Result<(), DescriptorErrorKind> {
+        let start = self.current_start();
+        self.expect_ident("reserved")?;
+
+        if matches!(self.peek(), Some(Token::StringLiteral(_) | Token::Ident(_))) {
+            let path = join(path, &[tag::enum_::RESERVED_NAME]);
+            let location = self.add_location_start(path.clone(), start);
+            self.parse_reserved_names(&mut enum_.reserved_name, &path, location)?;
+            self.end_location(location);
+        } else {
+            let path = join(path, &[tag::enum_::RESERVED_RANGE]);
+            let location = self.add_location_start(path.clone(), start);
+            loop {
+                let range_path = join(&path, &[enum_.reserved_range.len() as i32]);
+                let (start, end) = self.parse_range(&range_path, i32::MAX as u64, true)?;
+                enum_.reserved_range.push(EnumReservedRange {
+                    start: Some(start),
+                    end: Some(end),
+                });
+
+                if !self.try_consume(Token::Comma)? {
+                    break;
+                }
+            }
+            self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+            self.end_location(location);
+        }
+
+        Ok(())
+    }
+
+    fn parse_reserved_names(
+        &mut self,
+        names: &mut Vec<String>,
+        path: &[i32],
+        location: usize,
+    ) -> Result<(), DescriptorErrorKind> {
+        let editions = self.syntax == Syntax::Editions;
+        loop {
+            let name_location = self.add_location(join(path, &[names.len() as i32]));
+            let name = match self.peek() {
+                Some(Token::StringLiteral(_)) if !editions => {
+                    let (name, token) = self.parse_string("a string")?;
+                    let name = self.utf8_string(name, &token)?;
+                    if !is_identifier(&name) {
+                        return Err(DescriptorErrorKind::InvalidReservedName {
+                            editions,
+                            found: self.label(token.start, token.end),
+                        });
+                    }
+                    name
+                }
+                Some(Token::Ident(name)) if editions => {
+                    let name = name.to_string();
+                    self.bump()?;
+                    name
+                }
+                Some(Token::StringLiteral(_) | Token::Ident(_)) => {
+                    return Err(DescriptorErrorKind::InvalidReservedName {
+                        editions,
+                        found: self.current_label(),
+                    })
+                }
+                _ => return self.unexpected_token("a reserved name"),
+            };
+            self.end_location(name_location);
+            names.push(name);
+
+            if !self.try_consume(Token::Comma)? {
+                break;
+            }
+        }
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))
+    }
+
+    /// Parses a range of field or enum numbers, returning its start and end. For message ranges
+    /// the end is exclusive, and for enum ranges it is inclusive.
+    fn parse_range(
+        &mut self,
+        path: &[i32],
+        max: u64,
+        signed: bool,
+    ) -> Result<(i32, i32), DescriptorErrorKind> {
+        let location = self.add_location(path.to_vec());
+
+        let start_location = self.add_location(join(path, &[tag::message::reserved_range::START]));
+        let start_token = self.current.clone();
+        let start = if signed {
+            self.parse_signed_int(max)? as i32
+        } else {
+            self.parse_int(max)? as i32
+        };
+        self.end_location(start_location);
+
+        let end = if self.try_consume_ident("to")? {
+            let end_location = self.add_location(join(path, &[tag::message::reserved_range::END]));
+            let end = if self.try_consume_ident("max")? {
+                if signed {
+                    i32::MAX
+                } else {
+                    MAX_RANGE_SENTINEL - 1
+                }
+            } else if signed {
+                self.parse_signed_int(max)? as i32
+            } else {
+                self.parse_int(max)? as i32
+            };
+            self.end_location(end_location);
+            end
+        } else {
+            let start_token = start_token.expect("range start should have been parsed");
+            self.add_location_span(
+                join(path, &[tag::message::reserved_range::END]),
+                start_token.start,
+                start_token.end,
+            );
+            start
+        };
+        self.end_location(location);
+
+        if signed {
+            Ok((start, end))
+        } else {
+            Ok((start, end.wrapping_add(1)))
+        }
+    }
+
+    fn parse_oneof(
+        &mut self,
+        message: &mut DescriptorProto,
+        index: i32,
+        path: &[i32],
+        location: usize,
+        message_path: &[i32],
+    ) -> Result<(), DescriptorErrorKind> {
+        self.expect_ident("oneof")?;
+        let name = self.parse_name(join(path, &[tag::oneof::NAME]))?;
+        self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+
+        let mut options = Vec::new();
+        loop {
+            if self.current.is_none() {
+                return self.unexpected_token("'}'");
+            }
+
+            if self.at_ident("option") {
+                let options_path = join(path, &[tag::oneof::OPTIONS]);
+                let options_location = self.add_location(options_path.clone());
+                self.parse_option(&options_path, &mut options, OptionStyle::Statement)?;
+                self.end_location(options_location);
+            } else {
+                if matches!(
+                    self.peek(),
+                    Some(Token::Ident("optional" | "required" | "repeated"))
+                ) {
+                    return Err(DescriptorErrorKind::FieldLabelNotAllowed {
+                        label: self.current_text().to_owned(),
+                        context: "in oneofs",
+                        found: self.current_label(),
+                    });
+                }
+
+                let field_path = join(
+                    message_path,
+                    &[tag::message::FIELD, message.field.len() as i32],
+                );
+                let field_location = self.add_location(field_path.clone());
+                let mut field = FieldDescriptorProto {
+                    oneof_index: Some(index),
+                    ..Default::default()
+                };
+                field.set_label(FieldLabel::Optional);
+                let field = self.parse_field_no_label(
+                    field,
+                    &mut message.nested_type,
+                    message_path,
+                    tag::message::NESTED_TYPE,
+                    &field_path,
+                    field_location,
+                )?;
+                self.end_location(field_location);
+                message.field.push(field);
+            }
+
+            if self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+                break;
+            }
+        }
+
+        message.oneof_decl.push(OneofDescriptorProto {
+            name: Some(name),
+            options: (!options.is_empty()).then_some(OneofOptions {
+                uninterpreted_option: options,
+            }),
+        });
+        Ok(())
+    }
+
+    fn parse_extend(
+        &mut self,
+        extensions: &mut Vec<FieldDescriptorProto>,
+        messages: &mut Vec<DescriptorProto>,
+        parent_path: &[i32],
+        nested_type_tag: i32,
+        path: &[i32],
+        location: usize,
+    ) -> Result<(), DescriptorErrorKind> {
+        self.expect_ident("extend")?;
+        let extendee_start = self.current_start();
+        let extendee = self.parse_type_name()?;
+        let extendee_end = self.previous_end;
+        self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+
+        loop {
+            if self.current.is_none() {
+                return self.unexpected_token("'}'");
+            }
+
+            let field_path = join(path, &[extensions.len() as i32]);
+            let field_location = self.add_location(field_path.clone());
+            self.add_location_span(
+                join(&field_path, &[tag::field::EXTENDEE]),
+                extendee_start,
+                extendee_end,
+            );
+            let field = FieldDescriptorProto {
+                extendee: Some(extendee.clone()),
+                ..Default::default()
+            };
+            let field = self.parse_field(
+                field,
+                messages,
+                parent_path,
+                nested_type_tag,
+                &field_path,
+                field_location,
+            )?;
+            self.end_location(field_location);
+            extensions.push(field);
+
+            if self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_enum(
+        &mut self,
+        path: &[i32],
+        location: usize,
+    ) -> Result<EnumDescriptorProto, DescriptorErrorKind> {
+        self.expect_ident("enum")?;
+        let name = self.parse_name(join(path, &[tag::enum_::NAME]))?;
+        let mut enum_ = EnumDescriptorProto {
+            name: Some(name),
+            ..Default::default()
+        };
+
+        let mut options = Vec::new();
+        self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+        while !self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+            match self.peek() {
+                None => return self.unexpected_token("'}'"),
+                Some(Token::Semicolon) => {
+                    self.try_consume_end_of_declaration(Token::Semicolon, None)?;
+                }
+                Some(Token::Ident("option")) => {
+                    let options_path = join(path, &[tag::enum_::OPTIONS]);
+                    let options_location = self.add_location(options_path.clone());
+                    self.parse_option(&options_path, &mut options, OptionStyle::Statement)?;
+                    self.end_location(options_location);
+                }
+                Some(Token::Ident("reserved")) => self.parse_enum_reserved(&mut enum_, path)?,
+                _ => {
+                    let value_path = join(path, &[tag::enum_::VALUE, enum_.value.len() as i32]);
+                    let value_location = self.add_location(value_path.clone());
+                    let value = self.parse_enum_value(&value_path, value_location)?;
+                    self.end_location(value_location);
+                    enum_.value.push(value);
+                }
+            }
+        }
+
+        if !options.is_empty() {
+            enum_.options = Some(EnumOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        }
+        Ok(enum_)
+    }
+
+    fn parse_enum_value(
+        &mut self,
+        path: &[i32],
+        location: usize,
+    ) -> Result<EnumValueDescriptorProto, DescriptorErrorKind> {
+        let name = self.parse_name(join(path, &[tag::enum_value::NAME]))?;
+        self.expect(Token::Equals)?;
+        let number_location = self.add_location(join(path, &[tag::enum_value::NUMBER]));
+        let number = self.parse_signed_int(i32::MAX as u64)? as i32;
+        self.end_location(number_location);
+
+        let mut options = Vec::new();
+        if self.at(&Token::LeftBracket) {
+            let options_path = join(path, &[tag::enum_value::OPTIONS]);
+            let options_location = self.add_location(options_path.clone());
+            self.bump()?;
+            loop {
+                self.parse_option(&options_path, &mut options, OptionStyle::Assignment)?;
+                if !self.try_consume(Token::Comma)? {
+                    break;
+                }
+            }
+            self.expect(Token::RightBracket)?;
+            self.end_location(options_location);
+        }
+        self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+
+        Ok(EnumValueDescriptorProto {
+            name: Some(name),
+            number: Some(number),
+            options: (!options.is_empty()).then(|| EnumValueOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            }),
+        })
+    }
+
+    fn parse_service(
+        &mut self,
+        path: &[i32],
+        location: usize,
+    ) -> Result<ServiceDescriptorProto, DescriptorErrorKind> {
+        self.expect_ident("service")?;
+        let name = self.parse_name(join(path, &[tag::service::NAME]))?;
+        let mut service = ServiceDescriptorProto {
+            name: Some(name),
+            ..Default::default()
+        };
+
+        let mut options = Vec::new();
+        self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+        while !self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+            match self.peek() {
+                None => return self.unexpected_token("'}'"),
+                Some(Token::Semicolon) => {
+                    self.try_consume_end_of_declaration(Token::Semicolon, None)?;
+                }
+                Some(Token::Ident("option")) => {
+                    let options_path = join(path, &[tag::service::OPTIONS]);
+                    let options_location = self.add_location(options_path.clone());
+                    self.parse_option(&options_path, &mut options, OptionStyle::Statement)?;
+                    self.end_location(options_location);
+                }
+                _ => {
+                    let method_path =
+                        join(path, &[tag::service::METHOD, service.method.len() as i32]);
+                    let method_location = self.add_location(method_path.clone());
+                    let method = self.parse_method(&method_path, method_location)?;
+                    self.end_location(method_location);
+                    service.method.push(method);
+                }
+            }
+        }
+
+        if !options.is_empty() {
+            service.options = Some(ServiceOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        }
+        Ok(service)
+    }
+
+    fn parse_method(
+        &mut self,
+        path: &[i32],
+        location: usize,
+    ) -> Result<MethodDescriptorProto, DescriptorErrorKind> {
+        self.expect_ident("rpc")?;
+        let mut method = MethodDescriptorProto {
+            name: Some(self.parse_name(join(path, &[tag::method::NAME]))?),
+            ..Default::default()
+        };
+
+        self.expect(Token::LeftParen)?;
+        if self.at_ident("stream") {
+            let stream_location = self.add_location(join(path, &[tag::method::CLIENT_STREAMING]));
+            method.client_streaming = Some(true);
+            self.bump()?;
+            self.end_location(stream_location);
+        }
+        let input_location = self.add_location(join(path, &[tag::method::INPUT_TYPE]));
+        method.input_type = Some(self.parse_type_name()?);
+        self.end_location(input_location);
+        self.expect(Token::RightParen)?;
+
+        self.expect_ident("returns")?;
+        self.expect(Token::LeftParen)?;
+        if self.at_ident("stream") {
+            let stream_location = self.add_location(join(path, &[tag::method::SERVER_STREAMING]));
+            method.server_streaming = Some(true);
+            self.bump()?;
+            self.end_location(stream_location);
+        }
+        let output_location = self.add_location(join(path, &[tag::method::OUTPUT_TYPE]));
+        method.output_type = Some(self.parse_type_name()?);
+        self.end_location(output_location);
+        self.expect(Token::RightParen)?;
+
+        if self.at(&Token::LeftBrace) {
+            let mut options = Vec::new();
+            self.consume_end_of_declaration(Token::LeftBrace, Some(location))?;
+            while !self.try_consume_end_of_declaration(Token::RightBrace, None)? {
+                if self.current.is_none() {
+                    return self.unexpected_token("'}'");
+                }
+
+                if !self.try_consume_end_of_declaration(Token::Semicolon, None)? {
+                    let options_path = join(path, &[tag::method::OPTIONS]);
+                    let options_location = self.add_location(options_path.clone());
+                    self.parse_option(&options_path, &mut options, OptionStyle::Statement)?;
+                    self.end_location(options_location);
+                }
+            }
+            method.options = Some(MethodOptions {
+                uninterpreted_option: options,
+                ..Default::default()
+            });
+        } else {
+            self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        }
+
+        Ok(method)
+    }
+
+    fn parse_option(
+        &mut self,
+        path: &[i32],
+        options: &mut Vec<UninterpretedOption>,
+        style: OptionStyle,
+    ) -> Result<(), DescriptorErrorKind> {
+        let location = self.add_location(join(
+            path,
+            &[tag::UNINTERPRETED_OPTION, options.len() as i32],
+        ));
+        if style == OptionStyle::Statement {
+            self.expect_ident("option")?;
+        }
+
+        let mut option = UninterpretedOption::default();
+        loop {
+            if self.try_consume(Token::LeftParen)? {
+                let mut name = String::new();
+                if self.try_consume(Token::Dot)? {
+                    name.push('.');
+                }
+                name.push_str(&self.parse_ident()?);
+                while self.try_consume(Token::Dot)? {
+                    name.push('.');
+                    name.push_str(&self.parse_ident()?);
+                }
+                self.expect(Token::RightParen)?;
+                option.name.push(NamePart {
+                    name_part: name,
+                    is_extension: true,
+                });
+            } else {
+                option.name.push(NamePart {
+                    name_part: self.parse_ident()?,
+                    is_extension: false,
+                });
+            }
+
+            if !self.try_consume(Token::Dot)? {
+                break;
+            }
+        }
+        self.expect(Token::Equals)?;
+
+        let negative = self.try_consume(Token::Minus)?;
+        match self.peek() {
+            Some(Token::Ident(value)) => {
+                if !negative {
+                    option.identifier_value = Some(value.to_string());
+                } else if *value == "inf" {
+                    option.double_value = Some(f64::NEG_INFINITY);
+                } else if *value == "nan" {
+                    option.double_value = Some(f64::NAN);
+                } else {
+                    return self.unexpected_token("'inf' or 'nan'");
+                }
+                self.bump()?;
+            }
+            Some(Token::IntLiteral(int)) => {
+                let max = if negative {
+                    i64::MAX as u64 + 1
+                } else {
+                    u64::MAX
+                };
+                match int.to_u64().filter(|&value| value <= max) {
+                    Some(value) if negative => {
+                        option.negative_int_value = Some((value as i64).wrapping_neg());
+                    }
+                    Some(value) => option.positive_int_value = Some(value),
+                    None => {
+                        let value = self.parse_number()?;
+                        option.double_value = Some(if negative { -value } else { value });
+                        return self.finish_option(option, options, location, style);
+                    }
+                }
+                self.bump()?;
+            }
+            Some(Token::FloatLiteral(value)) => {
+                option.double_value = Some(if negative { -value } else { *value });
+                self.bump()?;
+            }
+            Some(Token::StringLiteral(_)) if !negative => {
+                option.string_value = Some(self.parse_string("a string")?.0);
+            }
+            Some(Token::LeftBrace) if !negative => {
+                option.aggregate_value = Some(self.parse_aggregate()?);
+            }
+            _ => return self.unexpected_token("an option value"),
+        }
+
+        self.finish_option(option, options, location, style)
+    }
+
+    fn finish_option(
+        &mut self,
+        option: UninterpretedOption,
+        options: &mut Vec<UninterpretedOption>,
+        location: usize,
+        style: OptionStyle,
+    ) -> Result<(), DescriptorErrorKind> {
+        options.push(option);
+        if style == OptionStyle::Statement {
+            self.consume_end_of_declaration(Token::Semicolon, Some(location))?;
+        }
+        self.end_location(location);
+        Ok(())
+    }
+
+    /// Parses the value of an option in the text format, which is stored as the source text of
+    /// its tokens separated by spaces, excluding the enclosing braces.
+    fn parse_aggregate(&mut self) -> Result<String, DescriptorErrorKind> {
+        self.expect(Token::LeftBrace)?;
+        let mut value = String::new();
+        let mut depth = 1;
+        loop {
+            match self.peek() {
+                None => return self.unexpected_token("'}'"),
+                Some(Token::LeftBrace) => depth += 1,
+                Some(Token::RightBrace) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.bump()?;
+                        return Ok(value);
+                    }
+                }
+                _ => (),
+            }
+
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(self.current_text());
+            self.bump()?;
+        }
+    }
+
+    /// Parses a scalar type, returning its type, or a message or enum type, returning its name.
+    fn parse_type(&mut self) -> Result<(Option<Type>, String), DescriptorErrorKind> {
+        let ty = match self.peek() {
+            Some(Token::Ident("double")) => Type::Double,
+            Some(Token::Ident("float")) => Type::Float,
+            Some(Token::Ident("int64")) => Type::Int64,
+            Some(Token::Ident("uint64")) => Type::Uint64,
+            Some(Token::Ident("int32")) => Type::Int32,
+            Some(Token::Ident("fixed64")) => Type::Fixed64,
+            Some(Token::Ident("fixed32")) => Type::Fixed32,
+            Some(Token::Ident("bool")) => Type::Bool,
+            Some(Token::Ident("string")) => Type::String,
+            Some(Token::Ident("group")) => {
+                if self.syntax == Syntax::Editions {
+                    return Err(DescriptorErrorKind::GroupSyntaxNotSupported {
+                        found: self.current_label(),
+                    });
+                }
+                Type::Group
+            }
+            Some(Token::Ident("bytes")) => Type::Bytes,
+            Some(Token::Ident("uint32")) => Type::Uint32,
+            Some(Token::Ident("sfixed32")) => Type::Sfixed32,
+            Some(Token::Ident("sfixed64")) => Type::Sfixed64,
+            Some(Token::Ident("sint32")) => Type::Sint32,
+            Some(Token::Ident("sint64")) => Type::Sint64,
+            _ => return Ok((None, self.parse_type_name()?)),
+        };
+
+        self.bump()?;
+        Ok((Some(ty), String::new()))
+    }
+
+    /// Parses a possibly fully-qualified reference to a message or enum type.
+    fn parse_type_name(&mut self) -> Result<String, DescriptorErrorKind> {
+        let mut name = String::new();
+        if self.try_consume(Token::Dot)? {
+            name.push('.');
+        }
+        name.push_str(&self.parse_ident()?);
+        while self.try_consume(Token::Dot)? {
+            name.push('.');
+            name.push_str(&self.parse_ident()?);
+        }
+        Ok(name)
+    }
+
+    /// Parses an identifier, recording its location at the given path.
+    fn parse_name(&mut self, path: Vec<i32>) -> Result<String, DescriptorErrorKind> {
+        let location = self.add_location(path);
+        let name = self.parse_ident()?;
+        self.end_location(location);
+        Ok(name)
+    }
+
+    fn parse_ident(&mut self) -> Result<String, DescriptorErrorKind> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.to_string();
+                self.bump()?;
+                Ok(ident)
+            }
+            _ => self.unexpected_token("an identifier"),
+        }
+    }
+
+    /// Parses one or more adjacent string literals, which are concatenated.
+    fn parse_string(
+        &mut self,
+        expected: &str,
+    ) -> Result<(Vec<u8>, SpannedToken<'a>), DescriptorErrorKind> {
+        let mut token = match self.current.clone() {
+            Some(
+                token @ SpannedToken {
+                    token: Token::StringLiteral(_),
+                    ..
+                },
+            ) => token,
+            _ => return self.unexpected_token(expected),
+        };
+
+        let mut value = Vec::new();
+        while let Some(Token::StringLiteral(string)) = self.peek() {
+            value.extend_from_slice(string);
+            let next = self.bump()?;
+            token.span.end = next.span.end;
+            token.end = next.end;
+        }
+        Ok((value, token))
+    }
+
+    fn parse_int(&mut self, max: u64) -> Result<u64, DescriptorErrorKind> {
+        match self.peek() {
+            Some(Token::IntLiteral(int)) => match int.to_u64() {
+                Some(value) if value <= max => {
+                    self.bump()?;
+                    Ok(value)
+                }
+                _ => Err(DescriptorErrorKind::IntegerOutOfRange {
+                    value: self.current_text().to_owned(),
+                    found: self.current_label(),
+                }),
+            },
+            _ => self.unexpected_token("an integer"),
+        }
+    }
+
+    fn parse_signed_int(&mut self, max: u64) -> Result<i64, DescriptorErrorKind> {
+        if self.try_consume(Token::Minus)? {
+            Ok((self.parse_int(max + 1)? as i64).wrapping_neg())
+        } else {
+            Ok(self.parse_int(max)? as i64)
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<f64, DescriptorErrorKind> {
+        let value = match self.peek() {
+            Some(Token::FloatLiteral(value)) => *value,
+            Some(Token::IntLiteral(int)) => match int.to_u64() {
+                Some(value) => value as f64,
+                None if int.radix == 10 => int.value.parse().expect("invalid decimal integer"),
+                None => u128::from_str_radix(int.value, int.radix)
+                    .map(|value| value as f64)
+                    .unwrap_or(f64::INFINITY),
+            },
+            Some(Token::Ident("inf")) => f64::INFINITY,
+            Some(Token::Ident("nan")) => f64::NAN,
+            _ => return self.unexpected_token("a number"),
+        };
+        self.bump()?;
+        Ok(value)
+    }
+
+    fn utf8_string(
+        &self,
+        value: Vec<u8>,
+        token: &SpannedToken,
+    ) -> Result<String, DescriptorErrorKind> {
+        String::from_utf8(value).map_err(|_| DescriptorErrorKind::InvalidStringCharacters {
+            found: self.label(token.start, token.end),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.current.as_ref().map(|token| &token.token)
+    }
+
+    fn at(&self, token: &Token) -> bool {
+        self.peek() == Some(token)
+    }
+
+    fn at_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(value)) if *value == ident)
+    }
+
+    fn try_consume(&mut self, token: Token) -> Result<bool, DescriptorErrorKind> {
+        if self.at(&token) {
+            self.bump()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn try_consume_ident(&mut self, ident: &str) -> Result<bool, DescriptorErrorKind> {
+        if self.at_ident(ident) {
+            self.bump()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<SpannedToken<'a>, DescriptorErrorKind> {
+        if self.at(&token) {
+            self.bump()
+        } else {
+            self.unexpected_token(format!("'{}'", symbol(&token)))
+        }
+    }
+
+    fn expect_ident(&mut self, ident: &str) -> Result<(), DescriptorErrorKind> {
+        if self.try_consume_ident(ident)? {
+            Ok(())
+        } else {
+            self.unexpected_token(format!("'{ident}'"))
+        }
+    }
+
+    /// Consumes a token ending a declaration, and attaches any comments to the given location.
+    fn consume_end_of_declaration(
+        &mut self,
+        token: Token,
+        location: Option<usize>,
+    ) -> Result<(), DescriptorErrorKind> {
+        let expected = format!("'{}'", symbol(&token));
+        if self.try_consume_end_of_declaration(token, location)? {
+            Ok(())
+        } else {
+            self.unexpected_token(expected)
+        }
+    }
+
+    fn try_consume_end_of_declaration(
+        &mut self,
+        token: Token,
+        location: Option<usize>,
+    ) -> Result<bool, DescriptorErrorKind> {
+        if !self.at(&token) {
+            return Ok(false);
+        }
+
+        let previous = self.current.take().expect("current token should be set");
+        self.previous_end = previous.end;
+        let mut comments = self.next_with_comments(Some(previous.start.0))?;
+
+        // The leading comments collected now belong to the next declaration, and the leading
+        // comments collected previously belong to this one.
+        mem::swap(&mut comments.leading, &mut self.upcoming_doc_comments);
+        match location {
+            Some(location) => {
+                mem::swap(&mut comments.detached, &mut self.upcoming_detached_comments);
+                self.attach_comments(location, comments);
+            }
+            None if token == Token::RightBrace => {
+                self.upcoming_detached_comments = comments.detached;
+            }
+            None => self.upcoming_detached_comments.extend(comments.detached),
+        }
+        Ok(true)
+    }
+
+    /// Consumes the current token, discarding any comments before the next token.
+    fn bump(&mut self) -> Result<SpannedToken<'a>, DescriptorErrorKind> {
+        let token = self.current.take().expect("current token should be set");
+        self.previous_end = token.end;
+        loop {
+            match self.next_raw()? {
+                Some(next) if next.token.is_comment() || next.token == Token::Newline => continue,
+                next => {
+                    self.current = next;
+                    return Ok(token);
+                }
+            }
+        }
+    }
+
+    /// Advances to the next token, collecting the comments before it. `previous_line` is the
+    /// line of the previous token, or `None` at the start of the file.
+    fn next_with_comments(
+        &mut self,
+        previous_line: Option<i32>,
+    ) -> Result<Comments, DescriptorErrorKind> {
+        let mut collector = CommentCollector::new(previous_line.is_some());
+        let mut trailing_comment_end_line = None;
+        let mut next = self.next_raw()?;
+
+        if let Some(previous_line) = previous_line {
+            // A comment starting on the same line as the previous token may be a trailing comment.
+            match next {
+                Some(SpannedToken {
+                    token: Token::LineComment(text),
+                    ..
+                }) => {
+                    trailing_comment_end_line = Some(previous_line);
+                    collector.line_comment(text);
+                    collector.flush();
+                    next = self.next_raw()?;
+                }
+                Some(SpannedToken {
+                    token: Token::BlockComment(text),
+                    end,
+                    ..
+                }) => {
+                    collector.block_comment(block_comment_text(text));
+                    trailing_comment_end_line = Some(end.0);
+                    next = self.next_raw()?;
+                    if matches!(
+                        next,
+                        Some(SpannedToken {
+                            token: Token::Newline,
+                            ..
+                        })
+                    ) {
+                        collector.flush();
+                        next = self.next_raw()?;
+                    }
+                }
+                Some(SpannedToken {
+                    token: Token::Newline,
+                    ..
+                }) => next = self.next_raw()?,
+                _ => {
+                    self.current = next;
+                    return Ok(Comments::default());
+                }
+            }
+        }
+
+        loop {
+            match next {
+                Some(SpannedToken {
+                    token: Token::LineComment(text),
+                    ..
+                }) => {
+                    collector.line_comment(text);
+                    next = self.next_raw()?;
+                }
+                Some(SpannedToken {
+                    token: Token::BlockComment(text),
+                    ..
+                }) => {
+                    collector.block_comment(block_comment_text(text));
+                    next = self.next_raw()?;
+                    if matches!(
+                        next,
+                        Some(SpannedToken {
+                            token: Token::Newline,
+                            ..
+                        })
+                    ) {
+                        next = self.next_raw()?;
+                    }
+                }
+                Some(SpannedToken {
+                    token: Token::Newline,
+                    ..
+                }) => {
+                    // A blank line detaches any comments from both the previous and next tokens.
+                    collector.flush();
+                    collector.can_attach_to_previous = false;
+                    next = self.next_raw()?;
+                }
+                _ => {
+                    if matches!(
+                        next,
+                        None | Some(SpannedToken {
+                            token: Token::RightBrace | Token::RightBracket | Token::RightParen,
+                            ..
+                        })
+                    ) {
+                        // Comments at the end of a scope cannot be leading comments.
+                        collector.flush();
+                    }
+                    if let Some(next) = &next {
+                        let line = next.start.0;
+                        if previous_line.unwrap_or(0) == line
+                            || trailing_comment_end_line == Some(line)
+                        {
+                            collector.maybe_detach();
+                        }
+                    }
+
+                    self.current = next;
+                    return Ok(collector.finish());
+                }
+            }
+        }
+    }
+
+    /// Lexes the next token, including comments and newlines.
+    fn next_raw(&mut self) -> Result<Option<SpannedToken<'a>>, DescriptorErrorKind> {
+        match self.lexer.next() {
+            Some(Ok(token)) => {
+                let span = self.lexer.span();
+                let start = self.advance_to(span.start);
+                let end = self.advance_to(span.end);
+                Ok(Some(SpannedToken {
+                    token,
+                    span,
+                    start,
+                    end,
+                }))
+            }
+            Some(Err(())) => {
+                let (error, span) = self
+                    .lexer
+                    .extras
+                    .error
+                    .take()
+                    .unwrap_or_else(|| (TokenError::InvalidToken, self.lexer.span()));
+                let start = self.advance_to(span.start);
+                let end = self.advance_to(span.end);
+                let found = self.label(start, end);
+                Err(match error {
+                    TokenError::InvalidToken => DescriptorErrorKind::InvalidToken { found },
+                    TokenError::InvalidStringCharacters => {
+                        DescriptorErrorKind::InvalidStringCharacters { found }
+                    }
+                    TokenError::InvalidStringEscape => {
+                        DescriptorErrorKind::InvalidStringEscape { found }
+                    }
+                    TokenError::UnterminatedString => {
+                        DescriptorErrorKind::UnterminatedString { found }
+                    }
+                    TokenError::UnterminatedBlockComment => {
+                        DescriptorErrorKind::UnterminatedBlockComment { found }
+                    }
+                })
+            }
+            None => {
+                self.advance_to(self.source.len());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Advances the current line and column to the given offset. Columns are measured in bytes,
+    /// with tabs advancing to the next multiple of 8.
+    fn advance_to(&mut self, offset: usize) -> (i32, i32) {
+        for &byte in &self.source.as_bytes()[self.offset..offset] {
+            match byte {
+                b'\n' => {
+                    self.line += 1;
+                    self.column = 0;
+                }
+                b'\t' => self.column += 8 - self.column % 8,
+                _ => self.column += 1,
+            }
+        }
+        self.offset = offset;
+        (self.line, self.column)
+    }
+
+    fn current_start(&self) -> (i32, i32) {
+        match &self.current {
+            Some(token) => token.start,
+            None => (self.line, self.column),
+        }
+    }
+
+    fn current_text(&self) -> &'a str {
+        match &self.current {
+            Some(token) => &self.source[token.span.clone()],
+            None => "",
+        }
+    }
+
+    fn current_label(&self) -> Label {
+        match &self.current {
+            Some(token) => self.label(token.start, token.end),
+            // Point to the last character of the file, since an empty span at the end of the
+            // file cannot be displayed.
+            None => {
+                let (line, column) = self.previous_end;
+                self.label((line, column.saturating_sub(1)), self.previous_end)
+            }
+        }
+    }
+
+    fn label(&self, start: (i32, i32), end: (i32, i32)) -> Label {
+        Label::from_span(
+            self.name,
+            "found here",
+            Box::default(),
+            [start.0, start.1, end.0, end.1],
+        )
+    }
+
+    fn unexpected_token<T>(&self, expected: impl ToString) -> Result<T, DescriptorErrorKind> {
+        match &self.current {
+            Some(token) => Err(DescriptorErrorKind::UnexpectedToken {
+                expected: expected.to_string(),
+                token: self.source[token.span.clone()].to_owned(),
+                found: self.label(token.start, token.end),
+            }),
+            None => Err(DescriptorErrorKind::UnexpectedEof {
+                expected: expected.to_string(),
+                found: self.current_label(),
+            }),
+        }
+    }
+
+    /// Adds a location starting at the current token. Its end is set by `end_location`.
+    fn add_location(&mut self, path: Vec<i32>) -> usize {
+        let start = self.current_start();
+        self.add_location_start(path, start)
+    }
+
+    fn add_location_start(&mut self, path: Vec<i32>, start: (i32, i32)) -> usize {
+        self.locations.push(Location {
+            path,
+            span: vec![start.0, start.1],
+            ..Default::default()
+        });
+        self.locations.len() - 1
+    }
+
+    fn add_location_span(&mut self, path: Vec<i32>, start: (i32, i32), end: (i32, i32)) {
+        let index = self.add_location_start(path, start);
+        set_location_end(&mut self.locations[index], end);
+    }
+
+    /// Sets the end of a location to the end of the previous token.
+    fn end_location(&mut self, index: usize) {
+        let end = self.previous_end;
+        set_location_end(&mut self.locations[index], end);
+    }
+
+    fn attach_comments(&mut self, index: usize, comments: Comments) {
+        let location = &mut self.locations[index];
+        if !comments.leading.is_empty() {
+            location.leading_comments = Some(comments.leading);
+        }
+        if !comments.trailing.is_empty() {
+            location.trailing_comments = Some(comments.trailing);
+        }
+        location.leading_detached_comments = comments.detached;
+    }
+}
+
+impl CommentCollector {
+    fn new(can_attach_to_previous: bool) -> Self {
+        CommentCollector {
+            comments: Comments::default(),
+            buffer: None,
+            is_line_comment: false,
+            can_attach_to_previous,
+            has_trailing_comment: false,
+            num_comments: 0,
+        }
+    }
+
+    fn line_comment(&mut self, text: &str) {
+        // Consecutive line comments are merged into a single comment.
+        if self.buffer.is_some() && !self.is_line_comment {
+            self.flush();
+        }
+        self.buffer.get_or_insert_with(String::new).push_str(text);
+        self.is_line_comment = true;
+    }
+
+    fn block_comment(&mut self, text: String) {
+        self.flush();
+        self.buffer = Some(text);
+        self.is_line_comment = false;
+    }
+
+    /// Completes the current comment, as a trailing comment of the previous token if possible,
+    /// or otherwise as a detached comment.
+    fn flush(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            if self.can_attach_to_previous {
+                self.comments.trailing.push_str(&buffer);
+                self.has_trailing_comment = true;
+                self.can_attach_to_previous = false;
+            } else {
+                self.comments.detached.push(buffer);
+            }
+            self.num_comments += 1;
+        }
+    }
+
+    /// Ensures a lone comment between two tokens on the same line is detached, since it is
+    /// ambiguous which token it belongs to.
+    fn maybe_detach(&mut self) {
+        let count = self.num_comments + usize::from(self.buffer.is_some());
+        if count == 1 {
+            if self.has_trailing_comment {
+                let trailing = mem::take(&mut self.comments.trailing);
+                self.comments.detached.insert(0, trailing);
+            }
+            self.can_attach_to_previous = false;
+            self.flush();
+        }
+    }
+
+    fn finish(self) -> Comments {
+        Comments {
+            leading: self.buffer.unwrap_or_default(),
+            ..self.comments
+        }
+    }
+}
+
+fn join(path: &[i32], rest: &[i32]) -> Vec<i32> {
+    let mut result = Vec::with_capacity(path.len() + rest.len());
+    result.extend_from_slice(path);
+    result.extend_from_slice(rest);
+    result
+}
+
+fn set_location_end(location: &mut Location, (line, column): (i32, i32)) {
+    if location.span[0] != line {
+        location.span.push(line);
+    }
+    location.span.push(column);
+}
+
+fn symbol(token: &Token) -> &'static str {
+    match token {
+        Token::Dot => ".",
+        Token::Minus => "-",
+        Token::Plus => "+",
+        Token::Equals => "=",
+        Token::LeftBrace => "{",
+        Token::RightBrace => "}",
+        Token::LeftBracket => "[",
+        Token::RightBracket => "]",
+        Token::LeftParen => "(",
+        Token::RightParen => ")",
+        Token::LeftAngleBracket => "<",
+        Token::RightAngleBracket => ">",
+        Token::Comma => ",",
+        Token::Colon => ":",
+        Token::Semicolon => ";",
+        Token::ForwardSlash => "/",
+        _ => unreachable!("expected a symbol token"),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Adds a oneof for each proto3 optional field in the message, to track its presence.
+fn generate_synthetic_oneofs(message: &mut DescriptorProto) {
+    let mut names: HashSet<String> = message
+        .field
+        .iter()
+        .map(|field| field.name().to_owned())
+        .chain(
+            message
+                .oneof_decl
+                .iter()
+                .map(|oneof| oneof.name().to_owned()),
+        )
+        .collect();
+
+    for field in &mut message.field {
+        if field.proto3_optional() {
+            let mut name = field.name().to_owned();
+            if !name.starts_with('_') {
+                name.insert(0, '_');
+            }
+            while names.contains(&name) {
+                name.insert(0, 'X');
+            }
+            names.insert(name.clone());
+
+            field.oneof_index = Some(message.oneof_decl.len() as i32);
+            message.oneof_decl.push(OneofDescriptorProto {
+                name: Some(name),
+                options: None,
+            });
+        }
+    }
+}
+
+/// Generates the entry message for a map field, and sets the type of the field to it.
+/// Returns `true` if the given type may be used as the key of a map field. Message and enum types
+/// (which have no parsed [`Type`]) are not allowed.
+fn is_valid_map_key_type(ty: Option<Type>) -> bool {
+    !matches!(
+        ty,
+        None | Some(
+            Type::Double | Type::Float | Type::Bytes | Type::Group | Type::Message | Type::Enum
+        )
+    )
+}
+
+fn generate_map_entry(field: &mut FieldDescriptorProto, map_type: MapType) -> DescriptorProto {
+    let mut entry_name = String::with_capacity(field.name().len() + 5);
+    let mut capitalize_next = true;
+    for ch in field.name().chars() {
+        if ch == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            entry_name.push(ch.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            entry_name.push(ch);
+        }
+    }
+    entry_name.push_str("Entry");
+    field.type_name = Some(entry_name.clone());
+
+    let entry_field = |name: &str, number: i32, (ty, type_name): (Option<Type>, String)| {
+        let mut entry_field = FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            ..Default::default()
+        };
+        entry_field.set_label(FieldLabel::Optional);
+        match ty {
+            Some(ty) => entry_field.set_type(ty),
+            None => entry_field.type_name = Some(type_name),
+        }
+
+        // The 'enforce_utf8' option is propagated to string keys and values.
+        if ty == Some(Type::String) {
+            let options: Vec<_> = field
+                .options
+                .iter()
+                .flat_map(|options| &options.uninterpreted_option)
+                .filter(|option| {
+                    option.name.len() == 1
+                        && option.name[0].name_part == "enforce_utf8"
+                        && !option.name[0].is_extension
+                })
+                .cloned()
+                .collect();
+            if !options.is_empty() {
+                entry_field.options = Some(FieldOptions {
+                    uninterpreted_option: options,
+                    ..Default::default()
+                });
+            }
+        }
+        entry_field
+    };
+
+    DescriptorProto {
+        name: Some(entry_name),
+        field: vec![
+            entry_field("key", 1, map_type.key),
+            entry_field("value", 2, map_type.value),
+        ],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Formats a floating point number using the shortest of 15 or 17 significant digits which
+/// round-trips, matching the format `protoc` uses for default values.
+fn simple_dtoa(value: f64) -> String {
+    if value.is_nan() {
+        return "nan".to_owned();
+    } else if value.is_infinite() {
+        return if value > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+
+    let result = format_general(value, 15);
+    if result.parse::<f64>() == Ok(value) {
+        result
+    } else {
+        format_general(value, 17)
+    }
+}
+
+/// Formats a number like the `%.{precision}g` format specifier in C.
+fn format_general(value: f64, precision: usize) -> String {
+    fn trim_zeros(mut value: String) -> String {
+        if value.contains('.') {
+            value.truncate(value.trim_end_matches('0').trim_end_matches('.').len());
+        }
+        value
+    }
+
+    if value == 0.0 {
+        return if value.is_sign_negative() { "-0" } else { "0" }.to_owned();
+    }
+
+    let scientific = format!("{:.*e}", precision - 1, value);
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("expected scientific notation");
+    let exponent: i32 = exponent.parse().expect("invalid exponent");
+
+    if exponent < -4 || exponent >= precision as i32 {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!(
+            "{}e{}{:02}",
+            trim_zeros(mantissa.to_owned()),
+            sign,
+            exponent.abs()
+        )
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        trim_zeros(format!("{value:.decimals$}"))
+    }
+}
+
+/// Escapes bytes using C-style escape sequences, matching the format `protoc` uses for default
+/// values.
+fn c_escape(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'\n' => result.push_str("\\n"),
+            b'\r' => result.push_str("\\r"),
+            b'\t' => result.push_str("\\t"),
+            b'\"' => result.push_str("\\\""),
+            b'\'' => result.push_str("\\\'"),
+            b'\\' => result.push_str("\\\\"),
+            0x20..=0x7e => result.push(byte as char),
+            _ => result.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_default_values() {
+        assert_eq!(simple_dtoa(1.5), "1.5");
+        assert_eq!(simple_dtoa(1e20), "1e+20");
+        assert_eq!(simple_dtoa(0.1), "0.1");
+        assert_eq!(simple_dtoa(0.0001), "0.0001");
+        assert_eq!(simple_dtoa(0.00001), "1e-05");
+        assert_eq!(simple_dtoa(123456789012345680.0), "1.2345678901234568e+17");
+        assert_eq!(simple_dtoa(f64::INFINITY), "inf");
+        assert_eq!(simple_dtoa(f64::NAN), "nan");
+
+        assert_eq!(c_escape(b"a\"b\n\x01\xff'"), "a\\\"b\\n\\001\\377\\'");
+    }
+}
@@ -0,0 +1,814 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
+
+use prost_types::{field_descriptor_proto, source_code_info::Location};
+
+use crate::{
+    descriptor::{api::decode_options, build::to_json_name, tag},
+    dynamic::fmt_string,
+    DynamicMessage, Edition, EnumDescriptor, ExtensionDescriptor, FieldDescriptor, FileDescriptor,
+    Kind, MessageDescriptor, OneofDescriptor, ServiceDescriptor, Syntax, Value,
+};
+
+const INDENT: &str = "  ";
+
+impl FileDescriptor {
+    /// Renders this file as `.proto` source code.
+    ///
+    /// The output includes all definitions in the file along with their options, including any
+    /// custom options. If the file descriptor includes source code info, comments are also
+    /// included. Compiling the output with `protoc` produces an equivalent file descriptor,
+    /// although the order of some definitions and the source locations may differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let file = pool.get_message_by_name("package.MyMessage").unwrap().parent_file();
+    /// let source = file.to_proto_source();
+    /// assert!(source.starts_with("syntax = \"proto3\";\n\npackage package;\n"));
+    /// assert!(source.contains("message MyMessage {\n  int32 foo = 1;\n"));
+    /// ```
+    pub fn to_proto_source(&self) -> String {
+        let mut printer = Printer::new(self);
+        printer
+            .print_file(self)
+            .expect("writing to a string should not fail");
+        printer.output
+    }
+}
+
+/// Writes definitions in the `.proto` format.
+struct Printer<'a> {
+    output: String,
+    indent: usize,
+    syntax: Syntax,
+    locations: HashMap<&'a [i32], &'a Location>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(file: &'a FileDescriptor) -> Self {
+        let locations = file
+            .file_descriptor_proto()
+            .source_code_info
+            .iter()
+            .flat_map(|info| &info.location)
+            .map(|location| (location.path.as_slice(), location))
+            .collect();
+
+        Printer {
+            output: String::new(),
+            indent: 0,
+            syntax: file.syntax(),
+            locations,
+        }
+    }
+
+    fn print_file(&mut self, file: &FileDescriptor) -> fmt::Result {
+        let raw = file.file_descriptor_proto();
+
+        self.leading_comments(&[tag::file::SYNTAX])?;
+        match self.syntax {
+            Syntax::Proto2 => self.output.push_str("syntax = \"proto2\";"),
+            Syntax::Proto3 => self.output.push_str("syntax = \"proto3\";"),
+            Syntax::Editions(Edition::Edition2023) => self.output.push_str("edition = \"2023\";"),
+            Syntax::Editions(Edition::Edition2024) => self.output.push_str("edition = \"2024\";"),
+        }
+        self.trailing_comments(&[tag::file::SYNTAX])?;
+
+        if !file.package_name().is_empty() {
+            self.output.push('\n');
+            self.leading_comments(&[tag::file::PACKAGE])?;
+            write!(self.output, "package {};", file.package_name())?;
+            self.trailing_comments(&[tag::file::PACKAGE])?;
+        }
+
+        if !raw.dependency.is_empty() {
+            self.output.push('\n');
+        }
+        for (index, dependency) in raw.dependency.iter().enumerate() {
+            let path = [tag::file::DEPENDENCY, index as i32];
+            self.leading_comments(&path)?;
+            self.output.push_str("import ");
+            if raw.public_dependency.contains(&(index as i32)) {
+                self.output.push_str("public ");
+            } else if raw.weak_dependency.contains(&(index as i32)) {
+                self.output.push_str("weak ");
+            }
+            fmt_string(&mut self.output, dependency.as_bytes())?;
+            self.output.push(';');
+            self.trailing_comments(&path)?;
+        }
+
+        let options = file.options();
+        if has_options(&options) {
+            self.output.push('\n');
+            self.print_option_statements(&options, &[tag::file::OPTIONS])?;
+        }
+
+        for enum_ in file.enums() {
+            self.output.push('\n');
+            self.print_enum(&enum_)?;
+        }
+        for message in file.messages() {
+            if !is_group_message(&message) {
+                self.output.push('\n');
+                self.print_message(&message)?;
+            }
+        }
+        for service in file.services() {
+            self.output.push('\n');
+            self.print_service(&service)?;
+        }
+        let extensions: Vec<_> = file.extensions().collect();
+        if !extensions.is_empty() {
+            self.output.push('\n');
+            self.print_extensions(&extensions, &[tag::file::EXTENSION])?;
+        }
+
+        Ok(())
+    }
+
+    fn print_message(&mut self, message: &MessageDescriptor) -> fmt::Result {
+        self.leading_comments(message.path())?;
+        self.pad();
+        write!(self.output, "message {} {{", message.name())?;
+        self.trailing_comments(message.path())?;
+        self.print_message_body(message)?;
+        self.pad();
+        self.output.push_str("}\n");
+        Ok(())
+    }
+
+    fn print_message_body(&mut self, message: &MessageDescriptor) -> fmt::Result {
+        self.indent += 1;
+
+        let options = message.options();
+        self.print_option_statements(&options, &join(message.path(), tag::message::OPTIONS))?;
+
+        for nested in message.child_messages() {
+            if !nested.is_map_entry() && !is_group_message(&nested) {
+                self.print_message(&nested)?;
+            }
+        }
+        for enum_ in message.child_enums() {
+            self.print_enum(&enum_)?;
+        }
+
+        let mut printed_oneofs = Vec::new();
+        for field in message.fields_in_index_order() {
+            match field.containing_oneof() {
+                Some(oneof) if !oneof.is_synthetic() => {
+                    if !printed_oneofs.contains(&oneof) {
+                        printed_oneofs.push(oneof.clone());
+                        self.print_oneof(&oneof)?;
+                    }
+                }
+                _ => self.print_field(&field)?,
+            }
+        }
+
+        let raw = message.raw();
+        for (index, range) in raw.extension_range.iter().enumerate() {
+            let path = join(
+                &join(message.path(), tag::message::EXTENSION_RANGE),
+                index as i32,
+            );
+            self.leading_comments(&path)?;
+            self.pad();
+            self.output.push_str("extensions ");
+            self.print_range(range.start(), range.end() - 1)?;
+
+            let options = decode_options(
+                message.parent_pool(),
+                "google.protobuf.ExtensionRangeOptions",
+                &range.options,
+            );
+            self.print_option_list(&options, &[])?;
+            self.output.push(';');
+            self.trailing_comments(&path)?;
+        }
+
+        let extensions: Vec<_> = message.child_extensions().collect();
+        self.print_extensions(&extensions, &join(message.path(), tag::message::EXTENSION))?;
+
+        let ranges = &message.descriptor_proto().reserved_range;
+        if !ranges.is_empty() {
+            let path = join(message.path(), tag::message::RESERVED_RANGE);
+            self.leading_comments(&path)?;
+            self.pad();
+            self.output.push_str("reserved ");
+            for (index, range) in ranges.iter().enumerate() {
+                if index != 0 {
+                    self.output.push_str(", ");
+                }
+                self.print_range(range.start(), range.end() - 1)?;
+            }
+            self.output.push(';');
+            self.trailing_comments(&path)?;
+        }
+        let names: Vec<_> = message.reserved_names().collect();
+        self.print_reserved_names(&names, &join(message.path(), tag::message::RESERVED_NAME))?;
+
+        self.indent -= 1;
+        Ok(())
+    }
+
+    fn print_oneof(&mut self, oneof: &OneofDescriptor) -> fmt::Result {
+        self.leading_comments(oneof.path())?;
+        self.pad();
+        write!(self.output, "oneof {} {{", oneof.name())?;
+        self.trailing_comments(oneof.path())?;
+
+        self.indent += 1;
+        self.print_option_statements(&oneof.options(), &join(oneof.path(), tag::oneof::OPTIONS))?;
+        for field in oneof.fields() {
+            self.print_field(&field)?;
+        }
+        self.indent -= 1;
+
+        self.pad();
+        self.output.push_str("}\n");
+        Ok(())
+    }
+
+    fn print_field(&mut self, field: &FieldDescriptor) -> fmt::Result {
+        let raw = field.field_descriptor_proto();
+        let is_oneof_field = field
+            .containing_oneof()
+            .is_some_and(|oneof| !oneof.is_synthetic());
+
+        self.leading_comments(field.path())?;
+        self.pad();
+        if !is_oneof_field && !field.is_map() {
+            self.print_label(raw)?;
+        }
+
+        let group = self.group_message(field.kind(), raw, field.parent_message().child_messages());
+        if let Some(group) = &group {
+            write!(self.output, "group {}", group.name())?;
+        } else if field.is_map() {
+            let entry = field.kind();
+            let entry = entry.as_message().expect("map entry should be a message");
+            self.output.push_str("map<");
+            self.print_type(&entry.map_entry_key_field().kind())?;
+            self.output.push_str(", ");
+            self.print_type(&entry.map_entry_value_field().kind())?;
+            write!(self.output, "> {}", field.name())?;
+        } else {
+            self.print_type(&field.kind())?;
+            write!(self.output, " {}", field.name())?;
+        }
+        write!(self.output, " = {}", field.number())?;
+
+        let mut options = Vec::new();
+        self.push_field_pseudo_options(raw, &field.kind(), &mut options)?;
+        if field.json_name() != to_json_name(field.name()) {
+            let mut json_name = String::from("json_name = ");
+            fmt_string(&mut json_name, field.json_name().as_bytes())?;
+            options.push(json_name);
+        }
+        self.print_option_list(&field.options(), &options)?;
+
+        self.finish_field(field.path(), group.as_ref())
+    }
+
+    fn print_extensions(
+        &mut self,
+        extensions: &[ExtensionDescriptor],
+        path: &[i32],
+    ) -> fmt::Result {
+        let mut index = 0;
+        while index < extensions.len() {
+            let extendee = extensions[index].containing_message();
+            let end = extensions[index..]
+                .iter()
+                .position(|extension| extension.containing_message() != extendee)
+                .map_or(extensions.len(), |len| index + len);
+
+            self.leading_comments(path)?;
+            self.pad();
+            write!(self.output, "extend .{} {{", extendee.full_name())?;
+            self.output.push('\n');
+            self.indent += 1;
+            for extension in &extensions[index..end] {
+                self.print_extension(extension)?;
+            }
+            self.indent -= 1;
+            self.pad();
+            self.output.push_str("}\n");
+
+            index = end;
+        }
+
+        Ok(())
+    }
+
+    fn print_extension(&mut self, extension: &ExtensionDescriptor) -> fmt::Result {
+        let raw = extension.field_descriptor_proto();
+
+        self.leading_comments(extension.path())?;
+        self.pad();
+        self.print_label(raw)?;
+
+        let scope: Vec<_> = match extension.parent_message() {
+            Some(parent) => parent.child_messages().collect(),
+            None => extension.parent_file().messages().collect(),
+        };
+        let group = self.group_message(extension.kind(), raw, scope.into_iter());
+        if let Some(group) = &group {
+            write!(self.output, "group {}", group.name())?;
+        } else {
+            self.print_type(&extension.kind())?;
+            write!(self.output, " {}", extension.name())?;
+        }
+        write!(self.output, " = {}", extension.number())?;
+
+        let mut options = Vec::new();
+        self.push_field_pseudo_options(raw, &extension.kind(), &mut options)?;
+        self.print_option_list(&extension.options(), &options)?;
+
+        self.finish_field(extension.path(), group.as_ref())
+    }
+
+    fn finish_field(&mut self, path: &[i32], group: Option<&MessageDescriptor>) -> fmt::Result {
+        match group {
+            Some(group) => {
+                self.output.push_str(" {");
+                self.trailing_comments(path)?;
+                self.print_message_body(group)?;
+                self.pad();
+                self.output.push_str("}\n");
+                Ok(())
+            }
+            None => {
+                self.output.push(';');
+                self.trailing_comments(path)
+            }
+        }
+    }
+
+    fn print_label(&mut self, raw: &prost_types::FieldDescriptorProto) -> fmt::Result {
+        let label = match raw.label() {
+            field_descriptor_proto::Label::Repeated => Some("repeated"),
+            field_descriptor_proto::Label::Required if self.syntax == Syntax::Proto2 => {
+                Some("required")
+            }
+            field_descriptor_proto::Label::Optional
+                if self.syntax == Syntax::Proto2 || raw.proto3_optional() =>
+            {
+                Some("optional")
+            }
+            _ => None,
+        };
+        if let Some(label) = label {
+            write!(self.output, "{label} ")?;
+        }
+        Ok(())
+    }
+
+    fn print_type(&mut self, kind: &Kind) -> fmt::Result {
+        let name = match kind {
+            Kind::Double => "double",
+            Kind::Float => "float",
+            Kind::Int32 => "int32",
+            Kind::Int64 => "int64",
+            Kind::Uint32 => "uint32",
+            Kind::Uint64 => "uint64",
+            Kind::Sint32 => "sint32",
+            Kind::Sint64 => "sint64",
+            Kind::Fixed32 => "fixed32",
+            Kind::Fixed64 => "fixed64",
+            Kind::Sfixed32 => "sfixed32",
+            Kind::Sfixed64 => "sfixed64",
+            Kind::Bool => "bool",
+            Kind::String => "string",
+            Kind::Bytes => "bytes",
+            Kind::Message(message) => return write!(self.output, ".{}", message.full_name()),
+            Kind::Enum(enum_) => return write!(self.output, ".{}", enum_.full_name()),
+        };
+        self.output.push_str(name);
+        Ok(())
+    }
+
+    /// Gets the message defined by a group field, if the field uses the group syntax.
+    fn group_message(
+        &self,
+        kind: Kind,
+        raw: &prost_types::FieldDescriptorProto,
+        mut scope: impl Iterator<Item = MessageDescriptor>,
+    ) -> Option<MessageDescriptor> {
+        if self.syntax != Syntax::Proto2 || raw.r#type() != field_descriptor_proto::Type::Group {
+            return None;
+        }
+
+        let message = kind.as_message()?.clone();
+        if message.name().to_ascii_lowercase() == raw.name() && scope.any(|m| m == message) {
+            Some(message)
+        } else {
+            None
+        }
+    }
+
+    /// Adds the `default` pseudo-option of a field, which is not stored in its options.
+    fn push_field_pseudo_options(
+        &self,
+        raw: &prost_types::FieldDescriptorProto,
+        kind: &Kind,
+        options: &mut Vec<String>,
+    ) -> fmt::Result {
+        if let Some(default) = &raw.default_value {
+            let mut option = String::from("default = ");
+            match kind {
+                Kind::String => fmt_string(&mut option, default.as_bytes())?,
+                // Bytes default values are already escaped.
+                Kind::Bytes => write!(option, "\"{default}\"")?,
+                _ => option.push_str(default),
+            }
+            options.push(option);
+        }
+        Ok(())
+    }
+
+    fn print_enum(&mut self, enum_: &EnumDescriptor) -> fmt::Result {
+        self.leading_comments(enum_.path())?;
+        self.pad();
+        write!(self.output, "enum {} {{", enum_.name())?;
+        self.trailing_comments(enum_.path())?;
+
+        self.indent += 1;
+        self.print_option_statements(&enum_.options(), &join(enum_.path(), tag::enum_::OPTIONS))?;
+        // Values are printed in declaration order, rather than sorted by number.
+        for value in &enum_.enum_descriptor_proto().value {
+            let value = enum_
+                .get_value_by_name(value.name())
+                .expect("enum value not found");
+            self.leading_comments(value.path())?;
+            self.pad();
+            write!(self.output, "{} = {}", value.name(), value.number())?;
+            self.print_option_list(&value.options(), &[])?;
+            self.output.push(';');
+            self.trailing_comments(value.path())?;
+        }
+
+        let ranges: Vec<_> = enum_.reserved_ranges().collect();
+        if !ranges.is_empty() {
+            let path = join(enum_.path(), tag::enum_::RESERVED_RANGE);
+            self.leading_comments(&path)?;
+            self.pad();
+            self.output.push_str("reserved ");
+            for (index, range) in ranges.into_iter().enumerate() {
+                if index != 0 {
+                    self.output.push_str(", ");
+                }
+                self.print_range(*range.start(), *range.end())?;
+            }
+            self.output.push(';');
+            self.trailing_comments(&path)?;
+        }
+        let names: Vec<_> = enum_.reserved_names().collect();
+        self.print_reserved_names(&names, &join(enum_.path(), tag::enum_::RESERVED_NAME))?;
+        self.indent -= 1;
+
+        self.pad();
+        self.output.push_str("}\n");
+        Ok(())
+    }
+
+    fn print_service(&mut self, service: &ServiceDescriptor) -> fmt::Result {
+        self.leading_comments(service.path())?;
+        self.pad();
+        write!(self.output, "service {} {{", service.name())?;
+        self.trailing_comments(service.path())?;
+
+        self.indent += 1;
+        self.print_option_statements(
+            &service.options(),
+            &join(service.path(), tag::service::OPTIONS),
+        )?;
+        for method in service.methods() {
+            self.leading_comments(method.path())?;
+            self.pad();
+            write!(self.output, "rpc {}(", method.name())?;
+            if method.is_client_streaming() {
+                self.output.push_str("stream ");
+            }
+            write!(self.output, ".{}) returns (", method.input().full_name())?;
+            if method.is_server_streaming() {
+                self.output.push_str("stream ");
+            }
+            write!(self.output, ".{})", method.output().full_name())?;
+
+            let options = method.options();
+            if has_options(&options) {
+                self.output.push_str(" {");
+                self.trailing_comments(method.path())?;
+                self.indent += 1;
+                self.print_option_statements(&options, &join(method.path(), tag::method::OPTIONS))?;
+                self.indent -= 1;
+                self.pad();
+                self.output.push_str("}\n");
+            } else if method.method_descriptor_proto().options.is_some() {
+                // An empty body still sets the options field.
+                self.output.push_str(" {}");
+                self.trailing_comments(method.path())?;
+            } else {
+                self.output.push(';');
+                self.trailing_comments(method.path())?;
+            }
+        }
+        self.indent -= 1;
+
+        self.pad();
+        self.output.push_str("}\n");
+        Ok(())
+    }
+
+    fn print_range(&mut self, start: i32, end: i32) -> fmt::Result {
+        if start == end {
+            write!(self.output, "{start}")
+        } else {
+            write!(self.output, "{start} to {end}")
+        }
+    }
+
+    fn print_reserved_names(&mut self, names: &[&str], path: &[i32]) -> fmt::Result {
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        self.leading_comments(path)?;
+        self.pad();
+        self.output.push_str("reserved ");
+        for (index, name) in names.iter().enumerate() {
+            if index != 0 {
+                self.output.push_str(", ");
+            }
+            // Reserved names are identifiers in editions, and strings otherwise.
+            if matches!(self.syntax, Syntax::Editions(_)) {
+                self.output.push_str(name);
+            } else {
+                fmt_string(&mut self.output, name.as_bytes())?;
+            }
+        }
+        self.output.push(';');
+        self.trailing_comments(path)
+    }
+
+    /// Prints each option set in the given options message as an `option` statement.
+    fn print_option_statements(&mut self, options: &DynamicMessage, path: &[i32]) -> fmt::Result {
+        for (number, option) in option_assignments(options)? {
+            let path = join(path, number);
+            self.leading_comments(&path)?;
+            self.pad();
+            write!(self.output, "option {option};")?;
+            self.trailing_comments(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Prints the options set in the given options message in the compact `[...]` syntax used
+    /// by fields and enum values.
+    fn print_option_list(&mut self, options: &DynamicMessage, extra: &[String]) -> fmt::Result {
+        let assignments = option_assignments(options)?;
+        if extra.is_empty() && assignments.is_empty() {
+            return Ok(());
+        }
+
+        self.output.push_str(" [");
+        let all = extra
+            .iter()
+            .map(String::as_str)
+            .chain(assignments.iter().map(|(_, option)| option.as_str()));
+        for (index, option) in all.enumerate() {
+            if index != 0 {
+                self.output.push_str(", ");
+            }
+            self.output.push_str(option);
+        }
+        self.output.push(']');
+        Ok(())
+    }
+
+    fn leading_comments(&mut self, path: &[i32]) -> fmt::Result {
+        let Some(location) = self.locations.get(path).copied() else {
+            return Ok(());
+        };
+
+        for comment in &location.leading_detached_comments {
+            self.print_comment(comment);
+            self.output.push('\n');
+        }
+        if let Some(comment) = &location.leading_comments {
+            self.print_comment(comment);
+        }
+        Ok(())
+    }
+
+    /// Finishes the current line, adding any trailing comments.
+    fn trailing_comments(&mut self, path: &[i32]) -> fmt::Result {
+        let comment = self
+            .locations
+            .get(path)
+            .and_then(|location| location.trailing_comments.as_deref());
+        match comment {
+            Some(comment) if !comment.trim_end_matches('\n').contains('\n') => {
+                writeln!(self.output, " //{}", comment.trim_end_matches('\n'))
+            }
+            Some(comment) => {
+                self.output.push('\n');
+                self.indent += 1;
+                self.print_comment(comment);
+                self.indent -= 1;
+                Ok(())
+            }
+            None => {
+                self.output.push('\n');
+                Ok(())
+            }
+        }
+    }
+
+    fn print_comment(&mut self, comment: &str) {
+        for line in comment.trim_end_matches('\n').split('\n') {
+            self.pad();
+            self.output.push_str("//");
+            self.output.push_str(line.trim_end());
+            self.output.push('\n');
+        }
+    }
+
+    fn pad(&mut self) {
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+    }
+}
+
+fn join(path: &[i32], tag: i32) -> Vec<i32> {
+    let mut result = path.to_vec();
+    result.push(tag);
+    result
+}
+
+fn is_group_message(message: &MessageDescriptor) -> bool {
+    if message.parent_file().syntax() != Syntax::Proto2 {
+        return false;
+    }
+
+    let is_group_field = |raw: &prost_types::FieldDescriptorProto, kind: Kind| {
+        raw.r#type() == field_descriptor_proto::Type::Group
+            && kind.as_message() == Some(message)
+            && raw.name() == message.name().to_ascii_lowercase()
+    };
+    let mut extensions = match message.parent_message() {
+        Some(parent) => {
+            if parent
+                .fields()
+                .any(|field| is_group_field(field.field_descriptor_proto(), field.kind()))
+            {
+                return true;
+            }
+            parent.child_extensions().collect::<Vec<_>>()
+        }
+        None => message.parent_file().extensions().collect(),
+    }
+    .into_iter();
+    extensions.any(|extension| is_group_field(extension.field_descriptor_proto(), extension.kind()))
+}
+
+fn has_options(options: &DynamicMessage) -> bool {
+    options.fields().any(|(field, _)| is_printed_option(&field))
+        || options.extensions().next().is_some()
+}
+
+fn is_printed_option(field: &FieldDescriptor) -> bool {
+    // Map entry messages are generated from map fields, and uninterpreted options are
+    // internal to the compiler.
+    !matches!(
+        (field.parent_message().full_name(), field.number() as i32),
+        (
+            "google.protobuf.MessageOptions",
+            tag::message::options::MAP_ENTRY
+        ) | (_, tag::UNINTERPRETED_OPTION)
+    )
+}
+
+/// Formats each option set in the given options message as an assignment, along with its
+/// field number.
+fn option_assignments(options: &DynamicMessage) -> Result<Vec<(i32, String)>, fmt::Error> {
+    let mut assignments = Vec::new();
+    for (field, value) in options.fields() {
+        if !is_printed_option(&field) {
+            continue;
+        }
+
+        if field.name() == "features" && !matches!(value, Value::List(_)) {
+            // Features are written individually, since aggregate syntax is harder to read.
+            if let Value::Message(features) = value {
+                for (feature, value) in features.fields() {
+                    let name = format!("features.{}", feature.name());
+                    push_assignments(
+                        &mut assignments,
+                        field.number(),
+                        &name,
+                        value,
+                        &feature.kind(),
+                    )?;
+                }
+                for (feature, value) in features.extensions() {
+                    let name = format!("features.(.{})", feature.full_name());
+                    push_assignments(
+                        &mut assignments,
+                        field.number(),
+                        &name,
+                        value,
+                        &feature.kind(),
+                    )?;
+                }
+                continue;
+            }
+        }
+
+        push_assignments(
+            &mut assignments,
+            field.number(),
+            field.name(),
+            value,
+            &field.kind(),
+        )?;
+    }
+    for (extension, value) in options.extensions() {
+        let name = format!("(.{})", extension.full_name());
+        push_assignments(
+            &mut assignments,
+            extension.number(),
+            &name,
+            value,
+            &extension.kind(),
+        )?;
+    }
+    Ok(assignments)
+}
+
+fn push_assignments(
+    assignments: &mut Vec<(i32, String)>,
+    number: u32,
+    name: &str,
+    value: &Value,
+    kind: &Kind,
+) -> fmt::Result {
+    // Repeated options are set by repeating the option for each element.
+    if let Value::List(values) = value {
+        for value in values {
+            push_assignments(assignments, number, name, value, kind)?;
+        }
+        return Ok(());
+    }
+
+    let mut assignment = format!("{name} = ");
+    fmt_option_value(&mut assignment, value, kind)?;
+    assignments.push((number as i32, assignment));
+    Ok(())
+}
+
+fn fmt_option_value(f: &mut String, value: &Value, kind: &Kind) -> fmt::Result {
+    match value {
+        Value::F32(value) => fmt_float(f, f64::from(*value)),
+        Value::F64(value) => fmt_float(f, *value),
+        Value::EnumNumber(number) => match kind.as_enum().and_then(|e| e.get_value(*number)) {
+            Some(value) => f.write_str(value.name()),
+            None => write!(f, "{number}"),
+        },
+        Value::Message(message) => {
+            let body = format!("{message:#}");
+            if body.is_empty() {
+                f.write_str("{}")
+            } else {
+                // Aggregate values are written on a single line.
+                f.write_str("{ ")?;
+                for (index, line) in body.lines().enumerate() {
+                    if index != 0 {
+                        f.write_char(' ')?;
+                    }
+                    f.write_str(line.trim_start())?;
+                }
+                f.write_str(" }")
+            }
+        }
+        value => write!(f, "{value}"),
+    }
+}
+
+fn fmt_float(f: &mut String, value: f64) -> fmt::Result {
+    if value.is_nan() {
+        f.write_str("nan")
+    } else if value.is_infinite() {
+        f.write_str(if value > 0.0 { "inf" } else { "-inf" })
+    } else {
+        write!(f, "{value:?}")
+    }
+}
@@ -0,0 +1,825 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use prost::{
+    bytes::Buf,
+    encoding::{self, decode_key, skip_field, DecodeContext, WireType},
+    DecodeError, Message,
+};
+
+use crate::{
+    descriptor::{
+        error::{DescriptorErrorKind, Label},
+        find_enum_proto, find_message_proto, tag,
+        types::{DescriptorProto, FileDescriptorProto, FileDescriptorSet, Options, SourceCodeInfo},
+        DefinitionKind, DescriptorPoolInner, EnumIndex, ExtensionIndex, FileIndex, KindIndex,
+        MessageIndex, ServiceIndex,
+    },
+    DescriptorError, DescriptorPool,
+};
+
+impl DescriptorPool {
+    /// Creates a new pool containing only the files needed by the given definitions.
+    ///
+    /// Each root is the fully-qualified name of a message, enum, service or extension in this
+    /// pool. The returned pool contains the files defining the roots, along with all files they
+    /// import, directly or indirectly. Files are copied unchanged, so the new pool may still
+    /// contain definitions which are not used by any root. To remove these as well, use
+    /// [`subset_definitions`](DescriptorPool::subset_definitions).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a root is not the name of a message, enum, service or extension in
+    /// this pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let subset = pool.subset(["package.MyMessage"]).unwrap();
+    /// assert!(subset.get_message_by_name("package.MyMessage").is_some());
+    /// // Imported files are included, even though they are unused by `MyMessage`.
+    /// assert!(subset.get_message_by_name("google.protobuf.Empty").is_some());
+    /// assert!(subset.get_file_by_name("google/protobuf/timestamp.proto").is_none());
+    /// ```
+    pub fn subset<I, S>(&self, roots: I) -> Result<DescriptorPool, DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let roots = self.inner.resolve_roots(roots)?;
+
+        let mut files = HashSet::new();
+        let mut stack: Vec<FileIndex> = roots.iter().map(|&(file, _)| file).collect();
+        while let Some(file) = stack.pop() {
+            if files.insert(file) {
+                stack.extend(&self.inner.files[file as usize].dependencies);
+            }
+        }
+
+        self.with_files(
+            self.inner
+                .files
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| files.contains(&(index as FileIndex)))
+                .map(|(_, file)| file.raw.clone()),
+        )
+    }
+
+    /// Creates a new pool containing only the definitions needed by the given definitions.
+    ///
+    /// Like [`subset`](DescriptorPool::subset), each root is the fully-qualified name of a
+    /// message, enum, service or extension in this pool. Unlike `subset`, any messages, enums,
+    /// services and extensions not reachable from a root are removed from the files in the new
+    /// pool, and imports of files which are no longer needed are dropped.
+    ///
+    /// A definition is reachable from a root if it is the type of a field of a reachable message,
+    /// the input or output type of a method of a reachable service, or a message containing a
+    /// reachable definition. Extensions of reachable messages are also included, along with their
+    /// types, as are any extensions used as custom options by a reachable definition or its file,
+    /// so that the options can still be decoded from the new pool. The [`source_code_info`](prost_types::FileDescriptorProto::source_code_info) of
+    /// each file is updated to match the remaining definitions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a root is not the name of a message, enum, service or extension in
+    /// this pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let subset = pool.subset_definitions(["package.MyMessage"]).unwrap();
+    /// assert!(subset.get_message_by_name("package.MyMessage").is_some());
+    /// assert!(subset.get_message_by_name("google.protobuf.Empty").is_none());
+    /// assert_eq!(subset.files().len(), 1);
+    /// ```
+    pub fn subset_definitions<I, S>(&self, roots: I) -> Result<DescriptorPool, DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let roots = self.inner.resolve_roots(roots)?;
+        self.with_files(Pruner::new(&self.inner, &roots).prune())
+    }
+
+    /// Encodes a `FileDescriptorSet` containing only the definitions needed by the given
+    /// definitions.
+    ///
+    /// The files are pruned in the same way as [`subset_definitions`](DescriptorPool::subset_definitions),
+    /// and are ordered so that each file appears after all the files it imports. Paths in the
+    /// [`source_code_info`](prost_types::FileDescriptorProto::source_code_info) of each file are
+    /// updated to refer to the remaining definitions, and locations of removed definitions are
+    /// dropped.
+    ///
+    /// This is useful for minimizing the size of descriptors sent to other processes, for
+    /// example in gRPC server reflection responses.
+    ///
+    /// As with [`encode_to_vec`](DescriptorPool::encode_to_vec), the encoded file descriptors
+    /// include any extension options, and the edition of files using the `editions` syntax. The
+    /// result can be decoded using [`DescriptorPool::decode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a root is not the name of a message, enum, service or extension in
+    /// this pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let bytes = pool.encode_pruned_file_descriptor_set(["package.MyMessage"]).unwrap();
+    /// let pruned = DescriptorPool::decode(bytes.as_slice()).unwrap();
+    /// assert_eq!(pruned.files().len(), 1);
+    /// assert_eq!(pruned.files().next().unwrap().name(), "doctest.proto");
+    /// assert_eq!(pruned.files().next().unwrap().dependencies().len(), 0);
+    /// ```
+    pub fn encode_pruned_file_descriptor_set<I, S>(
+        &self,
+        roots: I,
+    ) -> Result<Vec<u8>, DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let roots = self.inner.resolve_roots(roots)?;
+        Ok(FileDescriptorSet {
+            file: Pruner::new(&self.inner, &roots).prune(),
+        }
+        .encode_to_vec())
+    }
+
+    /// Removes a file from this pool.
+    ///
+    /// This is equivalent to calling [`remove_files`](DescriptorPool::remove_files) with a single
+    /// file name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file has not been added to this pool, or if another file in the
+    /// pool imports it.
+    pub fn remove_file(&mut self, name: &str) -> Result<(), DescriptorError> {
+        self.remove_files([name])
+    }
+
+    /// Removes a set of files from this pool.
+    ///
+    /// This can be used to replace files when their definitions change, by removing the old
+    /// versions and then adding the new ones. As with adding files, this does not affect any
+    /// existing clones of the pool, or descriptors obtained from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files have not been added to this pool, or if a file which
+    /// is not being removed imports one of them. In this case the pool is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::DescriptorPool;
+    /// # let mut pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// // The file is imported by 'doctest.proto', so cannot be removed alone.
+    /// assert!(pool.remove_file("google/protobuf/empty.proto").is_err());
+    ///
+    /// pool.remove_files(["doctest.proto", "google/protobuf/empty.proto"]).unwrap();
+    /// assert!(pool.get_message_by_name("package.MyMessage").is_none());
+    /// assert!(pool.get_message_by_name("google.protobuf.Empty").is_none());
+    /// assert!(pool.get_message_by_name("google.protobuf.Any").is_some());
+    /// ```
+    pub fn remove_files<I, S>(&mut self, names: I) -> Result<(), DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut errors = Vec::new();
+        let mut removed = HashSet::new();
+        for name in names {
+            match self.inner.file_names.get(name.as_ref()) {
+                Some(&index) => {
+                    removed.insert(index);
+                }
+                None => errors.push(DescriptorErrorKind::FileNotInPool {
+                    name: name.as_ref().to_owned(),
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            for (index, file) in self.inner.files.iter().enumerate() {
+                let index = index as FileIndex;
+                if removed.contains(&index) {
+                    continue;
+                }
+
+                for (i, dependency) in file.dependencies.iter().enumerate() {
+                    if removed.contains(dependency) {
+                        errors.push(DescriptorErrorKind::FileInUse {
+                            name: self.inner.files[*dependency as usize].raw.name().to_owned(),
+                            found: Label::new(
+                                &self.inner.files,
+                                "imported here",
+                                index,
+                                [tag::file::DEPENDENCY, i as i32].into(),
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(DescriptorError::new(errors));
+        }
+
+        *self = self.with_files(
+            self.inner
+                .files
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| !removed.contains(&(index as FileIndex)))
+                .map(|(_, file)| file.raw.clone()),
+        )?;
+        Ok(())
+    }
+
+    /// Builds a new pool from the given files, using the same feature set defaults as this pool.
+    fn with_files(
+        &self,
+        files: impl IntoIterator<Item = FileDescriptorProto>,
+    ) -> Result<DescriptorPool, DescriptorError> {
+        let mut pool = DescriptorPool::default();
+        if let Some(defaults) = &self.inner.feature_set_defaults {
+            std::sync::Arc::make_mut(&mut pool.inner).feature_set_defaults = Some(defaults.clone());
+        }
+        pool.build_files(files)?;
+        Ok(pool)
+    }
+}
+
+impl DescriptorPoolInner {
+    fn resolve_roots<I, S>(
+        &self,
+        roots: I,
+    ) -> Result<Vec<(FileIndex, DefinitionKind)>, DescriptorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut result = Vec::new();
+        let mut errors = Vec::new();
+        for root in roots {
+            let name = root.as_ref();
+            match self.names.get(name.strip_prefix('.').unwrap_or(name)) {
+                Some(definition)
+                    if matches!(
+                        definition.kind,
+                        DefinitionKind::Message(_)
+                            | DefinitionKind::Enum(_)
+                            | DefinitionKind::Service(_)
+                            | DefinitionKind::Extension(_)
+                    ) =>
+                {
+                    result.push((definition.file, definition.kind))
+                }
+                _ => errors.push(DescriptorErrorKind::InvalidRoot {
+                    name: name.to_owned(),
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(DescriptorError::new(errors))
+        }
+    }
+}
+
+/// Computes the definitions reachable from a set of roots, and removes all others from the
+/// files of a pool.
+struct Pruner<'a> {
+    pool: &'a DescriptorPoolInner,
+    messages: HashSet<MessageIndex>,
+    enums: HashSet<EnumIndex>,
+    extensions: HashSet<ExtensionIndex>,
+    services: HashSet<ServiceIndex>,
+    /// The paths of all reachable definitions, by file.
+    paths: HashMap<FileIndex, HashSet<Box<[i32]>>>,
+    /// The files containing definitions referenced by the reachable definitions in each file.
+    references: HashMap<FileIndex, HashSet<FileIndex>>,
+}
+
+/// Maps the indices of definitions in an original file to their indices in the pruned file.
+#[derive(Default)]
+struct Remap {
+    /// The new index of each element of a repeated field, by field number.
+    lists: HashMap<i32, Vec<Option<i32>>>,
+    /// The remapping for nested messages, by field number and original index.
+    children: HashMap<(i32, i32), Remap>,
+}
+
+impl<'a> Pruner<'a> {
+    fn new(pool: &'a DescriptorPoolInner, roots: &[(FileIndex, DefinitionKind)]) -> Self {
+        let mut pruner = Pruner {
+            pool,
+            messages: HashSet::new(),
+            enums: HashSet::new(),
+            extensions: HashSet::new(),
+            services: HashSet::new(),
+            paths: HashMap::new(),
+            references: HashMap::new(),
+        };
+
+        for &(_, kind) in roots {
+            match kind {
+                DefinitionKind::Message(index) => pruner.add_message(index),
+                DefinitionKind::Enum(index) => pruner.add_enum(index),
+                DefinitionKind::Service(index) => pruner.add_service(index),
+                DefinitionKind::Extension(index) => pruner.add_extension(index),
+                _ => unreachable!(),
+            }
+        }
+
+        pruner
+    }
+
+    fn add_message(&mut self, index: MessageIndex) {
+        if !self.messages.insert(index) {
+            return;
+        }
+
+        let pool = self.pool;
+        let message = &pool.messages[index as usize];
+        let file = message.id.file;
+        self.add_path(file, &message.id.path);
+        if let Some(parent) = message.parent {
+            self.add_message(parent);
+        }
+        for field in &message.fields {
+            self.add_kind(file, field.kind);
+        }
+        for &extension in &message.extensions {
+            self.add_extension(extension);
+        }
+
+        let raw = find_message_proto(&pool.files[file as usize].raw, &message.id.path);
+        self.add_options(file, "google.protobuf.MessageOptions", &raw.options);
+        for field in &raw.field {
+            self.add_options(file, "google.protobuf.FieldOptions", &field.options);
+        }
+        for oneof in &raw.oneof_decl {
+            self.add_options(file, "google.protobuf.OneofOptions", &oneof.options);
+        }
+        for range in &raw.extension_range {
+            self.add_options(
+                file,
+                "google.protobuf.ExtensionRangeOptions",
+                &range.options,
+            );
+        }
+    }
+
+    fn add_enum(&mut self, index: EnumIndex) {
+        if !self.enums.insert(index) {
+            return;
+        }
+
+        let pool = self.pool;
+        let enum_ = &pool.enums[index as usize];
+        let file = enum_.id.file;
+        self.add_path(file, &enum_.id.path);
+        if let Some(parent) = enum_.parent {
+            self.add_message(parent);
+        }
+
+        let raw = find_enum_proto(&pool.files[file as usize].raw, &enum_.id.path);
+        self.add_options(file, "google.protobuf.EnumOptions", &raw.options);
+        for value in &raw.value {
+            self.add_options(file, "google.protobuf.EnumValueOptions", &value.options);
+        }
+    }
+
+    fn add_extension(&mut self, index: ExtensionIndex) {
+        if !self.extensions.insert(index) {
+            return;
+        }
+
+        let pool = self.pool;
+        let extension = &pool.extensions[index as usize];
+        let file = extension.id.file;
+        self.add_path(file, &extension.id.path);
+        if let Some(parent) = extension.parent {
+            self.add_message(parent);
+        }
+        self.add_reference(file, pool.messages[extension.extendee as usize].id.file);
+        self.add_message(extension.extendee);
+        self.add_kind(file, extension.kind);
+
+        let raw_file = &pool.files[file as usize].raw;
+        let raw = match *extension.id.path {
+            [_, index] => &raw_file.extension[index as usize],
+            [ref parent @ .., _, index] => {
+                &find_message_proto(raw_file, parent).extension[index as usize]
+            }
+            _ => unreachable!("invalid extension path"),
+        };
+        self.add_options(file, "google.protobuf.FieldOptions", &raw.options);
+    }
+
+    fn add_service(&mut self, index: ServiceIndex) {
+        if !self.services.insert(index) {
+            return;
+        }
+
+        let pool = self.pool;
+        let service = &pool.services[index as usize];
+        let file = service.id.file;
+        self.add_path(file, &service.id.path);
+        for method in &service.methods {
+            for message in [method.input, method.output] {
+                self.add_reference(file, pool.messages[message as usize].id.file);
+                self.add_message(message);
+            }
+        }
+
+        let raw = &pool.files[file as usize].raw.service[service.id.path[1] as usize];
+        self.add_options(file, "google.protobuf.ServiceOptions", &raw.options);
+        for method in &raw.method {
+            self.add_options(file, "google.protobuf.MethodOptions", &method.options);
+        }
+    }
+
+    fn add_kind(&mut self, file: FileIndex, kind: KindIndex) {
+        match kind {
+            KindIndex::Message(index) | KindIndex::Group(index) => {
+                self.add_reference(file, self.pool.messages[index as usize].id.file);
+                self.add_message(index);
+            }
+            KindIndex::Enum(index) => {
+                self.add_reference(file, self.pool.enums[index as usize].id.file);
+                self.add_enum(index);
+            }
+            _ => (),
+        }
+    }
+
+    fn add_path(&mut self, file: FileIndex, path: &[i32]) {
+        let paths = self.paths.entry(file).or_default();
+        let is_new_file = paths.is_empty();
+        paths.insert(path.into());
+        if is_new_file {
+            let pool = self.pool;
+            self.add_options(
+                file,
+                "google.protobuf.FileOptions",
+                &pool.files[file as usize].raw.options,
+            );
+        }
+    }
+
+    /// Adds the extensions used as custom options in the given options message, including within
+    /// the values of other options.
+    fn add_options<T>(&mut self, file: FileIndex, name: &str, options: &Option<Options<T>>) {
+        let Some(options) = options else {
+            return;
+        };
+        // If the options type is not in this pool, it cannot have any extensions in this pool.
+        if let Some(DefinitionKind::Message(message)) =
+            self.pool.names.get(name).map(|definition| definition.kind)
+        {
+            let _ = self.add_option_fields(file, message, &options.encoded);
+        }
+    }
+
+    fn add_option_fields(
+        &mut self,
+        file: FileIndex,
+        message: MessageIndex,
+        mut buf: &[u8],
+    ) -> Result<(), DecodeError> {
+        let pool = self.pool;
+        let message = &pool.messages[message as usize];
+        while buf.has_remaining() {
+            let (number, wire_type) = decode_key(&mut buf)?;
+            let kind = if let Some(&field) = message.field_numbers.get(&number) {
+                Some(message.fields[field as usize].kind)
+            } else if let Some(&extension) = message
+                .extensions
+                .iter()
+                .find(|&&extension| pool.extensions[extension as usize].number == number)
+            {
+                let extension_file = pool.extensions[extension as usize].id.file;
+                self.add_reference(file, extension_file);
+                self.add_extension(extension);
+                Some(pool.extensions[extension as usize].kind)
+            } else {
+                None
+            };
+
+            match kind {
+                Some(KindIndex::Message(index)) if wire_type == WireType::LengthDelimited => {
+                    let mut value = Vec::new();
+                    encoding::bytes::merge(
+                        wire_type,
+                        &mut value,
+                        &mut buf,
+                        DecodeContext::default(),
+                    )?;
+                    self.add_option_fields(file, index, &value)?;
+                }
+                _ => skip_field(wire_type, number, &mut buf, DecodeContext::default())?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_reference(&mut self, file: FileIndex, referenced: FileIndex) {
+        if file != referenced {
+            self.references.entry(file).or_default().insert(referenced);
+        }
+    }
+
+    fn contains(&self, file: FileIndex, path: &[i32]) -> bool {
+        self.paths
+            .get(&file)
+            .is_some_and(|paths| paths.contains(path))
+    }
+
+    fn prune(&self) -> Vec<FileDescriptorProto> {
+        let all_references: HashSet<FileIndex> =
+            self.references.values().flatten().copied().collect();
+
+        // Work out which imports each file still needs. Public imports are kept if they
+        // re-export a file needed by any other file, since the importing file may rely on them.
+        let mut files: BTreeSet<FileIndex> = self.paths.keys().copied().collect();
+        let mut dependencies: HashMap<FileIndex, Vec<usize>> = HashMap::new();
+        let mut stack: Vec<FileIndex> = files.iter().copied().collect();
+        while let Some(file) = stack.pop() {
+            let raw = &self.pool.files[file as usize].raw;
+            let references = self.references.get(&file);
+            let mut kept = Vec::new();
+            for (i, &dependency) in self.pool.files[file as usize]
+                .dependencies
+                .iter()
+                .enumerate()
+            {
+                let is_public = raw.public_dependency.contains(&(i as i32));
+                let exported = self.public_closure(dependency);
+                if exported.iter().any(|f| {
+                    references.is_some_and(|r| r.contains(f))
+                        || (is_public && all_references.contains(f))
+                }) {
+                    kept.push(i);
+                    if files.insert(dependency) {
+                        stack.push(dependency);
+                    }
+                }
+            }
+            dependencies.insert(file, kept);
+        }
+
+        files
+            .into_iter()
+            .map(|file| self.prune_file(file, &dependencies[&file]))
+            .collect()
+    }
+
+    /// Gets the given file, and all files it publicly imports, directly or indirectly.
+    fn public_closure(&self, file: FileIndex) -> HashSet<FileIndex> {
+        let mut result = HashSet::new();
+        let mut stack = vec![file];
+        while let Some(file) = stack.pop() {
+            if result.insert(file) {
+                let inner = &self.pool.files[file as usize];
+                stack.extend(
+                    inner
+                        .raw
+                        .public_dependency
+                        .iter()
+                        .map(|&i| inner.dependencies[i as usize]),
+                );
+            }
+        }
+        result
+    }
+
+    fn prune_file(&self, index: FileIndex, dependencies: &[usize]) -> FileDescriptorProto {
+        let file = &self.pool.files[index as usize].raw;
+        let mut remap = Remap::default();
+        let mut path = Vec::new();
+
+        let dependency_map = remap_indices(file.dependency.len(), |i| dependencies.contains(&i));
+        let public_dependency = remap_dependencies(&file.public_dependency, &dependency_map);
+        let weak_dependency = remap_dependencies(&file.weak_dependency, &dependency_map);
+        remap.lists.insert(
+            tag::file::PUBLIC_DEPENDENCY,
+            remap_indices(file.public_dependency.len(), |i| {
+                dependency_map[file.public_dependency[i] as usize].is_some()
+            }),
+        );
+        remap.lists.insert(
+            tag::file::WEAK_DEPENDENCY,
+            remap_indices(file.weak_dependency.len(), |i| {
+                dependency_map[file.weak_dependency[i] as usize].is_some()
+            }),
+        );
+        remap.lists.insert(tag::file::DEPENDENCY, dependency_map);
+
+        let mut message_type = Vec::new();
+        let mut message_map = Vec::new();
+        for (i, message) in file.message_type.iter().enumerate() {
+            path.extend([tag::file::MESSAGE_TYPE, i as i32]);
+            if self.contains(index, &path) {
+                let (message, child) = self.prune_message(index, &mut path, message);
+                remap
+                    .children
+                    .insert((tag::file::MESSAGE_TYPE, i as i32), child);
+                message_map.push(Some(message_type.len() as i32));
+                message_type.push(message);
+            } else {
+                message_map.push(None);
+            }
+            path.clear();
+        }
+        remap.lists.insert(tag::file::MESSAGE_TYPE, message_map);
+
+        FileDescriptorProto {
+            name: file.name.clone(),
+            package: file.package.clone(),
+            dependency: dependencies
+                .iter()
+                .map(|&i| file.dependency[i].clone())
+                .collect(),
+            public_dependency,
+            weak_dependency,
+            message_type,
+            enum_type: self.retain(
+                index,
+                &mut path,
+                tag::file::ENUM_TYPE,
+                &file.enum_type,
+                &mut remap,
+            ),
+            service: self.retain(
+                index,
+                &mut path,
+                tag::file::SERVICE,
+                &file.service,
+                &mut remap,
+            ),
+            extension: self.retain(
+                index,
+                &mut path,
+                tag::file::EXTENSION,
+                &file.extension,
+                &mut remap,
+            ),
+            options: file.options.clone(),
+            source_code_info: file.source_code_info.as_ref().map(|info| SourceCodeInfo {
+                location: info
+                    .location
+                    .iter()
+                    .filter_map(|location| {
+                        let mut path = Vec::with_capacity(location.path.len());
+                        remap.remap_path(&location.path, &mut path).then(|| {
+                            let mut location = location.clone();
+                            location.path = path;
+                            location
+                        })
+                    })
+                    .collect(),
+            }),
+            syntax: file.syntax.clone(),
+            edition: file.edition,
+        }
+    }
+
+    fn prune_message(
+        &self,
+        file: FileIndex,
+        path: &mut Vec<i32>,
+        message: &DescriptorProto,
+    ) -> (DescriptorProto, Remap) {
+        let mut remap = Remap::default();
+
+        let mut nested_type = Vec::new();
+        let mut nested_map = Vec::new();
+        for (i, nested) in message.nested_type.iter().enumerate() {
+            let len = path.len();
+            path.extend([tag::message::NESTED_TYPE, i as i32]);
+            if self.contains(file, path) {
+                let (nested, child) = self.prune_message(file, path, nested);
+                remap
+                    .children
+                    .insert((tag::message::NESTED_TYPE, i as i32), child);
+                nested_map.push(Some(nested_type.len() as i32));
+                nested_type.push(nested);
+            } else {
+                nested_map.push(None);
+            }
+            path.truncate(len);
+        }
+        remap.lists.insert(tag::message::NESTED_TYPE, nested_map);
+
+        let message = DescriptorProto {
+            nested_type,
+            enum_type: self.retain(
+                file,
+                path,
+                tag::message::ENUM_TYPE,
+                &message.enum_type,
+                &mut remap,
+            ),
+            extension: self.retain(
+                file,
+                path,
+                tag::message::EXTENSION,
+                &message.extension,
+                &mut remap,
+            ),
+            ..message.clone()
+        };
+        (message, remap)
+    }
+
+    /// Keeps only the reachable elements of a list of enums, services or extensions.
+    fn retain<T: Clone>(
+        &self,
+        file: FileIndex,
+        path: &mut Vec<i32>,
+        field: i32,
+        items: &[T],
+        remap: &mut Remap,
+    ) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut map = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            let len = path.len();
+            path.extend([field, i as i32]);
+            if self.contains(file, path) {
+                map.push(Some(result.len() as i32));
+                result.push(item.clone());
+            } else {
+                map.push(None);
+            }
+            path.truncate(len);
+        }
+        remap.lists.insert(field, map);
+        result
+    }
+}
+
+impl Remap {
+    /// Writes the path of a source location in the pruned file to `result`, returning `false`
+    /// if it refers to a definition which has been removed.
+    fn remap_path(&self, path: &[i32], result: &mut Vec<i32>) -> bool {
+        match *path {
+            [field, index, ref rest @ ..] if self.lists.contains_key(&field) => {
+                let Some(new_index) = self.lists[&field].get(index as usize).copied().flatten()
+                else {
+                    return false;
+                };
+
+                result.extend([field, new_index]);
+                match self.children.get(&(field, index)) {
+                    Some(child) => child.remap_path(rest, result),
+                    None => {
+                        result.extend_from_slice(rest);
+                        true
+                    }
+                }
+            }
+            [field] if self.lists.contains_key(&field) => {
+                result.push(field);
+                self.lists[&field].iter().any(Option::is_some)
+            }
+            _ => {
+                result.extend_from_slice(path);
+                true
+            }
+        }
+    }
+}
+
+fn remap_indices(len: usize, mut keep: impl FnMut(usize) -> bool) -> Vec<Option<i32>> {
+    let mut next = 0;
+    (0..len)
+        .map(|i| {
+            keep(i).then(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+fn remap_dependencies(indices: &[i32], dependency_map: &[Option<i32>]) -> Vec<i32> {
+    indices
+        .iter()
+        .filter_map(|&i| dependency_map[i as usize])
+        .collect()
+}
@@ -17,6 +17,7 @@ pub(crate) mod file {
     pub(crate) const EXTENSION: i32 = 7;
     pub(crate) const OPTIONS: i32 = 8;
     pub(crate) const SYNTAX: i32 = 12;
+    pub(crate) const EDITION: i32 = 14;
 
     pub(crate) mod options {
         pub(crate) const JAVA_PACKAGE: i32 = 1;
@@ -39,6 +40,7 @@ pub(crate) mod file {
         pub(crate) const PHP_NAMESPACE: i32 = 41;
         pub(crate) const PHP_METADATA_NAMESPACE: i32 = 44;
         pub(crate) const RUBY_PACKAGE: i32 = 45;
+        pub(crate) const FEATURES: i32 = 50;
         pub(crate) const FILE_UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -59,6 +61,10 @@ pub(crate) mod message {
         pub(crate) const START: i32 = 1;
         pub(crate) const END: i32 = 2;
         pub(crate) const OPTIONS: i32 = 3;
+
+        pub(crate) mod options {
+            pub(crate) const FEATURES: i32 = 50;
+        }
     }
 
     pub(crate) mod reserved_range {
@@ -71,6 +77,7 @@ pub(crate) mod message {
         pub(crate) const NO_STANDARD_DESCRIPTOR_ACCESSOR: i32 = 2;
         pub(crate) const DEPRECATED: i32 = 3;
         pub(crate) const MAP_ENTRY: i32 = 7;
+        pub(crate) const FEATURES: i32 = 12;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -93,6 +100,7 @@ pub(crate) mod field {
         pub(crate) const LAZY: i32 = 5;
         pub(crate) const DEPRECATED: i32 = 3;
         pub(crate) const WEAK: i32 = 10;
+        pub(crate) const FEATURES: i32 = 21;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -100,6 +108,11 @@ pub(crate) mod field {
 pub(crate) mod oneof {
     pub(crate) const NAME: i32 = 1;
     pub(crate) const OPTIONS: i32 = 2;
+
+    pub(crate) mod options {
+        pub(crate) const FEATURES: i32 = 1;
+        pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
+    }
 }
 
 pub(crate) mod enum_ {
@@ -117,6 +130,7 @@ pub(crate) mod enum_ {
     pub(crate) mod options {
         pub(crate) const ALLOW_ALIAS: i32 = 2;
         pub(crate) const DEPRECATED: i32 = 3;
+        pub(crate) const FEATURES: i32 = 7;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -128,6 +142,7 @@ pub(crate) mod enum_value {
 
     pub(crate) mod options {
         pub(crate) const DEPRECATED: i32 = 1;
+        pub(crate) const FEATURES: i32 = 2;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -139,6 +154,7 @@ pub(crate) mod service {
 
     pub(crate) mod options {
         pub(crate) const DEPRECATED: i32 = 33;
+        pub(crate) const FEATURES: i32 = 34;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
@@ -154,6 +170,7 @@ pub(crate) mod method {
     pub(crate) mod options {
         pub(crate) const DEPRECATED: i32 = 33;
         pub(crate) const IDEMPOTENCY_LEVEL: i32 = 34;
+        pub(crate) const FEATURES: i32 = 35;
         pub(crate) const UNINTERPRETED_OPTION: i32 = 999;
     }
 }
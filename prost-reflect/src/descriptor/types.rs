@@ -44,6 +44,8 @@ pub(crate) struct FileDescriptorProto {
     pub source_code_info: Option<SourceCodeInfo>,
     #[prost(string, optional, tag = "12")]
     pub syntax: Option<String>,
+    #[prost(int32, optional, tag = "14")]
+    pub edition: Option<i32>,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -170,6 +172,103 @@ pub(crate) struct MethodDescriptorProto {
     pub server_streaming: Option<bool>,
 }
 
+#[derive(Clone, Copy, PartialEq, Message)]
+pub(crate) struct FeatureSet {
+    #[prost(enumeration = "feature_set::FieldPresence", optional, tag = "1")]
+    pub field_presence: Option<i32>,
+    #[prost(enumeration = "feature_set::EnumType", optional, tag = "2")]
+    pub enum_type: Option<i32>,
+    #[prost(
+        enumeration = "feature_set::RepeatedFieldEncoding",
+        optional,
+        tag = "3"
+    )]
+    pub repeated_field_encoding: Option<i32>,
+    #[prost(enumeration = "feature_set::Utf8Validation", optional, tag = "4")]
+    pub utf8_validation: Option<i32>,
+    #[prost(enumeration = "feature_set::MessageEncoding", optional, tag = "5")]
+    pub message_encoding: Option<i32>,
+    #[prost(enumeration = "feature_set::JsonFormat", optional, tag = "6")]
+    pub json_format: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct FeatureSetDefaults {
+    #[prost(message, repeated, tag = "1")]
+    pub defaults: Vec<feature_set_defaults::FeatureSetEditionDefault>,
+    #[prost(int32, optional, tag = "4")]
+    pub minimum_edition: Option<i32>,
+    #[prost(int32, optional, tag = "5")]
+    pub maximum_edition: Option<i32>,
+}
+
+pub(crate) mod feature_set_defaults {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(crate) struct FeatureSetEditionDefault {
+        #[prost(int32, optional, tag = "3")]
+        pub edition: Option<i32>,
+        #[prost(bytes = "vec", optional, tag = "2")]
+        pub features: Option<Vec<u8>>,
+        #[prost(bytes = "vec", optional, tag = "4")]
+        pub overridable_features: Option<Vec<u8>>,
+        #[prost(bytes = "vec", optional, tag = "5")]
+        pub fixed_features: Option<Vec<u8>>,
+    }
+}
+
+pub(crate) mod feature_set {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum FieldPresence {
+        Unknown = 0,
+        Explicit = 1,
+        Implicit = 2,
+        LegacyRequired = 3,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum EnumType {
+        Unknown = 0,
+        Open = 1,
+        Closed = 2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum RepeatedFieldEncoding {
+        Unknown = 0,
+        Packed = 1,
+        Expanded = 2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum Utf8Validation {
+        Unknown = 0,
+        Verify = 2,
+        None = 3,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum MessageEncoding {
+        Unknown = 0,
+        LengthPrefixed = 1,
+        Delimited = 2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub(crate) enum JsonFormat {
+        Unknown = 0,
+        Allow = 1,
+        LegacyBestEffort = 2,
+    }
+}
+
 #[derive(Clone, Default, PartialEq)]
 pub(crate) struct Options<T> {
     pub(crate) encoded: Vec<u8>,
@@ -207,6 +306,7 @@ impl FileDescriptorProto {
             options: file.options.map(Options::from_prost),
             source_code_info: file.source_code_info,
             syntax: file.syntax,
+            edition: None,
         }
     }
 
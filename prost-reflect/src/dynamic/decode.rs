@@ -0,0 +1,502 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use prost::{
+    bytes::{Buf, Bytes},
+    encoding::{self, DecodeContext, WireType},
+    DecodeError,
+};
+
+use crate::{
+    descriptor::{MAP_ENTRY_KEY_NUMBER, MAP_ENTRY_VALUE_NUMBER},
+    dynamic::{
+        fields::FieldDescriptorLike, message::closed_enum_type, required::MissingRequiredFields,
+        unknown::UnknownFieldValue,
+    },
+    feature_set::Utf8Validation,
+    DynamicMessage, FieldPath, Kind, MapKey, MessageDescriptor, PathSegment, UnknownField, Value,
+};
+
+/// The default recursion limit, matching the limit used by [`prost`].
+const DEFAULT_RECURSION_LIMIT: u32 = 100;
+
+/// Options to control decoding of messages from the protobuf binary format.
+///
+/// Used by [`DynamicMessage::decode_with_options()`] and [`DynamicMessage::merge_with_options()`].
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    unknown_fields: UnknownFieldPolicy,
+    recursion_limit: u32,
+    max_message_size: Option<usize>,
+    preserve_invalid_utf8: bool,
+    allow_partial: bool,
+}
+
+/// How fields not present in the message descriptor are handled when decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnknownFieldPolicy {
+    /// Unknown fields are stored in the message, and written back out when it is encoded.
+    Preserve,
+    /// Unknown fields are skipped.
+    Discard,
+    /// Decoding fails with [`MessageDecodeError::UnknownField`].
+    Error,
+}
+
+/// Error type returned by [`DynamicMessage::decode_with_options()`] and
+/// [`DynamicMessage::merge_with_options()`].
+///
+/// Paths included in errors are formatted using the [`FieldPath`] syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageDecodeError {
+    /// The input was not a valid encoding of the message.
+    Decode {
+        /// The path to the field which could not be decoded.
+        path: String,
+        /// The underlying error.
+        error: DecodeError,
+    },
+    /// An unknown field was found, and the [`UnknownFieldPolicy`] is
+    /// [`Error`](UnknownFieldPolicy::Error).
+    UnknownField {
+        /// The full name of the message type containing the field.
+        message: String,
+        /// The path to the message containing the field.
+        path: String,
+        /// The number of the unknown field.
+        number: u32,
+    },
+    /// Messages were nested more deeply than the configured recursion limit.
+    RecursionLimitReached {
+        /// The path to the message which exceeded the limit.
+        path: String,
+    },
+    /// The encoded message was larger than the configured maximum size.
+    MessageTooLarge {
+        /// The length of the encoded message in bytes.
+        len: usize,
+        /// The maximum allowed length in bytes.
+        limit: usize,
+    },
+    /// The decoded message did not set all of its `required` fields, and
+    /// [`DecodeOptions::allow_partial`] is `false`.
+    MissingRequiredFields {
+        /// The paths to the required fields which are not set.
+        paths: Vec<String>,
+    },
+}
+
+struct Decoder<'a> {
+    options: &'a DecodeOptions,
+    path: FieldPath,
+    depth: u32,
+}
+
+impl DecodeOptions {
+    /// Creates a new instance of [`DecodeOptions`], with the default options matching the
+    /// behaviour of [`DynamicMessage::decode()`].
+    pub const fn new() -> Self {
+        DecodeOptions {
+            unknown_fields: UnknownFieldPolicy::Preserve,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            max_message_size: None,
+            preserve_invalid_utf8: false,
+            allow_partial: true,
+        }
+    }
+
+    /// How to handle fields whose number is not defined in the message descriptor.
+    ///
+    /// The default value is [`UnknownFieldPolicy::Preserve`].
+    pub const fn unknown_fields(mut self, policy: UnknownFieldPolicy) -> Self {
+        self.unknown_fields = policy;
+        self
+    }
+
+    /// The maximum depth of nested messages and groups.
+    ///
+    /// The default value is `100`.
+    pub const fn recursion_limit(mut self, limit: u32) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// The maximum size, in bytes, of the encoded message.
+    ///
+    /// By default, there is no limit.
+    pub const fn max_message_size(mut self, limit: usize) -> Self {
+        self.max_message_size = Some(limit);
+        self
+    }
+
+    /// Whether to preserve invalid UTF-8 in string fields which do not require validation.
+    ///
+    /// String fields in `proto2` files, or with the `utf8_validation` feature set to `NONE`, are
+    /// not required to contain valid UTF-8. If this option is `true`, invalid values for these
+    /// fields are stored as unknown fields, so they are written back out unchanged when the
+    /// message is encoded. For repeated fields, the whole field is stored as unknown fields once
+    /// an invalid element is found. Otherwise, invalid UTF-8 is an error.
+    ///
+    /// String fields which require validation, and the keys and values of map fields, are always
+    /// validated.
+    ///
+    /// The default value is `false`.
+    pub const fn preserve_invalid_utf8(mut self, yes: bool) -> Self {
+        self.preserve_invalid_utf8 = yes;
+        self
+    }
+
+    /// Whether to accept messages which are missing `required` fields.
+    ///
+    /// If `false`, decoding fails with [`MessageDecodeError::MissingRequiredFields`] unless the
+    /// message, including any nested messages, is [initialized](DynamicMessage::is_initialized)
+    /// once all fields have been merged.
+    ///
+    /// The default value is `true`.
+    pub const fn allow_partial(mut self, yes: bool) -> Self {
+        self.allow_partial = yes;
+        self
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicMessage {
+    /// Decodes an instance of the message type specified by the [`MessageDescriptor`] from the buffer,
+    /// using the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, DecodeOptions, MessageDecodeError, UnknownFieldPolicy};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let options = DecodeOptions::new().unknown_fields(UnknownFieldPolicy::Error);
+    /// let err = DynamicMessage::decode_with_options(message_descriptor, b"\x08\x96\x01\x50\x01".as_ref(), &options).unwrap_err();
+    /// assert!(matches!(err, MessageDecodeError::UnknownField { number: 10, .. }));
+    /// ```
+    pub fn decode_with_options<B>(
+        desc: MessageDescriptor,
+        buf: B,
+        options: &DecodeOptions,
+    ) -> Result<Self, MessageDecodeError>
+    where
+        B: Buf,
+    {
+        let mut message = DynamicMessage::new(desc);
+        message.merge_with_options(buf, options)?;
+        Ok(message)
+    }
+
+    /// Decodes fields from the buffer and merges them into this message, using the given options.
+    pub fn merge_with_options<B>(
+        &mut self,
+        mut buf: B,
+        options: &DecodeOptions,
+    ) -> Result<(), MessageDecodeError>
+    where
+        B: Buf,
+    {
+        if let Some(limit) = options.max_message_size {
+            if buf.remaining() > limit {
+                return Err(MessageDecodeError::MessageTooLarge {
+                    len: buf.remaining(),
+                    limit,
+                });
+            }
+        }
+
+        // Nested messages are sliced out of the buffer, which does not copy for `Bytes`.
+        let mut buf = buf.copy_to_bytes(buf.remaining());
+        Decoder {
+            options,
+            path: FieldPath::new(),
+            depth: 0,
+        }
+        .merge_message(self, &mut buf, None)?;
+
+        if !options.allow_partial && !self.is_initialized() {
+            return Err(MessageDecodeError::MissingRequiredFields {
+                paths: self
+                    .missing_required_fields()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Decoder<'_> {
+    fn merge_message(
+        &mut self,
+        message: &mut DynamicMessage,
+        buf: &mut Bytes,
+        group: Option<u32>,
+    ) -> Result<(), MessageDecodeError> {
+        loop {
+            if group.is_none() && !buf.has_remaining() {
+                return Ok(());
+            }
+
+            let (number, wire_type) = encoding::decode_key(buf).map_err(|e| self.error(e))?;
+            if wire_type == WireType::EndGroup {
+                if group == Some(number) {
+                    return Ok(());
+                }
+
+                encoding::skip_field(wire_type, number, buf, DecodeContext::default())
+                    .map_err(|e| self.error(e))?;
+                unreachable!()
+            }
+
+            if let Some(field_desc) = message.desc.get_field(number) {
+                self.path.push(PathSegment::Field(field_desc.clone()));
+                self.merge_field(message, &field_desc, wire_type, buf)?;
+                self.path.pop();
+            } else if let Some(extension_desc) = message.desc.get_extension(number) {
+                self.path
+                    .push(PathSegment::Extension(extension_desc.clone()));
+                self.merge_field(message, &extension_desc, wire_type, buf)?;
+                self.path.pop();
+            } else {
+                match self.options.unknown_fields {
+                    UnknownFieldPolicy::Preserve => {
+                        let field = UnknownField::decode_value(
+                            number,
+                            wire_type,
+                            buf,
+                            DecodeContext::default(),
+                        )
+                        .map_err(|e| self.error(e))?;
+                        message.fields.add_unknown(number, field);
+                    }
+                    UnknownFieldPolicy::Discard => {
+                        encoding::skip_field(wire_type, number, buf, DecodeContext::default())
+                            .map_err(|e| self.error(e))?;
+                    }
+                    UnknownFieldPolicy::Error => {
+                        return Err(MessageDecodeError::UnknownField {
+                            message: message.desc.full_name().to_owned(),
+                            path: self.path.to_string(),
+                            number,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    fn merge_field(
+        &mut self,
+        message: &mut DynamicMessage,
+        field_desc: &impl FieldDescriptorLike,
+        wire_type: WireType,
+        buf: &mut Bytes,
+    ) -> Result<(), MessageDecodeError> {
+        if self.options.preserve_invalid_utf8
+            && wire_type == WireType::LengthDelimited
+            && matches!(field_desc.kind(), Kind::String)
+            && !field_desc.is_map()
+            && field_desc.features().utf8_validation() == Utf8Validation::None
+        {
+            let field = UnknownField::decode_value(
+                field_desc.number(),
+                wire_type,
+                buf,
+                DecodeContext::default(),
+            )
+            .map_err(|e| self.error(e))?;
+            let value = match field.value() {
+                UnknownFieldValue::LengthDelimited(bytes) => std::str::from_utf8(bytes).ok(),
+                _ => None,
+            };
+
+            match value {
+                Some(value) if field_desc.is_list() && !message.fields.is_unknown(field_desc.number()) => {
+                    match message.fields.get_mut(field_desc) {
+                        Value::List(values) => values.push(Value::String(value.to_owned())),
+                        value => panic!("mismatch between DynamicMessage value {value:?} and type {field_desc:?}"),
+                    }
+                }
+                Some(value) if !field_desc.is_list() => {
+                    message
+                        .fields
+                        .set(field_desc, Value::String(value.to_owned()));
+                }
+                _ => message.fields.add_unknown_for_field(field_desc, field),
+            }
+            return Ok(());
+        }
+
+        if let Some(enum_desc) = closed_enum_type(field_desc) {
+            return message
+                .merge_closed_enum_field(
+                    field_desc,
+                    &enum_desc,
+                    wire_type,
+                    buf,
+                    DecodeContext::default(),
+                )
+                .map_err(|e| self.error(e));
+        }
+
+        let value = message.fields.get_mut(field_desc);
+        self.merge_value(value, field_desc, wire_type, buf)
+    }
+
+    fn merge_value(
+        &mut self,
+        value: &mut Value,
+        field_desc: &impl FieldDescriptorLike,
+        wire_type: WireType,
+        buf: &mut Bytes,
+    ) -> Result<(), MessageDecodeError> {
+        match (value, field_desc.kind()) {
+            (Value::Message(message), Kind::Message(_)) => {
+                self.merge_nested_message(message, field_desc, wire_type, buf)
+            }
+            (Value::List(values), Kind::Message(message_desc)) if field_desc.is_list() => {
+                let mut message = DynamicMessage::new(message_desc);
+                self.path.push(PathSegment::Index(values.len()));
+                self.merge_nested_message(&mut message, field_desc, wire_type, buf)?;
+                self.path.pop();
+                values.push(Value::Message(message));
+                Ok(())
+            }
+            (Value::Map(values), Kind::Message(map_entry)) if field_desc.is_map() => {
+                self.merge_map_entry(values, &map_entry, wire_type, buf)
+            }
+            (value, _) => value
+                .merge_field(field_desc, wire_type, buf, DecodeContext::default())
+                .map_err(|e| self.error(e)),
+        }
+    }
+
+    fn merge_nested_message(
+        &mut self,
+        message: &mut DynamicMessage,
+        field_desc: &impl FieldDescriptorLike,
+        wire_type: WireType,
+        buf: &mut Bytes,
+    ) -> Result<(), MessageDecodeError> {
+        if self.depth >= self.options.recursion_limit {
+            return Err(MessageDecodeError::RecursionLimitReached {
+                path: self.path.to_string(),
+            });
+        }
+
+        if field_desc.is_group() {
+            encoding::check_wire_type(WireType::StartGroup, wire_type)
+                .map_err(|e| self.error(e))?;
+
+            self.depth += 1;
+            self.merge_message(message, buf, Some(field_desc.number()))?;
+            self.depth -= 1;
+        } else {
+            let mut bytes = Bytes::new();
+            encoding::bytes::merge(wire_type, &mut bytes, buf, DecodeContext::default())
+                .map_err(|e| self.error(e))?;
+
+            self.depth += 1;
+            self.merge_message(message, &mut bytes, None)?;
+            self.depth -= 1;
+        }
+
+        Ok(())
+    }
+
+    fn merge_map_entry(
+        &mut self,
+        values: &mut HashMap<MapKey, Value>,
+        map_entry: &MessageDescriptor,
+        wire_type: WireType,
+        buf: &mut Bytes,
+    ) -> Result<(), MessageDecodeError> {
+        let key_desc = map_entry.map_entry_key_field();
+        let value_desc = map_entry.map_entry_value_field();
+
+        let mut bytes = Bytes::new();
+        encoding::bytes::merge(wire_type, &mut bytes, buf, DecodeContext::default())
+            .map_err(|e| self.error(e))?;
+
+        let mut key = MapKey::default_value(&key_desc.kind());
+        let mut value = Value::default_value_for_field(&value_desc);
+        while bytes.has_remaining() {
+            let (number, wire_type) =
+                encoding::decode_key(&mut bytes).map_err(|e| self.error(e))?;
+            match number {
+                MAP_ENTRY_KEY_NUMBER => key
+                    .merge_field(&key_desc, wire_type, &mut bytes, DecodeContext::default())
+                    .map_err(|e| self.error(e))?,
+                MAP_ENTRY_VALUE_NUMBER => {
+                    self.path.push(PathSegment::Key(key.clone()));
+                    self.merge_value(&mut value, &value_desc, wire_type, &mut bytes)?;
+                    self.path.pop();
+                }
+                _ => encoding::skip_field(wire_type, number, &mut bytes, DecodeContext::default())
+                    .map_err(|e| self.error(e))?,
+            }
+        }
+
+        values.insert(key, value);
+        Ok(())
+    }
+
+    fn error(&self, error: DecodeError) -> MessageDecodeError {
+        MessageDecodeError::Decode {
+            path: self.path.to_string(),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for MessageDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageDecodeError::Decode { path, error } if path.is_empty() => {
+                write!(f, "failed to decode message: {error}")
+            }
+            MessageDecodeError::Decode { path, error } => {
+                write!(f, "failed to decode '{path}': {error}")
+            }
+            MessageDecodeError::UnknownField {
+                message,
+                path,
+                number,
+            } => {
+                write!(f, "unknown field {number} in message '{message}'")?;
+                if !path.is_empty() {
+                    write!(f, " at '{path}'")?;
+                }
+                Ok(())
+            }
+            MessageDecodeError::RecursionLimitReached { path } => {
+                write!(f, "recursion limit reached at '{path}'")
+            }
+            MessageDecodeError::MessageTooLarge { len, limit } => {
+                write!(
+                    f,
+                    "message of {len} bytes exceeds the maximum size of {limit} bytes"
+                )
+            }
+            MessageDecodeError::MissingRequiredFields { paths } => {
+                write!(f, "{}", MissingRequiredFields(paths))
+            }
+        }
+    }
+}
+
+impl Error for MessageDecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MessageDecodeError::Decode { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
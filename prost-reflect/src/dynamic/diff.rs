@@ -0,0 +1,534 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    DynamicMessage, ExtensionDescriptor, FieldDescriptor, FieldPath, MapKey, PathSegment,
+    UnknownField, Value,
+};
+
+/// Options to control the behavior of [`DynamicMessage::diff()`].
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    repeated_as_set: bool,
+    ignored_fields: HashSet<String>,
+    float_tolerance: f64,
+}
+
+/// The differences between two messages, as returned by [`DynamicMessage::diff()`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    changes: Vec<Change>,
+}
+
+/// A single difference between two messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    path: FieldPath,
+    kind: ChangeKind,
+}
+
+/// The kind of a [`Change`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// A field or map entry is set in the new message, but not the old.
+    Added(Value),
+    /// A field or map entry is set in the old message, but not the new.
+    Removed(Value),
+    /// A value is different between the old and new message.
+    ///
+    /// This is only used for scalar values, since changes to nested messages are reported for
+    /// their individual fields.
+    Modified {
+        /// The value in the old message.
+        old: Value,
+        /// The value in the new message.
+        new: Value,
+    },
+    /// An element was inserted into a list. The index in the path refers to the new list.
+    Inserted(Value),
+    /// An element was deleted from a list. The index in the path refers to the old list.
+    Deleted(Value),
+    /// An unknown field is present in the new message, but not the old. The path refers to
+    /// the message containing the field.
+    UnknownAdded(UnknownField),
+    /// An unknown field is present in the old message, but not the new. The path refers to
+    /// the message containing the field.
+    UnknownRemoved(UnknownField),
+}
+
+impl DynamicMessage {
+    /// Computes the differences between this message and `other`.
+    ///
+    /// Changes are reported relative to this message, so for example
+    /// [`Added`](ChangeKind::Added) refers to a field which is set in `other` but not in this
+    /// message. Changes to nested messages, lists and maps are reported at the most specific
+    /// path possible. Fields are visited in the order they are declared.
+    ///
+    /// If the messages have different types, a single [`Modified`](ChangeKind::Modified)
+    /// change with an empty path is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{diff::DiffOptions, DescriptorPool, DynamicMessage, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut old = DynamicMessage::new(message_descriptor.clone());
+    /// old.set_field_by_name("foo", Value::I32(1));
+    ///
+    /// let mut new = old.clone();
+    /// new.set_field_by_name("foo", Value::I32(2));
+    /// new.get_field_by_name_mut("nested").unwrap().as_message_mut().unwrap()
+    ///     .set_field_by_name("bar", Value::I32(3));
+    ///
+    /// let diff = old.diff(&new, &DiffOptions::new());
+    /// assert_eq!(diff.to_string(), "~ foo: 1 -> 2\n+ nested: {bar:3}\n");
+    /// ```
+    pub fn diff(&self, other: &DynamicMessage, options: &DiffOptions) -> Diff {
+        let mut differ = Differ::new(options);
+        if self.desc == other.desc {
+            differ.diff_message(self, other);
+        } else {
+            differ.push(ChangeKind::Modified {
+                old: Value::Message(self.clone()),
+                new: Value::Message(other.clone()),
+            });
+        }
+
+        Diff {
+            changes: differ.changes,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Creates a new instance of [`DiffOptions`], with the default options chosen to report
+    /// every difference between the messages.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Whether to compare repeated fields as unordered collections.
+    ///
+    /// If enabled, elements which appear in both lists are not reported, regardless of their
+    /// position. Otherwise, lists are compared in order, and a minimal set of insertions and
+    /// deletions is reported. If the lists are too long to align efficiently, elements are
+    /// instead compared by position.
+    ///
+    /// The default value is `false`.
+    pub fn repeated_as_set(mut self, yes: bool) -> Self {
+        self.repeated_as_set = yes;
+        self
+    }
+
+    /// Ignores any differences in the given field.
+    pub fn ignore_field(mut self, field: &FieldDescriptor) -> Self {
+        self.ignored_fields.insert(field.full_name().to_owned());
+        self
+    }
+
+    /// Ignores any differences in the given extension field.
+    pub fn ignore_extension(mut self, extension: &ExtensionDescriptor) -> Self {
+        self.ignored_fields.insert(extension.full_name().to_owned());
+        self
+    }
+
+    /// The maximum absolute difference between two `float` or `double` values for them to be
+    /// considered equal.
+    ///
+    /// Regardless of this option, `NaN` values are considered equal to each other.
+    ///
+    /// The default value is `0.0`.
+    pub fn float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = tolerance;
+        self
+    }
+}
+
+impl Diff {
+    /// Returns `true` if the messages are equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Gets the list of changes between the messages.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Converts this diff into the list of changes between the messages.
+    pub fn into_changes(self) -> Vec<Change> {
+        self.changes
+    }
+}
+
+impl fmt::Display for Diff {
+    /// Formats this diff with one change per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Change {
+    /// Gets the path of the value which changed.
+    pub fn path(&self) -> &FieldPath {
+        &self.path
+    }
+
+    /// Gets the kind of change.
+    pub fn kind(&self) -> &ChangeKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Change {
+    /// Formats this change as a single line, prefixed with `+`, `-` or `~`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, separator) = if self.path.is_empty() {
+            ("", "")
+        } else {
+            (".", ": ")
+        };
+        let path = &self.path;
+
+        match &self.kind {
+            ChangeKind::Added(value) | ChangeKind::Inserted(value) => {
+                write!(f, "+ {path}{separator}{value}")
+            }
+            ChangeKind::Removed(value) | ChangeKind::Deleted(value) => {
+                write!(f, "- {path}{separator}{value}")
+            }
+            ChangeKind::Modified { old, new } => write!(f, "~ {path}{separator}{old} -> {new}"),
+            ChangeKind::UnknownAdded(field) => write!(f, "+ {path}{prefix}{field}"),
+            ChangeKind::UnknownRemoved(field) => write!(f, "- {path}{prefix}{field}"),
+        }
+    }
+}
+
+/// The maximum size of the table used to align two lists. Beyond this, elements are compared
+/// by position.
+const MAX_ALIGN_CELLS: usize = 1 << 20;
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    path: FieldPath,
+    changes: Vec<Change>,
+}
+
+/// A step in the alignment of two lists.
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+impl<'a> Differ<'a> {
+    fn new(options: &'a DiffOptions) -> Self {
+        Differ {
+            options,
+            path: FieldPath::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    fn push_at(&mut self, segment: PathSegment, kind: ChangeKind) {
+        self.path.push(segment);
+        self.push(kind);
+        self.path.pop();
+    }
+
+    fn is_ignored(&self, full_name: &str) -> bool {
+        self.options.ignored_fields.contains(full_name)
+    }
+
+    fn is_equal(&mut self, old: &Value, new: &Value) -> bool {
+        let len = self.changes.len();
+        self.diff_value(old, new);
+        let equal = self.changes.len() == len;
+        self.changes.truncate(len);
+        equal
+    }
+
+    fn diff_message(&mut self, old: &DynamicMessage, new: &DynamicMessage) {
+        for field in old.desc.fields() {
+            if self.is_ignored(field.full_name()) {
+                continue;
+            }
+
+            self.path.push(PathSegment::Field(field.clone()));
+            if field.is_list() || field.is_map() || !field.supports_presence() {
+                self.diff_value(&old.get_field(&field), &new.get_field(&field));
+            } else {
+                self.diff_optional(
+                    old.has_field(&field)
+                        .then(|| old.get_field(&field))
+                        .as_deref(),
+                    new.has_field(&field)
+                        .then(|| new.get_field(&field))
+                        .as_deref(),
+                );
+            }
+            self.path.pop();
+        }
+
+        let mut extensions: BTreeMap<u32, (ExtensionDescriptor, Option<&Value>, Option<&Value>)> =
+            BTreeMap::new();
+        for (extension, value) in old.extensions() {
+            extensions.insert(extension.number(), (extension, Some(value), None));
+        }
+        for (extension, value) in new.extensions() {
+            extensions
+                .entry(extension.number())
+                .or_insert_with(|| (extension, None, None))
+                .2 = Some(value);
+        }
+        for (extension, old_value, new_value) in extensions.into_values() {
+            if self.is_ignored(extension.full_name()) {
+                continue;
+            }
+
+            self.path.push(PathSegment::Extension(extension.clone()));
+            if extension.is_list() || extension.is_map() {
+                let default = Value::default_value_for_extension(&extension);
+                self.diff_value(old_value.unwrap_or(&default), new_value.unwrap_or(&default));
+            } else {
+                self.diff_optional(old_value, new_value);
+            }
+            self.path.pop();
+        }
+
+        self.diff_unknown_fields(
+            old.unknown_fields().collect(),
+            new.unknown_fields().collect(),
+        );
+    }
+
+    fn diff_optional(&mut self, old: Option<&Value>, new: Option<&Value>) {
+        match (old, new) {
+            (None, None) => (),
+            (Some(old), None) => self.push(ChangeKind::Removed(old.clone())),
+            (None, Some(new)) => self.push(ChangeKind::Added(new.clone())),
+            (Some(old), Some(new)) => self.diff_value(old, new),
+        }
+    }
+
+    fn diff_value(&mut self, old: &Value, new: &Value) {
+        match (old, new) {
+            (Value::Message(old), Value::Message(new)) if old.desc == new.desc => {
+                self.diff_message(old, new)
+            }
+            (Value::List(old), Value::List(new)) => {
+                if self.options.repeated_as_set {
+                    self.diff_set(old, new)
+                } else {
+                    self.diff_list(old, new)
+                }
+            }
+            (Value::Map(old), Value::Map(new)) => self.diff_map(old, new),
+            (Value::F32(old_float), Value::F32(new_float)) => {
+                if !self.floats_equal(f64::from(*old_float), f64::from(*new_float)) {
+                    self.push(ChangeKind::Modified {
+                        old: old.clone(),
+                        new: new.clone(),
+                    });
+                }
+            }
+            (Value::F64(old_float), Value::F64(new_float)) => {
+                if !self.floats_equal(*old_float, *new_float) {
+                    self.push(ChangeKind::Modified {
+                        old: old.clone(),
+                        new: new.clone(),
+                    });
+                }
+            }
+            _ => {
+                if old != new {
+                    self.push(ChangeKind::Modified {
+                        old: old.clone(),
+                        new: new.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn floats_equal(&self, old: f64, new: f64) -> bool {
+        (old.is_nan() && new.is_nan())
+            || old == new
+            || (old - new).abs() <= self.options.float_tolerance
+    }
+
+    fn diff_list(&mut self, old: &[Value], new: &[Value]) {
+        let prefix = old
+            .iter()
+            .zip(new)
+            .take_while(|(old, new)| self.is_equal(old, new))
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(old, new)| self.is_equal(old, new))
+            .count();
+
+        let edits = self.align(
+            &old[prefix..old.len() - suffix],
+            &new[prefix..new.len() - suffix],
+        );
+
+        // Deletions immediately followed by insertions are paired up and reported as changes to
+        // the elements, so that edits to nested messages are shown at the most specific path.
+        let mut old_index = prefix;
+        let mut new_index = prefix;
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        for edit in edits.into_iter().chain([Edit::Keep]) {
+            match edit {
+                Edit::Delete => {
+                    deleted.push(old_index);
+                    old_index += 1;
+                }
+                Edit::Insert => {
+                    inserted.push(new_index);
+                    new_index += 1;
+                }
+                Edit::Keep => {
+                    let paired = deleted.len().min(inserted.len());
+                    for (&old_index, &new_index) in deleted.iter().zip(&inserted) {
+                        self.path.push(PathSegment::Index(new_index));
+                        self.diff_value(&old[old_index], &new[new_index]);
+                        self.path.pop();
+                    }
+                    for &old_index in &deleted[paired..] {
+                        self.push_at(
+                            PathSegment::Index(old_index),
+                            ChangeKind::Deleted(old[old_index].clone()),
+                        );
+                    }
+                    for &new_index in &inserted[paired..] {
+                        self.push_at(
+                            PathSegment::Index(new_index),
+                            ChangeKind::Inserted(new[new_index].clone()),
+                        );
+                    }
+                    deleted.clear();
+                    inserted.clear();
+                    old_index += 1;
+                    new_index += 1;
+                }
+            }
+        }
+    }
+
+    /// Finds the longest common subsequence of two lists, and returns the edits needed to
+    /// transform `old` into `new`.
+    ///
+    /// If the lists are too long, every element is deleted and reinserted, so that the caller
+    /// pairs them up by position.
+    fn align(&mut self, old: &[Value], new: &[Value]) -> Vec<Edit> {
+        if old.len().saturating_mul(new.len()) > MAX_ALIGN_CELLS {
+            let mut edits = Vec::with_capacity(old.len() + new.len());
+            edits.extend(old.iter().map(|_| Edit::Delete));
+            edits.extend(new.iter().map(|_| Edit::Insert));
+            return edits;
+        }
+
+        let width = new.len() + 1;
+        let mut lengths = vec![0usize; (old.len() + 1) * width];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i * width + j] = if self.is_equal(&old[i], &new[j]) {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let mut edits = Vec::with_capacity(old.len() + new.len());
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() && j < new.len() {
+            if lengths[i * width + j] == lengths[(i + 1) * width + j + 1] + 1
+                && self.is_equal(&old[i], &new[j])
+            {
+                edits.push(Edit::Keep);
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                edits.push(Edit::Delete);
+                i += 1;
+            } else {
+                edits.push(Edit::Insert);
+                j += 1;
+            }
+        }
+        edits.extend((i..old.len()).map(|_| Edit::Delete));
+        edits.extend((j..new.len()).map(|_| Edit::Insert));
+        edits
+    }
+
+    fn diff_set(&mut self, old: &[Value], new: &[Value]) {
+        let mut matched = vec![false; new.len()];
+        for (old_index, old_value) in old.iter().enumerate() {
+            let found = (0..new.len()).find(|&new_index| {
+                !matched[new_index] && self.is_equal(old_value, &new[new_index])
+            });
+            match found {
+                Some(new_index) => matched[new_index] = true,
+                None => self.push_at(
+                    PathSegment::Index(old_index),
+                    ChangeKind::Deleted(old_value.clone()),
+                ),
+            }
+        }
+
+        for (new_index, new_value) in new.iter().enumerate() {
+            if !matched[new_index] {
+                self.push_at(
+                    PathSegment::Index(new_index),
+                    ChangeKind::Inserted(new_value.clone()),
+                );
+            }
+        }
+    }
+
+    fn diff_map(&mut self, old: &HashMap<MapKey, Value>, new: &HashMap<MapKey, Value>) {
+        let mut keys: Vec<&MapKey> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            self.path.push(PathSegment::Key(key.clone()));
+            self.diff_optional(old.get(key), new.get(key));
+            self.path.pop();
+        }
+    }
+
+    fn diff_unknown_fields(&mut self, old: Vec<&UnknownField>, new: Vec<&UnknownField>) {
+        let mut matched = vec![false; new.len()];
+        for old_field in old {
+            match (0..new.len()).find(|&index| !matched[index] && new[index] == old_field) {
+                Some(index) => matched[index] = true,
+                None => self.push(ChangeKind::UnknownRemoved(old_field.clone())),
+            }
+        }
+
+        for (index, new_field) in new.into_iter().enumerate() {
+            if !matched[index] {
+                self.push(ChangeKind::UnknownAdded(new_field.clone()));
+            }
+        }
+    }
+}
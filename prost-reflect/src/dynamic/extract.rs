@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+
+use prost::{
+    bytes::{Buf, Bytes},
+    encoding::{self, DecodeContext, WireType},
+    DecodeError,
+};
+
+use crate::{
+    dynamic::path::{FieldPath, PathError, PathSegment},
+    ExtensionDescriptor, FieldDescriptor, Kind, MessageDescriptor, Value,
+};
+
+/// Extracts the values at a fixed set of paths from encoded messages, without decoding the rest
+/// of the message.
+///
+/// Fields which are not on any of the paths are skipped at the wire level, in the same way as
+/// unknown fields, so extracting a few fields from a large message is much cheaper than decoding
+/// it into a [`DynamicMessage`](crate::DynamicMessage) and looking them up. The paths are
+/// resolved when the extractor is created, so it can be reused for many messages of the same
+/// type.
+///
+/// Paths use the [`FieldPath` syntax](FieldPath#syntax), but may only contain field names and
+/// extensions. Every segment except the last must refer to a singular message field, while the
+/// last segment may refer to a field of any type.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{DescriptorPool, FieldExtractor, Value};
+/// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+/// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+/// let extractor = FieldExtractor::new(message_descriptor, ["foo", "nested.bar", "nested.foo"]).unwrap();
+///
+/// let values = extractor.extract(b"\x08\x96\x01\x1a\x02\x10\x42".as_ref()).unwrap();
+/// assert_eq!(values, [Some(Value::I32(150)), Some(Value::I32(66)), None]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldExtractor {
+    desc: MessageDescriptor,
+    paths: Vec<FieldPath>,
+    root: Node,
+}
+
+/// A node in the tree of requested paths.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// The field this node refers to, or `None` for the root message.
+    field: Option<Field>,
+    /// The indices of the paths which end at this node.
+    targets: Vec<usize>,
+    /// The nodes for fields of this message, if it is on the path to another field.
+    children: BTreeMap<u32, Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Field {
+    Field(FieldDescriptor),
+    Extension(ExtensionDescriptor),
+}
+
+impl FieldExtractor {
+    /// Creates an extractor for the given paths in messages of the type specified by the
+    /// [`MessageDescriptor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any path is not valid for the message type, or if a segment other
+    /// than the last does not refer to a singular message field.
+    pub fn new<I>(desc: MessageDescriptor, paths: I) -> Result<Self, PathError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut root = Node::default();
+        let mut parsed = Vec::new();
+        for (index, path) in paths.into_iter().enumerate() {
+            let path = FieldPath::parse(&desc, path.as_ref())?;
+
+            let mut node = &mut root;
+            for (len, segment) in path.segments().iter().enumerate() {
+                if !node.is_singular_message() {
+                    return Err(PathError::NotAMessage {
+                        path: path.prefix(len),
+                    });
+                }
+
+                let field = match segment {
+                    PathSegment::Field(field) => Field::Field(field.clone()),
+                    PathSegment::Extension(extension) => Field::Extension(extension.clone()),
+                    PathSegment::Index(_) | PathSegment::Key(_) => {
+                        return Err(PathError::NotAMessage {
+                            path: path.prefix(len),
+                        })
+                    }
+                };
+
+                node = node.children.entry(field.number()).or_insert_with(|| Node {
+                    field: Some(field),
+                    targets: Vec::new(),
+                    children: BTreeMap::new(),
+                });
+            }
+
+            node.targets.push(index);
+            parsed.push(path);
+        }
+
+        Ok(FieldExtractor {
+            desc,
+            paths: parsed,
+            root,
+        })
+    }
+
+    /// Gets a reference to the [`MessageDescriptor`] describing the type of messages this
+    /// extractor accepts.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.desc
+    }
+
+    /// Gets the paths extracted by this extractor, in the order they were given.
+    pub fn paths(&self) -> &[FieldPath] {
+        &self.paths
+    }
+
+    /// Extracts the values at each path from an encoded message.
+    ///
+    /// The returned vector has one entry for each path, in the order they were given to
+    /// [`new`][Self::new]. An entry is `None` if its field does not occur in the encoded bytes,
+    /// even if it would have a default value in a decoded message.
+    ///
+    /// Otherwise, occurrences of each field are merged in the same way as
+    /// [`DynamicMessage::decode`](crate::DynamicMessage::decode): repeated and map fields collect
+    /// every occurrence, nested messages are merged, and the last occurrence of any other field
+    /// takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fields along each path are not validly encoded. Since other fields
+    /// are only skipped, errors in their values may not be detected.
+    pub fn extract<B>(&self, mut buf: B) -> Result<Vec<Option<Value>>, DecodeError>
+    where
+        B: Buf,
+    {
+        let mut buf = buf.copy_to_bytes(buf.remaining());
+        let mut values = vec![None; self.paths.len()];
+
+        if !self.root.children.is_empty() {
+            self.root.extract_message(&mut buf, None, &mut values)?;
+        }
+
+        Ok(values)
+    }
+}
+
+impl Node {
+    fn is_singular_message(&self) -> bool {
+        match &self.field {
+            None => true,
+            Some(field) => {
+                !field.is_list() && !field.is_map() && matches!(field.kind(), Kind::Message(_))
+            }
+        }
+    }
+
+    /// Scans the fields of a message, extracting the values of any children of this node.
+    fn extract_message(
+        &self,
+        buf: &mut Bytes,
+        group: Option<u32>,
+        values: &mut [Option<Value>],
+    ) -> Result<(), DecodeError> {
+        while group.is_some() || buf.has_remaining() {
+            let (number, wire_type) = encoding::decode_key(buf)?;
+            if wire_type == WireType::EndGroup && group == Some(number) {
+                break;
+            }
+
+            match self.children.get(&number) {
+                Some(child) => child.extract_field(wire_type, buf, values)?,
+                None => encoding::skip_field(wire_type, number, buf, DecodeContext::default())?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the values for a single occurrence of the field this node refers to.
+    fn extract_field(
+        &self,
+        wire_type: WireType,
+        buf: &mut Bytes,
+        values: &mut [Option<Value>],
+    ) -> Result<(), DecodeError> {
+        let field = self.field.as_ref().expect("root node has no field");
+        let start = buf.clone();
+
+        for &index in &self.targets {
+            *buf = start.clone();
+            let value = values[index].get_or_insert_with(|| field.default_value());
+            field.merge_value(value, wire_type, buf)?;
+        }
+
+        if !self.children.is_empty() {
+            *buf = start;
+            if field.is_group() {
+                encoding::check_wire_type(WireType::StartGroup, wire_type)?;
+                self.extract_message(buf, Some(field.number()), values)?;
+            } else {
+                let mut message = Bytes::new();
+                encoding::bytes::merge(wire_type, &mut message, buf, DecodeContext::default())?;
+                self.extract_message(&mut message, None, values)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Field {
+    fn merge_value(
+        &self,
+        value: &mut Value,
+        wire_type: WireType,
+        buf: &mut Bytes,
+    ) -> Result<(), DecodeError> {
+        match self {
+            Field::Field(field) => value.merge_field(field, wire_type, buf, Default::default()),
+            Field::Extension(extension) => {
+                value.merge_field(extension, wire_type, buf, Default::default())
+            }
+        }
+    }
+
+    fn number(&self) -> u32 {
+        match self {
+            Field::Field(field) => field.number(),
+            Field::Extension(extension) => extension.number(),
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            Field::Field(field) => field.kind(),
+            Field::Extension(extension) => extension.kind(),
+        }
+    }
+
+    fn default_value(&self) -> Value {
+        match self {
+            Field::Field(field) => Value::default_value_for_field(field),
+            Field::Extension(extension) => Value::default_value_for_extension(extension),
+        }
+    }
+
+    fn is_group(&self) -> bool {
+        match self {
+            Field::Field(field) => field.is_group(),
+            Field::Extension(extension) => extension.is_group(),
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        match self {
+            Field::Field(field) => field.is_list(),
+            Field::Extension(extension) => extension.is_list(),
+        }
+    }
+
+    fn is_map(&self) -> bool {
+        match self {
+            Field::Field(field) => field.is_map(),
+            Field::Extension(extension) => extension.is_map(),
+        }
+    }
+}
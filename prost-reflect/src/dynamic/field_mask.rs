@@ -0,0 +1,572 @@
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use prost::Message;
+use prost_types::FieldMask;
+
+use crate::{DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, Value};
+
+/// Options to control the behavior of [`DynamicMessage::merge_with_mask()`].
+///
+/// By default, fields are merged using the usual protobuf semantics: repeated fields and maps
+/// are appended to, message fields are merged recursively, and other fields are only overwritten
+/// if they are set in the source message.
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    replace_message_fields: bool,
+    replace_repeated_fields: bool,
+    replace_primitive_fields: bool,
+}
+
+/// An error returned when a [`FieldMask`] is not valid for a message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMaskError {
+    kind: FieldMaskErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldMaskErrorKind {
+    EmptyPath,
+    FieldNotFound { path: String, message: String },
+    NotAMessage { path: String, field: String },
+    RepeatedField { path: String, field: String },
+    MessageMismatch { expected: String, actual: String },
+}
+
+/// A field mask, normalized into a tree of field names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct MaskTree {
+    children: BTreeMap<String, MaskNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MaskNode {
+    /// The whole field is included in the mask.
+    Leaf,
+    /// Only the given subfields of the field are included in the mask.
+    Branch(MaskTree),
+}
+
+/// Checks that every path in the mask is valid for the given message type.
+///
+/// Each path must be a sequence of field names separated by `.`, where every field except the
+/// last is a singular message field. Note that field names are used rather than JSON names.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{field_mask, DescriptorPool};
+/// # use prost_types::FieldMask;
+/// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+/// let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+/// let mask = FieldMask { paths: vec!["foo".to_owned(), "nested.bar".to_owned()] };
+/// assert!(field_mask::validate(&mask, &message_descriptor).is_ok());
+///
+/// let mask = FieldMask { paths: vec!["foo.bar".to_owned()] };
+/// assert_eq!(
+///     field_mask::validate(&mask, &message_descriptor).unwrap_err().to_string(),
+///     "invalid field mask path 'foo.bar': field 'foo' is not a message",
+/// );
+/// ```
+pub fn validate(mask: &FieldMask, desc: &MessageDescriptor) -> Result<(), FieldMaskError> {
+    MaskTree::new(mask)?.validate(desc, "")
+}
+
+/// Normalizes a field mask.
+///
+/// The paths of the returned mask are sorted, with duplicates and empty paths removed. Any
+/// path covered by another path in the mask (for example `a.b` is covered by `a`) is also
+/// removed.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::field_mask;
+/// # use prost_types::FieldMask;
+/// let mask = FieldMask { paths: vec!["b".to_owned(), "a.b".to_owned(), "a".to_owned(), "b".to_owned()] };
+/// assert_eq!(field_mask::normalize(&mask).paths, ["a", "b"]);
+/// ```
+pub fn normalize(mask: &FieldMask) -> FieldMask {
+    MaskTree::from_paths(&mask.paths).to_field_mask()
+}
+
+/// Gets the union of two field masks, containing every path covered by either mask.
+///
+/// The returned mask is [normalized](normalize).
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::field_mask;
+/// # use prost_types::FieldMask;
+/// let a = FieldMask { paths: vec!["a.b".to_owned(), "c".to_owned()] };
+/// let b = FieldMask { paths: vec!["a".to_owned(), "d".to_owned()] };
+/// assert_eq!(field_mask::union(&a, &b).paths, ["a", "c", "d"]);
+/// ```
+pub fn union(a: &FieldMask, b: &FieldMask) -> FieldMask {
+    MaskTree::from_paths(a.paths.iter().chain(&b.paths)).to_field_mask()
+}
+
+/// Gets the intersection of two field masks, containing only the paths covered by both masks.
+///
+/// The returned mask is [normalized](normalize).
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::field_mask;
+/// # use prost_types::FieldMask;
+/// let a = FieldMask { paths: vec!["a.b".to_owned(), "c".to_owned()] };
+/// let b = FieldMask { paths: vec!["a".to_owned(), "d".to_owned()] };
+/// assert_eq!(field_mask::intersect(&a, &b).paths, ["a.b"]);
+/// ```
+pub fn intersect(a: &FieldMask, b: &FieldMask) -> FieldMask {
+    MaskTree::from_paths(&a.paths)
+        .intersect(&MaskTree::from_paths(&b.paths))
+        .to_field_mask()
+}
+
+impl DynamicMessage {
+    /// Clears all fields of this message which are not covered by the given field mask.
+    ///
+    /// If a path refers to a field of a nested message, the nested message is kept, and its
+    /// fields are cleared recursively. Extension fields and unknown fields are always cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mask is not [valid](crate::field_mask::validate) for the type of this
+    /// message, in which case the message is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, DynamicMessage, Value};
+    /// # use prost_types::FieldMask;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut message = DynamicMessage::new(message_descriptor.clone());
+    /// message.set_field_by_name("foo", Value::I32(1));
+    /// message.set_field_by_name("bar", Value::I32(2));
+    ///
+    /// message.apply_mask(&FieldMask { paths: vec!["foo".to_owned()] }).unwrap();
+    /// assert!(message.has_field_by_name("foo"));
+    /// assert!(!message.has_field_by_name("bar"));
+    /// ```
+    pub fn apply_mask(&mut self, mask: &FieldMask) -> Result<(), FieldMaskError> {
+        let tree = MaskTree::new(mask)?;
+        tree.validate(&self.desc, "")?;
+        tree.apply(self);
+        Ok(())
+    }
+
+    /// Merges the fields of `src` covered by the given field mask into this message.
+    ///
+    /// If a path refers to a field of a nested message, the nested message in this message is
+    /// created if necessary, and only the given field is merged into it. The way individual
+    /// fields are merged is controlled by [`MergeOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `src` is a different message type to this message, or if the mask
+    /// is not [valid](crate::field_mask::validate) for the message type. In this case the
+    /// message is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{field_mask::MergeOptions, DescriptorPool, DynamicMessage, Value};
+    /// # use prost_types::FieldMask;
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut message = DynamicMessage::new(message_descriptor.clone());
+    /// message.set_field_by_name("foo", Value::I32(1));
+    ///
+    /// let mut update = DynamicMessage::new(message_descriptor.clone());
+    /// update.set_field_by_name("bar", Value::I32(2));
+    ///
+    /// let mask = FieldMask { paths: vec!["foo".to_owned(), "bar".to_owned()] };
+    /// message.merge_with_mask(&update, &mask, &MergeOptions::new()).unwrap();
+    /// assert_eq!(message.get_field_by_name("foo").unwrap().as_i32(), Some(1));
+    /// assert_eq!(message.get_field_by_name("bar").unwrap().as_i32(), Some(2));
+    ///
+    /// let options = MergeOptions::new().replace_primitive_fields(true);
+    /// message.merge_with_mask(&update, &mask, &options).unwrap();
+    /// assert!(!message.has_field_by_name("foo"));
+    /// ```
+    pub fn merge_with_mask(
+        &mut self,
+        src: &DynamicMessage,
+        mask: &FieldMask,
+        options: &MergeOptions,
+    ) -> Result<(), FieldMaskError> {
+        if self.desc != src.desc {
+            return Err(FieldMaskError::new(FieldMaskErrorKind::MessageMismatch {
+                expected: self.desc.full_name().to_owned(),
+                actual: src.desc.full_name().to_owned(),
+            }));
+        }
+
+        let tree = MaskTree::new(mask)?;
+        tree.validate(&self.desc, "")?;
+        tree.merge(self, src, options);
+        Ok(())
+    }
+}
+
+impl MergeOptions {
+    /// Creates a new instance of [`MergeOptions`], with the default options chosen to match the
+    /// usual protobuf merge semantics.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Whether to replace message fields in the destination with the value from the source,
+    /// instead of merging them.
+    ///
+    /// If enabled, message fields which are unset in the source are cleared in the destination.
+    ///
+    /// The default value is `false`.
+    pub fn replace_message_fields(mut self, yes: bool) -> Self {
+        self.replace_message_fields = yes;
+        self
+    }
+
+    /// Whether to replace repeated and map fields in the destination with the value from the
+    /// source, instead of appending to them.
+    ///
+    /// The default value is `false`.
+    pub fn replace_repeated_fields(mut self, yes: bool) -> Self {
+        self.replace_repeated_fields = yes;
+        self
+    }
+
+    /// Whether to clear other fields in the destination if they are unset in the source.
+    ///
+    /// If disabled, fields which are unset in the source are left unchanged in the destination.
+    /// Note that fields without [presence](crate::FieldDescriptor::supports_presence) are
+    /// considered unset if they have their default value.
+    ///
+    /// The default value is `false`.
+    pub fn replace_primitive_fields(mut self, yes: bool) -> Self {
+        self.replace_primitive_fields = yes;
+        self
+    }
+}
+
+impl FieldMaskError {
+    fn new(kind: FieldMaskErrorKind) -> Self {
+        FieldMaskError { kind }
+    }
+
+    /// Gets the field mask path which caused this error, if any.
+    pub fn path(&self) -> Option<&str> {
+        match &self.kind {
+            FieldMaskErrorKind::EmptyPath => Some(""),
+            FieldMaskErrorKind::FieldNotFound { path, .. }
+            | FieldMaskErrorKind::NotAMessage { path, .. }
+            | FieldMaskErrorKind::RepeatedField { path, .. } => Some(path),
+            FieldMaskErrorKind::MessageMismatch { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldMaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FieldMaskErrorKind::EmptyPath => write!(f, "invalid empty field mask path"),
+            FieldMaskErrorKind::FieldNotFound { path, message } => write!(
+                f,
+                "invalid field mask path '{path}': field not found in message '{message}'"
+            ),
+            FieldMaskErrorKind::NotAMessage { path, field } => write!(
+                f,
+                "invalid field mask path '{path}': field '{field}' is not a message"
+            ),
+            FieldMaskErrorKind::RepeatedField { path, field } => write!(
+                f,
+                "invalid field mask path '{path}': repeated field '{field}' cannot have subfields"
+            ),
+            FieldMaskErrorKind::MessageMismatch { expected, actual } => write!(
+                f,
+                "expected a message of type '{expected}', but found '{actual}'"
+            ),
+        }
+    }
+}
+
+impl Error for FieldMaskError {}
+
+impl MaskTree {
+    fn new(mask: &FieldMask) -> Result<Self, FieldMaskError> {
+        if mask.paths.iter().any(|path| path.is_empty()) {
+            return Err(FieldMaskError::new(FieldMaskErrorKind::EmptyPath));
+        }
+        Ok(MaskTree::from_paths(&mask.paths))
+    }
+
+    fn from_paths<I, S>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut tree = MaskTree::default();
+        for path in paths {
+            if !path.as_ref().is_empty() {
+                tree.insert(path.as_ref());
+            }
+        }
+        tree
+    }
+
+    fn insert(&mut self, path: &str) {
+        let (name, rest) = match path.split_once('.') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+
+        match rest {
+            None => {
+                self.children.insert(name.to_owned(), MaskNode::Leaf);
+            }
+            Some(rest) => match self
+                .children
+                .entry(name.to_owned())
+                .or_insert_with(|| MaskNode::Branch(MaskTree::default()))
+            {
+                MaskNode::Leaf => (),
+                MaskNode::Branch(tree) => tree.insert(rest),
+            },
+        }
+    }
+
+    fn intersect(&self, other: &MaskTree) -> MaskTree {
+        let mut result = MaskTree::default();
+        for (name, node) in &self.children {
+            let Some(other_node) = other.children.get(name) else {
+                continue;
+            };
+
+            match (node, other_node) {
+                (MaskNode::Leaf, node) | (node, MaskNode::Leaf) => {
+                    result.children.insert(name.clone(), node.clone());
+                }
+                (MaskNode::Branch(tree), MaskNode::Branch(other_tree)) => {
+                    let tree = tree.intersect(other_tree);
+                    if !tree.children.is_empty() {
+                        result.children.insert(name.clone(), MaskNode::Branch(tree));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn to_field_mask(&self) -> FieldMask {
+        let mut paths = Vec::new();
+        self.collect_paths("", &mut paths);
+        FieldMask { paths }
+    }
+
+    fn collect_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for (name, node) in &self.children {
+            let path = join_path(prefix, name);
+            match node {
+                MaskNode::Leaf => paths.push(path),
+                MaskNode::Branch(tree) => tree.collect_paths(&path, paths),
+            }
+        }
+    }
+
+    fn validate(&self, desc: &MessageDescriptor, prefix: &str) -> Result<(), FieldMaskError> {
+        for (name, node) in &self.children {
+            let path = join_path(prefix, name);
+            let Some(field) = desc.get_field_by_name(name) else {
+                return Err(FieldMaskError::new(FieldMaskErrorKind::FieldNotFound {
+                    path: first_leaf_path(path, node),
+                    message: desc.full_name().to_owned(),
+                }));
+            };
+
+            if let MaskNode::Branch(tree) = node {
+                if field.is_list() || field.is_map() {
+                    return Err(FieldMaskError::new(FieldMaskErrorKind::RepeatedField {
+                        path: first_leaf_path(path, node),
+                        field: name.clone(),
+                    }));
+                }
+                match field.kind() {
+                    Kind::Message(message) => tree.validate(&message, &path)?,
+                    _ => {
+                        return Err(FieldMaskError::new(FieldMaskErrorKind::NotAMessage {
+                            path: first_leaf_path(path, node),
+                            field: name.clone(),
+                        }))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, message: &mut DynamicMessage) {
+        message.take_extensions().for_each(drop);
+        message.take_unknown_fields().for_each(drop);
+
+        let desc = message.desc.clone();
+        for field in desc.fields() {
+            match self.children.get(field.name()) {
+                None => message.clear_field(&field),
+                Some(MaskNode::Leaf) => (),
+                Some(MaskNode::Branch(tree)) => {
+                    if message.has_field(&field) {
+                        if let Value::Message(message) = message.get_field_mut(&field) {
+                            tree.apply(message);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn merge(&self, dst: &mut DynamicMessage, src: &DynamicMessage, options: &MergeOptions) {
+        for (name, node) in &self.children {
+            let field = dst
+                .desc
+                .get_field_by_name(name)
+                .expect("mask should be validated");
+
+            match node {
+                MaskNode::Leaf => merge_field(dst, src, &field, options),
+                MaskNode::Branch(tree) => {
+                    if !src.has_field(&field) && !dst.has_field(&field) {
+                        continue;
+                    }
+
+                    let src_value = src.get_field(&field);
+                    if let (Value::Message(dst_message), Value::Message(src_message)) =
+                        (dst.get_field_mut(&field), src_value.as_ref())
+                    {
+                        tree.merge(dst_message, src_message, options);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn merge_field(
+    dst: &mut DynamicMessage,
+    src: &DynamicMessage,
+    field: &FieldDescriptor,
+    options: &MergeOptions,
+) {
+    let src_has_field = src.has_field(field);
+
+    if field.is_list() || field.is_map() {
+        if options.replace_repeated_fields {
+            dst.set_field(field, src.get_field(field).into_owned());
+        } else if src_has_field {
+            match (dst.get_field_mut(field), src.get_field(field).as_ref()) {
+                (Value::List(dst_list), Value::List(src_list)) => {
+                    dst_list.extend(src_list.iter().cloned())
+                }
+                (Value::Map(dst_map), Value::Map(src_map)) => dst_map.extend(
+                    src_map
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone())),
+                ),
+                _ => unreachable!("field should be a list or map"),
+            }
+        }
+    } else if field.kind().as_message().is_some() {
+        if options.replace_message_fields {
+            replace_field(dst, src, field);
+        } else if src_has_field {
+            if dst.has_field(field) {
+                if let (Value::Message(dst_message), Value::Message(src_message)) =
+                    (dst.get_field_mut(field), src.get_field(field).as_ref())
+                {
+                    merge_message(dst_message, src_message);
+                }
+            } else {
+                replace_field(dst, src, field);
+            }
+        }
+    } else if src_has_field || options.replace_primitive_fields {
+        replace_field(dst, src, field);
+    }
+}
+
+/// Merges `src` into `dst` using the usual protobuf semantics, as if `src` were encoded and then
+/// decoded into `dst`.
+fn merge_message(dst: &mut DynamicMessage, src: &DynamicMessage) {
+    for (field, value) in src.fields() {
+        if dst.has_field(&field) {
+            merge_value(dst.get_field_mut(&field), value);
+        } else {
+            dst.set_field(&field, value.clone());
+        }
+    }
+
+    for (extension, value) in src.extensions() {
+        if dst.has_extension(&extension) {
+            merge_value(dst.get_extension_mut(&extension), value);
+        } else {
+            dst.set_extension(&extension, value.clone());
+        }
+    }
+
+    // Unknown fields may belong to a known field whose value could not be decoded, so they are
+    // decoded into the destination message to handle them consistently.
+    let mut buf = Vec::new();
+    for unknown in src.unknown_fields() {
+        unknown.encode(&mut buf);
+    }
+    if !buf.is_empty() {
+        dst.merge(buf.as_slice())
+            .expect("unknown fields should be valid for the message type");
+    }
+}
+
+fn merge_value(dst: &mut Value, src: &Value) {
+    match (dst, src) {
+        (Value::List(dst_list), Value::List(src_list)) => dst_list.extend(src_list.iter().cloned()),
+        (Value::Map(dst_map), Value::Map(src_map)) => dst_map.extend(
+            src_map
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        ),
+        (Value::Message(dst_message), Value::Message(src_message)) => {
+            merge_message(dst_message, src_message)
+        }
+        (dst, src) => *dst = src.clone(),
+    }
+}
+
+fn replace_field(dst: &mut DynamicMessage, src: &DynamicMessage, field: &FieldDescriptor) {
+    if src.has_field(field) {
+        dst.set_field(field, src.get_field(field).into_owned());
+    } else {
+        dst.clear_field(field);
+    }
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// Gets a full path from the mask which starts with `path`, for use in error messages.
+fn first_leaf_path(mut path: String, mut node: &MaskNode) -> String {
+    while let MaskNode::Branch(tree) = node {
+        let Some((name, child)) = tree.children.iter().next() else {
+            break;
+        };
+        path.push('.');
+        path.push_str(name);
+        node = child;
+    }
+    path
+}
@@ -2,11 +2,14 @@ use std::{
     borrow::Cow,
     collections::btree_map::{self, BTreeMap},
     fmt,
-    mem::replace,
+    mem::{replace, take},
 };
 
+use prost::{bytes::Buf, encoding::DecodeContext};
+
 use crate::{
-    ExtensionDescriptor, FieldDescriptor, Kind, MessageDescriptor, OneofDescriptor, Value,
+    ExtensionDescriptor, FeatureSet, FieldDescriptor, Kind, MessageDescriptor, OneofDescriptor,
+    Value,
 };
 
 use super::{
@@ -24,6 +27,7 @@ pub(crate) trait FieldDescriptorLike: fmt::Debug {
     fn containing_oneof(&self) -> Option<OneofDescriptor>;
     fn supports_presence(&self) -> bool;
     fn kind(&self) -> Kind;
+    fn features(&self) -> FeatureSet;
     fn is_group(&self) -> bool;
     fn is_list(&self) -> bool;
     fn is_map(&self) -> bool;
@@ -46,6 +50,9 @@ pub(super) enum ValueOrUnknown {
     Taken,
     /// A protobuf value with known field type.
     Value(Value),
+    /// A list or map value, along with elements which could not be decoded for it, such as
+    /// values of a closed enum which are not defined in the enum.
+    ValueWithUnknown(Box<(Value, UnknownFieldSet)>),
     /// One or more unknown fields.
     Unknown(UnknownFieldSet),
 }
@@ -58,10 +65,7 @@ pub(super) enum ValueAndDescriptor<'a> {
 
 impl DynamicMessageFieldSet {
     fn get_value(&self, number: u32) -> Option<&Value> {
-        match self.fields.get(&number) {
-            Some(ValueOrUnknown::Value(value)) => Some(value),
-            Some(ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken) | None => None,
-        }
+        self.fields.get(&number).and_then(ValueOrUnknown::value)
     }
 
     pub(super) fn has(&self, desc: &impl FieldDescriptorLike) -> bool {
@@ -81,11 +85,11 @@ impl DynamicMessageFieldSet {
         self.clear_oneof_fields(desc);
         match self.fields.entry(desc.number()) {
             btree_map::Entry::Occupied(entry) => match entry.into_mut() {
-                ValueOrUnknown::Value(value) => value,
-                value => {
+                value @ (ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken) => {
                     *value = ValueOrUnknown::Value(desc.default_value());
                     value.unwrap_value_mut()
                 }
+                value => value.unwrap_value_mut(),
             },
             btree_map::Entry::Vacant(entry) => entry
                 .insert(ValueOrUnknown::Value(desc.default_value()))
@@ -117,7 +121,7 @@ impl DynamicMessageFieldSet {
     pub(crate) fn add_unknown(&mut self, number: u32, unknown: UnknownField) {
         match self.fields.entry(number) {
             btree_map::Entry::Occupied(mut entry) => match entry.get_mut() {
-                ValueOrUnknown::Value(_) => {
+                ValueOrUnknown::Value(_) | ValueOrUnknown::ValueWithUnknown(_) => {
                     panic!("expected no field to be found with number {number}")
                 }
                 value @ ValueOrUnknown::Taken => {
@@ -133,13 +137,94 @@ impl DynamicMessageFieldSet {
         }
     }
 
+    /// Returns `true` if the value of the field with the given number is stored as unknown fields.
+    pub(super) fn is_unknown(&self, number: u32) -> bool {
+        matches!(self.fields.get(&number), Some(ValueOrUnknown::Unknown(_)))
+    }
+
+    /// Stores a value which could not be decoded for a known field as an unknown field.
+    ///
+    /// Any existing value of the field is replaced. For list and map fields, the existing elements
+    /// are converted to unknown fields instead, so the field is encoded with its original elements.
+    pub(super) fn add_unknown_for_field(
+        &mut self,
+        desc: &impl FieldDescriptorLike,
+        unknown: UnknownField,
+    ) {
+        self.clear_oneof_fields(desc);
+        if let Some(value) = self
+            .fields
+            .get(&desc.number())
+            .and_then(ValueOrUnknown::value)
+        {
+            let mut unknowns = UnknownFieldSet::default();
+            if desc.is_list() || desc.is_map() {
+                let mut buf = Vec::new();
+                value.encode_field(desc, &mut buf, false);
+
+                let mut buf = buf.as_slice();
+                while buf.has_remaining() {
+                    unknowns.insert(
+                        UnknownField::decode(&mut buf, DecodeContext::default())
+                            .expect("failed to decode encoded field"),
+                    );
+                }
+                if let Some(ValueOrUnknown::ValueWithUnknown(value)) =
+                    self.fields.get_mut(&desc.number())
+                {
+                    take(&mut value.1)
+                        .into_iter()
+                        .for_each(|u| unknowns.insert(u));
+                }
+            }
+            self.fields
+                .insert(desc.number(), ValueOrUnknown::Unknown(unknowns));
+        }
+        self.add_unknown(desc.number(), unknown);
+    }
+
+    /// Stores an element which could not be decoded for a known list or map field as an unknown
+    /// field, keeping the existing elements of the field.
+    pub(super) fn add_unknown_element(
+        &mut self,
+        desc: &impl FieldDescriptorLike,
+        unknown: UnknownField,
+    ) {
+        debug_assert!(desc.is_list() || desc.is_map());
+        if self.is_unknown(desc.number()) {
+            self.add_unknown(desc.number(), unknown);
+            return;
+        }
+
+        self.get_mut(desc);
+        let entry = self
+            .fields
+            .get_mut(&desc.number())
+            .expect("field should be present");
+        match entry {
+            ValueOrUnknown::Value(_) => {
+                let value = replace(entry, ValueOrUnknown::Taken).unwrap_value();
+                *entry = ValueOrUnknown::ValueWithUnknown(Box::new((
+                    value,
+                    UnknownFieldSet::from_iter([unknown]),
+                )));
+            }
+            ValueOrUnknown::ValueWithUnknown(value) => value.1.insert(unknown),
+            ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => unreachable!(),
+        }
+    }
+
     pub(super) fn clear(&mut self, desc: &impl FieldDescriptorLike) {
         self.fields.remove(&desc.number());
     }
 
     pub(crate) fn take(&mut self, desc: &impl FieldDescriptorLike) -> Option<Value> {
-        match self.fields.remove(&desc.number()) {
-            Some(ValueOrUnknown::Value(value)) if desc.has(&value) => Some(value),
+        match self
+            .fields
+            .remove(&desc.number())
+            .and_then(ValueOrUnknown::into_value)
+        {
+            Some(value) if desc.has(&value) => Some(value),
             _ => None,
         }
     }
@@ -170,40 +255,66 @@ impl DynamicMessageFieldSet {
             })
             .map(|f| ValueAndDescriptor::Field(self.get(&f), f));
 
-        let extensions_unknowns =
-            self.fields
-                .iter()
-                .filter_map(move |(&number, value)| match value {
-                    ValueOrUnknown::Value(value) => {
-                        if let Some(extension) = message.get_extension(number) {
-                            if extension.has(value) {
-                                Some(ValueAndDescriptor::Extension(
-                                    Cow::Borrowed(value),
-                                    extension,
-                                ))
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                    ValueOrUnknown::Unknown(unknown) => Some(ValueAndDescriptor::Unknown(unknown)),
-                    ValueOrUnknown::Taken => None,
-                });
+        let extensions_unknowns = self.fields.iter().flat_map(move |(&number, value)| {
+            let extension = value.value().and_then(|value| {
+                let extension = message.get_extension(number)?;
+                if extension.has(value) {
+                    Some(ValueAndDescriptor::Extension(
+                        Cow::Borrowed(value),
+                        extension,
+                    ))
+                } else {
+                    None
+                }
+            });
+            extension
+                .into_iter()
+                .chain(value.unknown().map(ValueAndDescriptor::Unknown))
+        });
 
         fields.chain(extensions_unknowns)
     }
 
+    /// Iterates over the fields, extensions and unknown fields in the message, interleaved in field number order.
+    ///
+    /// Fields with their default value are not included.
+    pub(super) fn iter_number_order<'a>(
+        &'a self,
+        message: &'a MessageDescriptor,
+    ) -> impl Iterator<Item = ValueAndDescriptor<'a>> + 'a {
+        self.fields.iter().flat_map(move |(&number, value)| {
+            let field = value.value().and_then(|value| {
+                if let Some(field) = message.get_field(number) {
+                    if field.has(value) {
+                        Some(ValueAndDescriptor::Field(Cow::Borrowed(value), field))
+                    } else {
+                        None
+                    }
+                } else if let Some(extension) = message.get_extension(number) {
+                    if extension.has(value) {
+                        Some(ValueAndDescriptor::Extension(
+                            Cow::Borrowed(value),
+                            extension,
+                        ))
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            });
+            field
+                .into_iter()
+                .chain(value.unknown().map(ValueAndDescriptor::Unknown))
+        })
+    }
+
     pub(crate) fn iter_fields<'a>(
         &'a self,
         message: &'a MessageDescriptor,
     ) -> impl Iterator<Item = (FieldDescriptor, &'a Value)> + 'a {
         self.fields.iter().filter_map(move |(&number, value)| {
-            let value = match value {
-                ValueOrUnknown::Value(value) => value,
-                _ => return None,
-            };
+            let value = value.value()?;
             let field = match message.get_field(number) {
                 Some(field) => field,
                 _ => return None,
@@ -221,10 +332,7 @@ impl DynamicMessageFieldSet {
         message: &'a MessageDescriptor,
     ) -> impl Iterator<Item = (ExtensionDescriptor, &'a Value)> + 'a {
         self.fields.iter().filter_map(move |(&number, value)| {
-            let value = match value {
-                ValueOrUnknown::Value(value) => value,
-                _ => return None,
-            };
+            let value = value.value()?;
             let field = match message.get_extension(number) {
                 Some(field) => field,
                 _ => return None,
@@ -238,9 +346,11 @@ impl DynamicMessageFieldSet {
     }
 
     pub(super) fn iter_unknown(&self) -> impl Iterator<Item = &'_ UnknownField> {
-        self.fields.values().flat_map(move |value| match value {
-            ValueOrUnknown::Taken | ValueOrUnknown::Value(_) => [].iter(),
-            ValueOrUnknown::Unknown(unknowns) => unknowns.iter(),
+        self.fields.values().flat_map(move |value| {
+            value
+                .unknown()
+                .map(UnknownFieldSet::iter)
+                .unwrap_or_default()
         })
     }
 
@@ -249,10 +359,7 @@ impl DynamicMessageFieldSet {
         message: &'a MessageDescriptor,
     ) -> impl Iterator<Item = (FieldDescriptor, &'a mut Value)> + 'a {
         self.fields.iter_mut().filter_map(move |(&number, value)| {
-            let value = match value {
-                ValueOrUnknown::Value(value) => value,
-                _ => return None,
-            };
+            let value = value.value_mut()?;
             let field = match message.get_field(number) {
                 Some(field) => field,
                 _ => return None,
@@ -270,10 +377,7 @@ impl DynamicMessageFieldSet {
         message: &'a MessageDescriptor,
     ) -> impl Iterator<Item = (ExtensionDescriptor, &'a mut Value)> + 'a {
         self.fields.iter_mut().filter_map(move |(&number, value)| {
-            let value = match value {
-                ValueOrUnknown::Value(value) => value,
-                _ => return None,
-            };
+            let value = value.value_mut()?;
             let field = match message.get_extension(number) {
                 Some(field) => field,
                 _ => return None,
@@ -293,19 +397,13 @@ impl DynamicMessageFieldSet {
         self.fields
             .iter_mut()
             .filter_map(move |(&number, value_or_unknown)| {
-                let value = match value_or_unknown {
-                    ValueOrUnknown::Value(value) => value,
-                    _ => return None,
-                };
+                let value = value_or_unknown.value()?;
                 let field = match message.get_field(number) {
                     Some(field) => field,
                     _ => return None,
                 };
                 if field.has(value) {
-                    Some((
-                        field,
-                        replace(value_or_unknown, ValueOrUnknown::Taken).unwrap_value(),
-                    ))
+                    Some((field, value_or_unknown.take_value()))
                 } else {
                     None
                 }
@@ -319,19 +417,13 @@ impl DynamicMessageFieldSet {
         self.fields
             .iter_mut()
             .filter_map(move |(&number, value_or_unknown)| {
-                let value = match value_or_unknown {
-                    ValueOrUnknown::Value(value) => value,
-                    _ => return None,
-                };
+                let value = value_or_unknown.value()?;
                 let field = match message.get_extension(number) {
                     Some(field) => field,
                     _ => return None,
                 };
                 if field.has(value) {
-                    Some((
-                        field,
-                        replace(value_or_unknown, ValueOrUnknown::Taken).unwrap_value(),
-                    ))
+                    Some((field, value_or_unknown.take_value()))
                 } else {
                     None
                 }
@@ -341,12 +433,7 @@ impl DynamicMessageFieldSet {
     pub(crate) fn take_unknown(&mut self) -> impl Iterator<Item = UnknownField> + '_ {
         self.fields
             .values_mut()
-            .flat_map(move |value_or_unknown| match value_or_unknown {
-                ValueOrUnknown::Unknown(_) => replace(value_or_unknown, ValueOrUnknown::Taken)
-                    .unwrap_unknown()
-                    .into_iter(),
-                _ => vec![].into_iter(),
-            })
+            .flat_map(move |value_or_unknown| value_or_unknown.take_unknown().into_iter())
     }
 
     pub(super) fn clear_all(&mut self) {
@@ -355,24 +442,72 @@ impl DynamicMessageFieldSet {
 }
 
 impl ValueOrUnknown {
-    fn unwrap_value_mut(&mut self) -> &mut Value {
+    fn value(&self) -> Option<&Value> {
         match self {
-            ValueOrUnknown::Value(value) => value,
-            ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => unreachable!(),
+            ValueOrUnknown::Value(value) => Some(value),
+            ValueOrUnknown::ValueWithUnknown(value) => Some(&value.0),
+            ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => None,
         }
     }
 
-    fn unwrap_value(self) -> Value {
+    fn value_mut(&mut self) -> Option<&mut Value> {
+        match self {
+            ValueOrUnknown::Value(value) => Some(value),
+            ValueOrUnknown::ValueWithUnknown(value) => Some(&mut value.0),
+            ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => None,
+        }
+    }
+
+    fn unknown(&self) -> Option<&UnknownFieldSet> {
+        match self {
+            ValueOrUnknown::Unknown(unknowns) => Some(unknowns),
+            ValueOrUnknown::ValueWithUnknown(value) => Some(&value.1),
+            ValueOrUnknown::Value(_) | ValueOrUnknown::Taken => None,
+        }
+    }
+
+    fn into_value(self) -> Option<Value> {
         match self {
+            ValueOrUnknown::Value(value) => Some(value),
+            ValueOrUnknown::ValueWithUnknown(value) => Some(value.0),
+            ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => None,
+        }
+    }
+
+    fn unwrap_value_mut(&mut self) -> &mut Value {
+        self.value_mut().expect("expected a value")
+    }
+
+    fn unwrap_value(self) -> Value {
+        self.into_value().expect("expected a value")
+    }
+
+    /// Takes the value, leaving any unknown elements of the field in its place.
+    fn take_value(&mut self) -> Value {
+        match replace(self, ValueOrUnknown::Taken) {
             ValueOrUnknown::Value(value) => value,
+            ValueOrUnknown::ValueWithUnknown(value) => {
+                let (value, unknowns) = *value;
+                *self = ValueOrUnknown::Unknown(unknowns);
+                value
+            }
             ValueOrUnknown::Unknown(_) | ValueOrUnknown::Taken => unreachable!(),
         }
     }
 
-    fn unwrap_unknown(self) -> UnknownFieldSet {
-        match self {
+    /// Takes the unknown fields, leaving any value of the field in its place.
+    fn take_unknown(&mut self) -> UnknownFieldSet {
+        match replace(self, ValueOrUnknown::Taken) {
             ValueOrUnknown::Unknown(unknowns) => unknowns,
-            ValueOrUnknown::Value(_) | ValueOrUnknown::Taken => unreachable!(),
+            ValueOrUnknown::ValueWithUnknown(value) => {
+                let (value, unknowns) = *value;
+                *self = ValueOrUnknown::Value(value);
+                unknowns
+            }
+            value => {
+                *self = value;
+                UnknownFieldSet::default()
+            }
         }
     }
 }
@@ -411,6 +546,10 @@ impl FieldDescriptorLike for FieldDescriptor {
         self.kind()
     }
 
+    fn features(&self) -> FeatureSet {
+        self.features()
+    }
+
     fn is_group(&self) -> bool {
         self.is_group()
     }
@@ -466,6 +605,10 @@ impl FieldDescriptorLike for ExtensionDescriptor {
         self.kind()
     }
 
+    fn features(&self) -> FeatureSet {
+        self.features()
+    }
+
     fn is_group(&self) -> bool {
         self.is_group()
     }
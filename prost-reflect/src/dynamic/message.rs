@@ -1,17 +1,18 @@
 use prost::{
-    bytes::{Buf, BufMut},
+    bytes::{Buf, BufMut, Bytes},
     encoding::{DecodeContext, WireType},
     DecodeError, Message,
 };
 
 use crate::{
     descriptor::{FieldDescriptor, Kind, MAP_ENTRY_KEY_NUMBER, MAP_ENTRY_VALUE_NUMBER},
-    DynamicMessage, MapKey, Value,
+    DynamicMessage, EnumDescriptor, MapKey, MessageDescriptor, Value,
 };
 
 use super::{
     fields::{FieldDescriptorLike, ValueAndDescriptor},
-    unknown::UnknownField,
+    unknown::{UnknownField, UnknownFieldValue},
+    Either,
 };
 
 impl Message for DynamicMessage {
@@ -22,10 +23,10 @@ impl Message for DynamicMessage {
         for field in self.fields.iter(&self.desc, false, false) {
             match field {
                 ValueAndDescriptor::Field(value, field_desc) => {
-                    value.encode_field(&field_desc, buf)
+                    value.encode_field(&field_desc, buf, false)
                 }
                 ValueAndDescriptor::Extension(value, extension_desc) => {
-                    value.encode_field(&extension_desc, buf)
+                    value.encode_field(&extension_desc, buf, false)
                 }
                 ValueAndDescriptor::Unknown(unknowns) => unknowns.encode_raw(buf),
             }
@@ -43,15 +44,23 @@ impl Message for DynamicMessage {
         Self: Sized,
     {
         if let Some(field_desc) = self.desc.get_field(number) {
-            self.get_field_mut(&field_desc)
-                .merge_field(&field_desc, wire_type, buf, ctx)
+            if let Some(enum_desc) = closed_enum_type(&field_desc) {
+                self.merge_closed_enum_field(&field_desc, &enum_desc, wire_type, buf, ctx)
+            } else {
+                self.get_field_mut(&field_desc)
+                    .merge_field(&field_desc, wire_type, buf, ctx)
+            }
         } else if let Some(extension_desc) = self.desc.get_extension(number) {
-            self.get_extension_mut(&extension_desc).merge_field(
-                &extension_desc,
-                wire_type,
-                buf,
-                ctx,
-            )
+            if let Some(enum_desc) = closed_enum_type(&extension_desc) {
+                self.merge_closed_enum_field(&extension_desc, &enum_desc, wire_type, buf, ctx)
+            } else {
+                self.get_extension_mut(&extension_desc).merge_field(
+                    &extension_desc,
+                    wire_type,
+                    buf,
+                    ctx,
+                )
+            }
         } else {
             let field = UnknownField::decode_value(number, wire_type, buf, ctx)?;
             self.fields.add_unknown(number, field);
@@ -80,9 +89,158 @@ impl Message for DynamicMessage {
     }
 }
 
+impl DynamicMessage {
+    /// Encodes the message with a deterministic byte representation: fields, extensions and unknown fields
+    /// are written in field number order, and map entries are sorted by key.
+    pub(super) fn encode_raw_deterministic(&self, buf: &mut impl BufMut) {
+        for field in self.fields.iter_number_order(&self.desc) {
+            match field {
+                ValueAndDescriptor::Field(value, field_desc) => {
+                    value.encode_field(&field_desc, buf, true)
+                }
+                ValueAndDescriptor::Extension(value, extension_desc) => {
+                    value.encode_field(&extension_desc, buf, true)
+                }
+                ValueAndDescriptor::Unknown(unknowns) => unknowns.encode_raw(buf),
+            }
+        }
+    }
+
+    /// Merges a field whose values have a closed enum type.
+    ///
+    /// Values which are not defined in the enum are stored as unknown fields. For list and map
+    /// fields, only the undefined elements or entries are stored as unknown fields, and the defined
+    /// elements are kept in the field.
+    pub(super) fn merge_closed_enum_field(
+        &mut self,
+        field_desc: &impl FieldDescriptorLike,
+        enum_desc: &EnumDescriptor,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let number = field_desc.number();
+        if field_desc.is_map() {
+            let mut entry = Bytes::new();
+            prost::encoding::bytes::merge(wire_type, &mut entry, buf, ctx.clone())?;
+
+            let (key, value) = match field_desc.kind() {
+                Kind::Message(map_entry) => decode_map_entry(&map_entry, entry.clone(), ctx)?,
+                kind => panic!("invalid kind {kind:?} for map field"),
+            };
+
+            let is_defined = value
+                .as_enum_number()
+                .is_some_and(|value| enum_desc.get_value(value).is_some());
+            if is_defined && !self.fields.is_unknown(number) {
+                match self.fields.get_mut(field_desc) {
+                    Value::Map(map) => {
+                        map.insert(key, value);
+                    }
+                    value => panic!(
+                        "mismatch between DynamicMessage value {value:?} and type {field_desc:?}"
+                    ),
+                }
+            } else {
+                let unknown = UnknownField::new(number, UnknownFieldValue::LengthDelimited(entry));
+                self.fields.add_unknown_element(field_desc, unknown);
+            }
+        } else if wire_type == WireType::LengthDelimited && field_desc.is_list() {
+            let mut values = Vec::new();
+            prost::encoding::merge_loop(&mut values, buf, ctx, |values, buf, ctx| {
+                let mut value = 0;
+                prost::encoding::int32::merge(WireType::Varint, &mut value, buf, ctx)?;
+                values.push(value);
+                Ok(())
+            })?;
+
+            for value in values {
+                self.merge_closed_enum_value(field_desc, enum_desc, value);
+            }
+        } else {
+            let mut value = 0;
+            prost::encoding::int32::merge(wire_type, &mut value, buf, ctx)?;
+            self.merge_closed_enum_value(field_desc, enum_desc, value);
+        }
+
+        Ok(())
+    }
+
+    fn merge_closed_enum_value(
+        &mut self,
+        field_desc: &impl FieldDescriptorLike,
+        enum_desc: &EnumDescriptor,
+        value: i32,
+    ) {
+        let number = field_desc.number();
+        if enum_desc.get_value(value).is_none()
+            || (field_desc.is_list() && self.fields.is_unknown(number))
+        {
+            // Negative values are sign-extended, as for the `int32` encoding.
+            let unknown = UnknownField::new(number, UnknownFieldValue::Varint(value as i64 as u64));
+            if field_desc.is_list() {
+                self.fields.add_unknown_element(field_desc, unknown);
+            } else {
+                self.fields.add_unknown_for_field(field_desc, unknown);
+            }
+        } else if field_desc.is_list() {
+            match self.fields.get_mut(field_desc) {
+                Value::List(values) => values.push(Value::EnumNumber(value)),
+                value => panic!(
+                    "mismatch between DynamicMessage value {value:?} and type {field_desc:?}"
+                ),
+            }
+        } else {
+            self.fields.set(field_desc, Value::EnumNumber(value));
+        }
+    }
+}
+
+/// Decodes the key and value of a map entry, given the contents of the length-delimited entry.
+fn decode_map_entry(
+    map_entry: &MessageDescriptor,
+    mut buf: Bytes,
+    ctx: DecodeContext,
+) -> Result<(MapKey, Value), DecodeError> {
+    let key_desc = map_entry.map_entry_key_field();
+    let value_desc = map_entry.map_entry_value_field();
+
+    let mut key = MapKey::default_value(&key_desc.kind());
+    let mut value = Value::default_value_for_field(&value_desc);
+    while buf.has_remaining() {
+        let (number, wire_type) = prost::encoding::decode_key(&mut buf)?;
+        match number {
+            MAP_ENTRY_KEY_NUMBER => key.merge_field(&key_desc, wire_type, &mut buf, ctx.clone())?,
+            MAP_ENTRY_VALUE_NUMBER => {
+                value.merge_field(&value_desc, wire_type, &mut buf, ctx.clone())?
+            }
+            _ => prost::encoding::skip_field(wire_type, number, &mut buf, ctx.clone())?,
+        }
+    }
+
+    Ok((key, value))
+}
+
+/// Returns the enum type of the field, or of its values if it is a map, if it is a closed enum.
+pub(super) fn closed_enum_type(field_desc: &impl FieldDescriptorLike) -> Option<EnumDescriptor> {
+    let kind = match field_desc.kind() {
+        Kind::Message(map_entry) if field_desc.is_map() => map_entry.map_entry_value_field().kind(),
+        kind => kind,
+    };
+
+    match kind {
+        Kind::Enum(enum_desc) if enum_desc.is_closed() => Some(enum_desc),
+        _ => None,
+    }
+}
+
 impl Value {
-    pub(super) fn encode_field<B>(&self, field_desc: &impl FieldDescriptorLike, buf: &mut B)
-    where
+    pub(super) fn encode_field<B>(
+        &self,
+        field_desc: &impl FieldDescriptorLike,
+        buf: &mut B,
+        deterministic: bool,
+    ) where
         B: BufMut,
     {
         if !field_desc.supports_presence() && field_desc.is_default_value(self) {
@@ -132,7 +290,17 @@ impl Value {
                 prost::encoding::int32::encode(number, value, buf)
             }
             (Value::Message(message), Kind::Message(_)) => {
-                if field_desc.is_group() {
+                if deterministic {
+                    if field_desc.is_group() {
+                        prost::encoding::encode_key(number, WireType::StartGroup, buf);
+                        message.encode_raw_deterministic(buf);
+                        prost::encoding::encode_key(number, WireType::EndGroup, buf);
+                    } else {
+                        prost::encoding::encode_key(number, WireType::LengthDelimited, buf);
+                        prost::encoding::encode_varint(message.encoded_len() as u64, buf);
+                        message.encode_raw_deterministic(buf);
+                    }
+                } else if field_desc.is_group() {
                     prost::encoding::group::encode(number, message, buf)
                 } else {
                     prost::encoding::message::encode(number, message, buf)
@@ -245,7 +413,7 @@ impl Value {
                     }
                 } else {
                     for value in values {
-                        value.encode_field(field_desc, buf);
+                        value.encode_field(field_desc, buf, deterministic);
                     }
                 }
             }
@@ -253,14 +421,22 @@ impl Value {
                 let key_desc = map_entry.get_field(MAP_ENTRY_KEY_NUMBER).unwrap();
                 let value_desc = map_entry.get_field(MAP_ENTRY_VALUE_NUMBER).unwrap();
 
-                for (key, value) in values {
+                let entries = if deterministic {
+                    let mut entries: Vec<_> = values.iter().collect();
+                    entries.sort_unstable_by_key(|&(key, _)| key);
+                    Either::Left(entries.into_iter())
+                } else {
+                    Either::Right(values.iter())
+                };
+
+                for (key, value) in entries {
                     let len = key.encoded_len(&key_desc) + value.encoded_len(&value_desc);
 
                     prost::encoding::encode_key(number, WireType::LengthDelimited, buf);
                     prost::encoding::encode_varint(len as u64, buf);
 
                     key.encode_field(&key_desc, buf);
-                    value.encode_field(&value_desc, buf);
+                    value.encode_field(&value_desc, buf, deterministic);
                 }
             }
             (value, ty) => {
@@ -587,7 +763,7 @@ impl MapKey {
         }
     }
 
-    fn merge_field<B>(
+    pub(super) fn merge_field<B>(
         &mut self,
         field_desc: &FieldDescriptor,
         wire_type: WireType,
@@ -1,30 +1,60 @@
 /// Parsing and formatting for the protobuf [text format](https://developers.google.com/protocol-buffers/docs/text-format-spec).
 ///
 /// This module contains options for customizing the text format output. See the associated functions [`DynamicMessage::parse_text_format()`] and
-/// [`DynamicMessage::to_text_format()`].
+/// [`DynamicMessage::to_text_format()`], as well as [`parse_with_header()`](text_format::parse_with_header), which takes the message type from the
+/// `proto-message` header comment of the input.
+///
+/// It also provides [`Document`](text_format::Document), a lossless syntax tree for editing text format files while preserving their comments and layout.
 #[cfg(feature = "text-format")]
 #[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
 pub mod text_format;
 
+mod decode;
+/// Structural comparison of [`DynamicMessage`] values.
+///
+/// This module contains the types returned by [`DynamicMessage::diff()`], which reports the
+/// individual fields, list elements and map entries that differ between two messages.
+pub mod diff;
+mod extract;
+/// Utilities for working with [`FieldMask`](prost_types::FieldMask) values.
+///
+/// This module contains functions to validate and combine field masks. See also the associated
+/// functions [`DynamicMessage::apply_mask()`] and [`DynamicMessage::merge_with_mask()`].
+pub mod field_mask;
 mod fields;
 mod message;
+mod path;
+mod required;
+mod resolver;
 #[cfg(feature = "serde")]
 mod serde;
+/// Reading and writing streams of length-delimited messages.
+///
+/// This module contains adapters to decode a sequence of [`DynamicMessage`] values, each prefixed
+/// by its length as a varint, from an [`io::Read`](std::io::Read) or a [`Buf`](prost::bytes::Buf),
+/// and to write such a sequence to an [`io::Write`](std::io::Write).
+pub mod stream;
 #[cfg(not(feature = "text-format"))]
 mod text_format;
 mod unknown;
+mod view;
 
-use std::{borrow::Cow, collections::HashMap, error::Error, fmt};
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt, hash::Hasher};
 
+pub use self::decode::{DecodeOptions, MessageDecodeError, UnknownFieldPolicy};
+pub use self::extract::FieldExtractor;
+pub use self::path::{FieldPath, PathError, PathSegment};
+pub use self::resolver::TypeResolver;
 #[cfg(feature = "serde")]
 pub use self::serde::{DeserializeOptions, SerializeOptions};
 pub use self::unknown::UnknownField;
+pub use self::view::{MessageView, ValueView};
 
 pub(crate) use self::fields::FieldDescriptorLike;
 
 use prost::{
-    bytes::{Buf, Bytes},
-    DecodeError, Message,
+    bytes::{Buf, BufMut, Bytes},
+    DecodeError, EncodeError, Message,
 };
 
 use self::fields::DynamicMessageFieldSet;
@@ -139,6 +169,34 @@ impl DynamicMessage {
         Ok(message)
     }
 
+    /// Decodes a length-delimited instance of the message type specified by the
+    /// [`MessageDescriptor`] from the buffer.
+    ///
+    /// This is the dynamic equivalent of [`Message::decode_length_delimited`]. To decode a
+    /// stream of length-delimited messages, see
+    /// [`LengthDelimitedReader`](stream::LengthDelimitedReader) and
+    /// [`LengthDelimitedIter`](stream::LengthDelimitedIter).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut buf = b"\x03\x08\x96\x01\x02\x08\x01".as_ref();
+    /// let message = DynamicMessage::decode_length_delimited(message_descriptor, &mut buf).unwrap();
+    /// assert_eq!(message.get_field_by_name("foo").unwrap().as_ref(), &Value::I32(150));
+    /// assert_eq!(buf, b"\x02\x08\x01");
+    /// ```
+    pub fn decode_length_delimited<B>(desc: MessageDescriptor, buf: B) -> Result<Self, DecodeError>
+    where
+        B: Buf,
+    {
+        let mut message = DynamicMessage::new(desc);
+        message.merge_length_delimited(buf)?;
+        Ok(message)
+    }
+
     /// Returns `true` if this message has the given field set.
     ///
     /// If the field type supports distinguishing whether a value has been set (see [`supports_presence`][FieldDescriptor::supports_presence]),
@@ -580,6 +638,63 @@ impl DynamicMessage {
         let buf = self.encode_to_vec();
         T::decode(buf.as_slice())
     }
+
+    /// Encodes the message to a buffer using a deterministic byte representation.
+    ///
+    /// The [`Message`] implementation of [`DynamicMessage`] writes map entries in an
+    /// unspecified order, so encoding equal messages may produce different bytes. This method
+    /// instead writes fields, extensions and unknown fields in field number order, and map
+    /// entries sorted by key, so equal messages always produce identical output.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost::Message;
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let dynamic_message = DynamicMessage::decode(message_descriptor, b"\x08\x96\x01".as_ref()).unwrap();
+    /// let mut buf = Vec::new();
+    /// dynamic_message.encode_deterministic(&mut buf).unwrap();
+    /// assert_eq!(buf, b"\x08\x96\x01");
+    /// ```
+    pub fn encode_deterministic<B>(&self, buf: &mut B) -> Result<(), EncodeError>
+    where
+        B: BufMut,
+    {
+        if self.encoded_len() > buf.remaining_mut() {
+            // The encoded length does not depend on field order, so the regular encoding
+            // reports the same error.
+            return self.encode(buf);
+        }
+
+        self.encode_raw_deterministic(buf);
+        Ok(())
+    }
+
+    /// Returns the canonical byte representation of this message.
+    ///
+    /// This is the output of [`encode_deterministic`][Self::encode_deterministic], and is
+    /// suitable for use as a cache key or for content addressing. Note that unknown fields
+    /// are written in the order they were decoded, so messages which differ only in the order
+    /// of unknown fields with the same number have different canonical bytes.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.encode_raw_deterministic(&mut buf);
+        buf
+    }
+
+    /// Feeds the canonical byte representation of this message into the given [`Hasher`].
+    ///
+    /// Messages with equal [`canonical_bytes`][Self::canonical_bytes] produce equal hashes.
+    pub fn canonical_hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        state.write(&self.canonical_bytes());
+    }
 }
 
 impl ReflectMessage for DynamicMessage {
@@ -634,8 +749,9 @@ impl Value {
 
     /// Returns `true` if this value can be set for a given field.
     ///
-    /// Note this only checks if the value can be successfully encoded. It doesn't
-    /// check, for example, that enum values are in the defined range.
+    /// This checks that the value can be successfully encoded, and that any enum numbers are
+    /// defined in the enum type if it is [closed](crate::EnumDescriptor::is_closed). Fields with an open
+    /// enum type accept any number.
     pub fn is_valid_for_field(&self, field_desc: &FieldDescriptor) -> bool {
         match (self, field_desc.kind()) {
             (Value::List(list), kind) if field_desc.is_list() => {
@@ -673,9 +789,16 @@ impl Value {
 
     /// Returns `true` if this value can be encoded as the given [`Kind`].
     ///
+    /// For a [closed](crate::EnumDescriptor::is_closed) enum type, the enum number must also be defined in
+    /// the enum.
+    ///
     /// Unlike [`is_valid_for_field`](Value::is_valid_for_field), this method does not
     /// look at field cardinality, so it will never return `true` for lists or maps.
     pub fn is_valid(&self, kind: &Kind) -> bool {
+        if let (Value::EnumNumber(number), Kind::Enum(enum_desc)) = (self, kind) {
+            return !enum_desc.is_closed() || enum_desc.get_value(*number).is_some();
+        }
+
         matches!(
             (self, kind),
             (Value::Bool(_), Kind::Bool)
@@ -687,7 +810,6 @@ impl Value {
                 | (Value::F64(_), Kind::Double)
                 | (Value::String(_), Kind::String)
                 | (Value::Bytes(_), Kind::Bytes)
-                | (Value::EnumNumber(_), Kind::Enum(_))
                 | (Value::Message(_), Kind::Message(_))
         )
     }
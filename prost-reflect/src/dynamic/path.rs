@@ -0,0 +1,843 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Write},
+};
+
+use crate::{
+    dynamic::{fields::FieldDescriptorLike, fmt_string},
+    DynamicMessage, ExtensionDescriptor, FieldDescriptor, Kind, MapKey, MessageDescriptor, Value,
+};
+
+/// A path to a value nested within a [`DynamicMessage`].
+///
+/// A path is a sequence of [`PathSegment`]s, each of which selects a field, extension, list
+/// element or map entry of the value selected by the previous segments.
+///
+/// # Syntax
+///
+/// Paths are written with fields separated by `.`, extensions as their full name wrapped in
+/// square brackets, and list indices and map keys in square brackets after the field they
+/// index. For example, `nested.values[2].[my.package.ext].map_field["key"]`. String map keys
+/// are quoted and escaped as in the protobuf text format, while other map keys are written as
+/// plain integer or boolean literals.
+///
+/// The same syntax is used by [`FieldPath::parse()`] and the [`Display`](fmt::Display)
+/// implementation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+/// A single segment of a [`FieldPath`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// A field of a message.
+    Field(FieldDescriptor),
+    /// An extension field of a message.
+    Extension(ExtensionDescriptor),
+    /// An element of a list, selected by its index.
+    Index(usize),
+    /// An entry of a map, selected by its key.
+    Key(MapKey),
+}
+
+/// Error type returned when parsing a [`FieldPath`], or when reading or writing a value
+/// through a path.
+///
+/// Paths included in errors are formatted using the [`FieldPath`] syntax, and refer to the
+/// value which caused the error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    /// The path could not be parsed.
+    InvalidSyntax {
+        /// The path which could not be parsed.
+        path: String,
+        /// The byte offset of the error in the path.
+        position: usize,
+    },
+    /// A field was not found in a message.
+    FieldNotFound {
+        /// The full name of the message type.
+        message: String,
+        /// The name of the field.
+        name: String,
+    },
+    /// An extension was not found for a message.
+    ExtensionNotFound {
+        /// The full name of the message type.
+        message: String,
+        /// The full name of the extension.
+        name: String,
+    },
+    /// A field name was used on a value which is not a message.
+    NotAMessage {
+        /// The path to the value.
+        path: String,
+    },
+    /// An index or key was used on a value which is not a list or map.
+    NotAListOrMap {
+        /// The path to the value.
+        path: String,
+    },
+    /// A map key was not valid for the key type of the map.
+    InvalidMapKey {
+        /// The path to the map.
+        path: String,
+        /// The invalid key.
+        key: String,
+    },
+    /// An index was greater than or equal to the length of a list.
+    IndexOutOfBounds {
+        /// The path to the list element.
+        path: String,
+        /// The length of the list.
+        len: usize,
+    },
+    /// A key was not present in a map.
+    KeyNotFound {
+        /// The path to the map entry.
+        path: String,
+    },
+    /// A value was not valid for the type of the field or element it was assigned to.
+    InvalidType {
+        /// The path to the field or element.
+        path: String,
+        /// The invalid value.
+        value: Value,
+    },
+}
+
+/// The type of the value at a prefix of a path.
+enum Cursor {
+    Message(MessageDescriptor),
+    List(Kind),
+    Map(Kind, Kind),
+    Scalar,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    path: FieldPath,
+}
+
+impl FieldPath {
+    /// Creates a new, empty path, which refers to the root message.
+    pub fn new() -> Self {
+        FieldPath::default()
+    }
+
+    /// Parses a path into the given message type.
+    ///
+    /// See the [type-level documentation](FieldPath#syntax) for the syntax of paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, FieldPath, PathSegment};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let path = FieldPath::parse(&message_descriptor, "nested.foo").unwrap();
+    /// assert_eq!(path.segments().len(), 2);
+    /// assert_eq!(path.to_string(), "nested.foo");
+    ///
+    /// assert_eq!(
+    ///     FieldPath::parse(&message_descriptor, "nested.baz").unwrap_err().to_string(),
+    ///     "field 'baz' not found in message 'package.MyMessage'",
+    /// );
+    /// ```
+    pub fn parse(desc: &MessageDescriptor, path: &str) -> Result<Self, PathError> {
+        Parser {
+            input: path,
+            pos: 0,
+            path: FieldPath::new(),
+        }
+        .parse(desc)
+    }
+
+    /// Returns `true` if this path has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Gets the segments of this path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Appends a segment to the end of this path.
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// Removes the last segment of this path, returning it if the path was not empty.
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+}
+
+impl DynamicMessage {
+    /// Gets the value at the given path.
+    ///
+    /// If a field along the path is not set, its default value is returned.
+    ///
+    /// See the [`FieldPath` documentation](FieldPath#syntax) for the syntax of paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not valid for this message type, or if it refers to a
+    /// list element or map entry which does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, DynamicMessage, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut message = DynamicMessage::new(message_descriptor);
+    /// message.set_path("nested.foo", Value::I32(5)).unwrap();
+    /// assert_eq!(message.get_path("nested.foo").unwrap().as_i32(), Some(5));
+    /// assert_eq!(message.get_path("nested.nested.foo").unwrap().as_i32(), Some(0));
+    /// ```
+    pub fn get_path(&self, path: &str) -> Result<Cow<'_, Value>, PathError> {
+        let path = FieldPath::parse(&self.desc, path)?;
+        match self.lookup(&path, true)? {
+            Some(value) => Ok(Cow::Borrowed(value)),
+            None => match path.segments.last() {
+                Some(PathSegment::Field(field)) => {
+                    Ok(Cow::Owned(Value::default_value_for_field(field)))
+                }
+                Some(PathSegment::Extension(extension)) => {
+                    Ok(Cow::Owned(Value::default_value_for_extension(extension)))
+                }
+                _ => unreachable!("missing list elements and map entries are errors"),
+            },
+        }
+    }
+
+    /// Sets the value at the given path.
+    ///
+    /// Any unset message fields and map entries along the path are initialized to their
+    /// default values.
+    ///
+    /// See the [`FieldPath` documentation](FieldPath#syntax) for the syntax of paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not valid for this message type, if it refers to a
+    /// list element which does not exist, or if the value is not valid for the type of the
+    /// field. In this case the message is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, DynamicMessage, PathError, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut message = DynamicMessage::new(message_descriptor);
+    /// message.set_path("nested.nested.bar", Value::I32(5)).unwrap();
+    /// assert_eq!(message.to_string(), "nested{nested{bar:5}}");
+    ///
+    /// assert_eq!(
+    ///     message.set_path("foo", Value::String("hello".to_owned())),
+    ///     Err(PathError::InvalidType { path: "foo".to_owned(), value: Value::String("hello".to_owned()) }),
+    /// );
+    /// ```
+    pub fn set_path(&mut self, path: &str, value: Value) -> Result<(), PathError> {
+        let path = FieldPath::parse(&self.desc, path)?;
+        let (last, parents) = path
+            .segments
+            .split_last()
+            .expect("path should not be empty");
+
+        let is_valid = match last {
+            PathSegment::Field(field) => {
+                value.is_valid_for_field(field) && has_message_types(&value, &field.kind())
+            }
+            PathSegment::Extension(extension) => {
+                value.is_valid_for_extension(extension)
+                    && has_message_types(&value, &extension.kind())
+            }
+            PathSegment::Index(_) | PathSegment::Key(_) => parents
+                .last()
+                .and_then(container_element_kind)
+                .is_some_and(|kind| value.is_valid(&kind) && has_message_types(&value, &kind)),
+        };
+        if !is_valid {
+            return Err(PathError::InvalidType {
+                path: path.to_string(),
+                value,
+            });
+        }
+
+        // Check all list indices are in bounds before making any changes.
+        self.lookup(&path, false)?;
+
+        match (last, self.get_parent_mut(&path, true)) {
+            (PathSegment::Field(field), Some(ValueMut::Message(message))) => {
+                message.set_field(field, value)
+            }
+            (PathSegment::Extension(extension), Some(ValueMut::Message(message))) => {
+                message.set_extension(extension, value)
+            }
+            (PathSegment::Index(index), Some(ValueMut::Value(Value::List(list)))) => {
+                list[*index] = value;
+            }
+            (PathSegment::Key(key), Some(ValueMut::Value(Value::Map(map)))) => {
+                map.insert(key.clone(), value);
+            }
+            _ => unreachable!("parent should have the type checked by the parser"),
+        }
+
+        Ok(())
+    }
+
+    /// Clears the value at the given path.
+    ///
+    /// If the path refers to a field, the field is cleared. If it refers to a list element or
+    /// map entry, the element or entry is removed. If any message field along the path is
+    /// unset, or a map entry does not exist, then the message is left unchanged.
+    ///
+    /// See the [`FieldPath` documentation](FieldPath#syntax) for the syntax of paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not valid for this message type, or if it refers to a
+    /// list element which does not exist. In this case the message is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, DynamicMessage, Value};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let mut message = DynamicMessage::new(message_descriptor);
+    /// message.set_path("nested.foo", Value::I32(5)).unwrap();
+    /// message.set_path("nested.bar", Value::I32(6)).unwrap();
+    ///
+    /// message.clear_path("nested.foo").unwrap();
+    /// assert_eq!(message.to_string(), "nested{bar:6}");
+    /// ```
+    pub fn clear_path(&mut self, path: &str) -> Result<(), PathError> {
+        let path = FieldPath::parse(&self.desc, path)?;
+        let last = path.segments.last().expect("path should not be empty");
+
+        // Check all list indices are in bounds before making any changes.
+        self.lookup(&path, false)?;
+
+        match (last, self.get_parent_mut(&path, false)) {
+            (PathSegment::Field(field), Some(ValueMut::Message(message))) => {
+                message.clear_field(field)
+            }
+            (PathSegment::Extension(extension), Some(ValueMut::Message(message))) => {
+                message.clear_extension(extension)
+            }
+            (PathSegment::Index(index), Some(ValueMut::Value(Value::List(list)))) => {
+                list.remove(*index);
+            }
+            (PathSegment::Key(key), Some(ValueMut::Value(Value::Map(map)))) => {
+                map.remove(key);
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Gets the value stored at the given path, or `None` if a field along the path is unset.
+    ///
+    /// Returns an error if the path contains an index which is out of bounds, treating unset
+    /// fields as empty lists. If `require_keys` is true, a missing map entry is also an error.
+    fn lookup(&self, path: &FieldPath, require_keys: bool) -> Result<Option<&Value>, PathError> {
+        let mut message = self;
+        let mut value = None;
+        for (index, segment) in path.segments.iter().enumerate() {
+            value = match segment {
+                PathSegment::Field(field) => get_stored(message.fields.get(field)),
+                PathSegment::Extension(extension) => get_stored(message.fields.get(extension)),
+                PathSegment::Index(list_index) => {
+                    let list = value.and_then(Value::as_list).unwrap_or_default();
+                    match list.get(*list_index) {
+                        Some(element) => Some(element),
+                        None => {
+                            return Err(PathError::IndexOutOfBounds {
+                                path: path.prefix(index + 1),
+                                len: list.len(),
+                            })
+                        }
+                    }
+                }
+                PathSegment::Key(key) => value.and_then(Value::as_map).and_then(|map| map.get(key)),
+            };
+
+            match value {
+                Some(Value::Message(nested)) => message = nested,
+                Some(_) => (),
+                None => {
+                    if require_keys && matches!(segment, PathSegment::Key(_)) {
+                        return Err(PathError::KeyNotFound {
+                            path: path.prefix(index + 1),
+                        });
+                    }
+                    return path.check_unset(index + 1, require_keys).map(|()| None);
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Gets a mutable reference to the value containing the last segment of the path.
+    ///
+    /// If `create` is true, unset fields and map entries are initialized. Otherwise, `None` is
+    /// returned if any of them are unset. The path must have been checked with
+    /// [`lookup()`](Self::lookup) first.
+    fn get_parent_mut(&mut self, path: &FieldPath, create: bool) -> Option<ValueMut<'_>> {
+        let parents = &path.segments[..path.segments.len() - 1];
+
+        let mut current = ValueMut::Message(self);
+        let mut element_kind = None;
+        for segment in parents {
+            current = match (segment, current) {
+                (PathSegment::Field(field), ValueMut::Message(message)) => {
+                    if !create && !message.has_field(field) {
+                        return None;
+                    }
+                    ValueMut::Value(message.get_field_mut(field))
+                }
+                (PathSegment::Extension(extension), ValueMut::Message(message)) => {
+                    if !create && !message.has_extension(extension) {
+                        return None;
+                    }
+                    ValueMut::Value(message.get_extension_mut(extension))
+                }
+                (PathSegment::Index(index), ValueMut::Value(Value::List(list))) => {
+                    ValueMut::Value(list.get_mut(*index)?)
+                }
+                (PathSegment::Key(key), ValueMut::Value(Value::Map(map))) => {
+                    if create {
+                        let kind = element_kind.as_ref()?;
+                        ValueMut::Value(
+                            map.entry(key.clone())
+                                .or_insert_with(|| Value::default_value(kind)),
+                        )
+                    } else {
+                        ValueMut::Value(map.get_mut(key)?)
+                    }
+                }
+                _ => return None,
+            };
+
+            element_kind = container_element_kind(segment);
+            if let ValueMut::Value(Value::Message(message)) = current {
+                current = ValueMut::Message(message);
+            }
+        }
+
+        Some(current)
+    }
+}
+
+impl FieldPath {
+    pub(super) fn prefix(&self, len: usize) -> String {
+        FieldPath {
+            segments: self.segments[..len].to_vec(),
+        }
+        .to_string()
+    }
+
+    /// Checks the segments of the path starting at `start` can be resolved, given that the
+    /// value before them is unset.
+    fn check_unset(&self, start: usize, require_keys: bool) -> Result<(), PathError> {
+        for (index, segment) in self.segments.iter().enumerate().skip(start) {
+            match segment {
+                PathSegment::Index(_) => {
+                    return Err(PathError::IndexOutOfBounds {
+                        path: self.prefix(index + 1),
+                        len: 0,
+                    })
+                }
+                PathSegment::Key(_) if require_keys => {
+                    return Err(PathError::KeyNotFound {
+                        path: self.prefix(index + 1),
+                    })
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+enum ValueMut<'a> {
+    Message(&'a mut DynamicMessage),
+    Value(&'a mut Value),
+}
+
+/// Returns the stored value of a field, or `None` if it is not set.
+fn get_stored(value: Cow<'_, Value>) -> Option<&Value> {
+    match value {
+        Cow::Borrowed(value) => Some(value),
+        Cow::Owned(_) => None,
+    }
+}
+
+/// Checks that any messages in the value have the expected type, which is not checked by
+/// [`Value::is_valid()`].
+fn has_message_types(value: &Value, kind: &Kind) -> bool {
+    match (value, kind) {
+        (Value::Message(message), Kind::Message(desc)) => message.desc == *desc,
+        (Value::List(list), kind) => list.iter().all(|value| has_message_types(value, kind)),
+        (Value::Map(map), Kind::Message(entry)) => {
+            let value_kind = entry.map_entry_value_field().kind();
+            map.values()
+                .all(|value| has_message_types(value, &value_kind))
+        }
+        _ => true,
+    }
+}
+
+/// If the segment refers to a list or map field, returns the type of its elements or values.
+fn container_element_kind(segment: &PathSegment) -> Option<Kind> {
+    match segment {
+        PathSegment::Field(field) => field_element_kind(field),
+        PathSegment::Extension(extension) => field_element_kind(extension),
+        PathSegment::Index(_) | PathSegment::Key(_) => None,
+    }
+}
+
+fn field_element_kind(field: &impl FieldDescriptorLike) -> Option<Kind> {
+    if field.is_map() {
+        Some(
+            field
+                .kind()
+                .as_message()
+                .expect("map field should be a message")
+                .map_entry_value_field()
+                .kind(),
+        )
+    } else if field.is_list() {
+        Some(field.kind())
+    } else {
+        None
+    }
+}
+
+impl Cursor {
+    fn new(field: &impl FieldDescriptorLike) -> Self {
+        if field.is_map() {
+            let entry = field.kind();
+            let entry = entry.as_message().expect("map field should be a message");
+            Cursor::Map(
+                entry.map_entry_key_field().kind(),
+                entry.map_entry_value_field().kind(),
+            )
+        } else if field.is_list() {
+            Cursor::List(field.kind())
+        } else {
+            Cursor::from_kind(field.kind())
+        }
+    }
+
+    fn from_kind(kind: Kind) -> Self {
+        match kind {
+            Kind::Message(message) => Cursor::Message(message),
+            _ => Cursor::Scalar,
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self, desc: &MessageDescriptor) -> Result<FieldPath, PathError> {
+        let mut cursor = self.parse_field(desc)?;
+        while let Some(ch) = self.peek() {
+            cursor = match ch {
+                '.' => {
+                    self.pos += 1;
+                    match cursor {
+                        Cursor::Message(message) => self.parse_field(&message)?,
+                        _ => {
+                            return Err(PathError::NotAMessage {
+                                path: self.path.to_string(),
+                            })
+                        }
+                    }
+                }
+                '[' => {
+                    self.pos += 1;
+                    let cursor = match cursor {
+                        Cursor::List(kind) => self.parse_index(kind)?,
+                        Cursor::Map(key_kind, value_kind) => {
+                            self.parse_key(&key_kind, value_kind)?
+                        }
+                        _ => {
+                            return Err(PathError::NotAListOrMap {
+                                path: self.path.to_string(),
+                            })
+                        }
+                    };
+                    self.expect(']')?;
+                    cursor
+                }
+                _ => return Err(self.syntax_error()),
+            };
+        }
+
+        Ok(self.path)
+    }
+
+    fn parse_field(&mut self, desc: &MessageDescriptor) -> Result<Cursor, PathError> {
+        if self.peek() == Some('[') {
+            self.pos += 1;
+            let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '.');
+            if name.is_empty() {
+                return Err(self.syntax_error());
+            }
+            self.expect(']')?;
+
+            let name = name.strip_prefix('.').unwrap_or(name);
+            let extension = desc.get_extension_by_full_name(name).ok_or_else(|| {
+                PathError::ExtensionNotFound {
+                    message: desc.full_name().to_owned(),
+                    name: name.to_owned(),
+                }
+            })?;
+            let cursor = Cursor::new(&extension);
+            self.path.push(PathSegment::Extension(extension));
+            Ok(cursor)
+        } else {
+            let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            if name.is_empty() {
+                return Err(self.syntax_error());
+            }
+
+            let field = desc
+                .get_field_by_name(name)
+                .ok_or_else(|| PathError::FieldNotFound {
+                    message: desc.full_name().to_owned(),
+                    name: name.to_owned(),
+                })?;
+            let cursor = Cursor::new(&field);
+            self.path.push(PathSegment::Field(field));
+            Ok(cursor)
+        }
+    }
+
+    fn parse_index(&mut self, kind: Kind) -> Result<Cursor, PathError> {
+        let start = self.pos;
+        let index = self.take_while(|ch| ch.is_ascii_digit());
+        let index = index.parse().map_err(|_| PathError::InvalidSyntax {
+            path: self.input.to_owned(),
+            position: start,
+        })?;
+
+        self.path.push(PathSegment::Index(index));
+        Ok(Cursor::from_kind(kind))
+    }
+
+    fn parse_key(&mut self, key_kind: &Kind, value_kind: Kind) -> Result<Cursor, PathError> {
+        let key = match MapKey::default_value(key_kind) {
+            MapKey::String(_) => MapKey::String(self.parse_string()?),
+            default => {
+                let literal =
+                    self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '+');
+                let key = match default {
+                    MapKey::Bool(_) => literal.parse().ok().map(MapKey::Bool),
+                    MapKey::I32(_) => literal.parse().ok().map(MapKey::I32),
+                    MapKey::I64(_) => literal.parse().ok().map(MapKey::I64),
+                    MapKey::U32(_) => literal.parse().ok().map(MapKey::U32),
+                    MapKey::U64(_) => literal.parse().ok().map(MapKey::U64),
+                    MapKey::String(_) => unreachable!(),
+                };
+                key.ok_or_else(|| PathError::InvalidMapKey {
+                    path: self.path.to_string(),
+                    key: literal.to_owned(),
+                })?
+            }
+        };
+
+        self.path.push(PathSegment::Key(key));
+        Ok(Cursor::from_kind(value_kind))
+    }
+
+    fn parse_string(&mut self) -> Result<String, PathError> {
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => {
+                let literal = self.take_while(|ch| ch != ']');
+                return Err(PathError::InvalidMapKey {
+                    path: self.path.to_string(),
+                    key: literal.to_owned(),
+                });
+            }
+        };
+        let start = self.pos;
+        self.pos += 1;
+
+        let mut bytes = Vec::new();
+        loop {
+            let ch = self.peek().ok_or_else(|| self.syntax_error())?;
+            self.pos += ch.len_utf8();
+            match ch {
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.syntax_error())?;
+                    self.pos += escape.len_utf8();
+                    match escape {
+                        'n' => bytes.push(b'\n'),
+                        'r' => bytes.push(b'\r'),
+                        't' => bytes.push(b'\t'),
+                        '\\' | '\'' | '"' => bytes.push(escape as u8),
+                        '0'..='7' => {
+                            let start = self.pos - 1;
+                            let digits =
+                                1 + self.take_while_max(2, |ch| matches!(ch, '0'..='7')).len();
+                            let value = u8::from_str_radix(&self.input[start..start + digits], 8)
+                                .map_err(|_| self.syntax_error_at(start))?;
+                            bytes.push(value);
+                        }
+                        'x' => {
+                            let start = self.pos;
+                            let digits = self.take_while_max(2, |ch| ch.is_ascii_hexdigit());
+                            let value = u8::from_str_radix(digits, 16)
+                                .map_err(|_| self.syntax_error_at(start))?;
+                            bytes.push(value);
+                        }
+                        _ => return Err(self.syntax_error_at(self.pos - escape.len_utf8())),
+                    }
+                }
+                _ if ch == quote => break,
+                _ => {
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.syntax_error_at(start))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PathError> {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.syntax_error())
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        self.take_while_max(usize::MAX, f)
+    }
+
+    fn take_while_max(&mut self, max: usize, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        for ch in self.input[start..].chars().take(max) {
+            if !f(ch) {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn syntax_error(&self) -> PathError {
+        self.syntax_error_at(self.pos)
+    }
+
+    fn syntax_error_at(&self, position: usize) -> PathError {
+        PathError::InvalidSyntax {
+            path: self.input.to_owned(),
+            position,
+        }
+    }
+}
+
+impl From<Vec<PathSegment>> for FieldPath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        FieldPath { segments }
+    }
+}
+
+impl FromIterator<PathSegment> for FieldPath {
+    fn from_iter<T: IntoIterator<Item = PathSegment>>(iter: T) -> Self {
+        FieldPath {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Field(field) => {
+                    if index != 0 {
+                        f.write_char('.')?;
+                    }
+                    f.write_str(field.name())?;
+                }
+                PathSegment::Extension(extension) => {
+                    if index != 0 {
+                        f.write_char('.')?;
+                    }
+                    write!(f, "[{}]", extension.full_name())?;
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Key(key) => {
+                    f.write_char('[')?;
+                    fmt_map_key(f, key)?;
+                    f.write_char(']')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fmt_map_key(f: &mut fmt::Formatter<'_>, key: &MapKey) -> fmt::Result {
+    match key {
+        MapKey::Bool(value) => write!(f, "{value}"),
+        MapKey::I32(value) => write!(f, "{value}"),
+        MapKey::I64(value) => write!(f, "{value}"),
+        MapKey::U32(value) => write!(f, "{value}"),
+        MapKey::U64(value) => write!(f, "{value}"),
+        MapKey::String(value) => fmt_string(f, value.as_bytes()),
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::InvalidSyntax { path, position } => {
+                write!(f, "invalid syntax at position {position} in path '{path}'")
+            }
+            PathError::FieldNotFound { message, name } => {
+                write!(f, "field '{name}' not found in message '{message}'")
+            }
+            PathError::ExtensionNotFound { message, name } => {
+                write!(f, "extension '{name}' not found for message '{message}'")
+            }
+            PathError::NotAMessage { path } => write!(f, "the value at '{path}' is not a message"),
+            PathError::NotAListOrMap { path } => {
+                write!(f, "the value at '{path}' is not a list or map")
+            }
+            PathError::InvalidMapKey { path, key } => {
+                write!(f, "invalid key '{key}' for the map at '{path}'")
+            }
+            PathError::IndexOutOfBounds { path, len } => {
+                write!(f, "index out of bounds at '{path}': the length is {len}")
+            }
+            PathError::KeyNotFound { path } => write!(f, "map key not found at '{path}'"),
+            PathError::InvalidType { path, value } => {
+                write!(f, "the value '{value}' is not valid for '{path}'")
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::{DynamicMessage, FieldPath, PathSegment, Value};
+
+impl DynamicMessage {
+    /// Returns `true` if all `required` fields of this message are set, including those of any
+    /// nested messages in fields, lists, maps and extensions.
+    ///
+    /// Messages which do not use `proto2` required fields are always initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let dynamic_message = DynamicMessage::new(message_descriptor);
+    /// assert!(dynamic_message.is_initialized());
+    /// ```
+    pub fn is_initialized(&self) -> bool {
+        self.desc
+            .fields()
+            .all(|field| !field.is_required() || self.has_field(&field))
+            && self.fields().all(|(_, value)| value.is_initialized())
+            && self.extensions().all(|(_, value)| value.is_initialized())
+    }
+
+    /// Returns the paths of all `required` fields which are not set in this message or any
+    /// nested messages.
+    ///
+    /// Fields are visited in the order they are defined in the message, followed by extensions,
+    /// and map entries are visited in key order. The message is [initialized](Self::is_initialized)
+    /// if and only if the result is empty.
+    pub fn missing_required_fields(&self) -> Vec<FieldPath> {
+        let mut missing = Vec::new();
+        self.find_missing_required_fields(&mut FieldPath::new(), &mut missing);
+        missing
+    }
+
+    fn find_missing_required_fields(&self, path: &mut FieldPath, missing: &mut Vec<FieldPath>) {
+        for field in self.desc.fields() {
+            if self.has_field(&field) {
+                let value = self.get_field(&field);
+                path.push(PathSegment::Field(field));
+                value.find_missing_required_fields(path, missing);
+                path.pop();
+            } else if field.is_required() {
+                path.push(PathSegment::Field(field));
+                missing.push(path.clone());
+                path.pop();
+            }
+        }
+
+        for (extension, value) in self.extensions() {
+            path.push(PathSegment::Extension(extension));
+            value.find_missing_required_fields(path, missing);
+            path.pop();
+        }
+    }
+}
+
+impl Value {
+    fn is_initialized(&self) -> bool {
+        match self {
+            Value::Message(message) => message.is_initialized(),
+            Value::List(values) => values.iter().all(Value::is_initialized),
+            Value::Map(values) => values.values().all(Value::is_initialized),
+            _ => true,
+        }
+    }
+
+    fn find_missing_required_fields(&self, path: &mut FieldPath, missing: &mut Vec<FieldPath>) {
+        match self {
+            Value::Message(message) => message.find_missing_required_fields(path, missing),
+            Value::List(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.find_missing_required_fields(path, missing);
+                    path.pop();
+                }
+            }
+            Value::Map(values) => {
+                let mut entries: Vec<_> = values.iter().collect();
+                entries.sort_unstable_by(|(l, _), (r, _)| l.cmp(r));
+                for (key, value) in entries {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.find_missing_required_fields(path, missing);
+                    path.pop();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Displays the paths included in errors for messages with missing required fields.
+pub(super) struct MissingRequiredFields<'a, T>(pub &'a [T]);
+
+impl<T> fmt::Display for MissingRequiredFields<'_, T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing required fields")?;
+        for (i, path) in self.0.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { ", " };
+            write!(f, "{sep}'{path}'")?;
+        }
+        Ok(())
+    }
+}
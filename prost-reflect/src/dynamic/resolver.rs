@@ -0,0 +1,83 @@
+use std::{fmt, sync::Arc};
+
+use crate::{dynamic::get_type_url_message_name, DescriptorPool, MessageDescriptor};
+
+/// Resolves the message types named by the type URLs of `google.protobuf.Any` messages.
+///
+/// By default, the payload of an `Any` message is resolved against the [`DescriptorPool`] of the
+/// message containing it. A custom resolver can be used to look up types in a separate registry
+/// instead, for example one which is populated lazily as new types are encountered.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{DescriptorPool, MessageDescriptor, TypeResolver};
+/// struct Registry {
+///     pools: Vec<DescriptorPool>,
+/// }
+///
+/// impl TypeResolver for Registry {
+///     fn resolve_message_type(&self, type_url: &str) -> Option<MessageDescriptor> {
+///         self.pools
+///             .iter()
+///             .find_map(|pool| pool.resolve_message_type(type_url))
+///     }
+/// }
+/// ```
+pub trait TypeResolver: Send + Sync {
+    /// Resolves the message type for a type URL, such as
+    /// `type.googleapis.com/google.protobuf.Duration`.
+    ///
+    /// Returns `None` if the type is not known.
+    fn resolve_message_type(&self, type_url: &str) -> Option<MessageDescriptor>;
+}
+
+impl TypeResolver for DescriptorPool {
+    /// Resolves the type URL by looking up the message name after its last `/` in this pool.
+    fn resolve_message_type(&self, type_url: &str) -> Option<MessageDescriptor> {
+        let message_name = get_type_url_message_name(type_url).ok()?;
+        self.get_message_by_name(message_name)
+    }
+}
+
+/// An optional [`TypeResolver`], stored in the options for serialization, parsing and formatting.
+#[derive(Clone, Default)]
+pub(crate) struct OptionalTypeResolver(Option<Arc<dyn TypeResolver>>);
+
+impl OptionalTypeResolver {
+    pub(crate) const fn new() -> Self {
+        OptionalTypeResolver(None)
+    }
+
+    #[cfg_attr(not(any(feature = "serde", feature = "text-format")), allow(dead_code))]
+    pub(crate) fn set<R>(&mut self, resolver: R)
+    where
+        R: TypeResolver + 'static,
+    {
+        self.0 = Some(Arc::new(resolver));
+    }
+
+    /// Resolves the message type for a type URL, using the custom resolver if one is set, or
+    /// `pool` otherwise.
+    pub(crate) fn resolve(
+        &self,
+        pool: &DescriptorPool,
+        type_url: &str,
+    ) -> Result<MessageDescriptor, String> {
+        let message_name = get_type_url_message_name(type_url)?;
+        let message_desc = match &self.0 {
+            Some(resolver) => resolver.resolve_message_type(type_url),
+            None => pool.get_message_by_name(message_name),
+        };
+        message_desc.ok_or_else(|| format!("message '{message_name}' not found"))
+    }
+}
+
+impl fmt::Debug for OptionalTypeResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(..)"),
+            None => f.write_str("None"),
+        }
+    }
+}
@@ -566,10 +566,14 @@ impl<'de> Visitor<'de> for MessageVisitorInner<'_> {
     {
         let desc = self.0.descriptor();
         while let Some(key) = map.next_key::<Cow<str>>()? {
-            if let Some(field) = desc
-                .get_field_by_json_name(key.as_ref())
-                .or_else(|| desc.get_field_by_name(key.as_ref()))
-            {
+            let field = if self.1.allow_proto_field_names_only {
+                desc.get_field_by_name(key.as_ref())
+            } else {
+                desc.get_field_by_json_name(key.as_ref())
+                    .or_else(|| desc.get_field_by_name(key.as_ref()))
+            };
+
+            if let Some(field) = field {
                 if let Some(value) =
                     map.next_value_seed(OptionalFieldDescriptorSeed(&field, self.1))?
                 {
@@ -615,10 +619,20 @@ impl Visitor<'_> for EnumVisitor<'_> {
     where
         E: Error,
     {
-        match self.0.get_value_by_name(v) {
+        let value = self.0.get_value_by_name(v).or_else(|| {
+            if self.1.case_insensitive_enum_names {
+                self.0
+                    .values()
+                    .find(|value| value.name().eq_ignore_ascii_case(v))
+            } else {
+                None
+            }
+        });
+
+        match value {
             Some(e) => Ok(Some(e.number())),
             None => {
-                if self.1.deny_unknown_fields {
+                if self.1.deny_unknown_enum_values() {
                     Err(Error::custom(format!("unrecognized enum value '{v}'")))
                 } else {
                     Ok(None)
@@ -632,7 +646,13 @@ impl Visitor<'_> for EnumVisitor<'_> {
     where
         E: Error,
     {
-        Ok(Some(v))
+        if !self.0.is_closed() || self.0.get_value(v).is_some() {
+            Ok(Some(v))
+        } else if self.1.deny_unknown_enum_values() {
+            Err(Error::custom(format!("unrecognized enum value {v}")))
+        } else {
+            Ok(None)
+        }
     }
 
     #[inline]
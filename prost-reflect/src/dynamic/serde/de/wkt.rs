@@ -5,7 +5,7 @@ use std::{
     marker::PhantomData,
 };
 
-use prost::Message;
+use prost::{bytes::Bytes, Message};
 use serde::de::{
     DeserializeSeed, Deserializer, Error, IgnoredAny, IntoDeserializer, MapAccess, SeqAccess,
     Visitor,
@@ -13,7 +13,6 @@ use serde::de::{
 
 use crate::{
     dynamic::{
-        get_type_url_message_name,
         serde::{
             case::camel_case_to_snake_case, check_duration, check_timestamp, is_well_known_type,
             DeserializeOptions,
@@ -23,7 +22,11 @@ use crate::{
     DescriptorPool,
 };
 
-use super::{deserialize_message, kind::MessageVisitorInner, MessageSeed};
+use super::{
+    deserialize_message,
+    kind::{BytesVisitor, MessageVisitorInner},
+    MessageSeed,
+};
 
 pub struct GoogleProtobufAnyVisitor<'a>(pub &'a DescriptorPool, pub &'a DeserializeOptions);
 pub struct GoogleProtobufNullVisitor<'a>(pub &'a DeserializeOptions);
@@ -34,6 +37,7 @@ pub struct GoogleProtobufListVisitor;
 pub struct GoogleProtobufStructVisitor;
 pub struct GoogleProtobufValueVisitor;
 pub struct GoogleProtobufEmptyVisitor;
+struct BytesSeed;
 
 impl<'de> Visitor<'de> for GoogleProtobufAnyVisitor<'_> {
     type Value = prost_types::Any;
@@ -55,13 +59,24 @@ impl<'de> Visitor<'de> for GoogleProtobufAnyVisitor<'_> {
             PhantomData::<String>,
         )?;
 
-        let message_name = get_type_url_message_name(&type_url).map_err(Error::custom)?;
-        let message_desc = self
-            .0
-            .get_message_by_name(message_name)
-            .ok_or_else(|| Error::custom(format!("message '{message_name}' not found")))?;
+        let message_desc = match self.1.type_resolver.resolve(self.0, &type_url) {
+            Ok(message_desc) => message_desc,
+            Err(_) if self.1.unresolved_any_as_bytes => {
+                let value = match buffered_entries.remove("value") {
+                    Some(value) => value.deserialize_str(BytesVisitor).map_err(Error::custom)?,
+                    None => find_field(&mut map, &mut buffered_entries, "value", BytesSeed)?,
+                };
+                finish_map(map, buffered_entries, self.1)?;
+
+                return Ok(prost_types::Any {
+                    type_url,
+                    value: value.into(),
+                });
+            }
+            Err(err) => return Err(Error::custom(err)),
+        };
 
-        let payload_message = if is_well_known_type(message_name) {
+        let payload_message = if is_well_known_type(message_desc.full_name()) {
             let payload_message = match buffered_entries.remove("value") {
                 Some(value) => {
                     deserialize_message(&message_desc, value, self.1).map_err(Error::custom)?
@@ -73,18 +88,7 @@ impl<'de> Visitor<'de> for GoogleProtobufAnyVisitor<'_> {
                     MessageSeed(&message_desc, self.1),
                 )?,
             };
-
-            if self.1.deny_unknown_fields {
-                if let Some(key) = buffered_entries.keys().next() {
-                    return Err(Error::custom(format!("unrecognized field name '{key}'")));
-                }
-                if let Some(key) = map.next_key::<Cow<str>>()? {
-                    return Err(Error::custom(format!("unrecognized field name '{key}'")));
-                }
-            } else {
-                drop(buffered_entries);
-                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
-            }
+            finish_map(map, buffered_entries, self.1)?;
 
             payload_message
         } else {
@@ -118,7 +122,7 @@ impl Visitor<'_> for GoogleProtobufNullVisitor<'_> {
     {
         if v == "NULL_VALUE" {
             Ok(Some(0))
-        } else if self.0.deny_unknown_fields {
+        } else if self.0.deny_unknown_enum_values() {
             Err(Error::custom("expected null"))
         } else {
             Ok(None)
@@ -366,6 +370,41 @@ impl<'de> Visitor<'de> for GoogleProtobufEmptyVisitor {
     }
 }
 
+impl<'de> DeserializeSeed<'de> for BytesSeed {
+    type Value = Bytes;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(BytesVisitor)
+    }
+}
+
+/// Checks for, or skips, any remaining entries in an `Any` map after its `value` field.
+fn finish_map<'de, A>(
+    mut map: A,
+    buffered_entries: HashMap<Cow<str>, serde_value::Value>,
+    options: &DeserializeOptions,
+) -> Result<(), A::Error>
+where
+    A: MapAccess<'de>,
+{
+    if options.deny_unknown_fields {
+        if let Some(key) = buffered_entries.keys().next() {
+            return Err(Error::custom(format!("unrecognized field name '{key}'")));
+        }
+        if let Some(key) = map.next_key::<Cow<str>>()? {
+            return Err(Error::custom(format!("unrecognized field name '{key}'")));
+        }
+    } else {
+        drop(buffered_entries);
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+    }
+
+    Ok(())
+}
+
 fn find_field<'de, A, D>(
     map: &mut A,
     buffered_entries: &mut HashMap<Cow<str>, serde_value::Value>,
@@ -3,11 +3,16 @@ mod de;
 mod ser;
 
 use serde::{
-    de::{DeserializeSeed, Deserializer},
+    de::{DeserializeSeed, Deserializer, Error as _},
     ser::{Serialize, Serializer},
 };
 
-use crate::{DynamicMessage, MessageDescriptor};
+use std::collections::BTreeMap;
+
+use crate::{
+    dynamic::{required::MissingRequiredFields, resolver::OptionalTypeResolver},
+    DynamicMessage, FieldDescriptor, MessageDescriptor, TypeResolver,
+};
 
 /// Options to control serialization of messages.
 ///
@@ -19,6 +24,9 @@ pub struct SerializeOptions {
     use_enum_numbers: bool,
     use_proto_field_name: bool,
     skip_default_fields: bool,
+    enum_number_fields: BTreeMap<String, bool>,
+    type_resolver: OptionalTypeResolver,
+    unresolved_any_as_bytes: bool,
 }
 
 /// Options to control deserialization of messages.
@@ -28,6 +36,12 @@ pub struct SerializeOptions {
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 pub struct DeserializeOptions {
     deny_unknown_fields: bool,
+    ignore_unknown_enum_values: bool,
+    allow_proto_field_names_only: bool,
+    case_insensitive_enum_names: bool,
+    type_resolver: OptionalTypeResolver,
+    unresolved_any_as_bytes: bool,
+    allow_partial: bool,
 }
 
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
@@ -167,7 +181,15 @@ impl DynamicMessage {
     where
         D: Deserializer<'de>,
     {
-        de::deserialize_message(&desc, deserializer, options)
+        let message = de::deserialize_message(&desc, deserializer, options)?;
+
+        if !options.allow_partial && !message.is_initialized() {
+            return Err(D::Error::custom(MissingRequiredFields(
+                &message.missing_required_fields(),
+            )));
+        }
+
+        Ok(message)
     }
 }
 
@@ -177,16 +199,103 @@ impl DeserializeOptions {
     pub const fn new() -> Self {
         DeserializeOptions {
             deny_unknown_fields: true,
+            ignore_unknown_enum_values: false,
+            allow_proto_field_names_only: false,
+            case_insensitive_enum_names: false,
+            type_resolver: OptionalTypeResolver::new(),
+            unresolved_any_as_bytes: false,
+            allow_partial: true,
         }
     }
 
     /// Whether to error during deserialization when encountering unknown message fields.
     ///
+    /// If `false`, unknown enum value names are also ignored, as if
+    /// [`ignore_unknown_enum_values`][Self::ignore_unknown_enum_values] was set.
+    ///
     /// The default value is `true`.
     pub const fn deny_unknown_fields(mut self, yes: bool) -> Self {
         self.deny_unknown_fields = yes;
         self
     }
+
+    /// Whether to ignore enum values which are not defined in the enum type.
+    ///
+    /// If `true`, a singular field with an unknown enum value is left unset, and unknown values in
+    /// repeated fields and map values are dropped. Otherwise, deserialization fails. This applies
+    /// to values given by name, and to values given as integers for
+    /// [closed](crate::EnumDescriptor::is_closed) enums. Unknown values of open enums given as
+    /// integers are always kept as their numeric value.
+    ///
+    /// The default value is `false`.
+    pub const fn ignore_unknown_enum_values(mut self, yes: bool) -> Self {
+        self.ignore_unknown_enum_values = yes;
+        self
+    }
+
+    /// Whether to only accept the proto field name for message fields.
+    ///
+    /// By default, fields can be given either by their lowerCamelCase JSON name or their name in
+    /// the proto file. If this option is set, the JSON name is only accepted if it is the same as
+    /// the proto field name, and otherwise is treated as an unknown field.
+    ///
+    /// The default value is `false`.
+    pub const fn allow_proto_field_names_only(mut self, yes: bool) -> Self {
+        self.allow_proto_field_names_only = yes;
+        self
+    }
+
+    /// Whether to match enum value names case-insensitively.
+    ///
+    /// The spec requires enum value names to match exactly, so this option is disabled by default.
+    /// If enabled, an exact match is still preferred if there is one.
+    ///
+    /// The default value is `false`.
+    pub const fn case_insensitive_enum_names(mut self, yes: bool) -> Self {
+        self.case_insensitive_enum_names = yes;
+        self
+    }
+
+    /// Sets the resolver used to find the message type of `google.protobuf.Any` payloads.
+    ///
+    /// By default, types are looked up in the [`DescriptorPool`](crate::DescriptorPool) of the
+    /// message being deserialized.
+    pub fn type_resolver<R>(mut self, resolver: R) -> Self
+    where
+        R: TypeResolver + 'static,
+    {
+        self.type_resolver.set(resolver);
+        self
+    }
+
+    /// Whether to accept `google.protobuf.Any` messages whose type cannot be resolved, if their
+    /// payload is given as raw bytes.
+    ///
+    /// If `true`, an `Any` message with an unknown type may be written as an object with only the
+    /// `@type` field and a `value` field containing the base64-encoded payload, in the form written by
+    /// [`SerializeOptions::unresolved_any_as_bytes`]. The payload is kept without being decoded.
+    /// Otherwise, deserialization fails if the type cannot be resolved.
+    ///
+    /// The default value is `false`.
+    pub const fn unresolved_any_as_bytes(mut self, yes: bool) -> Self {
+        self.unresolved_any_as_bytes = yes;
+        self
+    }
+
+    /// Whether to accept messages which are missing `required` fields.
+    ///
+    /// If `false`, deserialization fails unless the message, including any nested messages, is
+    /// [initialized](DynamicMessage::is_initialized).
+    ///
+    /// The default value is `true`.
+    pub const fn allow_partial(mut self, yes: bool) -> Self {
+        self.allow_partial = yes;
+        self
+    }
+
+    fn deny_unknown_enum_values(&self) -> bool {
+        self.deny_unknown_fields && !self.ignore_unknown_enum_values
+    }
 }
 
 impl Default for DeserializeOptions {
@@ -204,6 +313,9 @@ impl SerializeOptions {
             use_enum_numbers: false,
             use_proto_field_name: false,
             skip_default_fields: true,
+            enum_number_fields: BTreeMap::new(),
+            type_resolver: OptionalTypeResolver::new(),
+            unresolved_any_as_bytes: false,
         }
     }
 
@@ -230,6 +342,14 @@ impl SerializeOptions {
         self
     }
 
+    /// Whether to encode the values of the given enum field as their numeric value, overriding
+    /// [`use_enum_numbers`][Self::use_enum_numbers] for that field.
+    pub fn use_enum_numbers_for_field(mut self, field: &FieldDescriptor, yes: bool) -> Self {
+        self.enum_number_fields
+            .insert(field.full_name().to_owned(), yes);
+        self
+    }
+
     /// Whether to use the proto field name instead of the lowerCamelCase name in JSON field names.
     ///
     /// The default value is `false`.
@@ -248,6 +368,73 @@ impl SerializeOptions {
         self.skip_default_fields = yes;
         self
     }
+
+    /// Whether to serialize fields which do not track presence, even if they have their default
+    /// value.
+    ///
+    /// This applies to repeated and map fields, and to singular proto3 fields not marked
+    /// `optional`. Fields which track presence, such as messages, are still only serialized if
+    /// they are set. This is the inverse of [`skip_default_fields`][Self::skip_default_fields],
+    /// under the name used by other protobuf implementations.
+    ///
+    /// The default value is `false`.
+    pub const fn always_print_fields_with_no_presence(mut self, yes: bool) -> Self {
+        self.skip_default_fields = !yes;
+        self
+    }
+
+    /// Sets the resolver used to find the message type of `google.protobuf.Any` payloads.
+    ///
+    /// By default, types are looked up in the [`DescriptorPool`](crate::DescriptorPool) of the
+    /// message being serialized.
+    pub fn type_resolver<R>(mut self, resolver: R) -> Self
+    where
+        R: TypeResolver + 'static,
+    {
+        self.type_resolver.set(resolver);
+        self
+    }
+
+    /// Whether to serialize `google.protobuf.Any` messages whose type cannot be resolved as raw
+    /// bytes.
+    ///
+    /// If `true`, an `Any` message with an unknown type is written as an object with its `@type`
+    /// and a `value` field containing the base64-encoded payload. This is not part of the standard
+    /// JSON mapping, but can be read back using [`DeserializeOptions::unresolved_any_as_bytes`].
+    /// Otherwise, serialization fails if the type cannot be resolved.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost::Message;
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, Value, SerializeOptions, bytes::Bytes};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let message_descriptor = pool.get_message_by_name("google.protobuf.Any").unwrap();
+    /// let mut dynamic_message = DynamicMessage::new(message_descriptor);
+    /// dynamic_message.set_field_by_name("type_url", Value::String("type.googleapis.com/package.Unknown".to_owned()));
+    /// dynamic_message.set_field_by_name("value", Value::Bytes(Bytes::from_static(b"\x08\x96\x01")));
+    ///
+    /// let mut serializer = serde_json::Serializer::new(vec![]);
+    /// let options = SerializeOptions::new().unresolved_any_as_bytes(true);
+    /// dynamic_message.serialize_with_options(&mut serializer, &options).unwrap();
+    /// assert_eq!(
+    ///     serializer.into_inner(),
+    ///     br#"{"@type":"type.googleapis.com/package.Unknown","value":"CJYB"}"#,
+    /// );
+    /// ```
+    pub const fn unresolved_any_as_bytes(mut self, yes: bool) -> Self {
+        self.unresolved_any_as_bytes = yes;
+        self
+    }
+
+    fn use_enum_numbers_for(&self, field_name: &str) -> bool {
+        self.enum_number_fields
+            .get(field_name)
+            .copied()
+            .unwrap_or(self.use_enum_numbers)
+    }
 }
 
 impl Default for SerializeOptions {
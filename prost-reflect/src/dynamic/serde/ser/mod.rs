@@ -59,18 +59,26 @@ where
         .iter(&value.desc, !options.skip_default_fields, false);
 
     for field in fields {
-        let (name, value, ref kind) = match field {
+        let (name, value, ref kind, use_enum_numbers) = match field {
             ValueAndDescriptor::Field(value, ref field_desc) => {
                 let name = if options.use_proto_field_name {
                     field_desc.name()
                 } else {
                     field_desc.json_name()
                 };
-                (name, value, field_desc.kind())
-            }
-            ValueAndDescriptor::Extension(value, ref extension_desc) => {
-                (extension_desc.json_name(), value, extension_desc.kind())
+                (
+                    name,
+                    value,
+                    field_desc.kind(),
+                    options.use_enum_numbers_for(field_desc.full_name()),
+                )
             }
+            ValueAndDescriptor::Extension(value, ref extension_desc) => (
+                extension_desc.json_name(),
+                value,
+                extension_desc.kind(),
+                options.use_enum_numbers_for(extension_desc.full_name()),
+            ),
             ValueAndDescriptor::Unknown(_) => continue,
         };
 
@@ -80,6 +88,7 @@ where
                 value: &ValueAndKind {
                     value: value.as_ref(),
                     kind,
+                    use_enum_numbers,
                 },
                 options,
             },
@@ -92,6 +101,7 @@ where
 struct ValueAndKind<'a> {
     value: &'a Value,
     kind: &'a Kind,
+    use_enum_numbers: bool,
 }
 
 impl<'a> Serialize for SerializeWrapper<'a, ValueAndKind<'a>> {
@@ -156,7 +166,7 @@ impl<'a> Serialize for SerializeWrapper<'a, ValueAndKind<'a>> {
 
                 if enum_ty.full_name() == "google.protobuf.NullValue" {
                     serializer.serialize_none()
-                } else if self.options.use_enum_numbers {
+                } else if self.value.use_enum_numbers {
                     serializer.serialize_i32(*number)
                 } else if let Some(enum_value) = enum_ty.get_value(*number) {
                     serializer.serialize_str(enum_value.name())
@@ -172,6 +182,7 @@ impl<'a> Serialize for SerializeWrapper<'a, ValueAndKind<'a>> {
                         value: &ValueAndKind {
                             value,
                             kind: self.value.kind,
+                            use_enum_numbers: self.value.use_enum_numbers,
                         },
                         options: self.options,
                     })?;
@@ -200,6 +211,7 @@ impl<'a> Serialize for SerializeWrapper<'a, ValueAndKind<'a>> {
                             value: &ValueAndKind {
                                 value,
                                 kind: &value_kind,
+                                use_enum_numbers: self.value.use_enum_numbers,
                             },
                             options: self.options,
                         },
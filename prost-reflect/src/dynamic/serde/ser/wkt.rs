@@ -1,10 +1,9 @@
-use base64::{display::Base64Display, prelude::BASE64_STANDARD};
+use base64::{display::Base64Display, prelude::BASE64_STANDARD, Engine};
 use prost::{DecodeError, Message};
 use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
 
 use crate::{
     dynamic::{
-        get_type_url_message_name,
         serde::{
             case::snake_case_to_camel_case, check_duration, check_timestamp, is_well_known_type,
             SerializeOptions,
@@ -59,20 +58,27 @@ where
 {
     let raw: prost_types::Any = msg.transcode_to().map_err(decode_to_ser_err)?;
 
-    let message_name = get_type_url_message_name(&raw.type_url).map_err(Error::custom)?;
-
-    let message_desc = msg
-        .descriptor()
-        .parent_pool()
-        .get_message_by_name(message_name)
-        .ok_or_else(|| Error::custom(format!("message '{message_name}' not found")))?;
+    let message_desc = match options
+        .type_resolver
+        .resolve(msg.descriptor().parent_pool(), &raw.type_url)
+    {
+        Ok(message_desc) => message_desc,
+        Err(_) if options.unresolved_any_as_bytes => {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_entry("@type", &raw.type_url)?;
+            map.serialize_entry("value", &BASE64_STANDARD.encode(&raw.value))?;
+            return map.end();
+        }
+        Err(err) => return Err(Error::custom(err)),
+    };
+    let is_well_known_type = is_well_known_type(message_desc.full_name());
 
     let mut payload_message = DynamicMessage::new(message_desc);
     payload_message
         .merge(raw.value.as_ref())
         .map_err(decode_to_ser_err)?;
 
-    if is_well_known_type(message_name) {
+    if is_well_known_type {
         let mut map = serializer.serialize_map(Some(2))?;
         map.serialize_entry("@type", &raw.type_url)?;
         map.serialize_entry(
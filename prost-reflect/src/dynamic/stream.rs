@@ -0,0 +1,395 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+};
+
+use prost::{bytes::Buf, encoding, DecodeError, Message};
+
+use crate::{DynamicMessage, MessageDescriptor};
+
+/// An iterator over a stream of length-delimited messages read from an [`io::Read`].
+///
+/// Each message in the stream is prefixed by its length, encoded as a varint, in the format
+/// written by [`Message::encode_length_delimited`] and [`LengthDelimitedWriter`].
+///
+/// The length prefix is read one byte at a time, so the reader should usually be buffered,
+/// for example by wrapping it in a [`BufReader`](std::io::BufReader).
+///
+/// Iteration stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// # use prost::Message;
+/// # use prost_reflect::{DescriptorPool, DynamicMessage, Value, stream::LengthDelimitedReader};
+/// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+/// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+/// let bytes = b"\x03\x08\x96\x01\x00\x02\x08\x01";
+/// let messages = LengthDelimitedReader::new(message_descriptor, bytes.as_ref())
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(messages.len(), 3);
+/// assert_eq!(messages[0].get_field_by_name("foo").unwrap().as_ref(), &Value::I32(150));
+/// assert_eq!(messages[1].get_field_by_name("foo").unwrap().as_ref(), &Value::I32(0));
+/// assert_eq!(messages[2].get_field_by_name("foo").unwrap().as_ref(), &Value::I32(1));
+/// ```
+#[derive(Debug)]
+pub struct LengthDelimitedReader<R> {
+    desc: MessageDescriptor,
+    reader: R,
+    offset: u64,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+/// An iterator over a stream of length-delimited messages read from a [`Buf`].
+///
+/// This is equivalent to [`LengthDelimitedReader`], but reads from an in-memory buffer. If the
+/// buffer is a [`Bytes`](prost::bytes::Bytes), then string and bytes fields of the decoded
+/// messages may share its allocation.
+///
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub struct LengthDelimitedIter<B> {
+    desc: MessageDescriptor,
+    buf: B,
+    offset: u64,
+    done: bool,
+}
+
+/// Writes a stream of length-delimited messages to an [`io::Write`].
+///
+/// Each message is prefixed by its length, encoded as a varint, in the same format as
+/// [`Message::encode_length_delimited`].
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{DescriptorPool, DynamicMessage, Value, stream::LengthDelimitedWriter};
+/// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+/// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+/// let mut message = DynamicMessage::new(message_descriptor);
+/// let mut writer = LengthDelimitedWriter::new(Vec::new());
+///
+/// message.set_field_by_name("foo", Value::I32(150));
+/// assert_eq!(writer.write(&message).unwrap(), 0);
+/// message.set_field_by_name("foo", Value::I32(1));
+/// assert_eq!(writer.write(&message).unwrap(), 4);
+///
+/// assert_eq!(writer.into_inner(), b"\x03\x08\x96\x01\x02\x08\x01");
+/// ```
+#[derive(Debug)]
+pub struct LengthDelimitedWriter<W> {
+    writer: W,
+    offset: u64,
+    buf: Vec<u8>,
+}
+
+/// An error reading a stream of length-delimited messages.
+#[derive(Debug)]
+pub enum StreamError {
+    /// An I/O error occurred while reading a record.
+    Io {
+        /// The byte offset of the start of the record in the stream.
+        offset: u64,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// The stream ended part of the way through a record.
+    UnexpectedEof {
+        /// The byte offset of the start of the record in the stream.
+        offset: u64,
+    },
+    /// A record could not be decoded.
+    Decode {
+        /// The byte offset of the start of the record in the stream.
+        offset: u64,
+        /// The underlying error.
+        error: DecodeError,
+    },
+}
+
+impl<R> LengthDelimitedReader<R>
+where
+    R: Read,
+{
+    /// Creates an iterator over the messages in `reader`, which are decoded as the type
+    /// specified by the [`MessageDescriptor`].
+    pub fn new(desc: MessageDescriptor, reader: R) -> Self {
+        LengthDelimitedReader {
+            desc,
+            reader,
+            offset: 0,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Gets a reference to the [`MessageDescriptor`] describing the type of messages in the
+    /// stream.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.desc
+    }
+
+    /// Returns the byte offset of the next record in the stream.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps this iterator, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_message(&mut self) -> Result<Option<DynamicMessage>, StreamError> {
+        let offset = self.offset;
+        let reader = &mut self.reader;
+        let (len, prefix_len) = match read_length(offset, || {
+            let mut byte = 0;
+            match reader.read_exact(std::slice::from_mut(&mut byte)) {
+                Ok(()) => Ok(Some(byte)),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                Err(err) => Err(err),
+            }
+        })? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+
+        self.buf.clear();
+        let read = reader
+            .take(len)
+            .read_to_end(&mut self.buf)
+            .map_err(|error| StreamError::Io { offset, error })?;
+        if read as u64 != len {
+            return Err(StreamError::UnexpectedEof { offset });
+        }
+
+        let message = DynamicMessage::decode(self.desc.clone(), self.buf.as_slice())
+            .map_err(|error| StreamError::Decode { offset, error })?;
+        self.offset += prefix_len as u64 + len;
+        Ok(Some(message))
+    }
+}
+
+impl<R> Iterator for LengthDelimitedReader<R>
+where
+    R: Read,
+{
+    type Item = Result<DynamicMessage, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_message().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+impl<B> LengthDelimitedIter<B>
+where
+    B: Buf,
+{
+    /// Creates an iterator over the messages in `buf`, which are decoded as the type specified
+    /// by the [`MessageDescriptor`].
+    pub fn new(desc: MessageDescriptor, buf: B) -> Self {
+        LengthDelimitedIter {
+            desc,
+            buf,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Gets a reference to the [`MessageDescriptor`] describing the type of messages in the
+    /// buffer.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.desc
+    }
+
+    /// Returns the byte offset of the next record in the buffer.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Unwraps this iterator, returning the remaining contents of the buffer.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    fn read_message(&mut self) -> Result<Option<DynamicMessage>, StreamError> {
+        let offset = self.offset;
+        let buf = &mut self.buf;
+        let (len, prefix_len) =
+            match read_length(offset, || Ok(buf.has_remaining().then(|| buf.get_u8())))? {
+                Some(len) => len,
+                None => return Ok(None),
+            };
+
+        if len > buf.remaining() as u64 {
+            return Err(StreamError::UnexpectedEof { offset });
+        }
+
+        let message = DynamicMessage::decode(self.desc.clone(), buf.copy_to_bytes(len as usize))
+            .map_err(|error| StreamError::Decode { offset, error })?;
+        self.offset += prefix_len as u64 + len;
+        Ok(Some(message))
+    }
+}
+
+impl<B> Iterator for LengthDelimitedIter<B>
+where
+    B: Buf,
+{
+    type Item = Result<DynamicMessage, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_message().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+impl<W> LengthDelimitedWriter<W>
+where
+    W: Write,
+{
+    /// Creates a writer which appends length-delimited messages to `writer`.
+    pub fn new(writer: W) -> Self {
+        LengthDelimitedWriter {
+            writer,
+            offset: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Writes a length-delimited message, returning the byte offset of its record in the stream.
+    pub fn write<M>(&mut self, message: &M) -> io::Result<u64>
+    where
+        M: Message,
+    {
+        self.buf.clear();
+        message
+            .encode_length_delimited(&mut self.buf)
+            .expect("vec should have sufficient capacity");
+        self.writer.write_all(&self.buf)?;
+
+        let offset = self.offset;
+        self.offset += self.buf.len() as u64;
+        Ok(offset)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the number of bytes written so far, which is the offset of the next record.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps this writer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl StreamError {
+    /// Returns the byte offset of the start of the record which caused the error.
+    pub fn offset(&self) -> u64 {
+        match *self {
+            StreamError::Io { offset, .. }
+            | StreamError::UnexpectedEof { offset }
+            | StreamError::Decode { offset, .. } => offset,
+        }
+    }
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io { offset, error } => {
+                write!(f, "failed to read record at offset {offset}: {error}")
+            }
+            StreamError::UnexpectedEof { offset } => {
+                write!(f, "unexpected end of stream in record at offset {offset}")
+            }
+            StreamError::Decode { offset, error } => {
+                write!(f, "failed to decode record at offset {offset}: {error}")
+            }
+        }
+    }
+}
+
+impl Error for StreamError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StreamError::Io { error, .. } => Some(error),
+            StreamError::UnexpectedEof { .. } => None,
+            StreamError::Decode { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Reads the varint length prefix of a record, returning the length and the size of the prefix,
+/// or `None` if the stream ends before the record.
+fn read_length(
+    offset: u64,
+    mut next_byte: impl FnMut() -> io::Result<Option<u8>>,
+) -> Result<Option<(u64, usize)>, StreamError> {
+    let mut bytes = [0; 10];
+    for index in 0..bytes.len() {
+        bytes[index] = match next_byte() {
+            Ok(Some(byte)) => byte,
+            Ok(None) if index == 0 => return Ok(None),
+            Ok(None) => return Err(StreamError::UnexpectedEof { offset }),
+            Err(error) => return Err(StreamError::Io { offset, error }),
+        };
+
+        if bytes[index] < 0x80 {
+            let len = encoding::decode_varint(&mut &bytes[..=index])
+                .map_err(|error| StreamError::Decode { offset, error })?;
+            return Ok(Some((len, index + 1)));
+        }
+    }
+
+    // The varint is longer than the maximum of ten bytes, so decoding it will fail.
+    match encoding::decode_varint(&mut bytes.as_ref()) {
+        Ok(_) => unreachable!(),
+        Err(error) => Err(StreamError::Decode { offset, error }),
+    }
+}
@@ -0,0 +1,1534 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    mem,
+    str::FromStr,
+};
+
+use logos::{Lexer, Logos, Span};
+
+use crate::{
+    dynamic::text_format::{
+        parse::{lex::Token, ParseErrorKind},
+        ParseError, ParseOptions,
+    },
+    DynamicMessage, MessageDescriptor,
+};
+
+/// A lossless syntax tree of a message written in the protobuf [text format](https://developers.google.com/protocol-buffers/docs/text-format-spec).
+///
+/// Unlike [`DynamicMessage`], a [`Document`] keeps the comments and whitespace of its input, so it
+/// can be used to edit hand-written files. Formatting a document using [`Display`] reproduces the
+/// input exactly, and the edit methods [`set()`](Document::set), [`insert()`](Document::insert)
+/// and [`remove()`](Document::remove) only change the text of the fields they touch.
+///
+/// Documents are parsed without a message descriptor. Use [`validate()`](Document::validate) or
+/// [`to_message()`](Document::to_message) to check the contents against a message type.
+///
+/// # Field paths
+///
+/// Fields are selected using their names separated by `.`, such as `nested.bar`. Extensions and
+/// expanded `google.protobuf.Any` fields are written in brackets, such as `[my.package.ext]`, and
+/// fields written by number, such as `1: 150`, are selected by their number.
+///
+/// Each part of the path may be followed by an index, such as `items[1]`. The index counts all
+/// values of the field in order, including the elements of lists like `items: [1, 2]`. Where the
+/// index is omitted, intermediate parts of the path select the first value of the field.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::text_format::Document;
+/// let mut document: Document = "# The answer\nfoo: 150  # not 42\n".parse().unwrap();
+/// document.set("foo", "42").unwrap();
+/// document.insert("nested.bar", "5").unwrap();
+/// assert_eq!(document.to_string(), "# The answer\nfoo: 42  # not 42\nnested {\n  bar: 5\n}\n");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+pub struct Document {
+    body: MessageBody,
+}
+
+/// An error that may occur while editing a [`Document`].
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+pub struct EditError {
+    kind: EditErrorKind,
+}
+
+#[derive(Debug)]
+enum EditErrorKind {
+    InvalidPath { path: String },
+    ValueNotFound { path: String },
+    NotAMessage { path: String },
+    NestedList { path: String },
+    InvalidValue { err: ParseError },
+}
+
+/// The fields of a message, along with the surrounding whitespace and comments.
+#[derive(Debug, Clone, Default)]
+struct MessageBody {
+    /// Trivia following the opening delimiter, up to the end of its line.
+    open_trailing: String,
+    fields: Vec<Field>,
+    /// Trivia preceding the closing delimiter, or the end of the document.
+    trailing: String,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    /// Trivia preceding the field, including any comment lines above it.
+    leading: String,
+    /// The field name as written in the source.
+    name: String,
+    /// The normalized field name, used to match paths.
+    key: String,
+    /// The text between the name and the value, including the colon if present.
+    separator: String,
+    value: FieldValue,
+    /// The text following the value, up to and including a ',' or ';' if present.
+    terminator: String,
+    /// Trivia following the field, up to the end of its line.
+    trailing: String,
+}
+
+#[derive(Debug, Clone)]
+enum FieldValue {
+    Scalar(String),
+    Message(MessageValue),
+    List(ListValue),
+}
+
+#[derive(Debug, Clone)]
+struct MessageValue {
+    open: char,
+    body: MessageBody,
+}
+
+#[derive(Debug, Clone)]
+struct ListValue {
+    items: Vec<ListItem>,
+    /// Trivia preceding the closing bracket.
+    trailing: String,
+}
+
+#[derive(Debug, Clone)]
+struct ListItem {
+    leading: String,
+    value: FieldValue,
+    /// The text following the value, up to the end of the line if it is followed by a ','.
+    separator: String,
+}
+
+/// The position of a single value within a message body.
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Field(usize),
+    ListItem(usize, usize),
+}
+
+struct Segment {
+    key: String,
+    index: Option<usize>,
+}
+
+struct CstParser<'a> {
+    source: &'a str,
+    lexer: Lexer<'a, Token<'a>>,
+    peek: Option<Result<(Token<'a>, Span), ParseErrorKind>>,
+    pos: usize,
+}
+
+impl Document {
+    /// Parses a [`Document`] from the given text.
+    ///
+    /// This only checks the syntax of the input. Use [`validate()`](Document::validate) to check
+    /// it against a message type.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        CstParser::new(input)
+            .parse_document()
+            .map_err(|kind| ParseError::new(kind, input))
+    }
+
+    /// Checks that this document is a valid message of the given type.
+    pub fn validate(&self, desc: MessageDescriptor) -> Result<(), ParseError> {
+        self.to_message(desc).map(drop)
+    }
+
+    /// Parses the contents of this document as a [`DynamicMessage`] of the given type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::{DescriptorPool, Value, text_format::Document};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let document = Document::parse("foo: 150  # comment").unwrap();
+    /// let dynamic_message = document.to_message(message_descriptor).unwrap();
+    /// assert_eq!(dynamic_message.get_field_by_name("foo").unwrap().as_ref(), &Value::I32(150));
+    /// ```
+    pub fn to_message(&self, desc: MessageDescriptor) -> Result<DynamicMessage, ParseError> {
+        self.to_message_with_options(desc, &ParseOptions::new())
+    }
+
+    /// Parses the contents of this document as a [`DynamicMessage`] of the given type, with
+    /// custom options.
+    pub fn to_message_with_options(
+        &self,
+        desc: MessageDescriptor,
+        options: &ParseOptions,
+    ) -> Result<DynamicMessage, ParseError> {
+        DynamicMessage::parse_text_format_with_options(desc, &self.to_string(), options)
+    }
+
+    /// Gets the text of the value at the given path, if it exists.
+    ///
+    /// If the last part of the path has no index, the value of the first occurrence of the field
+    /// is returned, which may be a list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::text_format::Document;
+    /// let document = Document::parse("foo: [1, 2] foo: 3 nested { bar: 'x' }").unwrap();
+    /// assert_eq!(document.get("foo").as_deref(), Some("[1, 2]"));
+    /// assert_eq!(document.get("foo[2]").as_deref(), Some("3"));
+    /// assert_eq!(document.get("nested.bar").as_deref(), Some("'x'"));
+    /// assert_eq!(document.get("nested.baz"), None);
+    /// ```
+    pub fn get(&self, path: &str) -> Option<String> {
+        let segments = parse_path(path).ok()?;
+        let (last, parents) = segments.split_last()?;
+
+        let mut body = &self.body;
+        for segment in parents {
+            let location = *body
+                .locations(&segment.key)
+                .get(segment.index.unwrap_or(0))?;
+            match body.value(location) {
+                FieldValue::Message(message) => body = &message.body,
+                _ => return None,
+            }
+        }
+
+        match last.index {
+            Some(index) => {
+                let location = *body.locations(&last.key).get(index)?;
+                Some(body.value(location).to_string())
+            }
+            None => body
+                .fields
+                .iter()
+                .find(|field| field.key == last.key)
+                .map(|field| field.value.to_string()),
+        }
+    }
+
+    /// Sets the value at the given path, where `value` is written in the text format.
+    ///
+    /// If the last part of the path has an index, the value at that position is replaced.
+    /// Otherwise, the value of the first occurrence of the field is replaced, or the field is
+    /// added if it is not present. Any missing messages in the path are created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::text_format::Document;
+    /// let mut document = Document::parse("foo: 1 # keep me\nnested {\n  bar: [1, 2]\n}\n").unwrap();
+    /// document.set("foo", "2").unwrap();
+    /// document.set("nested.bar[1]", "5").unwrap();
+    /// assert_eq!(document.to_string(), "foo: 2 # keep me\nnested {\n  bar: [1, 5]\n}\n");
+    /// ```
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let value = parse_value(value)?;
+        let (last, parents) = segments.split_last().expect("path is empty");
+        let (body, indent) = body_at_mut(&mut self.body, parents, path, true)?;
+
+        match last.index {
+            Some(index) => match body.locations(&last.key).get(index) {
+                Some(&Location::Field(field)) => body.fields[field].set_value(value),
+                Some(&Location::ListItem(field, item)) => {
+                    if matches!(value, FieldValue::List(_)) {
+                        return Err(EditError::new(EditErrorKind::NestedList {
+                            path: path.to_owned(),
+                        }));
+                    }
+                    body.list_mut(field).items[item].value = value;
+                }
+                None => return Err(EditError::value_not_found(path)),
+            },
+            None => match body.fields.iter().position(|field| field.key == last.key) {
+                Some(field) => body.fields[field].set_value(value),
+                None => {
+                    body.insert_field(body.fields.len(), &last.key, value, indent.as_deref());
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a new value at the given path, where `value` is written in the text format.
+    ///
+    /// If the last part of the path has an index, the value is inserted before the value at that
+    /// position, or after the last value if the index is equal to the number of values. Otherwise,
+    /// the value is added after the last occurrence of the field. Values inserted next to an
+    /// element of a list are added to the list. Any missing messages in the path are created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::text_format::Document;
+    /// let mut document = Document::parse("foo: [1, 3]\nbar: 1\n").unwrap();
+    /// document.insert("foo[1]", "2").unwrap();
+    /// document.insert("bar", "2").unwrap();
+    /// assert_eq!(document.to_string(), "foo: [1, 2, 3]\nbar: 1\nbar: 2\n");
+    /// ```
+    pub fn insert(&mut self, path: &str, value: &str) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let value = parse_value(value)?;
+        let (last, parents) = segments.split_last().expect("path is empty");
+        let (body, indent) = body_at_mut(&mut self.body, parents, path, true)?;
+
+        let locations = body.locations(&last.key);
+        let index = last.index.unwrap_or(locations.len());
+        if index > locations.len() {
+            return Err(EditError::value_not_found(path));
+        }
+
+        let is_list = matches!(value, FieldValue::List(_));
+        match (
+            locations.get(index),
+            index.checked_sub(1).map(|i| locations[i]),
+        ) {
+            (Some(&Location::Field(field)), _) => {
+                body.insert_field(field, &last.key, value, indent.as_deref());
+            }
+            (Some(&Location::ListItem(field, item)), _) => {
+                if is_list {
+                    return Err(EditError::new(EditErrorKind::NestedList {
+                        path: path.to_owned(),
+                    }));
+                }
+                body.list_mut(field).insert(item, value);
+            }
+            (None, Some(Location::ListItem(field, item))) if !is_list => {
+                body.list_mut(field).insert(item + 1, value);
+            }
+            (None, Some(Location::Field(field) | Location::ListItem(field, _))) => {
+                body.insert_field(field + 1, &last.key, value, indent.as_deref());
+            }
+            (None, None) => match body.fields.iter().rposition(|field| field.key == last.key) {
+                Some(field)
+                    if !is_list && matches!(body.fields[field].value, FieldValue::List(_)) =>
+                {
+                    body.list_mut(field).insert(0, value);
+                }
+                Some(field) => {
+                    body.insert_field(field + 1, &last.key, value, indent.as_deref());
+                }
+                None => {
+                    body.insert_field(body.fields.len(), &last.key, value, indent.as_deref());
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Removes the value at the given path.
+    ///
+    /// If the last part of the path has an index, only the value at that position is removed.
+    /// Otherwise, all occurrences of the field are removed. Comments on the lines above a removed
+    /// field are removed along with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::text_format::Document;
+    /// let mut document = Document::parse("foo: 1\n# The bar field\nbar: [1, 2]\n").unwrap();
+    /// document.remove("bar[0]").unwrap();
+    /// assert_eq!(document.to_string(), "foo: 1\n# The bar field\nbar: [2]\n");
+    /// document.remove("bar").unwrap();
+    /// assert_eq!(document.to_string(), "foo: 1\n");
+    /// ```
+    pub fn remove(&mut self, path: &str) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let (last, parents) = segments.split_last().expect("path is empty");
+        let (body, _) = body_at_mut(&mut self.body, parents, path, false)?;
+
+        match last.index {
+            Some(index) => match body.locations(&last.key).get(index) {
+                Some(&Location::Field(field)) => body.remove_field(field),
+                Some(&Location::ListItem(field, item)) => {
+                    let list = body.list_mut(field);
+                    list.remove(item);
+                    if list.items.is_empty() {
+                        body.remove_field(field);
+                    }
+                }
+                None => return Err(EditError::value_not_found(path)),
+            },
+            None => {
+                let fields: Vec<usize> = (0..body.fields.len())
+                    .filter(|&field| body.fields[field].key == last.key)
+                    .collect();
+                if fields.is_empty() {
+                    return Err(EditError::value_not_found(path));
+                }
+                for field in fields.into_iter().rev() {
+                    body.remove_field(field);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Formats this document in a canonical layout.
+    ///
+    /// Each field is written on its own line, indented by two spaces for each level of nesting.
+    /// Comments are kept, and single blank lines between fields are preserved, but other
+    /// whitespace, optional separators, and `<` `>` message delimiters are normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost_reflect::text_format::Document;
+    /// let document = Document::parse("foo:1, nested< bar :2 ;> # comment").unwrap();
+    /// assert_eq!(document.to_canonical_string(), "foo: 1\nnested {\n  bar: 2\n} # comment\n");
+    /// ```
+    pub fn to_canonical_string(&self) -> String {
+        let mut output = String::new();
+        write_canonical_fields(&mut output, &self.body, 0);
+        output
+    }
+}
+
+impl FromStr for Document {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse(s)
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.body.fmt(f)
+    }
+}
+
+impl MessageBody {
+    fn locations(&self, key: &str) -> Vec<Location> {
+        let mut locations = Vec::new();
+        for (index, field) in self.fields.iter().enumerate() {
+            if field.key != key {
+                continue;
+            }
+
+            match &field.value {
+                FieldValue::List(list) => locations
+                    .extend((0..list.items.len()).map(|item| Location::ListItem(index, item))),
+                _ => locations.push(Location::Field(index)),
+            }
+        }
+        locations
+    }
+
+    fn value(&self, location: Location) -> &FieldValue {
+        match location {
+            Location::Field(field) => &self.fields[field].value,
+            Location::ListItem(field, item) => match &self.fields[field].value {
+                FieldValue::List(list) => &list.items[item].value,
+                _ => unreachable!("expected list value"),
+            },
+        }
+    }
+
+    fn value_mut(&mut self, location: Location) -> &mut FieldValue {
+        match location {
+            Location::Field(field) => &mut self.fields[field].value,
+            Location::ListItem(field, item) => &mut self.list_mut(field).items[item].value,
+        }
+    }
+
+    fn list_mut(&mut self, field: usize) -> &mut ListValue {
+        match &mut self.fields[field].value {
+            FieldValue::List(list) => list,
+            _ => unreachable!("expected list value"),
+        }
+    }
+
+    /// Inserts a new field at the given position, following the layout of the neighbouring
+    /// fields. If there are none, `indent` is used, or `None` for the top level of a document.
+    fn insert_field(
+        &mut self,
+        index: usize,
+        key: &str,
+        value: FieldValue,
+        indent: Option<&str>,
+    ) -> usize {
+        let separator = match value {
+            FieldValue::Message(_) => " ",
+            _ => ": ",
+        };
+        let mut field = Field {
+            leading: String::new(),
+            name: key.to_owned(),
+            key: key.to_owned(),
+            separator: separator.to_owned(),
+            value,
+            terminator: String::new(),
+            trailing: String::new(),
+        };
+
+        let neighbour = match index.checked_sub(1) {
+            Some(prev) => Some(prev),
+            None if index < self.fields.len() => Some(index),
+            None => None,
+        };
+        let multiline = match neighbour {
+            Some(neighbour) => {
+                self.fields[neighbour].trailing.ends_with('\n')
+                    || (indent.is_none() && index == self.fields.len() && self.trailing.is_empty())
+            }
+            None => indent.is_none() || self.open_trailing.ends_with('\n'),
+        };
+
+        if multiline {
+            match (neighbour, indent) {
+                (Some(neighbour), _) => {
+                    field.leading = self
+                        .line_indent(neighbour, indent.is_none())
+                        .or(indent)
+                        .unwrap_or_default()
+                        .to_owned();
+                    if neighbour < index && !self.fields[neighbour].trailing.ends_with('\n') {
+                        self.fields[neighbour].trailing.push('\n');
+                    }
+                }
+                (None, Some(indent)) => field.leading = indent.to_owned(),
+                (None, None) => {
+                    field.leading = mem::take(&mut self.trailing);
+                    if !field.leading.is_empty() && !field.leading.ends_with('\n') {
+                        field.leading.push('\n');
+                    }
+                }
+            }
+            field.trailing.push('\n');
+        } else {
+            match neighbour {
+                Some(next) if next == index => {
+                    field.leading = mem::replace(&mut self.fields[next].leading, " ".to_owned());
+                }
+                _ => field.leading.push(' '),
+            }
+            if neighbour.is_none() && self.trailing.is_empty() {
+                self.trailing.push(' ');
+            }
+        }
+
+        self.fields.insert(index, field);
+        index
+    }
+
+    /// Removes the field at the given position, along with any comments directly above it.
+    fn remove_field(&mut self, index: usize) {
+        let field = self.fields.remove(index);
+        let next_leading = match self.fields.get_mut(index) {
+            Some(next) => &mut next.leading,
+            None => &mut self.trailing,
+        };
+
+        // Comments separated from the field by a blank line are kept.
+        let mut detached = &field.leading[..detached_len(&field.leading)];
+        if !detached.is_empty() {
+            if starts_with_blank_line(next_leading) {
+                let last_line = detached[..detached.len() - 1]
+                    .rfind('\n')
+                    .map_or(0, |i| i + 1);
+                detached = &detached[..last_line];
+            }
+            next_leading.insert_str(0, detached);
+        } else if !field.trailing.ends_with('\n') && !next_leading.contains('\n') {
+            *next_leading = field.leading;
+        }
+    }
+
+    /// The indentation of the field at the given position, if it is at the start of a line.
+    fn line_indent(&self, index: usize, top_level: bool) -> Option<&str> {
+        let field = &self.fields[index];
+        let starts_line = field.leading.contains('\n')
+            || match index.checked_sub(1) {
+                Some(prev) => self.fields[prev].trailing.ends_with('\n'),
+                None => top_level || self.open_trailing.ends_with('\n'),
+            };
+        starts_line.then(|| &field.leading[field.leading.rfind('\n').map_or(0, |i| i + 1)..])
+    }
+}
+
+impl Field {
+    fn set_value(&mut self, value: FieldValue) {
+        if !matches!(value, FieldValue::Message(_)) && !self.separator.contains(':') {
+            self.separator = ": ".to_owned();
+        }
+        self.value = value;
+    }
+}
+
+impl ListValue {
+    fn insert(&mut self, index: usize, value: FieldValue) {
+        let (leading, separator) = match self.items.get(1) {
+            Some(second) if self.items[0].separator.ends_with('\n') => {
+                (second.leading.clone(), ",\n".to_owned())
+            }
+            Some(second) => (second.leading.clone(), ",".to_owned()),
+            None => (" ".to_owned(), ",".to_owned()),
+        };
+
+        if index == self.items.len() {
+            let leading = match self.items.last_mut() {
+                Some(last) => {
+                    last.separator = separator;
+                    leading
+                }
+                None => String::new(),
+            };
+            self.items.push(ListItem {
+                leading,
+                value,
+                separator: String::new(),
+            });
+        } else {
+            let leading = if index == 0 {
+                mem::replace(&mut self.items[0].leading, leading)
+            } else {
+                leading
+            };
+            self.items.insert(
+                index,
+                ListItem {
+                    leading,
+                    value,
+                    separator,
+                },
+            );
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        let item = self.items.remove(index);
+        if index == 0 {
+            if let Some(first) = self.items.first_mut() {
+                first.leading = item.leading;
+            }
+        } else if index == self.items.len() {
+            self.items[index - 1].separator = item.separator;
+        }
+    }
+}
+
+impl Display for MessageBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.open_trailing)?;
+        for field in &self.fields {
+            field.fmt(f)?;
+        }
+        f.write_str(&self.trailing)
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{}{}",
+            self.leading, self.name, self.separator, self.value, self.terminator, self.trailing
+        )
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Scalar(text) => f.write_str(text),
+            FieldValue::Message(message) => {
+                let close = if message.open == '<' { '>' } else { '}' };
+                write!(f, "{}{}{}", message.open, message.body, close)
+            }
+            FieldValue::List(list) => {
+                f.write_str("[")?;
+                for item in &list.items {
+                    write!(f, "{}{}{}", item.leading, item.value, item.separator)?;
+                }
+                write!(f, "{}]", list.trailing)
+            }
+        }
+    }
+}
+
+impl EditError {
+    fn new(kind: EditErrorKind) -> Self {
+        EditError { kind }
+    }
+
+    fn value_not_found(path: &str) -> Self {
+        EditError::new(EditErrorKind::ValueNotFound {
+            path: path.to_owned(),
+        })
+    }
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            EditErrorKind::InvalidPath { path } => write!(f, "invalid field path '{path}'"),
+            EditErrorKind::ValueNotFound { path } => write!(f, "no value found at path '{path}'"),
+            EditErrorKind::NotAMessage { path } => {
+                write!(
+                    f,
+                    "path '{path}' goes through a value which is not a message"
+                )
+            }
+            EditErrorKind::NestedList { path } => {
+                write!(f, "cannot add a list as an element of the list at '{path}'")
+            }
+            EditErrorKind::InvalidValue { err } => write!(f, "invalid value: {err}"),
+        }
+    }
+}
+
+impl Error for EditError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            EditErrorKind::InvalidValue { err } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Finds the message body selected by the given path segments, optionally creating any missing
+/// fields. Also returns the indentation to use for new fields in the body.
+fn body_at_mut<'a>(
+    mut body: &'a mut MessageBody,
+    segments: &[Segment],
+    path: &str,
+    create: bool,
+) -> Result<(&'a mut MessageBody, Option<String>), EditError> {
+    let mut indent: Option<String> = None;
+    for segment in segments {
+        let locations = body.locations(&segment.key);
+        let location = match locations.get(segment.index.unwrap_or(0)) {
+            Some(&location) => location,
+            None if create && segment.index.is_none_or(|index| index == locations.len()) => {
+                let index = body
+                    .fields
+                    .iter()
+                    .rposition(|field| field.key == segment.key)
+                    .map_or(body.fields.len(), |field| field + 1);
+                let message = FieldValue::Message(MessageValue {
+                    open: '{',
+                    body: MessageBody::default(),
+                });
+                let index = body.insert_field(index, &segment.key, message, indent.as_deref());
+
+                let field_indent = body
+                    .line_indent(index, indent.is_none())
+                    .unwrap_or_default()
+                    .to_owned();
+                let field = &mut body.fields[index];
+                if field.trailing.ends_with('\n') {
+                    if let FieldValue::Message(message) = &mut field.value {
+                        message.body.open_trailing = "\n".to_owned();
+                        message.body.trailing = field_indent;
+                    }
+                }
+                Location::Field(index)
+            }
+            None => return Err(EditError::value_not_found(path)),
+        };
+
+        let field = match location {
+            Location::Field(field) | Location::ListItem(field, _) => field,
+        };
+        let field_indent = body
+            .line_indent(field, indent.is_none())
+            .or(indent.as_deref())
+            .unwrap_or_default();
+        indent = Some(format!("{field_indent}  "));
+
+        match body.value_mut(location) {
+            FieldValue::Message(message) => body = &mut message.body,
+            _ => {
+                return Err(EditError::new(EditErrorKind::NotAMessage {
+                    path: path.to_owned(),
+                }))
+            }
+        }
+    }
+
+    Ok((body, indent))
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, EditError> {
+    let invalid = || {
+        EditError::new(EditErrorKind::InvalidPath {
+            path: path.to_owned(),
+        })
+    };
+
+    let mut segments = Vec::new();
+    let mut rest = path;
+    loop {
+        let key = if let Some(extension) = rest.strip_prefix('[') {
+            let end = extension.find(']').ok_or_else(invalid)?;
+            let name: String = extension[..end]
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .collect();
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            rest = &extension[end + 1..];
+            format!("[{name}]")
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let name = &rest[..end];
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            {
+                return Err(invalid());
+            }
+            rest = &rest[end..];
+            match name.parse::<u32>() {
+                Ok(number) => number.to_string(),
+                Err(_) => name.to_owned(),
+            }
+        };
+
+        let index = match rest.strip_prefix('[') {
+            Some(index) => {
+                let end = index.find(']').ok_or_else(invalid)?;
+                rest = &index[end + 1..];
+                Some(index[..end].parse().map_err(|_| invalid())?)
+            }
+            None => None,
+        };
+
+        segments.push(Segment { key, index });
+        if rest.is_empty() {
+            return Ok(segments);
+        }
+        rest = rest.strip_prefix('.').ok_or_else(invalid)?;
+    }
+}
+
+fn parse_value(input: &str) -> Result<FieldValue, EditError> {
+    CstParser::new(input)
+        .parse_standalone_value()
+        .map_err(|kind| {
+            EditError::new(EditErrorKind::InvalidValue {
+                err: ParseError::new(kind, input),
+            })
+        })
+}
+
+impl<'a> CstParser<'a> {
+    fn new(source: &'a str) -> Self {
+        CstParser {
+            source,
+            lexer: Token::lexer(source),
+            peek: None,
+            pos: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Document, ParseErrorKind> {
+        let body = self.parse_body(None)?;
+        Ok(Document { body })
+    }
+
+    fn parse_standalone_value(&mut self) -> Result<FieldValue, ParseErrorKind> {
+        self.take_trivia()?;
+        let value = self.parse_field_value()?;
+        if self.peek()?.is_some() {
+            return self.unexpected_token("end of input");
+        }
+        Ok(value)
+    }
+
+    fn parse_body(&mut self, terminator: Option<&Token>) -> Result<MessageBody, ParseErrorKind> {
+        let open_trailing = match terminator {
+            Some(_) => self.take_line_trivia()?,
+            None => String::new(),
+        };
+
+        let mut fields = Vec::new();
+        loop {
+            match self.peek()? {
+                Some((Token::Ident(_) | Token::IntLiteral(_) | Token::LeftBracket, _)) => {
+                    fields.push(self.parse_field()?)
+                }
+                Some((tok, _)) if Some(&tok) == terminator => break,
+                None if terminator.is_none() => break,
+                _ => match terminator {
+                    Some(terminator) => {
+                        self.unexpected_token(format!("'{terminator}' or a field name"))?
+                    }
+                    None => self.unexpected_token("a field name")?,
+                },
+            }
+        }
+
+        Ok(MessageBody {
+            open_trailing,
+            fields,
+            trailing: self.take_trivia()?,
+        })
+    }
+
+    fn parse_field(&mut self) -> Result<Field, ParseErrorKind> {
+        let leading = self.take_trivia()?;
+
+        let start = self.pos;
+        let key = match self.peek()? {
+            Some((Token::Ident(name), _)) => {
+                self.bump();
+                name.to_owned()
+            }
+            Some((Token::IntLiteral(int), _)) => {
+                self.bump();
+                match u32::from_str_radix(int.value, int.radix) {
+                    Ok(number) => number.to_string(),
+                    Err(_) => int.value.to_owned(),
+                }
+            }
+            Some((Token::LeftBracket, _)) => {
+                self.bump();
+                let mut key = "[".to_owned();
+                loop {
+                    match self.peek()? {
+                        Some((Token::Ident(name), _)) => key.push_str(name),
+                        Some((Token::Dot, _)) => key.push('.'),
+                        Some((Token::ForwardSlash, _)) => key.push('/'),
+                        Some((Token::RightBracket, _)) if key.len() > 1 => {
+                            self.bump();
+                            key.push(']');
+                            break key;
+                        }
+                        _ => self.unexpected_token("an identifier")?,
+                    }
+                    self.bump();
+                }
+            }
+            _ => self.unexpected_token("a field name")?,
+        };
+        let name = self.source[start..self.pos].to_owned();
+
+        let start = self.pos;
+        match self.peek()? {
+            Some((Token::Colon, _)) => {
+                self.bump();
+            }
+            Some((Token::LeftBrace | Token::LeftAngleBracket, _)) => (),
+            _ => self.unexpected_token("':' or a message value")?,
+        };
+        self.take_trivia()?;
+        let separator = self.source[start..self.pos].to_owned();
+
+        let value = self.parse_field_value()?;
+
+        let start = self.pos;
+        let terminator = match self.peek()? {
+            Some((Token::Comma | Token::Semicolon, _)) => {
+                self.bump();
+                self.source[start..self.pos].to_owned()
+            }
+            _ => String::new(),
+        };
+
+        Ok(Field {
+            leading,
+            name,
+            key,
+            separator,
+            value,
+            terminator,
+            trailing: self.take_line_trivia()?,
+        })
+    }
+
+    fn parse_field_value(&mut self) -> Result<FieldValue, ParseErrorKind> {
+        match self.peek()? {
+            Some((Token::LeftBracket, _)) => self.parse_list(),
+            _ => self.parse_value(),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<FieldValue, ParseErrorKind> {
+        self.bump();
+
+        let mut items = Vec::new();
+        if let Some((Token::RightBracket, _)) = self.peek()? {
+            let trailing = self.take_trivia()?;
+            self.bump();
+            return Ok(FieldValue::List(ListValue { items, trailing }));
+        }
+
+        loop {
+            let leading = self.take_trivia()?;
+            let value = self.parse_value()?;
+
+            let start = self.pos;
+            match self.peek()? {
+                Some((Token::Comma, _)) => {
+                    self.bump();
+                    let separator = self.source[start..self.pos].to_owned();
+                    items.push(ListItem {
+                        leading,
+                        value,
+                        separator: separator + &self.take_line_trivia()?,
+                    });
+                }
+                Some((Token::RightBracket, _)) => {
+                    items.push(ListItem {
+                        leading,
+                        value,
+                        separator: String::new(),
+                    });
+                    let trailing = self.take_trivia()?;
+                    self.bump();
+                    return Ok(FieldValue::List(ListValue { items, trailing }));
+                }
+                _ => self.unexpected_token("',' or ']'")?,
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<FieldValue, ParseErrorKind> {
+        let start = self.pos;
+        match self.peek()? {
+            Some((Token::LeftBrace, _)) => return self.parse_message('{', Token::RightBrace),
+            Some((Token::LeftAngleBracket, _)) => {
+                return self.parse_message('<', Token::RightAngleBracket)
+            }
+            Some((Token::Minus, _)) => {
+                self.bump();
+                match self.peek()? {
+                    Some((Token::IntLiteral(_) | Token::FloatLiteral(_) | Token::Ident(_), _)) => {
+                        self.bump();
+                    }
+                    _ => self.unexpected_token("a number")?,
+                }
+            }
+            Some((Token::IntLiteral(_) | Token::FloatLiteral(_) | Token::Ident(_), _)) => {
+                self.bump();
+            }
+            Some((Token::StringLiteral(_), _)) => {
+                self.bump();
+                while let Some((Token::StringLiteral(_), _)) = self.peek()? {
+                    self.bump();
+                }
+            }
+            _ => self.unexpected_token("a value")?,
+        }
+        Ok(FieldValue::Scalar(self.source[start..self.pos].to_owned()))
+    }
+
+    fn parse_message(&mut self, open: char, close: Token) -> Result<FieldValue, ParseErrorKind> {
+        self.bump();
+        let body = self.parse_body(Some(&close))?;
+        self.bump();
+        Ok(FieldValue::Message(MessageValue { open, body }))
+    }
+
+    /// Consumes the whitespace and comments before the next token.
+    fn take_trivia(&mut self) -> Result<String, ParseErrorKind> {
+        let end = self.next_start()?;
+        let trivia = self.source[self.pos..end].to_owned();
+        self.pos = end;
+        Ok(trivia)
+    }
+
+    /// Consumes the whitespace and comments before the next token, up to the end of the
+    /// current line or the input. If the next token is on the same line, nothing is consumed.
+    fn take_line_trivia(&mut self) -> Result<String, ParseErrorKind> {
+        let end = self.next_start()?;
+        match self.source[self.pos..end].find('\n') {
+            Some(len) => {
+                let trivia = self.source[self.pos..][..=len].to_owned();
+                self.pos += len + 1;
+                Ok(trivia)
+            }
+            None if end == self.source.len() => self.take_trivia(),
+            None => Ok(String::new()),
+        }
+    }
+
+    fn next_start(&mut self) -> Result<usize, ParseErrorKind> {
+        match self.peek()? {
+            Some((_, span)) => Ok(span.start),
+            None => Ok(self.source.len()),
+        }
+    }
+
+    fn bump(&mut self) -> Span {
+        let (_, span) = self
+            .peek
+            .take()
+            .expect("called bump without peek returning Some()")
+            .expect("called bump on invalid token");
+        self.pos = span.end;
+        span
+    }
+
+    fn peek(&mut self) -> Result<Option<(Token<'a>, Span)>, ParseErrorKind> {
+        if self.peek.is_none() {
+            self.peek = self.next();
+        }
+        self.peek.clone().transpose()
+    }
+
+    fn next(&mut self) -> Option<Result<(Token<'a>, Span), ParseErrorKind>> {
+        debug_assert!(self.peek.is_none());
+        match self.lexer.next() {
+            Some(Err(())) => Some(Err(self.lexer.extras.error.take().unwrap_or_else(|| {
+                ParseErrorKind::InvalidToken {
+                    span: self.lexer.span(),
+                }
+            }))),
+            Some(Ok(tok)) => Some(Ok((tok, self.lexer.span()))),
+            None => None,
+        }
+    }
+
+    fn unexpected_token<T>(&mut self, expected: impl ToString) -> Result<T, ParseErrorKind> {
+        match self.peek()? {
+            Some((found, span)) => Err(ParseErrorKind::UnexpectedToken {
+                expected: expected.to_string(),
+                found: found.to_string(),
+                span,
+            }),
+            None => Err(ParseErrorKind::UnexpectedEof {
+                expected: expected.to_string(),
+            }),
+        }
+    }
+}
+
+/// Returns the length of the trivia up to the end of its last blank line.
+fn detached_len(trivia: &str) -> usize {
+    let mut len = 0;
+    let mut offset = 0;
+    for line in trivia.split_inclusive('\n') {
+        offset += line.len();
+        if line.ends_with('\n') && line.trim().is_empty() {
+            len = offset;
+        }
+    }
+    len
+}
+
+fn starts_with_blank_line(trivia: &str) -> bool {
+    trivia
+        .split_inclusive('\n')
+        .next()
+        .is_some_and(|line| line.ends_with('\n') && line.trim().is_empty())
+}
+
+fn write_canonical_fields(output: &mut String, body: &MessageBody, depth: usize) {
+    for (index, field) in body.fields.iter().enumerate() {
+        if write_comment_lines(output, &field.leading, depth, index > 0) {
+            output.push('\n');
+        }
+
+        write_indent(output, depth);
+        output.push_str(&field.key);
+
+        let mut comments: Vec<&str> = trivia_comments(&field.separator).collect();
+        match &field.value {
+            FieldValue::Message(message) => {
+                output.push(' ');
+                write_canonical_message(output, &message.body, depth);
+            }
+            value => {
+                output.push_str(": ");
+                write_canonical_value(output, value, depth, &mut comments);
+            }
+        }
+        comments.extend(trivia_comments(&field.terminator));
+        comments.extend(trivia_comments(&field.trailing));
+        write_trailing_comments(output, &comments, depth);
+    }
+
+    write_comment_lines(output, &body.trailing, depth, !body.fields.is_empty());
+}
+
+fn write_canonical_message(output: &mut String, body: &MessageBody, depth: usize) {
+    let open_comments: Vec<&str> = trivia_comments(&body.open_trailing).collect();
+    if body.fields.is_empty() && open_comments.is_empty() && !has_comments(&body.trailing) {
+        output.push_str("{}");
+        return;
+    }
+
+    output.push('{');
+    write_trailing_comments(output, &open_comments, depth + 1);
+    write_canonical_fields(output, body, depth + 1);
+    write_indent(output, depth);
+    output.push('}');
+}
+
+fn write_canonical_value<'a>(
+    output: &mut String,
+    value: &'a FieldValue,
+    depth: usize,
+    comments: &mut Vec<&'a str>,
+) {
+    match value {
+        FieldValue::Scalar(text) => write_canonical_scalar(output, text, comments),
+        FieldValue::Message(message) => write_canonical_message(output, &message.body, depth),
+        FieldValue::List(list) => {
+            let multiline = has_comments(&list.trailing)
+                || list.items.iter().any(|item| {
+                    matches!(item.value, FieldValue::Message(_))
+                        || has_comments(&item.leading)
+                        || has_comments(&item.separator)
+                });
+
+            output.push('[');
+            if multiline {
+                output.push('\n');
+                for (index, item) in list.items.iter().enumerate() {
+                    write_comment_lines(output, &item.leading, depth + 1, false);
+                    write_indent(output, depth + 1);
+
+                    let mut item_comments = Vec::new();
+                    write_canonical_value(output, &item.value, depth + 1, &mut item_comments);
+                    if index + 1 < list.items.len() {
+                        output.push(',');
+                    }
+                    item_comments.extend(trivia_comments(&item.separator));
+                    write_trailing_comments(output, &item_comments, depth + 1);
+                }
+                write_comment_lines(output, &list.trailing, depth + 1, false);
+                write_indent(output, depth);
+            } else {
+                for (index, item) in list.items.iter().enumerate() {
+                    if index > 0 {
+                        output.push_str(", ");
+                    }
+                    write_canonical_value(output, &item.value, depth, comments);
+                }
+            }
+            output.push(']');
+        }
+    }
+}
+
+/// Writes a scalar value with a single space between tokens, such as concatenated strings.
+fn write_canonical_scalar<'a>(output: &mut String, text: &'a str, comments: &mut Vec<&'a str>) {
+    let mut end = 0;
+    let mut after_minus = true;
+    for (token, span) in Token::lexer(text).spanned() {
+        comments.extend(trivia_comments(&text[end..span.start]));
+        if !after_minus {
+            output.push(' ');
+        }
+        output.push_str(&text[span.clone()]);
+        after_minus = matches!(token, Ok(Token::Minus));
+        end = span.end;
+    }
+}
+
+/// Writes each comment in the trivia on its own line. Returns `true` if the trivia ends with a
+/// blank line which should be preserved before the next field.
+fn write_comment_lines(
+    output: &mut String,
+    trivia: &str,
+    depth: usize,
+    mut allow_blank: bool,
+) -> bool {
+    let mut blank = false;
+    let mut lines = trivia.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        if let Some(start) = line.find('#') {
+            if blank && allow_blank {
+                output.push('\n');
+            }
+            write_indent(output, depth);
+            output.push_str(line[start..].trim_end());
+            output.push('\n');
+            blank = false;
+            allow_blank = true;
+        } else if lines.peek().is_some() {
+            blank = true;
+        }
+    }
+    blank && allow_blank
+}
+
+/// Writes comments at the end of the current line, and terminates the line.
+fn write_trailing_comments(output: &mut String, comments: &[&str], depth: usize) {
+    for (index, comment) in comments.iter().enumerate() {
+        if index == 0 {
+            output.push(' ');
+        } else {
+            output.push('\n');
+            write_indent(output, depth);
+        }
+        output.push_str(comment);
+    }
+    output.push('\n');
+}
+
+fn write_indent(output: &mut String, depth: usize) {
+    for _ in 0..depth {
+        output.push_str("  ");
+    }
+}
+
+fn trivia_comments(trivia: &str) -> impl Iterator<Item = &str> {
+    trivia
+        .split('\n')
+        .filter_map(|line| line.find('#').map(|start| line[start..].trim_end()))
+}
+
+fn has_comments(trivia: &str) -> bool {
+    trivia.contains('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(input: &str, f: impl FnOnce(&mut Document) -> Result<(), EditError>) -> String {
+        let mut document = Document::parse(input).unwrap();
+        f(&mut document).unwrap();
+        document.to_string()
+    }
+
+    #[test]
+    fn roundtrip() {
+        for input in [
+            "",
+            "\n\n",
+            "# only a comment",
+            "foo: 1",
+            "foo:1,bar:2;baz :3",
+            "  foo  :  -  inf  # comment\n",
+            "s: 'a' \"b\"\n  # between\n  'c'\n",
+            "nested < a: 1; b { } >\n",
+            "list: [ 1 , 2,3 ]\nempty: [ ]\nmessages: [{a: 1}, <b: 2>]",
+            "[ext.name] { x: 1 }\n[type.googleapis.com/foo.Bar] {}\n1: 5\n",
+            "# header\n\n# doc\nfoo: 1 # trailing\n\nnested {\n  # inner\n  bar: 2\n\n  # end\n}\n# eof",
+        ] {
+            assert_eq!(Document::parse(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn get() {
+        let document = Document::parse(
+            "foo: [1, 2]\nfoo: 3\nnested { bar: 'x' }\nnested { bar: 'y' }\n[ext.name] { 1: 2 }",
+        )
+        .unwrap();
+
+        assert_eq!(document.get("foo").as_deref(), Some("[1, 2]"));
+        assert_eq!(document.get("foo[1]").as_deref(), Some("2"));
+        assert_eq!(document.get("foo[2]").as_deref(), Some("3"));
+        assert_eq!(document.get("foo[3]"), None);
+        assert_eq!(document.get("nested").as_deref(), Some("{ bar: 'x' }"));
+        assert_eq!(document.get("nested.bar").as_deref(), Some("'x'"));
+        assert_eq!(document.get("nested[1].bar").as_deref(), Some("'y'"));
+        assert_eq!(document.get("[ ext.name ].1").as_deref(), Some("2"));
+        assert_eq!(document.get("foo.bar"), None);
+        assert_eq!(document.get("foo..bar"), None);
+    }
+
+    #[test]
+    fn set() {
+        let input = "# header\nfoo: 1 # one\nnested {\n  bar: 2\n  list: [1, 2]\n}\n";
+        assert_eq!(
+            edit(input, |d| d.set("foo", "'x' # comment\n 'y'")),
+            "# header\nfoo: 'x' # comment\n 'y' # one\nnested {\n  bar: 2\n  list: [1, 2]\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.set("nested.list[1]", "{ a: 1 }")),
+            "# header\nfoo: 1 # one\nnested {\n  bar: 2\n  list: [1, { a: 1 }]\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.set("nested.baz", "3")),
+            "# header\nfoo: 1 # one\nnested {\n  bar: 2\n  list: [1, 2]\n  baz: 3\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.set("other.inner.value", "3")),
+            "# header\nfoo: 1 # one\nnested {\n  bar: 2\n  list: [1, 2]\n}\nother {\n  inner {\n    value: 3\n  }\n}\n"
+        );
+        assert_eq!(edit("msg {}", |d| d.set("msg", "5")), "msg: 5");
+        assert_eq!(
+            edit("a: 1 b { c: 2 }", |d| d.set("b.d.e", "3")),
+            "a: 1 b { c: 2 d { e: 3 } }"
+        );
+        assert_eq!(edit("a: 1", |d| d.set("b", "2")), "a: 1\nb: 2\n");
+        assert_eq!(edit("", |d| d.set("a.b", "1")), "a {\n  b: 1\n}\n");
+        assert_eq!(edit("# comment", |d| d.set("a", "1")), "# comment\na: 1\n");
+    }
+
+    #[test]
+    fn insert() {
+        let input = "foo: 1\n\nnested {\n  bar: [1, 3]\n  baz: 1\n}\n";
+        assert_eq!(
+            edit(input, |d| d.insert("foo", "2")),
+            "foo: 1\nfoo: 2\n\nnested {\n  bar: [1, 3]\n  baz: 1\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.insert("foo[0]", "0")),
+            "foo: 0\nfoo: 1\n\nnested {\n  bar: [1, 3]\n  baz: 1\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.insert("nested.bar[1]", "2")),
+            "foo: 1\n\nnested {\n  bar: [1, 2, 3]\n  baz: 1\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.insert("nested.bar", "4")),
+            "foo: 1\n\nnested {\n  bar: [1, 3, 4]\n  baz: 1\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.insert("nested.bar", "[4]")),
+            "foo: 1\n\nnested {\n  bar: [1, 3]\n  bar: [4]\n  baz: 1\n}\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.insert("nested[1].baz", "2")),
+            "foo: 1\n\nnested {\n  bar: [1, 3]\n  baz: 1\n}\nnested {\n  baz: 2\n}\n"
+        );
+        assert_eq!(
+            edit("a { b: 1 }", |d| d.insert("a.b[0]", "0")),
+            "a { b: 0 b: 1 }"
+        );
+        assert_eq!(edit("a: []", |d| d.insert("a", "1")), "a: [1]");
+        assert_eq!(edit("a: [1]", |d| d.insert("a[0]", "0")), "a: [0, 1]");
+        assert_eq!(
+            edit("a: [\n  1,\n  2\n]", |d| d.insert("a", "3")),
+            "a: [\n  1,\n  2,\n  3\n]"
+        );
+    }
+
+    #[test]
+    fn remove() {
+        let input = "# header\n\n# foo\nfoo: 1\nbar: [1, 2, 3] # bar\n\n# baz\nbaz: 1\n# end\n";
+        assert_eq!(
+            edit(input, |d| d.remove("foo")),
+            "# header\n\nbar: [1, 2, 3] # bar\n\n# baz\nbaz: 1\n# end\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.remove("bar")),
+            "# header\n\n# foo\nfoo: 1\n\n# baz\nbaz: 1\n# end\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.remove("baz")),
+            "# header\n\n# foo\nfoo: 1\nbar: [1, 2, 3] # bar\n\n# end\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.remove("bar[0]")),
+            "# header\n\n# foo\nfoo: 1\nbar: [2, 3] # bar\n\n# baz\nbaz: 1\n# end\n"
+        );
+        assert_eq!(
+            edit(input, |d| d.remove("bar[2]")),
+            "# header\n\n# foo\nfoo: 1\nbar: [1, 2] # bar\n\n# baz\nbaz: 1\n# end\n"
+        );
+        assert_eq!(
+            edit("a: 1 b: 2 a: 3 c { a: 4 }", |d| d.remove("a")),
+            "b: 2 c { a: 4 }"
+        );
+        assert_eq!(edit("a: [1] b: 2", |d| d.remove("a[0]")), "b: 2");
+        assert_eq!(edit("m { a: 1 b: 2 }", |d| d.remove("m.b")), "m { a: 1 }");
+    }
+
+    #[test]
+    fn edit_errors() {
+        let mut document = Document::parse("a: 1 l: [1]").unwrap();
+
+        let error = |result: Result<(), EditError>| result.unwrap_err().to_string();
+        assert_eq!(error(document.set("", "1")), "invalid field path ''");
+        assert_eq!(error(document.set("a.", "1")), "invalid field path 'a.'");
+        assert_eq!(
+            error(document.set("a[x]", "1")),
+            "invalid field path 'a[x]'"
+        );
+        assert_eq!(error(document.set("[a", "1")), "invalid field path '[a'");
+        assert_eq!(
+            error(document.set("a[1]", "1")),
+            "no value found at path 'a[1]'"
+        );
+        assert_eq!(
+            error(document.insert("a[2]", "1")),
+            "no value found at path 'a[2]'"
+        );
+        assert_eq!(error(document.remove("b")), "no value found at path 'b'");
+        assert_eq!(
+            error(document.remove("b.c")),
+            "no value found at path 'b.c'"
+        );
+        assert_eq!(
+            error(document.set("a.b", "1")),
+            "path 'a.b' goes through a value which is not a message"
+        );
+        assert_eq!(
+            error(document.set("l[0]", "[2]")),
+            "cannot add a list as an element of the list at 'l[0]'"
+        );
+        assert_eq!(
+            error(document.set("a", "1 2")),
+            "invalid value: expected end of input, but found '2'"
+        );
+        assert_eq!(
+            error(document.set("a", "{")),
+            "invalid value: expected '}' or a field name, but reached end of input"
+        );
+
+        assert_eq!(document.to_string(), "a: 1 l: [1]");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input: &str| Document::parse(input).unwrap_err().to_string();
+        assert_eq!(error("a: }"), "expected a value, but found '}'");
+        assert_eq!(
+            error("a { b: 1"),
+            "expected '}' or a field name, but reached end of input"
+        );
+        assert_eq!(
+            error("a < b: 1 }"),
+            "expected '>' or a field name, but found '}'"
+        );
+        assert_eq!(error("a: [1 2]"), "expected ',' or ']', but found '2'");
+        assert_eq!(
+            error("a 1"),
+            "expected ':' or a message value, but found '1'"
+        );
+        assert_eq!(error("[]: 1"), "expected an identifier, but found ']'");
+    }
+
+    #[test]
+    fn canonical() {
+        let document = Document::parse(
+            "# header\n\n\nfoo:1,bar : 'a'\n  'b';nested< # open\n  # inner\n x:-1 y{}\n\n\n  z {a:1} # z\n  # end\n>\nlist:[1,2]\nmessages: [{a: 1}, # first\n{}]",
+        )
+        .unwrap();
+        assert_eq!(
+            document.to_canonical_string(),
+            "# header\n\nfoo: 1\nbar: 'a' 'b'\nnested { # open\n  # inner\n  x: -1\n  y {}\n\n  z {\n    a: 1\n  } # z\n  # end\n}\nlist: [1, 2]\nmessages: [\n  {\n    a: 1\n  }, # first\n  {}\n]\n"
+        );
+        assert_eq!(
+            Document::parse(&document.to_canonical_string())
+                .unwrap()
+                .to_canonical_string(),
+            document.to_canonical_string()
+        );
+    }
+}
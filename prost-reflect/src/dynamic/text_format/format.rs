@@ -5,8 +5,8 @@ use prost::Message;
 use crate::{
     dynamic::{
         fields::ValueAndDescriptor,
-        fmt_string, get_type_url_message_name,
-        text_format::FormatOptions,
+        fmt_string,
+        text_format::{is_group_like, FormatOptions},
         unknown::{UnknownField, UnknownFieldSet, UnknownFieldValue},
     },
     DynamicMessage, Kind, MapKey, Value,
@@ -32,7 +32,7 @@ where
 
     pub fn fmt_message(&mut self, message: &DynamicMessage) -> fmt::Result {
         if self.options.expand_any {
-            if let Some((type_url, body)) = as_any(message) {
+            if let Some((type_url, body)) = self.as_any(message) {
                 self.f.write_char('[')?;
                 self.f.write_str(&type_url)?;
                 self.f.write_str("]")?;
@@ -159,7 +159,7 @@ where
     fn fmt_message_field(&mut self, field: ValueAndDescriptor) -> fmt::Result {
         match field {
             ValueAndDescriptor::Field(value, desc) => {
-                if desc.is_group() {
+                if is_group_like(&desc) {
                     write!(self.f, "{}", desc.kind().as_message().unwrap().name())?;
                 } else {
                     write!(self.f, "{}", desc.name())?;
@@ -294,22 +294,21 @@ where
         }
         Ok(())
     }
-}
-
-fn as_any(message: &DynamicMessage) -> Option<(String, DynamicMessage)> {
-    if message.desc.full_name() != "google.protobuf.Any" {
-        return None;
-    }
 
-    let any = message.transcode_to::<prost_types::Any>().ok()?;
-    let message_name = get_type_url_message_name(&any.type_url).ok()?;
+    fn as_any(&self, message: &DynamicMessage) -> Option<(String, DynamicMessage)> {
+        if message.desc.full_name() != "google.protobuf.Any" {
+            return None;
+        }
 
-    let desc = message
-        .desc
-        .parent_pool()
-        .get_message_by_name(message_name)?;
-    let body = DynamicMessage::decode(desc, any.value.as_slice()).ok()?;
-    Some((any.type_url, body))
+        let any = message.transcode_to::<prost_types::Any>().ok()?;
+        let desc = self
+            .options
+            .type_resolver
+            .resolve(message.desc.parent_pool(), &any.type_url)
+            .ok()?;
+        let body = DynamicMessage::decode(desc, any.value.as_slice()).ok()?;
+        Some((any.type_url, body))
+    }
 }
 
 #[cfg(test)]
@@ -1,14 +1,21 @@
+#[cfg(feature = "text-format")]
+mod cst;
 mod format;
 #[cfg(feature = "text-format")]
 mod parse;
 
 #[cfg(feature = "text-format")]
-pub use self::parse::ParseError;
+pub use self::{
+    cst::{Document, EditError},
+    parse::ParseError,
+};
 #[cfg(feature = "text-format")]
-use crate::{DynamicMessage, MessageDescriptor};
+use crate::{DescriptorPool, DynamicMessage, MessageDescriptor, TypeResolver};
 
 pub(super) use self::format::Writer;
 
+use crate::{dynamic::resolver::OptionalTypeResolver, FieldDescriptor, Kind};
+
 /// Options to control printing of the protobuf text format.
 ///
 /// Used by [`DynamicMessage::to_text_format_with_options()`].
@@ -20,6 +27,23 @@ pub struct FormatOptions {
     expand_any: bool,
     skip_default_fields: bool,
     print_message_fields_in_index_order: bool,
+    type_resolver: OptionalTypeResolver,
+}
+
+/// Options to control parsing of the protobuf text format.
+///
+/// Used by [`DynamicMessage::parse_text_format_with_options()`].
+#[derive(Debug, Clone)]
+#[cfg(feature = "text-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+pub struct ParseOptions {
+    type_resolver: OptionalTypeResolver,
+    allow_unknown_fields: bool,
+    allow_unknown_extensions: bool,
+    allow_field_numbers: bool,
+    allow_partial: bool,
+    recursion_limit: u32,
+    reject_duplicate_fields: bool,
 }
 
 #[cfg(feature = "text-format")]
@@ -58,7 +82,31 @@ impl DynamicMessage {
     /// ```
     #[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
     pub fn merge_text_format(&mut self, input: &str) -> Result<(), ParseError> {
-        parse::Parser::new(input)
+        self.merge_text_format_with_options(input, &ParseOptions::new())
+    }
+
+    /// Parse a [`DynamicMessage`] from the given message encoded using the [text format](https://developers.google.com/protocol-buffers/docs/text-format-spec),
+    /// with custom options.
+    #[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+    pub fn parse_text_format_with_options(
+        desc: MessageDescriptor,
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let mut message = DynamicMessage::new(desc);
+        message.merge_text_format_with_options(input, options)?;
+        Ok(message)
+    }
+
+    /// Merges the given message encoded using the [text format](https://developers.google.com/protocol-buffers/docs/text-format-spec) into this message,
+    /// with custom options.
+    #[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+    pub fn merge_text_format_with_options(
+        &mut self,
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<(), ParseError> {
+        parse::Parser::new(input, options)
             .parse_message(self)
             .map_err(|kind| ParseError::new(kind, input))
     }
@@ -105,6 +153,36 @@ impl DynamicMessage {
     }
 }
 
+/// Parse a [`DynamicMessage`] encoded using the [text format](https://developers.google.com/protocol-buffers/docs/text-format-spec),
+/// with the message type given by the header comments at the start of the input.
+///
+/// The message type is given by a `# proto-message:` comment, with either its full name or a name
+/// relative to the package of the file given by an optional `# proto-file:` comment. Both must be
+/// present in the given pool. An error is returned if the header is missing, or it names a file or
+/// message which cannot be found.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{DescriptorPool, ReflectMessage, Value, text_format};
+/// # let pool = DescriptorPool::decode(include_bytes!("../../file_descriptor_set.bin").as_ref()).unwrap();
+/// let input = "# proto-message: package.MyMessage\n\nfoo: 150\n";
+/// let dynamic_message = text_format::parse_with_header(&pool, input).unwrap();
+/// assert_eq!(dynamic_message.descriptor().full_name(), "package.MyMessage");
+/// assert_eq!(dynamic_message.get_field_by_name("foo").unwrap().as_ref(), &Value::I32(150));
+///
+/// let err = text_format::parse_with_header(&pool, "foo: 150").unwrap_err();
+/// assert_eq!(err.to_string(), "the input does not have a 'proto-message' header");
+/// ```
+#[cfg(feature = "text-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text-format")))]
+pub fn parse_with_header(pool: &DescriptorPool, input: &str) -> Result<DynamicMessage, ParseError> {
+    let desc = parse::Header::parse(input)
+        .resolve(pool)
+        .map_err(|kind| ParseError::new(kind, input))?;
+    DynamicMessage::parse_text_format(desc, input)
+}
+
 impl FormatOptions {
     /// Creates new instance of [`FormatOptions`] with default options.
     pub fn new() -> Self {
@@ -211,6 +289,21 @@ impl FormatOptions {
         self.expand_any = yes;
         self
     }
+
+    /// Sets the resolver used to find the message type of `google.protobuf.Any` payloads when
+    /// [`expand_any`][Self::expand_any] is enabled.
+    ///
+    /// By default, types are looked up in the [`DescriptorPool`](crate::DescriptorPool) of the
+    /// message being formatted. If the type cannot be resolved, or the payload cannot be decoded,
+    /// the `Any` message is printed in its unexpanded form, with the payload as raw bytes.
+    #[cfg(feature = "text-format")]
+    pub fn type_resolver<R>(mut self, resolver: R) -> Self
+    where
+        R: TypeResolver + 'static,
+    {
+        self.type_resolver.set(resolver);
+        self
+    }
 }
 
 impl Default for FormatOptions {
@@ -221,6 +314,151 @@ impl Default for FormatOptions {
             expand_any: true,
             skip_default_fields: true,
             print_message_fields_in_index_order: false,
+            type_resolver: OptionalTypeResolver::new(),
+        }
+    }
+}
+
+#[cfg(feature = "text-format")]
+impl ParseOptions {
+    /// Creates a new instance of [`ParseOptions`], with the default options.
+    pub const fn new() -> Self {
+        ParseOptions {
+            type_resolver: OptionalTypeResolver::new(),
+            allow_unknown_fields: false,
+            allow_unknown_extensions: false,
+            allow_field_numbers: false,
+            allow_partial: true,
+            recursion_limit: 100,
+            reject_duplicate_fields: true,
+        }
+    }
+
+    /// Whether to skip fields whose name is not defined in the message descriptor.
+    ///
+    /// If `true`, the value of an unknown field is parsed but discarded. Otherwise, parsing
+    /// fails.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost::Message;
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, Value, text_format::ParseOptions};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../../file_descriptor_set.bin").as_ref()).unwrap();
+    /// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+    /// let input = "foo: 150, unknown { value: [1, 2] }";
+    /// assert!(DynamicMessage::parse_text_format(message_descriptor.clone(), input).is_err());
+    ///
+    /// let options = ParseOptions::new().allow_unknown_fields(true);
+    /// let dynamic_message = DynamicMessage::parse_text_format_with_options(message_descriptor, input, &options).unwrap();
+    /// assert_eq!(dynamic_message.encode_to_vec(), b"\x08\x96\x01");
+    /// ```
+    pub const fn allow_unknown_fields(mut self, yes: bool) -> Self {
+        self.allow_unknown_fields = yes;
+        self
+    }
+
+    /// Whether to skip extensions, written as `[package.name]`, which are not defined for the
+    /// message.
+    ///
+    /// If `true`, the value of an unknown extension is parsed but discarded. Otherwise, parsing
+    /// fails.
+    ///
+    /// The default value is `false`.
+    pub const fn allow_unknown_extensions(mut self, yes: bool) -> Self {
+        self.allow_unknown_extensions = yes;
+        self
+    }
+
+    /// Whether to accept field numbers in place of field names, such as `1: 150`.
+    ///
+    /// Numbers are matched against both the fields and the extensions of the message. Numbers
+    /// which match neither are treated as unknown fields.
+    ///
+    /// The default value is `false`.
+    pub const fn allow_field_numbers(mut self, yes: bool) -> Self {
+        self.allow_field_numbers = yes;
+        self
+    }
+
+    /// Whether to accept messages which are missing `required` fields.
+    ///
+    /// If `false`, parsing fails if any message in the input, including nested messages, does not
+    /// set all of its required fields.
+    ///
+    /// The default value is `true`.
+    pub const fn allow_partial(mut self, yes: bool) -> Self {
+        self.allow_partial = yes;
+        self
+    }
+
+    /// The maximum depth of nested messages.
+    ///
+    /// The default value is `100`.
+    pub const fn recursion_limit(mut self, limit: u32) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Whether to error when a non-repeated field, or more than one field of a oneof, is set
+    /// multiple times.
+    ///
+    /// If `false`, a later value replaces any earlier one, and repeated values for a message
+    /// field are merged together. This also applies to fields already set in the message
+    /// passed to [`DynamicMessage::merge_text_format_with_options()`].
+    ///
+    /// The default value is `true`.
+    pub const fn reject_duplicate_fields(mut self, yes: bool) -> Self {
+        self.reject_duplicate_fields = yes;
+        self
+    }
+
+    /// Sets the resolver used to find the message type of expanded `google.protobuf.Any` payloads,
+    /// such as `[type.googleapis.com/package.MyMessage] { foo: 150 }`.
+    ///
+    /// By default, types are looked up in the [`DescriptorPool`](crate::DescriptorPool) of the
+    /// message being parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use prost::Message;
+    /// # use prost_reflect::{DynamicMessage, DescriptorPool, Value, text_format::ParseOptions};
+    /// # let pool = DescriptorPool::decode(include_bytes!("../../file_descriptor_set.bin").as_ref()).unwrap();
+    /// let registry = pool.clone();
+    /// # let pool = DescriptorPool::global();
+    /// let message_descriptor = pool.get_message_by_name("google.protobuf.Any").unwrap();
+    /// let input = "[type.googleapis.com/package.MyMessage] { foo: 150 }";
+    ///
+    /// let options = ParseOptions::new().type_resolver(registry);
+    /// let dynamic_message = DynamicMessage::parse_text_format_with_options(message_descriptor, input, &options).unwrap();
+    /// assert_eq!(dynamic_message.get_field_by_name("value").unwrap().as_ref(), &Value::Bytes(b"\x08\x96\x01".as_ref().into()));
+    /// ```
+    pub fn type_resolver<R>(mut self, resolver: R) -> Self
+    where
+        R: TypeResolver + 'static,
+    {
+        self.type_resolver.set(resolver);
+        self
+    }
+}
+
+#[cfg(feature = "text-format")]
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if the field is written using the name of its message type, as with proto2 groups.
+fn is_group_like(field: &FieldDescriptor) -> bool {
+    match field.kind() {
+        Kind::Message(message) if field.is_group() => {
+            message.name().to_ascii_lowercase() == field.name()
+                && message.parent_message().as_ref() == Some(field.parent_message())
         }
+        _ => false,
     }
 }
@@ -112,6 +112,28 @@ pub(crate) enum ParseErrorKind {
         span: Span,
     },
     InvalidMapKey,
+    RecursionLimitExceeded {
+        #[cfg_attr(feature = "miette", label("exceeded here"))]
+        span: Span,
+    },
+    RequiredFieldNotSet {
+        field_name: String,
+        message_name: String,
+        #[cfg_attr(feature = "miette", label("defined here"))]
+        span: Span,
+    },
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(help(
+            "add a comment such as '# proto-message: my.package.Message' to the start of the input"
+        ))
+    )]
+    MissingMessageHeader,
+    FileNotFound {
+        file_name: String,
+        #[cfg_attr(feature = "miette", label("used here"))]
+        span: Span,
+    },
 }
 
 impl Display for ParseErrorKind {
@@ -179,6 +201,23 @@ impl Display for ParseErrorKind {
                 "the field type must be 'google.protobuf.Any' to use Any expansion syntax"
             ),
             ParseErrorKind::InvalidMapKey => write!(f, "invalid value type for map key"),
+            ParseErrorKind::RecursionLimitExceeded { .. } => {
+                write!(f, "recursion limit exceeded")
+            }
+            ParseErrorKind::RequiredFieldNotSet {
+                field_name,
+                message_name,
+                ..
+            } => write!(
+                f,
+                "required field '{field_name}' is not set for message '{message_name}'"
+            ),
+            ParseErrorKind::MissingMessageHeader => {
+                write!(f, "the input does not have a 'proto-message' header")
+            }
+            ParseErrorKind::FileNotFound { file_name, .. } => {
+                write!(f, "file '{file_name}' not found")
+            }
         }
     }
 }
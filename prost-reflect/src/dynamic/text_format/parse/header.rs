@@ -0,0 +1,77 @@
+use logos::Span;
+
+use super::ParseErrorKind;
+use crate::{DescriptorPool, MessageDescriptor};
+
+/// The `proto-file` and `proto-message` directives in the comments at the start of a file.
+pub(in crate::dynamic::text_format) struct Header<'a> {
+    proto_file: Option<(&'a str, Span)>,
+    proto_message: Option<(&'a str, Span)>,
+}
+
+impl<'a> Header<'a> {
+    pub fn parse(input: &'a str) -> Self {
+        let mut header = Header {
+            proto_file: None,
+            proto_message: None,
+        };
+
+        for line in input.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Some(comment) = trimmed.strip_prefix('#') else {
+                break;
+            };
+
+            let comment = comment.trim_start();
+            let (directive, value) = if let Some(value) = comment.strip_prefix("proto-file:") {
+                (&mut header.proto_file, value.trim())
+            } else if let Some(value) = comment.strip_prefix("proto-message:") {
+                (&mut header.proto_message, value.trim())
+            } else {
+                continue;
+            };
+
+            if directive.is_none() && !value.is_empty() {
+                let start = value.as_ptr() as usize - input.as_ptr() as usize;
+                *directive = Some((value, start..start + value.len()));
+            }
+        }
+
+        header
+    }
+
+    /// Finds the message type named by the header in the given pool.
+    ///
+    /// If a `proto-file` directive is present, the file must exist in the pool, and message names
+    /// may be given relative to the package of the file.
+    pub fn resolve(&self, pool: &DescriptorPool) -> Result<MessageDescriptor, ParseErrorKind> {
+        let (message_name, message_span) = self
+            .proto_message
+            .clone()
+            .ok_or(ParseErrorKind::MissingMessageHeader)?;
+        let message_name = message_name.strip_prefix('.').unwrap_or(message_name);
+
+        let mut message = pool.get_message_by_name(message_name);
+        if let Some((file_name, file_span)) = &self.proto_file {
+            let file =
+                pool.get_file_by_name(file_name)
+                    .ok_or_else(|| ParseErrorKind::FileNotFound {
+                        file_name: file_name.to_string(),
+                        span: file_span.clone(),
+                    })?;
+
+            if message.is_none() && !file.package_name().is_empty() {
+                message =
+                    pool.get_message_by_name(&format!("{}.{}", file.package_name(), message_name));
+            }
+        }
+
+        message.ok_or_else(|| ParseErrorKind::MessageNotFound {
+            message_name: message_name.to_owned(),
+            span: message_span,
+        })
+    }
+}
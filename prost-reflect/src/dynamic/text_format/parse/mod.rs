@@ -1,5 +1,6 @@
 mod error;
-mod lex;
+mod header;
+pub(super) mod lex;
 
 use std::{borrow::Cow, convert::TryFrom, iter::once};
 
@@ -7,33 +8,39 @@ use logos::{Lexer, Logos, Span};
 use prost::Message;
 
 pub use self::error::ParseError;
+pub(super) use self::{error::ParseErrorKind, header::Header};
 
-use self::{
-    error::ParseErrorKind,
-    lex::{Int, Token},
-};
+use self::lex::{Int, Token};
 use crate::{
     descriptor::{MAP_ENTRY_KEY_NUMBER, MAP_ENTRY_VALUE_NUMBER},
-    dynamic::fields::FieldDescriptorLike,
+    dynamic::{
+        fields::FieldDescriptorLike,
+        text_format::{is_group_like, ParseOptions},
+    },
     DynamicMessage, EnumDescriptor, FieldDescriptor, Kind, MapKey, MessageDescriptor, Value,
 };
 
 pub(in crate::dynamic::text_format) struct Parser<'a> {
     lexer: Lexer<'a, Token<'a>>,
     peek: Option<Result<(Token<'a>, Span), ParseErrorKind>>,
+    options: &'a ParseOptions,
+    depth: u32,
 }
 
 enum FieldName {
     Ident(String),
+    Number(u32),
     Extension(String),
     Any(String, String),
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(input: &'a str) -> Self {
+    pub fn new(input: &'a str, options: &'a ParseOptions) -> Self {
         Parser {
             lexer: Token::lexer(input),
             peek: None,
+            options,
+            depth: 0,
         }
     }
 
@@ -41,7 +48,7 @@ impl<'a> Parser<'a> {
         while self.peek()?.is_some() {
             self.parse_field(message)?;
         }
-        Ok(())
+        self.check_required_fields(message, 0..self.lexer.source().len())
     }
 
     fn parse_message_value(
@@ -53,13 +60,18 @@ impl<'a> Parser<'a> {
             Some((Token::LeftAngleBracket, _)) => (Token::RightAngleBracket, self.bump()),
             _ => self.unexpected_token("'{' or '<'")?,
         };
+        self.enter_message(&start)?;
 
         loop {
             match self.peek()? {
-                Some((Token::Ident(_) | Token::LeftBracket, _)) => self.parse_field(message)?,
+                Some((tok, _)) if self.is_field_name_start(&tok) => self.parse_field(message)?,
                 Some((tok, _)) if tok == terminator => {
                     let end = self.bump();
-                    return Ok(join_span(start, end));
+                    self.depth -= 1;
+
+                    let span = join_span(start, end);
+                    self.check_required_fields(message, span.clone())?;
+                    return Ok(span);
                 }
                 _ => self.unexpected_token(format!("'{terminator}' or a field name"))?,
             }
@@ -68,53 +80,62 @@ impl<'a> Parser<'a> {
 
     fn parse_field(&mut self, message: &mut DynamicMessage) -> Result<(), ParseErrorKind> {
         let (name, span) = self.parse_field_name()?;
-
-        match self.peek()? {
-            Some((Token::Colon, _)) => {
-                self.bump();
-            }
-            Some((Token::LeftBrace | Token::LeftAngleBracket, _)) => (),
-            _ => self.unexpected_token("':' or a message value")?,
-        };
+        self.parse_field_separator()?;
 
         match name {
-            FieldName::Ident(field_name) => {
-                let field = find_field(&message.desc, &field_name).ok_or_else(|| {
-                    ParseErrorKind::FieldNotFound {
+            FieldName::Ident(field_name) => match find_field(&message.desc, &field_name) {
+                Some(field) => self.parse_field_value(message, &field)?,
+                None if self.options.allow_unknown_fields => self.skip_field_value()?,
+                None => {
+                    return Err(ParseErrorKind::FieldNotFound {
                         field_name,
                         message_name: message.desc.full_name().to_owned(),
                         span,
-                    }
-                })?;
-
-                self.parse_field_value(message, &field)?;
-            }
-            FieldName::Extension(extension_name) => {
-                let extension = message
-                    .desc
-                    .get_extension_by_full_name(&extension_name)
-                    .ok_or_else(|| ParseErrorKind::ExtensionNotFound {
-                        extension_name,
+                    })
+                }
+            },
+            FieldName::Number(number) => {
+                if let Some(field) = message.desc.get_field(number) {
+                    self.parse_field_value(message, &field)?;
+                } else if let Some(extension) = message.desc.get_extension(number) {
+                    self.parse_field_value(message, &extension)?;
+                } else if self.options.allow_unknown_fields {
+                    self.skip_field_value()?;
+                } else {
+                    return Err(ParseErrorKind::FieldNotFound {
+                        field_name: number.to_string(),
                         message_name: message.desc.full_name().to_owned(),
                         span,
-                    })?;
-
-                self.parse_field_value(message, &extension)?;
+                    });
+                }
+            }
+            FieldName::Extension(extension_name) => {
+                match message.desc.get_extension_by_full_name(&extension_name) {
+                    Some(extension) => self.parse_field_value(message, &extension)?,
+                    None if self.options.allow_unknown_extensions => self.skip_field_value()?,
+                    None => {
+                        return Err(ParseErrorKind::ExtensionNotFound {
+                            extension_name,
+                            message_name: message.desc.full_name().to_owned(),
+                            span,
+                        })
+                    }
+                }
             }
             FieldName::Any(domain, message_name) => {
-                let value_message = match message
-                    .desc
-                    .parent_pool()
-                    .get_message_by_name(&message_name)
+                let type_url = format!("{domain}/{message_name}");
+                let value_message = match self
+                    .options
+                    .type_resolver
+                    .resolve(message.desc.parent_pool(), &type_url)
                 {
-                    Some(msg) => msg,
-                    None => return Err(ParseErrorKind::MessageNotFound { message_name, span }),
+                    Ok(msg) => msg,
+                    Err(_) => return Err(ParseErrorKind::MessageNotFound { message_name, span }),
                 };
 
                 let mut value = DynamicMessage::new(value_message);
                 self.parse_message_value(&mut value)?;
 
-                let type_url = format!("{domain}/{message_name}");
                 let value = value.encode_to_vec();
 
                 if !(message.desc.full_name() == "google.protobuf.Any"
@@ -130,16 +151,44 @@ impl<'a> Parser<'a> {
             }
         }
 
+        self.parse_field_terminator()
+    }
+
+    fn is_field_name_start(&self, tok: &Token) -> bool {
+        match tok {
+            Token::Ident(_) | Token::LeftBracket => true,
+            Token::IntLiteral(_) => self.options.allow_field_numbers,
+            _ => false,
+        }
+    }
+
+    fn parse_field_separator(&mut self) -> Result<(), ParseErrorKind> {
+        match self.peek()? {
+            Some((Token::Colon, _)) => {
+                self.bump();
+            }
+            Some((Token::LeftBrace | Token::LeftAngleBracket, _)) => (),
+            _ => self.unexpected_token("':' or a message value")?,
+        };
+        Ok(())
+    }
+
+    fn parse_field_terminator(&mut self) -> Result<(), ParseErrorKind> {
         if matches!(self.peek()?, Some((Token::Comma | Token::Semicolon, _))) {
             self.bump();
         }
-
         Ok(())
     }
 
     fn parse_field_name(&mut self) -> Result<(FieldName, Span), ParseErrorKind> {
         match self.peek()? {
             Some((Token::Ident(ident), _)) => Ok((FieldName::Ident(ident.to_owned()), self.bump())),
+            Some((Token::IntLiteral(int), _)) if self.options.allow_field_numbers => {
+                match u32::from_str_radix(int.value, int.radix) {
+                    Ok(number) => Ok((FieldName::Number(number), self.bump())),
+                    Err(_) => self.unexpected_token("a field number"),
+                }
+            }
             Some((Token::LeftBracket, _)) => {
                 let start = self.bump();
 
@@ -213,6 +262,17 @@ impl<'a> Parser<'a> {
                 result.insert(key, value);
             }
             Ok(())
+        } else if !self.options.reject_duplicate_fields {
+            if field.kind().as_message().is_some() && message.fields.has(field) {
+                if let Value::Message(existing) = message.fields.get_mut(field) {
+                    self.parse_message_value(existing)?;
+                    return Ok(());
+                }
+            }
+
+            let (value, _) = self.parse_value(&field.kind())?;
+            message.fields.set(field, value);
+            Ok(())
         } else {
             let kind = field.kind();
             let (value, span) = self.parse_value(&kind)?;
@@ -323,6 +383,116 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn skip_field_value(&mut self) -> Result<(), ParseErrorKind> {
+        match self.peek()? {
+            Some((Token::LeftBracket, _)) => {
+                self.bump();
+
+                if let Some((Token::RightBracket, _)) = self.peek()? {
+                    self.bump();
+                    return Ok(());
+                }
+
+                loop {
+                    self.skip_value()?;
+                    match self.peek()? {
+                        Some((Token::Comma, _)) => {
+                            self.bump();
+                        }
+                        Some((Token::RightBracket, _)) => {
+                            self.bump();
+                            return Ok(());
+                        }
+                        _ => self.unexpected_token("',' or ']'")?,
+                    }
+                }
+            }
+            _ => self.skip_value(),
+        }
+    }
+
+    fn skip_value(&mut self) -> Result<(), ParseErrorKind> {
+        match self.peek()? {
+            Some((Token::LeftBrace | Token::LeftAngleBracket, _)) => self.skip_message_value(),
+            Some((Token::Minus, _)) => {
+                self.bump();
+                match self.peek()? {
+                    Some((Token::IntLiteral(_) | Token::FloatLiteral(_) | Token::Ident(_), _)) => {
+                        self.bump();
+                        Ok(())
+                    }
+                    _ => self.unexpected_token("a number"),
+                }
+            }
+            Some((Token::IntLiteral(_) | Token::FloatLiteral(_) | Token::Ident(_), _)) => {
+                self.bump();
+                Ok(())
+            }
+            Some((Token::StringLiteral(_), _)) => {
+                self.parse_bytes()?;
+                Ok(())
+            }
+            _ => self.unexpected_token("a value"),
+        }
+    }
+
+    fn skip_message_value(&mut self) -> Result<(), ParseErrorKind> {
+        let (terminator, start) = match self.peek()? {
+            Some((Token::LeftBrace, _)) => (Token::RightBrace, self.bump()),
+            Some((Token::LeftAngleBracket, _)) => (Token::RightAngleBracket, self.bump()),
+            _ => self.unexpected_token("'{' or '<'")?,
+        };
+        self.enter_message(&start)?;
+
+        loop {
+            match self.peek()? {
+                Some((tok, _)) if self.is_field_name_start(&tok) => {
+                    self.parse_field_name()?;
+                    self.parse_field_separator()?;
+                    self.skip_field_value()?;
+                    self.parse_field_terminator()?;
+                }
+                Some((tok, _)) if tok == terminator => {
+                    self.bump();
+                    self.depth -= 1;
+                    return Ok(());
+                }
+                _ => self.unexpected_token(format!("'{terminator}' or a field name"))?,
+            }
+        }
+    }
+
+    fn enter_message(&mut self, span: &Span) -> Result<(), ParseErrorKind> {
+        if self.depth >= self.options.recursion_limit {
+            return Err(ParseErrorKind::RecursionLimitExceeded { span: span.clone() });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn check_required_fields(
+        &self,
+        message: &DynamicMessage,
+        span: Span,
+    ) -> Result<(), ParseErrorKind> {
+        if self.options.allow_partial {
+            return Ok(());
+        }
+
+        match message
+            .desc
+            .fields()
+            .find(|field| field.is_required() && !message.has_field(field))
+        {
+            Some(field) => Err(ParseErrorKind::RequiredFieldNotSet {
+                field_name: field.text_name().to_owned(),
+                message_name: message.desc.full_name().to_owned(),
+                span,
+            }),
+            None => Ok(()),
+        }
+    }
+
     fn parse_float(&mut self) -> Result<(f64, Span), ParseErrorKind> {
         let (negative, start) = match self.peek()? {
             Some((Token::Minus, _)) => (true, self.bump()),
@@ -533,7 +703,18 @@ impl<'a> Parser<'a> {
                     })
                 }
             }
-            Some((Token::Minus | Token::IntLiteral(_), _)) => self.parse_i32(),
+            Some((Token::Minus | Token::IntLiteral(_), _)) => {
+                let (value, span) = self.parse_i32()?;
+                if desc.is_closed() && desc.get_value(value).is_none() {
+                    Err(ParseErrorKind::EnumValueNotFound {
+                        value_name: value.to_string(),
+                        enum_name: desc.full_name().to_owned(),
+                        span,
+                    })
+                } else {
+                    Ok((value, span))
+                }
+            }
             _ => self.unexpected_token("an enum value")?,
         }
     }
@@ -623,13 +804,13 @@ impl<'a> Parser<'a> {
 
 fn find_field(desc: &MessageDescriptor, name: &str) -> Option<FieldDescriptor> {
     if let Some(field) = desc.get_field_by_name(name) {
-        if !field.is_group() {
+        if !is_group_like(&field) {
             return Some(field);
         }
     }
 
     if let Some(field) = desc.get_field_by_name(&name.to_ascii_lowercase()) {
-        if field.is_group() && name == field.kind().as_message().unwrap().name() {
+        if is_group_like(&field) && name == field.kind().as_message().unwrap().name() {
             return Some(field);
         }
     }
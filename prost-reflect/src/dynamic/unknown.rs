@@ -58,6 +58,10 @@ impl UnknownField {
         }
     }
 
+    pub(crate) fn new(number: u32, value: UnknownFieldValue) -> Self {
+        UnknownField { number, value }
+    }
+
     pub(crate) fn value(&self) -> &UnknownFieldValue {
         &self.value
     }
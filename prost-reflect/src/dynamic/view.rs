@@ -0,0 +1,486 @@
+use std::str;
+
+use prost::{
+    encoding::{self, DecodeContext, WireType},
+    DecodeError,
+};
+
+use crate::{
+    descriptor::{MAP_ENTRY_KEY_NUMBER, MAP_ENTRY_VALUE_NUMBER},
+    dynamic::fields::FieldDescriptorLike,
+    DynamicMessage, ExtensionDescriptor, FieldDescriptor, Kind, MessageDescriptor,
+};
+
+/// A read-only view of an encoded protobuf message, which decodes fields on demand.
+///
+/// Unlike [`DynamicMessage`], creating a [`MessageView`] does not decode or allocate anything.
+/// Each field is decoded from the underlying bytes when it is accessed, and strings, bytes and
+/// nested messages borrow from the buffer instead of being copied. This makes views cheap when
+/// only a few fields of a large message are needed, at the cost of scanning the buffer on each
+/// access.
+///
+/// The bytes are not validated when the view is created, so any errors are reported when fields
+/// are accessed.
+///
+/// # Examples
+///
+/// ```
+/// # use prost_reflect::{DescriptorPool, MessageView, ValueView};
+/// # let pool = DescriptorPool::decode(include_bytes!("../file_descriptor_set.bin").as_ref()).unwrap();
+/// # let message_descriptor = pool.get_message_by_name("package.MyMessage").unwrap();
+/// let view = MessageView::new(message_descriptor, b"\x08\x96\x01\x1a\x02\x10\x42");
+/// assert_eq!(view.get_field_by_name("foo").unwrap(), Some(ValueView::I32(150)));
+///
+/// let nested = view.get_field_by_name("nested").unwrap().unwrap();
+/// let nested = nested.as_message().unwrap();
+/// assert_eq!(nested.get_field_by_name("bar").unwrap(), Some(ValueView::I32(66)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageView<'a> {
+    desc: MessageDescriptor,
+    buf: &'a [u8],
+}
+
+/// A value decoded from a [`MessageView`].
+///
+/// This is the borrowed equivalent of [`Value`](crate::Value).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueView<'a> {
+    /// A boolean value, encoded as the `bool` protobuf type.
+    Bool(bool),
+    /// A 32-bit signed integer, encoded as one of the `int32`, `sint32` or `sfixed32` protobuf types.
+    I32(i32),
+    /// A 64-bit signed integer, encoded as one of the `int64`, `sint64` or `sfixed64` protobuf types.
+    I64(i64),
+    /// A 32-bit unsigned integer, encoded as one of the `uint32` or `ufixed32` protobuf types.
+    U32(u32),
+    /// A 64-bit unsigned integer, encoded as one of the `uint64` or `ufixed64` protobuf types.
+    U64(u64),
+    /// A 32-bit floating point number, encoded as the `float` protobuf type.
+    F32(f32),
+    /// A 64-bit floating point number, encoded as the `double` protobuf type.
+    F64(f64),
+    /// A string, encoded as the `string` protobuf type.
+    String(&'a str),
+    /// A byte string, encoded as the `bytes` protobuf type.
+    Bytes(&'a [u8]),
+    /// An enumeration value, encoded as a protobuf enum.
+    EnumNumber(i32),
+    /// A protobuf message.
+    Message(MessageView<'a>),
+    /// A list of values, encoded as a protobuf repeated field.
+    List(Vec<ValueView<'a>>),
+    /// The entries of a map, in the order they were encoded.
+    ///
+    /// If a key occurs more than once, the last entry takes precedence.
+    Map(Vec<(ValueView<'a>, ValueView<'a>)>),
+}
+
+/// A single field, as it occurs in the encoded bytes.
+struct Record<'a> {
+    number: u32,
+    wire_type: WireType,
+    data: &'a [u8],
+}
+
+impl<'a> MessageView<'a> {
+    /// Creates a view of the message type specified by the [`MessageDescriptor`] over the encoded bytes.
+    pub fn new(desc: MessageDescriptor, buf: &'a [u8]) -> Self {
+        MessageView { desc, buf }
+    }
+
+    /// Gets a reference to the [`MessageDescriptor`] describing this message type.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.desc
+    }
+
+    /// Gets the encoded bytes of this message.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// Decodes the value of the given field.
+    ///
+    /// Returns `None` if the field does not occur in the encoded bytes. Values of repeated and map
+    /// fields are collected from every occurrence of the field. For other fields, the last
+    /// occurrence is returned, so nested messages which are split over multiple occurrences are
+    /// not merged. Use [`to_dynamic`][Self::to_dynamic] if full merge semantics are required.
+    pub fn get_field(
+        &self,
+        field_desc: &FieldDescriptor,
+    ) -> Result<Option<ValueView<'a>>, DecodeError> {
+        self.get(field_desc)
+    }
+
+    /// Decodes the value of the field with the given number.
+    ///
+    /// Returns `None` if the message has no field with the given number, or it does not occur in
+    /// the encoded bytes. See [`get_field`][Self::get_field] for more details.
+    pub fn get_field_by_number(&self, number: u32) -> Result<Option<ValueView<'a>>, DecodeError> {
+        match self.desc.get_field(number) {
+            Some(field_desc) => self.get(&field_desc),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes the value of the field with the given name.
+    ///
+    /// Returns `None` if the message has no field with the given name, or it does not occur in
+    /// the encoded bytes. See [`get_field`][Self::get_field] for more details.
+    pub fn get_field_by_name(&self, name: &str) -> Result<Option<ValueView<'a>>, DecodeError> {
+        match self.desc.get_field_by_name(name) {
+            Some(field_desc) => self.get(&field_desc),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes the value of the given extension field.
+    ///
+    /// See [`get_field`][Self::get_field] for more details.
+    pub fn get_extension(
+        &self,
+        extension_desc: &ExtensionDescriptor,
+    ) -> Result<Option<ValueView<'a>>, DecodeError> {
+        self.get(extension_desc)
+    }
+
+    /// Iterates over the occurrences of known fields in the encoded bytes, in the order they were encoded.
+    ///
+    /// A repeated field may occur several times, in which case each occurrence is yielded separately
+    /// as a [`ValueView::List`] or [`ValueView::Map`] holding the values it contains. Unknown fields
+    /// and extensions are skipped. Iteration stops after the first error.
+    pub fn fields(
+        &self,
+    ) -> impl Iterator<Item = Result<(FieldDescriptor, ValueView<'a>), DecodeError>> + '_ {
+        let mut buf = self.buf;
+        std::iter::from_fn(move || loop {
+            let record = match next_record(&mut buf) {
+                Ok(Some(record)) => record,
+                Ok(None) => return None,
+                Err(err) => {
+                    buf = &[];
+                    return Some(Err(err));
+                }
+            };
+
+            if let Some(field_desc) = self.desc.get_field(record.number) {
+                let value = record_value(&field_desc, record);
+                if value.is_err() {
+                    buf = &[];
+                }
+                return Some(value.map(|value| (field_desc, value)));
+            }
+        })
+    }
+
+    /// Decodes the whole message into a [`DynamicMessage`].
+    pub fn to_dynamic(&self) -> Result<DynamicMessage, DecodeError> {
+        DynamicMessage::decode(self.desc.clone(), self.buf)
+    }
+
+    fn get(
+        &self,
+        field_desc: &impl FieldDescriptorLike,
+    ) -> Result<Option<ValueView<'a>>, DecodeError> {
+        let mut result = None;
+        let mut buf = self.buf;
+        while let Some(record) = next_record(&mut buf)? {
+            if record.number != field_desc.number() {
+                continue;
+            }
+
+            match (&mut result, record_value(field_desc, record)?) {
+                (Some(ValueView::List(values)), ValueView::List(more)) => values.extend(more),
+                (Some(ValueView::Map(entries)), ValueView::Map(more)) => entries.extend(more),
+                (result, value) => *result = Some(value),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl<'a> ValueView<'a> {
+    /// Returns the value if it is a `ValueView::Bool`, or `None` if it is any other type.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ValueView::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::U32`, or `None` if it is any other type.
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            ValueView::U32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::U64`, or `None` if it is any other type.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ValueView::U64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::I64`, or `None` if it is any other type.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ValueView::I64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::I32`, or `None` if it is any other type.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            ValueView::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::F32`, or `None` if it is any other type.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            ValueView::F32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::F64`, or `None` if it is any other type.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ValueView::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::EnumNumber`, or `None` if it is any other type.
+    pub fn as_enum_number(&self) -> Option<i32> {
+        match *self {
+            ValueView::EnumNumber(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::String`, or `None` if it is any other type.
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            ValueView::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `ValueView::Bytes`, or `None` if it is any other type.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match *self {
+            ValueView::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value if it is a `ValueView::Message`, or `None` if it is any other type.
+    pub fn as_message(&self) -> Option<&MessageView<'a>> {
+        match self {
+            ValueView::Message(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value if it is a `ValueView::List`, or `None` if it is any other type.
+    pub fn as_list(&self) -> Option<&[ValueView<'a>]> {
+        match self {
+            ValueView::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value if it is a `ValueView::Map`, or `None` if it is any other type.
+    pub fn as_map(&self) -> Option<&[(ValueView<'a>, ValueView<'a>)]> {
+        match self {
+            ValueView::Map(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn default_value(kind: &Kind) -> ValueView<'static> {
+        match kind {
+            Kind::Double => ValueView::F64(0.0),
+            Kind::Float => ValueView::F32(0.0),
+            Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => ValueView::I32(0),
+            Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => ValueView::I64(0),
+            Kind::Uint32 | Kind::Fixed32 => ValueView::U32(0),
+            Kind::Uint64 | Kind::Fixed64 => ValueView::U64(0),
+            Kind::Bool => ValueView::Bool(false),
+            Kind::String => ValueView::String(""),
+            Kind::Bytes => ValueView::Bytes(b""),
+            Kind::Message(desc) => ValueView::Message(MessageView::new(desc.clone(), b"")),
+            Kind::Enum(desc) => ValueView::EnumNumber(desc.default_value().number()),
+        }
+    }
+}
+
+/// Reads the next field from the buffer, returning the bytes of its value.
+///
+/// For length-delimited fields the length prefix is included, while for groups the end group
+/// tag is excluded.
+fn next_record<'a>(buf: &mut &'a [u8]) -> Result<Option<Record<'a>>, DecodeError> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+
+    let (number, wire_type) = encoding::decode_key(buf)?;
+    let start = *buf;
+    encoding::skip_field(wire_type, number, buf, DecodeContext::default())?;
+
+    let mut data = &start[..start.len() - buf.len()];
+    if wire_type == WireType::StartGroup {
+        data = &data[..data.len() - encoding::key_len(number)];
+    }
+
+    Ok(Some(Record {
+        number,
+        wire_type,
+        data,
+    }))
+}
+
+/// Decodes the value contributed by a single occurrence of a field.
+fn record_value<'a>(
+    field_desc: &impl FieldDescriptorLike,
+    record: Record<'a>,
+) -> Result<ValueView<'a>, DecodeError> {
+    let kind = field_desc.kind();
+    if field_desc.is_map() {
+        let map_entry = match &kind {
+            Kind::Message(map_entry) => map_entry,
+            _ => panic!("map field {field_desc:?} is not a message"),
+        };
+        let mut data = record.data;
+        let entry = length_delimited(record.wire_type, &mut data)?;
+        Ok(ValueView::Map(vec![decode_map_entry(map_entry, entry)?]))
+    } else if field_desc.is_list() {
+        if record.wire_type == WireType::LengthDelimited && field_desc.is_packable() {
+            let mut data = record.data;
+            let len = encoding::decode_varint(&mut data)? as usize;
+            let mut data = &data[..len];
+
+            let mut values = Vec::new();
+            while !data.is_empty() {
+                values.push(decode_scalar(&kind, kind.wire_type(), &mut data)?);
+            }
+            Ok(ValueView::List(values))
+        } else {
+            Ok(ValueView::List(vec![decode_value(
+                &kind,
+                field_desc.is_group(),
+                record.wire_type,
+                record.data,
+            )?]))
+        }
+    } else {
+        decode_value(&kind, field_desc.is_group(), record.wire_type, record.data)
+    }
+}
+
+fn decode_value<'a>(
+    kind: &Kind,
+    is_group: bool,
+    wire_type: WireType,
+    mut data: &'a [u8],
+) -> Result<ValueView<'a>, DecodeError> {
+    match kind {
+        Kind::Message(desc) if is_group => {
+            encoding::check_wire_type(WireType::StartGroup, wire_type)?;
+            Ok(ValueView::Message(MessageView::new(desc.clone(), data)))
+        }
+        Kind::Message(desc) => {
+            let payload = length_delimited(wire_type, &mut data)?;
+            Ok(ValueView::Message(MessageView::new(desc.clone(), payload)))
+        }
+        Kind::String => {
+            let mut encoded = data;
+            let payload = length_delimited(wire_type, &mut data)?;
+            match str::from_utf8(payload) {
+                Ok(value) => Ok(ValueView::String(value)),
+                Err(_) => {
+                    // Let prost report the error, for consistency with `DynamicMessage::decode`.
+                    encoding::string::merge(
+                        wire_type,
+                        &mut String::new(),
+                        &mut encoded,
+                        DecodeContext::default(),
+                    )?;
+                    unreachable!()
+                }
+            }
+        }
+        Kind::Bytes => Ok(ValueView::Bytes(length_delimited(wire_type, &mut data)?)),
+        _ => decode_scalar(kind, wire_type, &mut data),
+    }
+}
+
+fn decode_scalar<'a>(
+    kind: &Kind,
+    wire_type: WireType,
+    buf: &mut &'a [u8],
+) -> Result<ValueView<'a>, DecodeError> {
+    macro_rules! merge {
+        ($ty:ident, $variant:ident) => {{
+            let mut value = Default::default();
+            encoding::$ty::merge(wire_type, &mut value, buf, DecodeContext::default())?;
+            ValueView::$variant(value)
+        }};
+    }
+
+    Ok(match kind {
+        Kind::Double => merge!(double, F64),
+        Kind::Float => merge!(float, F32),
+        Kind::Int32 => merge!(int32, I32),
+        Kind::Int64 => merge!(int64, I64),
+        Kind::Uint32 => merge!(uint32, U32),
+        Kind::Uint64 => merge!(uint64, U64),
+        Kind::Sint32 => merge!(sint32, I32),
+        Kind::Sint64 => merge!(sint64, I64),
+        Kind::Fixed32 => merge!(fixed32, U32),
+        Kind::Fixed64 => merge!(fixed64, U64),
+        Kind::Sfixed32 => merge!(sfixed32, I32),
+        Kind::Sfixed64 => merge!(sfixed64, I64),
+        Kind::Bool => merge!(bool, Bool),
+        Kind::Enum(_) => merge!(int32, EnumNumber),
+        Kind::String | Kind::Bytes | Kind::Message(_) => {
+            panic!("invalid type {kind:?} for scalar value")
+        }
+    })
+}
+
+/// Returns the payload of a length-delimited value, which has already been checked by `next_record`.
+fn length_delimited<'a>(wire_type: WireType, data: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    encoding::check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = encoding::decode_varint(data)? as usize;
+    Ok(&data[..len])
+}
+
+fn decode_map_entry<'a>(
+    map_entry: &MessageDescriptor,
+    mut buf: &'a [u8],
+) -> Result<(ValueView<'a>, ValueView<'a>), DecodeError> {
+    let key_desc = map_entry.map_entry_key_field();
+    let value_desc = map_entry.map_entry_value_field();
+
+    let mut key = None;
+    let mut value = None;
+    while let Some(record) = next_record(&mut buf)? {
+        match record.number {
+            MAP_ENTRY_KEY_NUMBER => key = Some(record_value(&key_desc, record)?),
+            MAP_ENTRY_VALUE_NUMBER => value = Some(record_value(&value_desc, record)?),
+            _ => (),
+        }
+    }
+
+    Ok((
+        key.unwrap_or_else(|| ValueView::default_value(&key_desc.kind())),
+        value.unwrap_or_else(|| ValueView::default_value(&value_desc.kind())),
+    ))
+}
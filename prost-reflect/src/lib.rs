@@ -15,12 +15,19 @@ mod reflect;
 
 pub use {prost, prost::bytes, prost_types};
 
+pub use self::descriptor::lint;
 pub use self::descriptor::{
-    Cardinality, DescriptorError, DescriptorPool, EnumDescriptor, EnumValueDescriptor,
-    ExtensionDescriptor, FieldDescriptor, FileDescriptor, Kind, MessageDescriptor,
-    MethodDescriptor, OneofDescriptor, ServiceDescriptor, Syntax,
+    feature_set, BreakingChange, BreakingChangeKind, Cardinality, Descriptor, DescriptorError,
+    DescriptorPool, Edition, EnumDescriptor, EnumValueDescriptor, ExtensionDescriptor, FeatureSet,
+    FieldDescriptor, FileDescriptor, Kind, MessageDescriptor, MethodDescriptor, OneofDescriptor,
+    ServiceDescriptor, Span, Syntax,
+};
+pub use self::dynamic::{diff, field_mask, stream};
+pub use self::dynamic::{
+    DecodeOptions, DynamicMessage, FieldExtractor, FieldPath, MapKey, MessageDecodeError,
+    MessageView, PathError, PathSegment, SetFieldError, TypeResolver, UnknownField,
+    UnknownFieldPolicy, Value, ValueView,
 };
-pub use self::dynamic::{DynamicMessage, MapKey, SetFieldError, UnknownField, Value};
 pub use self::reflect::ReflectMessage;
 
 #[cfg(feature = "serde")]
@@ -32,3 +39,6 @@ pub use prost_reflect_derive::ReflectMessage;
 
 #[cfg(feature = "text-format")]
 pub use self::dynamic::text_format;
+
+#[cfg(feature = "proto-parse")]
+pub use self::descriptor::{FileResolver, IncludePaths};
@@ -1,5 +1,6 @@
 use crate::descriptor::types::descriptor_proto::ExtensionRange;
 use crate::descriptor::types::descriptor_proto::ReservedRange;
+use crate::descriptor::types::enum_descriptor_proto::EnumReservedRange;
 use crate::descriptor::types::field_descriptor_proto::Label::*;
 use crate::descriptor::types::field_descriptor_proto::Type::*;
 use crate::descriptor::types::DescriptorProto;
@@ -94,6 +95,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/source_context.proto".into()),
@@ -155,6 +157,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/type.proto".into()),
@@ -778,6 +781,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/api.proto".into()),
@@ -1065,6 +1069,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/descriptor.proto".into()),
@@ -1091,7 +1096,11 @@ fn make_descriptor() -> FileDescriptorSet {
                         extension: vec![],
                         nested_type: vec![],
                         enum_type: vec![],
-                        extension_range: vec![],
+                        extension_range: vec![ExtensionRange {
+                            start: Some(536000000),
+                            end: Some(536000001),
+                            options: None,
+                        }],
                         oneof_decl: vec![],
                         options: None,
                         reserved_range: vec![],
@@ -1585,7 +1594,15 @@ fn make_descriptor() -> FileDescriptorSet {
                                 default_value: Some("UNVERIFIED".into()),
                                 oneof_index: None,
                                 json_name: Some("verification".into()),
-                                options: None,
+                                options: Some(Options::from_prost(FieldOptions {
+                                    ctype: None,
+                                    packed: None,
+                                    jstype: None,
+                                    lazy: None,
+                                    deprecated: None,
+                                    weak: None,
+                                    uninterpreted_option: vec![],
+                                })),
                                 proto3_optional: None,
                             },
                         ],
@@ -2971,6 +2988,21 @@ fn make_descriptor() -> FileDescriptorSet {
                                 options: None,
                                 proto3_optional: None,
                             },
+                            FieldDescriptorProto {
+                                name: Some("feature_support".into()),
+                                number: Some(22),
+                                label: Some(Optional.into()),
+                                r#type: Some(Message.into()),
+                                type_name: Some(
+                                    ".google.protobuf.FieldOptions.FeatureSupport".into(),
+                                ),
+                                extendee: None,
+                                default_value: None,
+                                oneof_index: None,
+                                json_name: Some("featureSupport".into()),
+                                options: None,
+                                proto3_optional: None,
+                            },
                             FieldDescriptorProto {
                                 name: Some("uninterpreted_option".into()),
                                 number: Some(999),
@@ -2986,45 +3018,112 @@ fn make_descriptor() -> FileDescriptorSet {
                             },
                         ],
                         extension: vec![],
-                        nested_type: vec![DescriptorProto {
-                            name: Some("EditionDefault".into()),
-                            field: vec![
-                                FieldDescriptorProto {
-                                    name: Some("edition".into()),
-                                    number: Some(3),
-                                    label: Some(Optional.into()),
-                                    r#type: Some(Enum.into()),
-                                    type_name: Some(".google.protobuf.Edition".into()),
-                                    extendee: None,
-                                    default_value: None,
-                                    oneof_index: None,
-                                    json_name: Some("edition".into()),
-                                    options: None,
-                                    proto3_optional: None,
-                                },
-                                FieldDescriptorProto {
-                                    name: Some("value".into()),
-                                    number: Some(2),
-                                    label: Some(Optional.into()),
-                                    r#type: Some(String.into()),
-                                    type_name: None,
-                                    extendee: None,
-                                    default_value: None,
-                                    oneof_index: None,
-                                    json_name: Some("value".into()),
-                                    options: None,
-                                    proto3_optional: None,
-                                },
-                            ],
-                            extension: vec![],
-                            nested_type: vec![],
-                            enum_type: vec![],
-                            extension_range: vec![],
-                            oneof_decl: vec![],
-                            options: None,
-                            reserved_range: vec![],
-                            reserved_name: vec![],
-                        }],
+                        nested_type: vec![
+                            DescriptorProto {
+                                name: Some("EditionDefault".into()),
+                                field: vec![
+                                    FieldDescriptorProto {
+                                        name: Some("edition".into()),
+                                        number: Some(3),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(Enum.into()),
+                                        type_name: Some(".google.protobuf.Edition".into()),
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("edition".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                    FieldDescriptorProto {
+                                        name: Some("value".into()),
+                                        number: Some(2),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(String.into()),
+                                        type_name: None,
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("value".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                ],
+                                extension: vec![],
+                                nested_type: vec![],
+                                enum_type: vec![],
+                                extension_range: vec![],
+                                oneof_decl: vec![],
+                                options: None,
+                                reserved_range: vec![],
+                                reserved_name: vec![],
+                            },
+                            DescriptorProto {
+                                name: Some("FeatureSupport".into()),
+                                field: vec![
+                                    FieldDescriptorProto {
+                                        name: Some("edition_introduced".into()),
+                                        number: Some(1),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(Enum.into()),
+                                        type_name: Some(".google.protobuf.Edition".into()),
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("editionIntroduced".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                    FieldDescriptorProto {
+                                        name: Some("edition_deprecated".into()),
+                                        number: Some(2),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(Enum.into()),
+                                        type_name: Some(".google.protobuf.Edition".into()),
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("editionDeprecated".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                    FieldDescriptorProto {
+                                        name: Some("deprecation_warning".into()),
+                                        number: Some(3),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(String.into()),
+                                        type_name: None,
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("deprecationWarning".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                    FieldDescriptorProto {
+                                        name: Some("edition_removed".into()),
+                                        number: Some(4),
+                                        label: Some(Optional.into()),
+                                        r#type: Some(Enum.into()),
+                                        type_name: Some(".google.protobuf.Edition".into()),
+                                        extendee: None,
+                                        default_value: None,
+                                        oneof_index: None,
+                                        json_name: Some("editionRemoved".into()),
+                                        options: None,
+                                        proto3_optional: None,
+                                    },
+                                ],
+                                extension: vec![],
+                                nested_type: vec![],
+                                enum_type: vec![],
+                                extension_range: vec![],
+                                oneof_decl: vec![],
+                                options: None,
+                                reserved_range: vec![],
+                                reserved_name: vec![],
+                            },
+                        ],
                         enum_type: vec![
                             EnumDescriptorProto {
                                 name: Some("CType".into()),
@@ -3351,6 +3450,21 @@ fn make_descriptor() -> FileDescriptorSet {
                                 options: None,
                                 proto3_optional: None,
                             },
+                            FieldDescriptorProto {
+                                name: Some("feature_support".into()),
+                                number: Some(4),
+                                label: Some(Optional.into()),
+                                r#type: Some(Message.into()),
+                                type_name: Some(
+                                    ".google.protobuf.FieldOptions.FeatureSupport".into(),
+                                ),
+                                extendee: None,
+                                default_value: None,
+                                oneof_index: None,
+                                json_name: Some("featureSupport".into()),
+                                options: None,
+                                proto3_optional: None,
+                            },
                             FieldDescriptorProto {
                                 name: Some("uninterpreted_option".into()),
                                 number: Some(999),
@@ -3893,18 +4007,21 @@ fn make_descriptor() -> FileDescriptorSet {
                                         options: None,
                                     },
                                     EnumValueDescriptorProto {
-                                        name: Some("NONE".into()),
-                                        number: Some(1),
+                                        name: Some("VERIFY".into()),
+                                        number: Some(2),
                                         options: None,
                                     },
                                     EnumValueDescriptorProto {
-                                        name: Some("VERIFY".into()),
-                                        number: Some(2),
+                                        name: Some("NONE".into()),
+                                        number: Some(3),
                                         options: None,
                                     },
                                 ],
                                 options: None,
-                                reserved_range: vec![],
+                                reserved_range: vec![EnumReservedRange {
+                                    start: Some(1),
+                                    end: Some(1),
+                                }],
                                 reserved_name: vec![],
                             },
                             EnumDescriptorProto {
@@ -3957,17 +4074,17 @@ fn make_descriptor() -> FileDescriptorSet {
                         extension_range: vec![
                             ExtensionRange {
                                 start: Some(1000),
-                                end: Some(1001),
+                                end: Some(9995),
                                 options: None,
                             },
                             ExtensionRange {
-                                start: Some(1001),
-                                end: Some(1002),
+                                start: Some(9995),
+                                end: Some(10000),
                                 options: None,
                             },
                             ExtensionRange {
-                                start: Some(9995),
-                                end: Some(10000),
+                                start: Some(10000),
+                                end: Some(10001),
                                 options: None,
                             },
                         ],
@@ -4043,15 +4160,28 @@ fn make_descriptor() -> FileDescriptorSet {
                                     proto3_optional: None,
                                 },
                                 FieldDescriptorProto {
-                                    name: Some("features".into()),
-                                    number: Some(2),
+                                    name: Some("overridable_features".into()),
+                                    number: Some(4),
+                                    label: Some(Optional.into()),
+                                    r#type: Some(Message.into()),
+                                    type_name: Some(".google.protobuf.FeatureSet".into()),
+                                    extendee: None,
+                                    default_value: None,
+                                    oneof_index: None,
+                                    json_name: Some("overridableFeatures".into()),
+                                    options: None,
+                                    proto3_optional: None,
+                                },
+                                FieldDescriptorProto {
+                                    name: Some("fixed_features".into()),
+                                    number: Some(5),
                                     label: Some(Optional.into()),
                                     r#type: Some(Message.into()),
                                     type_name: Some(".google.protobuf.FeatureSet".into()),
                                     extendee: None,
                                     default_value: None,
                                     oneof_index: None,
-                                    json_name: Some("features".into()),
+                                    json_name: Some("fixedFeatures".into()),
                                     options: None,
                                     proto3_optional: None,
                                 },
@@ -4062,8 +4192,17 @@ fn make_descriptor() -> FileDescriptorSet {
                             extension_range: vec![],
                             oneof_decl: vec![],
                             options: None,
-                            reserved_range: vec![],
-                            reserved_name: vec![],
+                            reserved_range: vec![
+                                ReservedRange {
+                                    start: Some(1),
+                                    end: Some(2),
+                                },
+                                ReservedRange {
+                                    start: Some(2),
+                                    end: Some(3),
+                                },
+                            ],
+                            reserved_name: vec!["features".into()],
                         }],
                         enum_type: vec![],
                         extension_range: vec![],
@@ -4183,7 +4322,11 @@ fn make_descriptor() -> FileDescriptorSet {
                             reserved_name: vec![],
                         }],
                         enum_type: vec![],
-                        extension_range: vec![],
+                        extension_range: vec![ExtensionRange {
+                            start: Some(536000000),
+                            end: Some(536000001),
+                            options: None,
+                        }],
                         oneof_decl: vec![],
                         options: None,
                         reserved_range: vec![],
@@ -4332,6 +4475,11 @@ fn make_descriptor() -> FileDescriptorSet {
                             number: Some(0),
                             options: None,
                         },
+                        EnumValueDescriptorProto {
+                            name: Some("EDITION_LEGACY".into()),
+                            number: Some(900),
+                            options: None,
+                        },
                         EnumValueDescriptorProto {
                             name: Some("EDITION_PROTO2".into()),
                             number: Some(998),
@@ -4347,6 +4495,11 @@ fn make_descriptor() -> FileDescriptorSet {
                             number: Some(1000),
                             options: None,
                         },
+                        EnumValueDescriptorProto {
+                            name: Some("EDITION_2024".into()),
+                            number: Some(1001),
+                            options: None,
+                        },
                         EnumValueDescriptorProto {
                             name: Some("EDITION_1_TEST_ONLY".into()),
                             number: Some(1),
@@ -4372,6 +4525,11 @@ fn make_descriptor() -> FileDescriptorSet {
                             number: Some(99999),
                             options: None,
                         },
+                        EnumValueDescriptorProto {
+                            name: Some("EDITION_MAX".into()),
+                            number: Some(2147483647),
+                            options: None,
+                        },
                     ],
                     options: None,
                     reserved_range: vec![],
@@ -4404,6 +4562,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: None,
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/duration.proto".into()),
@@ -4478,6 +4637,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/empty.proto".into()),
@@ -4525,6 +4685,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/field_mask.proto".into()),
@@ -4584,6 +4745,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/struct.proto".into()),
@@ -4817,6 +4979,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/timestamp.proto".into()),
@@ -4891,6 +5054,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/wrappers.proto".into()),
@@ -5144,6 +5308,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: Some("proto3".into()),
+                edition: None,
             },
             FileDescriptorProto {
                 name: Some("google/protobuf/compiler/plugin.proto".into()),
@@ -5464,6 +5629,7 @@ fn make_descriptor() -> FileDescriptorSet {
                 })),
                 source_code_info: None,
                 syntax: None,
+                edition: None,
             },
         ],
     }
@@ -6,7 +6,10 @@ use std::{
 use insta::assert_yaml_snapshot;
 use miette::JSONReportHandler;
 use prost::Message;
-use prost_reflect::{DescriptorError, DescriptorPool, DynamicMessage, ReflectMessage};
+use prost_reflect::{
+    lint::{self, LintContext, LintRule, Linter},
+    Cardinality, DescriptorError, DescriptorPool, DynamicMessage, Edition, ReflectMessage, Syntax,
+};
 use prost_types::FileDescriptorSet;
 
 fn test_data_dir() -> PathBuf {
@@ -69,9 +72,23 @@ fn check_ok(name: &str, add_wkt: bool) {
 
 fn check_err(name: &str, add_wkt: bool) {
     let actual_err = check(name, add_wkt).unwrap_err();
+    assert_err_snapshot(name, &actual_err);
+}
+
+fn check_parse_err(name: &str) {
+    let file_name = format!("{}.proto", name);
+    let source = fs::read_to_string(test_data_dir().join(&file_name)).unwrap();
+
+    let actual_err = DescriptorPool::new()
+        .add_proto_source(&file_name, &source)
+        .unwrap_err();
+    assert_err_snapshot(name, &actual_err);
+}
+
+fn assert_err_snapshot(name: &str, err: &DescriptorError) {
     let mut actual_json = String::new();
     JSONReportHandler::new()
-        .render_report(&mut actual_json, &actual_err)
+        .render_report(&mut actual_json, err)
         .unwrap();
     let actual = serde_json::from_str::<serde_json::Value>(&actual_json).unwrap();
 
@@ -95,6 +112,15 @@ macro_rules! check_ok {
     };
 }
 
+macro_rules! check_parse_err {
+    ($name:ident) => {
+        #[test]
+        fn $name() {
+            check_parse_err(stringify!($name));
+        }
+    };
+}
+
 macro_rules! check_err {
     ($name:ident) => {
         #[test]
@@ -210,3 +236,489 @@ check_err!(dependency_not_imported);
 check_ok!(dependency_resolution_transitive);
 check_ok!(dependency_resolution_transitive2);
 check_err!(dependency_resolution_transitive3);
+check_ok!(editions1);
+check_err!(editions2);
+check_err!(editions3);
+check_ok!(editions4);
+check_parse_err!(parse_unexpected_token);
+check_parse_err!(parse_unexpected_eof);
+check_parse_err!(parse_invalid_string_escape);
+check_parse_err!(parse_unterminated_block_comment);
+check_parse_err!(parse_integer_out_of_range);
+check_parse_err!(parse_invalid_group_name);
+check_parse_err!(parse_map_field_in_oneof);
+check_parse_err!(parse_invalid_map_key_type1);
+check_parse_err!(parse_invalid_map_key_type2);
+check_parse_err!(parse_label_in_editions);
+check_parse_err!(parse_group_in_editions);
+check_parse_err!(parse_invalid_reserved_name);
+check_parse_err!(parse_duplicate_package);
+check_parse_err!(parse_unknown_syntax);
+check_parse_err!(parse_name_resolution);
+
+#[test]
+fn editions_resolved_features() {
+    let pool = check("editions1", false).unwrap();
+    let file = pool.get_file_by_name("test.proto").unwrap();
+    assert_eq!(file.syntax(), Syntax::Editions(Edition::Edition2023));
+
+    let message = pool.get_message_by_name("test.editions.Message").unwrap();
+
+    let explicit_field = message.get_field_by_name("explicit_field").unwrap();
+    assert!(explicit_field.supports_presence());
+    assert_eq!(explicit_field.cardinality(), Cardinality::Optional);
+
+    let implicit_field = message.get_field_by_name("implicit_field").unwrap();
+    assert!(!implicit_field.supports_presence());
+
+    let required_field = message.get_field_by_name("required_field").unwrap();
+    assert!(required_field.is_required());
+    assert_eq!(required_field.cardinality(), Cardinality::Required);
+
+    let packed_field = message.get_field_by_name("packed_field").unwrap();
+    assert!(packed_field.is_packed());
+
+    let expanded_field = message.get_field_by_name("expanded_field").unwrap();
+    assert!(!expanded_field.is_packed());
+
+    let delimited_field = message.get_field_by_name("delimited_field").unwrap();
+    assert!(delimited_field.is_group());
+
+    let map_field = message.get_field_by_name("map_field").unwrap();
+    assert!(map_field.is_map());
+    assert!(!map_field.is_group());
+}
+
+fn compile_sources(
+    sources: &'static [(&'static str, &'static str)],
+) -> impl Fn(&str) -> std::io::Result<String> {
+    move |name| match sources.iter().find(|(file, _)| *file == name) {
+        Some((_, source)) => Ok(source.to_string()),
+        None => Err(std::io::ErrorKind::NotFound.into()),
+    }
+}
+
+#[test]
+fn compile_import_cycle() {
+    let resolver = compile_sources(&[
+        ("a.proto", "syntax = 'proto3';\nimport 'b.proto';\n"),
+        ("b.proto", "syntax = 'proto3';\nimport 'a.proto';\n"),
+    ]);
+    let err = DescriptorPool::new()
+        .compile(["a.proto"], &resolver)
+        .unwrap_err();
+    assert_err_snapshot("compile_import_cycle", &err);
+}
+
+#[test]
+fn compile_file_not_found() {
+    let resolver = compile_sources(&[("a.proto", "syntax = 'proto3';\nimport 'b.proto';\n")]);
+    let err = DescriptorPool::new()
+        .compile(["a.proto"], &resolver)
+        .unwrap_err();
+    assert_err_snapshot("compile_file_not_found", &err);
+}
+
+#[test]
+fn compile_well_known_types() {
+    let resolver = compile_sources(&[(
+        "a.proto",
+        "syntax = 'proto3';\nimport 'google/protobuf/any.proto';\nmessage A { google.protobuf.Any any = 1; }\n",
+    )]);
+    let mut pool = DescriptorPool::new();
+    pool.compile(["a.proto"], &resolver).unwrap();
+
+    let field = pool
+        .get_message_by_name("A")
+        .unwrap()
+        .get_field_by_name("any")
+        .unwrap();
+    assert_eq!(
+        field.kind().as_message().unwrap().full_name(),
+        "google.protobuf.Any"
+    );
+}
+
+#[test]
+fn compat_breaking_changes() {
+    let old_source = fs::read_to_string(test_data_dir().join("compat_old.proto")).unwrap();
+    let new_source = fs::read_to_string(test_data_dir().join("compat_new.proto")).unwrap();
+
+    let mut old = DescriptorPool::new();
+    old.add_proto_source("compat.proto", &old_source).unwrap();
+    let mut new = DescriptorPool::new();
+    new.add_proto_source("compat.proto", &new_source).unwrap();
+
+    let actual: Vec<_> = old
+        .check_compatibility(&new)
+        .into_iter()
+        .map(|change| {
+            let source = if change.kind().is_removal() {
+                &old_source
+            } else {
+                &new_source
+            };
+            let kind = format!("{:?}", change.kind());
+            let (wire, json) = (change.is_wire_breaking(), change.is_json_breaking());
+            let change = change.with_source_code(source);
+
+            let mut report = String::new();
+            JSONReportHandler::new()
+                .render_report(&mut report, &change)
+                .unwrap();
+            serde_json::json!({
+                "kind": kind,
+                "wire": wire,
+                "json": json,
+                "report": serde_json::from_str::<serde_json::Value>(&report).unwrap(),
+            })
+        })
+        .collect();
+
+    insta::with_settings!({ sort_maps => true }, {
+        assert_yaml_snapshot!("compat_breaking_changes", actual);
+    });
+
+    let old_file = old.get_file_by_name("compat.proto").unwrap();
+    let new_file = new.get_file_by_name("compat.proto").unwrap();
+    assert_eq!(old_file.check_compatibility(&new_file).len(), actual.len());
+}
+
+const SUBSET_SOURCES: &[(&str, &str)] = &[
+    (
+        "a.proto",
+        r#"syntax = "proto2";
+
+package a;
+
+import "d.proto";
+import "c.proto";
+
+// An unused message.
+message Unused {
+  optional c.Other other = 1;
+}
+
+// The request.
+message Request {
+  // A nested message.
+  message Nested {
+    optional b.Used used = 1;
+  }
+
+  // An unused nested message.
+  message Skipped {}
+
+  optional Nested nested = 1;
+
+  extensions 100 to 200;
+}
+
+// The response.
+message Response {}
+
+// An extension of the request.
+extend Request {
+  optional int32 ext = 100;
+}
+
+// An unused service.
+service UnusedService {
+  rpc Other(Unused) returns (Unused);
+}
+
+// The service.
+service Service {
+  // Calls the service.
+  rpc Call(Request) returns (Response);
+}
+"#,
+    ),
+    (
+        "b.proto",
+        r#"syntax = "proto3";
+
+package b;
+
+message Used {
+  // The kind.
+  enum Kind {
+    KIND_UNSPECIFIED = 0;
+  }
+
+  Kind kind = 1;
+}
+
+message Unused {}
+"#,
+    ),
+    (
+        "c.proto",
+        "syntax = 'proto3';\npackage c;\nmessage Other {}\n",
+    ),
+    ("d.proto", "syntax = 'proto3';\nimport public 'b.proto';\n"),
+];
+
+fn subset_pool() -> DescriptorPool {
+    let mut pool = DescriptorPool::new();
+    pool.compile(["a.proto"], &compile_sources(SUBSET_SOURCES))
+        .unwrap();
+    pool
+}
+
+#[test]
+fn subset_files() {
+    let pool = subset_pool();
+
+    let subset = pool.subset(["a.Response"]).unwrap();
+    assert_eq!(subset.files().len(), 4);
+    assert!(subset.get_message_by_name("a.Unused").is_some());
+
+    let subset = pool.subset([".b.Used.Kind", "c.Other"]).unwrap();
+    let files: Vec<_> = subset.files().map(|file| file.name().to_owned()).collect();
+    assert_eq!(files, ["b.proto", "c.proto"]);
+
+    let err = pool.subset(["a.Request.nested", "a.Missing"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'a.Request.nested' is not a message, enum, service or extension defined in the pool"
+    );
+}
+
+#[test]
+fn subset_definitions() {
+    let pool = subset_pool();
+
+    let subset = pool.subset_definitions(["a.Service"]).unwrap();
+    let mut actual = String::new();
+    for file in subset.files() {
+        actual.push_str(&format!("// {}\n", file.name()));
+        actual.push_str(&file.to_proto_source());
+    }
+    insta::assert_snapshot!("subset_definitions", actual);
+
+    let call = subset
+        .get_service_by_name("a.Service")
+        .unwrap()
+        .methods()
+        .next()
+        .unwrap();
+    assert_eq!(call.leading_comments(), Some(" Calls the service.\n"));
+    assert_eq!(
+        call.span(),
+        pool.get_service_by_name("a.Service")
+            .unwrap()
+            .methods()
+            .next()
+            .unwrap()
+            .span()
+    );
+}
+
+const SUBSET_OPTION_SOURCES: &[(&str, &str)] = &[
+    (
+        "options.proto",
+        r#"syntax = "proto2";
+
+package options;
+
+import "google/protobuf/descriptor.proto";
+
+message Http {
+  optional string path = 1;
+}
+
+extend google.protobuf.MethodOptions {
+  optional Http http = 50000;
+}
+
+extend google.protobuf.FieldOptions {
+  optional bool secret = 50001;
+}
+"#,
+    ),
+    (
+        "unused.proto",
+        r#"syntax = "proto2";
+
+package unused;
+
+import "google/protobuf/descriptor.proto";
+
+extend google.protobuf.FileOptions {
+  optional string unused = 50002;
+}
+"#,
+    ),
+    (
+        "service.proto",
+        r#"syntax = "proto3";
+
+package service;
+
+import "options.proto";
+import "unused.proto";
+
+message Request {
+  string token = 1 [(options.secret) = true];
+}
+
+service Service {
+  rpc Call(Request) returns (Request) {
+    option (options.http) = { path: "/call" };
+  }
+}
+"#,
+    ),
+];
+
+#[test]
+fn subset_definitions_custom_options() {
+    let mut pool = DescriptorPool::new();
+    pool.compile(["service.proto"], &compile_sources(SUBSET_OPTION_SOURCES))
+        .unwrap();
+
+    let subset = pool.subset_definitions(["service.Service"]).unwrap();
+    let files: Vec<_> = subset.files().map(|file| file.name().to_owned()).collect();
+    assert_eq!(
+        files,
+        [
+            "google/protobuf/descriptor.proto",
+            "options.proto",
+            "service.proto"
+        ]
+    );
+    assert_eq!(
+        subset
+            .get_file_by_name("service.proto")
+            .unwrap()
+            .dependencies()
+            .map(|file| file.name().to_owned())
+            .collect::<Vec<_>>(),
+        ["options.proto"]
+    );
+
+    let http = subset.get_extension_by_name("options.http").unwrap();
+    let method = subset
+        .get_service_by_name("service.Service")
+        .unwrap()
+        .methods()
+        .next()
+        .unwrap();
+    assert_eq!(
+        method
+            .options()
+            .get_extension(&http)
+            .as_message()
+            .unwrap()
+            .get_field_by_name("path")
+            .unwrap()
+            .as_str(),
+        Some("/call")
+    );
+
+    let secret = subset.get_extension_by_name("options.secret").unwrap();
+    let field = subset
+        .get_message_by_name("service.Request")
+        .unwrap()
+        .get_field_by_name("token")
+        .unwrap();
+    assert_eq!(field.options().get_extension(&secret).as_bool(), Some(true));
+}
+
+#[test]
+fn remove_files() {
+    let mut pool = subset_pool();
+
+    let err = pool.remove_file("b.proto").unwrap_err();
+    assert_err_snapshot(
+        "remove_file_in_use",
+        &err.with_source_code(SUBSET_SOURCES[3].1),
+    );
+    let err = pool.remove_file("missing.proto").unwrap_err();
+    assert_eq!(err.to_string(), "file 'missing.proto' has not been added");
+    assert_eq!(pool.files().len(), 4);
+
+    pool.remove_files(["a.proto", "c.proto"]).unwrap();
+    assert_eq!(pool.files().len(), 2);
+    assert!(pool.get_message_by_name("a.Request").is_none());
+    assert!(pool.get_message_by_name("b.Used").is_some());
+
+    pool.remove_files(["b.proto", "d.proto"]).unwrap();
+    assert_eq!(pool.files().len(), 0);
+}
+
+fn lint_source(name: &str, linter: &Linter) -> Vec<serde_json::Value> {
+    let source = fs::read_to_string(test_data_dir().join("lint.proto")).unwrap();
+    let mut pool = DescriptorPool::new();
+    pool.add_proto_source(name, &source).unwrap();
+
+    linter
+        .lint_file(&pool.get_file_by_name(name).unwrap())
+        .into_iter()
+        .map(|diagnostic| {
+            let diagnostic = diagnostic.with_source_code(&source);
+            let mut report = String::new();
+            JSONReportHandler::new()
+                .render_report(&mut report, &diagnostic)
+                .unwrap();
+            serde_json::from_str(&report).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn lint_default_rules() {
+    let actual = lint_source("test/lint/lint.proto", &Linter::with_default_rules());
+
+    insta::with_settings!({ sort_maps => true }, {
+        assert_yaml_snapshot!("lint_default_rules", actual);
+    });
+}
+
+#[test]
+fn lint_package_directory() {
+    let linter = Linter::new().with_rule(lint::PackageDirectoryMatch);
+    assert!(lint_source("test/lint/lint.proto", &linter).is_empty());
+
+    let actual = lint_source("lint.proto", &linter);
+    assert_eq!(actual.len(), 1);
+    assert_eq!(
+        actual[0]["message"],
+        "file 'lint.proto' with package 'test.lint' should be in directory 'test/lint'"
+    );
+}
+
+#[test]
+fn lint_custom_rule() {
+    struct NoServices;
+
+    impl LintRule for NoServices {
+        fn name(&self) -> &str {
+            "no-services"
+        }
+
+        fn check_service(
+            &self,
+            service: &prost_reflect::ServiceDescriptor,
+            cx: &mut LintContext<'_>,
+        ) {
+            cx.report(
+                &service.parent_file(),
+                service.path(),
+                "services are not allowed",
+            );
+        }
+    }
+
+    let linter = Linter::with_default_rules()
+        .without_rule("missing-comments")
+        .with_rule(NoServices);
+    let actual = lint_source("test/lint/lint.proto", &linter);
+    assert!(actual
+        .iter()
+        .any(|diagnostic| diagnostic["code"] == "no-services"));
+    assert!(!actual
+        .iter()
+        .any(|diagnostic| diagnostic["code"] == "missing-comments"));
+}